@@ -12,7 +12,7 @@ import {
   buildStationApplication,
 } from "./envelope";
 import { OpenLinkNatsClient } from "./nats-client";
-import type { OpenLinkEnvelope, OpenLinkMessage } from "./types";
+import type { OpenLinkEnvelope, OpenLinkMessage, UserRole } from "./types";
 
 /**
  * High-level TypeScript OpenLink client with API names aligned to Rust SDK where possible.
@@ -39,6 +39,23 @@ export class OpenLinkClient {
     return new OpenLinkClient(inner);
   }
 
+  static async connect_with_authorization_code_as(
+    nats_url: string,
+    auth_url: string,
+    authorization_code: string,
+    network_id: string,
+    role: UserRole
+  ): Promise<OpenLinkClient> {
+    const inner = await OpenLinkNatsClient.connect({
+      natsUrl: nats_url,
+      authUrl: auth_url,
+      oidcCode: authorization_code,
+      networkId: network_id,
+      role,
+    });
+    return new OpenLinkClient(inner);
+  }
+
   network_id(): string {
     return this.inner.networkId;
   }
@@ -4,6 +4,14 @@ export type ResponseAttribute = "WU" | "AN" | "R" | "Y" | "N" | "NE";
 
 export const LOGICAL_ACK_DOWNLINK_ID = "DM100" as const;
 export const LOGICAL_ACK_UPLINK_ID = "UM227" as const;
+export const ERROR_DOWNLINK_ID = "DM62" as const;
+export const ERROR_UPLINK_ID = "UM159" as const;
+
+export const MAX_UPLINK_DELAY_PREFIX = "SET MAX UPLINK DELAY VALUE TO";
+export const UPLINK_DELAYED_TEXT =
+  "UPLINK DELAYED IN NETWORK AND REJECTED - RESEND OR CONTACT BY VOICE";
+export const DOWNLINK_DELAYED_TEXT =
+  "DOWNLINK DELAYED IN NETWORK AND REJECTED - RESEND OR CONTACT BY VOICE";
 
 export interface CatalogResponseIntent {
   intent: string;
@@ -63,6 +71,44 @@ export function logicalAckElementIdForSender(isAircraftSender: boolean): string
   return isAircraftSender ? logicalAckDownlinkId() : logicalAckUplinkId();
 }
 
+/** `UM169` element carrying "SET MAX UPLINK DELAY VALUE TO [seconds] SEC". */
+export function setMaxUplinkDelayElement(seconds: number): MessageElement {
+  return {
+    id: "UM169",
+    args: [{ type: "FreeText", value: `${MAX_UPLINK_DELAY_PREFIX} ${seconds} SEC` }],
+  };
+}
+
+export function maxUplinkDelaySeconds(elements: MessageElement[]): number | null {
+  for (const element of elements) {
+    if (element.id !== "UM169") continue;
+    for (const arg of element.args) {
+      if (arg.type !== "FreeText") continue;
+      const text = String(arg.value).trim().toUpperCase();
+      if (!text.startsWith(MAX_UPLINK_DELAY_PREFIX)) continue;
+      const seconds = Number.parseInt(text.slice(MAX_UPLINK_DELAY_PREFIX.length).trim(), 10);
+      if (Number.isInteger(seconds) && seconds >= 0) return seconds;
+    }
+  }
+  return null;
+}
+
+export function uplinkExceedsMaxDelay(
+  sentAt: string,
+  now: Date,
+  maxDelaySeconds: number | null | undefined
+): boolean {
+  if (!maxDelaySeconds) return false;
+  return now.getTime() - new Date(sentAt).getTime() > maxDelaySeconds * 1000;
+}
+
+export function shouldReportDiscarded(elements: MessageElement[]): boolean {
+  return (
+    !messageContainsLogicalAck(elements) &&
+    !elements.some((e) => e.id === ERROR_DOWNLINK_ID || e.id === ERROR_UPLINK_ID)
+  );
+}
+
 export function closesDialogueResponseElements(elements: MessageElement[]): boolean {
   const hasStandby = elements.some((e) => e.id === "DM2" || e.id === "UM1" || e.id === "UM2");
   const hasClosing = elements.some((e) =>
@@ -136,3 +182,6 @@ export const logical_ack_matches_outgoing = logicalAckMatchesOutgoing;
 export const closes_dialogue_response_elements = closesDialogueResponseElements;
 export const response_attr_to_intents = responseAttrToIntents;
 export const choose_short_response_intents = chooseShortResponseIntents;
+export const max_uplink_delay_seconds = maxUplinkDelaySeconds;
+export const uplink_exceeds_max_delay = uplinkExceedsMaxDelay;
+export const should_report_discarded = shouldReportDiscarded;
@@ -6,6 +6,8 @@ import type {
   CpdlcMessageType,
   OpenLinkEnvelope,
   OpenLinkMessage,
+  StationMetadata,
+  StationStatus,
 } from "./types";
 
 const minSequences = new Map<string, number>();
@@ -31,11 +33,13 @@ export function buildEnvelope(
   networkId: string,
   networkAddress: string,
   payload: OpenLinkMessage,
-  token: string
+  token: string,
+  timeToLiveMs?: number
 ): OpenLinkEnvelope {
-  return {
+  const now = new Date();
+  const envelope: OpenLinkEnvelope = {
     id: uuidv4(),
-    timestamp: new Date().toISOString(),
+    timestamp: now.toISOString(),
     routing: {
       source: { Address: [networkId, networkAddress] },
       destination: { Server: networkId },
@@ -43,6 +47,10 @@ export function buildEnvelope(
     payload,
     token,
   };
+  if (timeToLiveMs !== undefined) {
+    envelope.expires_at = new Date(now.getTime() + timeToLiveMs).toISOString();
+  }
+  return envelope;
 }
 
 export function buildStationOnline(
@@ -50,7 +58,8 @@ export function buildStationOnline(
   networkAddress: string,
   callsign: string,
   acarsAddress: string,
-  token: string
+  token: string,
+  metadata: StationMetadata = {}
 ): OpenLinkEnvelope {
   return buildEnvelope(
     networkId,
@@ -58,7 +67,7 @@ export function buildStationOnline(
     {
       type: "Meta",
       data: {
-        StationStatus: [networkAddress, "Online", { callsign, address: acarsAddress }],
+        StationStatus: [networkAddress, "Online", { callsign, address: acarsAddress }, metadata],
       },
     },
     token
@@ -70,7 +79,8 @@ export function buildStationOffline(
   networkAddress: string,
   callsign: string,
   acarsAddress: string,
-  token: string
+  token: string,
+  metadata: StationMetadata = {}
 ): OpenLinkEnvelope {
   return buildEnvelope(
     networkId,
@@ -78,13 +88,49 @@ export function buildStationOffline(
     {
       type: "Meta",
       data: {
-        StationStatus: [networkAddress, "Offline", { callsign, address: acarsAddress }],
+        StationStatus: [networkAddress, "Offline", { callsign, address: acarsAddress }, metadata],
       },
     },
     token
   );
 }
 
+export function buildStationStatus(
+  networkId: string,
+  networkAddress: string,
+  status: StationStatus,
+  callsign: string,
+  acarsAddress: string,
+  token: string,
+  metadata: StationMetadata = {}
+): OpenLinkEnvelope {
+  return buildEnvelope(
+    networkId,
+    networkAddress,
+    {
+      type: "Meta",
+      data: {
+        StationStatus: [networkAddress, status, { callsign, address: acarsAddress }, metadata],
+      },
+    },
+    token
+  );
+}
+
+export function buildStationLookup(
+  networkId: string,
+  networkAddress: string,
+  callsign: string,
+  token: string
+): OpenLinkEnvelope {
+  return buildEnvelope(
+    networkId,
+    networkAddress,
+    { type: "Meta", data: { StationLookup: callsign } },
+    token
+  );
+}
+
 function buildCpdlcMessage(
   aircraftCallsign: string,
   acarsAddress: string,
@@ -1,5 +1,5 @@
 import { connect, type NatsConnection, type Subscription, StringCodec } from "nats.ws";
-import type { OpenLinkEnvelope } from "./types";
+import type { DirectoryEvent, DirectoryQuery, DirectoryResponse, OpenLinkEnvelope, StationInfo, UserRole } from "./types";
 
 const sc = StringCodec();
 
@@ -11,13 +11,33 @@ function inboxSubject(networkId: string, address: string): string {
   return `openlink.v1.${networkId}.inbox.${address}`;
 }
 
+function directoryQuerySubject(networkId: string): string {
+  return `openlink.v1.${networkId}.directory.query`;
+}
+
+function directoryEventsSubject(networkId: string): string {
+  return `openlink.v1.${networkId}.directory.events`;
+}
+
+function repliesPrefix(networkId: string, address: string): string {
+  return `openlink.v1.${networkId}.replies.${address}`;
+}
+
 interface AuthResponse {
   jwt: string;
   cid: string;
+  role: UserRole;
   network: string;
+  expires_at: number;
+  refresh_token?: string;
 }
 
-async function authenticate(authUrl: string, oidcCode: string, network: string): Promise<AuthResponse> {
+async function authenticate(
+  authUrl: string,
+  oidcCode: string,
+  network: string,
+  role?: UserRole
+): Promise<AuthResponse> {
   const exchangeUrl = authUrl.startsWith("http") ? "/api/auth/exchange" : `${authUrl}/exchange`;
 
   const response = await fetch(exchangeUrl, {
@@ -27,6 +47,7 @@ async function authenticate(authUrl: string, oidcCode: string, network: string):
       oidc_code: oidcCode,
       user_nkey_public: "UA" + "A".repeat(54),
       network,
+      role,
     }),
   });
 
@@ -41,6 +62,7 @@ async function authenticate(authUrl: string, oidcCode: string, network: string):
 export class OpenLinkNatsClient {
   private nc: NatsConnection;
   private sub: Subscription | null = null;
+  private directorySub: Subscription | null = null;
   private _networkId: string;
   private _networkAddress: string;
   private _cid: string;
@@ -81,12 +103,15 @@ export class OpenLinkNatsClient {
     authUrl: string;
     oidcCode: string;
     networkId: string;
+    /** Role requested from the auth service; defaults to pilot when granted. */
+    role?: UserRole;
   }): Promise<OpenLinkNatsClient> {
-    const auth = await authenticate(opts.authUrl, opts.oidcCode, opts.networkId);
+    const auth = await authenticate(opts.authUrl, opts.oidcCode, opts.networkId, opts.role);
 
     const nc = await connect({
       servers: opts.natsUrl,
       token: auth.jwt,
+      inboxPrefix: repliesPrefix(opts.networkId, auth.cid),
     });
 
     return new OpenLinkNatsClient(nc, opts.networkId, auth.cid, auth.cid, auth.jwt);
@@ -109,10 +134,51 @@ export class OpenLinkNatsClient {
     })();
   }
 
-  async publish(envelope: OpenLinkEnvelope): Promise<void> {
+  /**
+   * Publish an envelope on this client's outbox. Failed attempts are retried
+   * with the same payload (same envelope id), which the server deduplicates.
+   */
+  async publish(envelope: OpenLinkEnvelope, attempts = 3): Promise<void> {
     const subject = outboxSubject(this._networkId, this._networkAddress);
-    this.nc.publish(subject, sc.encode(JSON.stringify(envelope)));
-    await this.nc.flush();
+    const payload = sc.encode(JSON.stringify(envelope));
+    for (let attempt = 1; ; attempt++) {
+      try {
+        this.nc.publish(subject, payload);
+        await this.nc.flush();
+        return;
+      } catch (err) {
+        if (attempt >= attempts) throw err;
+        await new Promise((resolve) => setTimeout(resolve, 200 * attempt));
+      }
+    }
+  }
+
+  /** Ask the server for the reachable stations matching `query`, sorted by callsign. */
+  async queryDirectory(query: DirectoryQuery = {}, timeoutMs = 5000): Promise<StationInfo[]> {
+    const reply = await this.nc.request(
+      directoryQuerySubject(this._networkId),
+      sc.encode(JSON.stringify(query)),
+      { timeout: timeoutMs }
+    );
+    const response: DirectoryResponse = JSON.parse(sc.decode(reply.data));
+    return response.stations;
+  }
+
+  /** Receive live directory changes. Subscribe before querying to avoid missing updates. */
+  watchDirectory(handler: (event: DirectoryEvent) => void): void {
+    this.directorySub?.unsubscribe();
+    this.directorySub = this.nc.subscribe(directoryEventsSubject(this._networkId));
+    const sub = this.directorySub;
+
+    void (async () => {
+      for await (const msg of sub) {
+        try {
+          handler(JSON.parse(sc.decode(msg.data)));
+        } catch {
+          // ignore malformed payloads
+        }
+      }
+    })();
   }
 
   async disconnect(): Promise<void> {
@@ -120,6 +186,10 @@ export class OpenLinkNatsClient {
       this.sub.unsubscribe();
       this.sub = null;
     }
+    if (this.directorySub) {
+      this.directorySub.unsubscribe();
+      this.directorySub = null;
+    }
     await this.nc.drain();
   }
 }
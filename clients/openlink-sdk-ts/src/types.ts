@@ -10,6 +10,8 @@ export interface OpenLinkEnvelope {
   };
   payload: OpenLinkMessage;
   token: string;
+  /** ISO-8601 instant after which the message must be discarded. */
+  expires_at?: string;
 }
 
 export type OpenLinkRoutingEndpoint =
@@ -20,8 +22,73 @@ export type OpenLinkMessage =
   | { type: "Acars"; data: AcarsEnvelope }
   | { type: "Meta"; data: MetaPayload };
 
-export interface MetaPayload {
-  StationStatus: [string, "Online" | "Offline", AcarsRoutingEndpoint];
+export type MetaPayload =
+  | { StationStatus: [string, StationStatus, AcarsRoutingEndpoint, StationMetadata?] }
+  | { StationLookup: string }
+  | { StationInfo: [string, StationInfo | null] }
+  | { EnvelopeRejected: EnvelopeRejection }
+  | { ServerNotice: ServerNotice };
+
+export type StationStatus = "Online" | "Offline" | "Away" | "Closing";
+
+export type StationRole = "Atc" | "Aircraft" | "Aoc";
+
+export type UserRole = "Pilot" | "Controller" | "Bot" | "Observer";
+
+export type DatalinkApplication = "CpdlcFans" | "CpdlcAtnB1" | "Dcl" | "Atis" | "AdsC";
+
+export interface StationMetadata {
+  role?: StationRole | null;
+  facility?: string | null;
+  frequency?: string | null;
+  applications?: DatalinkApplication[];
+  accepting_logons?: boolean | null;
+}
+
+export interface StationInfo {
+  callsign: string;
+  acars_address: string;
+  status: StationStatus;
+  metadata: StationMetadata;
+  last_updated: string;
+}
+
+export interface DirectoryQuery {
+  role?: StationRole | null;
+  application?: DatalinkApplication | null;
+  callsign_prefix?: string | null;
+}
+
+export interface DirectoryResponse {
+  stations: StationInfo[];
+}
+
+export type DirectoryEvent = { Changed: StationInfo } | { Removed: string };
+
+export type RejectionCode =
+  | "SenderMismatch"
+  | "CallsignNotOwned"
+  | "CallsignOffline"
+  | "AircraftMismatch"
+  | "CallsignInUse"
+  | "CallsignReserved"
+  | "DeliveryExpired"
+  | "Expired"
+  | "RateLimited"
+  | "Unauthenticated"
+  | "RoleNotPermitted";
+
+export interface EnvelopeRejection {
+  envelope_id: string;
+  code: RejectionCode;
+  reason: string;
+}
+
+export type NoticeCode = "CallsignTakenOver" | "Maintenance";
+
+export interface ServerNotice {
+  code: NoticeCode;
+  message: string;
 }
 
 export interface AcarsRoutingEndpoint {
@@ -96,6 +163,16 @@ export interface CpdlcSessionView {
   active_connection: CpdlcConnectionInfo | null;
   inactive_connection: CpdlcConnectionInfo | null;
   next_data_authority: string | null;
+  transfer?: CpdlcTransferInfo | null;
+}
+
+export interface CpdlcTransferInfo {
+  kind: "Contact" | "Monitor";
+  from: string;
+  unit: string;
+  frequency: string;
+  next_data_authority: string | null;
+  acknowledged: boolean;
 }
 
 export interface CpdlcConnectionInfo {
@@ -0,0 +1,255 @@
+//! Authorization code flow: login and consent page, PKCE.
+//!
+//! `GET /authorize` shows a page to pick the user and approve or deny the
+//! login, or logs in as `login_hint` straight away. Approving redirects
+//! with a single-use code bound to the client, redirect URI, nonce and
+//! PKCE challenge; denying redirects with `error=access_denied`.
+
+use axum::{
+    extract::{Form, Query, State},
+    http::StatusCode,
+    response::{Html, IntoResponse, Redirect, Response},
+};
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::Utc;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+use crate::{html_escape, with_query, AppState};
+
+/// Seconds expired and redeemed codes are remembered, so that they are
+/// refused with `invalid_grant` rather than taken for login codes.
+const CODE_RETENTION: i64 = 3600;
+
+/// A code issued by `/authorize`.
+pub struct AuthorizationCode {
+    /// Login code of the user.
+    pub login: String,
+    pub client_id: Option<String>,
+    pub redirect_uri: String,
+    pub nonce: Option<String>,
+    pub pkce: Option<Pkce>,
+    pub expires_at: i64,
+    /// Set once exchanged; codes are single-use.
+    pub redeemed: bool,
+}
+
+/// Code challenge of an authorization request.
+pub struct Pkce {
+    challenge: String,
+    method: PkceMethod,
+}
+
+#[derive(Clone, Copy)]
+enum PkceMethod {
+    S256,
+    Plain,
+}
+
+impl Pkce {
+    fn new(challenge: String, method: Option<&str>) -> Result<Self, String> {
+        let method = match method.unwrap_or("plain") {
+            "S256" => PkceMethod::S256,
+            "plain" => PkceMethod::Plain,
+            other => return Err(format!("unsupported code_challenge_method '{}'", other)),
+        };
+        Ok(Self { challenge, method })
+    }
+
+    fn matches(&self, verifier: &str) -> bool {
+        match self.method {
+            PkceMethod::S256 => URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes())) == self.challenge,
+            PkceMethod::Plain => verifier == self.challenge,
+        }
+    }
+}
+
+/// Check the `code_verifier` of a token request against the challenge of its code.
+pub fn verify_pkce(pkce: Option<&Pkce>, verifier: Option<&str>) -> Result<(), &'static str> {
+    match (pkce, verifier) {
+        (None, None) => Ok(()),
+        (None, Some(_)) => Err("code_verifier sent for a code issued without code_challenge"),
+        (Some(_), None) => Err("missing code_verifier"),
+        (Some(pkce), Some(verifier)) if pkce.matches(verifier) => Ok(()),
+        (Some(_), Some(_)) => Err("code_verifier does not match code_challenge"),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct AuthorizeParams {
+    client_id: Option<String>,
+    redirect_uri: String,
+    state: Option<String>,
+    response_type: Option<String>,
+    nonce: Option<String>,
+    code_challenge: Option<String>,
+    code_challenge_method: Option<String>,
+    /// Log in as this user without showing the login page.
+    login_hint: Option<String>,
+}
+
+impl AuthorizeParams {
+    /// Redirect back to the client with `params` and the request's `state`.
+    fn redirect(&self, params: &[(&str, &str)]) -> Response {
+        let mut params = params.to_vec();
+        if let Some(ref state) = self.state {
+            params.push(("state", state));
+        }
+        Redirect::to(&with_query(&self.redirect_uri, &params)).into_response()
+    }
+
+    fn error(&self, error: &str, description: &str) -> Response {
+        self.redirect(&[("error", error), ("error_description", description)])
+    }
+
+    /// The PKCE challenge of the request, or the error to redirect with.
+    fn check(&self) -> Result<Option<Pkce>, (&'static str, String)> {
+        if self.response_type.as_deref().is_some_and(|t| t != "code") {
+            return Err(("unsupported_response_type", "only the code flow is supported".to_string()));
+        }
+        self.code_challenge
+            .clone()
+            .map(|challenge| Pkce::new(challenge, self.code_challenge_method.as_deref()))
+            .transpose()
+            .map_err(|e| ("invalid_request", e))
+    }
+}
+
+/// `GET /authorize`
+pub async fn authorize_page(State(state): State<Arc<AppState>>, Query(params): Query<AuthorizeParams>) -> Response {
+    println!("MOCK-OIDC: Authorize request for client_id={:?}", params.client_id);
+    if let Err((error, description)) = params.check() {
+        return params.error(error, &description);
+    }
+    if let Some(login) = params.login_hint.clone() {
+        return finish(&state, params, &login, false);
+    }
+
+    let hidden: String = [
+        ("client_id", params.client_id.as_deref()),
+        ("redirect_uri", Some(params.redirect_uri.as_str())),
+        ("state", params.state.as_deref()),
+        ("response_type", params.response_type.as_deref()),
+        ("nonce", params.nonce.as_deref()),
+        ("code_challenge", params.code_challenge.as_deref()),
+        ("code_challenge_method", params.code_challenge_method.as_deref()),
+    ]
+    .into_iter()
+    .filter_map(|(name, value)| value.map(|value| format!(r#"  <input type="hidden" name="{}" value="{}">"#, name, html_escape(value))))
+    .collect::<Vec<_>>()
+    .join("\n");
+    Html(format!(
+        r#"<!doctype html>
+<title>Mock OIDC login</title>
+<h1>Log in to {client}</h1>
+<form method="post" action="/authorize">
+{hidden}
+  <label>Log in as <input name="login" value="PILOT" list="users"></label>
+  {users}
+  <button name="action" value="approve">Approve</button>
+  <button name="action" value="deny">Deny</button>
+</form>"#,
+        client = html_escape(params.client_id.as_deref().unwrap_or("unknown client")),
+        users = user_list(&state),
+    ))
+    .into_response()
+}
+
+/// Known users as a `<datalist id="users">` for the login pages.
+pub fn user_list(state: &AppState) -> String {
+    let options: String = state
+        .users
+        .logins()
+        .map(|login| format!(r#"<option value="{}">"#, html_escape(login)))
+        .collect();
+    format!(r#"<datalist id="users">{}</datalist>"#, options)
+}
+
+#[derive(Deserialize)]
+pub struct Consent {
+    #[serde(flatten)]
+    params: AuthorizeParams,
+    #[serde(default)]
+    login: Option<String>,
+    #[serde(default)]
+    action: Option<String>,
+}
+
+/// `POST /authorize` — the user approved or denied the login.
+pub async fn consent(State(state): State<Arc<AppState>>, Form(consent): Form<Consent>) -> Response {
+    let login = consent.login.filter(|l| !l.is_empty()).unwrap_or_else(|| "PILOT".to_string());
+    let deny = consent.action.as_deref() == Some("deny");
+    finish(&state, consent.params, &login, deny)
+}
+
+fn finish(state: &AppState, params: AuthorizeParams, login: &str, deny: bool) -> Response {
+    let Some(user) = state.users.find(login) else {
+        return (StatusCode::NOT_FOUND, Html(format!("Unknown user {}", html_escape(login)))).into_response();
+    };
+    if deny || user.deny_consent || state.faults().deny_consent {
+        println!("MOCK-OIDC: Login as '{}' denied", login);
+        return params.error("access_denied", "the user denied the login");
+    }
+    let pkce = match params.check() {
+        Ok(pkce) => pkce,
+        Err((error, description)) => return params.error(error, &description),
+    };
+
+    let code = uuid::Uuid::new_v4().simple().to_string();
+    let now = Utc::now().timestamp();
+    let ttl = state.faults().code_ttl_seconds as i64;
+    let mut codes = state.codes.lock().unwrap();
+    codes.retain(|_, issued| issued.expires_at + CODE_RETENTION > now);
+    codes.insert(code.clone(), AuthorizationCode {
+        login: login.to_string(),
+        client_id: params.client_id.clone(),
+        redirect_uri: params.redirect_uri.clone(),
+        nonce: params.nonce.clone(),
+        pkce,
+        expires_at: now + ttl,
+        redeemed: false,
+    });
+    println!("MOCK-OIDC: Login as '{}' approved, code {}", login, code);
+    params.redirect(&[("code", &code)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // BASE64URL(SHA256(VERIFIER)), computed with Python's hashlib
+    const VERIFIER: &str = "dBjftJeZ4CVP-mJ92K27uhbUJU1p1r_wW1gFWFOEjXk";
+    const CHALLENGE: &str = "ngF5GsXcbwljx6u133FFr3Xht9xooA_DuaX_3QwODtc";
+
+    #[test]
+    fn s256_challenge_matches_its_verifier() {
+        let pkce = Pkce::new(CHALLENGE.to_string(), Some("S256")).unwrap();
+        assert!(pkce.matches(VERIFIER));
+        assert!(!pkce.matches(CHALLENGE));
+    }
+
+    #[test]
+    fn plain_is_the_default_method() {
+        let pkce = Pkce::new(VERIFIER.to_string(), None).unwrap();
+        assert!(pkce.matches(VERIFIER));
+        assert!(!pkce.matches("another-verifier"));
+        assert!(Pkce::new(VERIFIER.to_string(), Some("S512")).is_err());
+    }
+
+    #[test]
+    fn verify_pkce_needs_a_matching_verifier_exactly_when_challenged() {
+        let pkce = Pkce::new(CHALLENGE.to_string(), Some("S256")).unwrap();
+        assert_eq!(verify_pkce(None, None), Ok(()));
+        assert_eq!(verify_pkce(Some(&pkce), Some(VERIFIER)), Ok(()));
+        assert_eq!(verify_pkce(Some(&pkce), None), Err("missing code_verifier"));
+        assert_eq!(
+            verify_pkce(Some(&pkce), Some("wrong")),
+            Err("code_verifier does not match code_challenge")
+        );
+        assert_eq!(
+            verify_pkce(None, Some(VERIFIER)),
+            Err("code_verifier sent for a code issued without code_challenge")
+        );
+    }
+}
@@ -0,0 +1,155 @@
+//! Device authorization grant (RFC 8628).
+
+use axum::{
+    extract::{Form, Query, State},
+    http::StatusCode,
+    response::{Html, IntoResponse, Json, Response},
+};
+use chrono::Utc;
+use rand::Rng;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use crate::authorize::user_list;
+use crate::token::{issue_tokens, token_error};
+use crate::{html_escape, AppState, ISSUER};
+
+/// Lifetime of a device code in seconds.
+const DEVICE_CODE_TTL: i64 = 600;
+
+/// A pending device login.
+pub struct DeviceLogin {
+    user_code: String,
+    client_id: Option<String>,
+    expires_at: i64,
+    /// Login code of the user once they approved, `None` while pending.
+    approved_as: Option<String>,
+    denied: bool,
+}
+
+#[derive(Deserialize)]
+pub struct DeviceAuthorizationRequest {
+    client_id: Option<String>,
+}
+
+/// `POST /device_authorization`
+pub async fn device_authorization(
+    State(state): State<Arc<AppState>>,
+    Form(req): Form<DeviceAuthorizationRequest>,
+) -> Json<Value> {
+    println!("MOCK-OIDC: Device authorization request for client_id={:?}", req.client_id);
+
+    let device_code = uuid::Uuid::new_v4().to_string();
+    let user_code = random_user_code();
+    let now = Utc::now().timestamp();
+    let mut logins = state.device_logins.lock().unwrap();
+    logins.retain(|_, login| login.expires_at > now);
+    logins.insert(device_code.clone(), DeviceLogin {
+        user_code: user_code.clone(),
+        client_id: req.client_id,
+        expires_at: now + DEVICE_CODE_TTL,
+        approved_as: None,
+        denied: false,
+    });
+
+    Json(json!({
+        "device_code": device_code,
+        "user_code": user_code,
+        "verification_uri": format!("{}/device", ISSUER),
+        "verification_uri_complete": format!("{}/device?user_code={}", ISSUER, user_code),
+        "expires_in": DEVICE_CODE_TTL,
+        "interval": 1
+    }))
+}
+
+/// `XXXX-XXXX` from consonants only, as RFC 8628 suggests.
+fn random_user_code() -> String {
+    const ALPHABET: &[u8] = b"BCDFGHJKLMNPQRSTVWXZ";
+    let mut rng = rand::thread_rng();
+    let mut code: String = (0..8)
+        .map(|_| ALPHABET[rng.gen_range(0..ALPHABET.len())] as char)
+        .collect();
+    code.insert(4, '-');
+    code
+}
+
+#[derive(Deserialize)]
+pub struct DevicePageParams {
+    user_code: Option<String>,
+}
+
+/// `GET /device`
+pub async fn device_page(State(state): State<Arc<AppState>>, Query(params): Query<DevicePageParams>) -> Html<String> {
+    let user_code = html_escape(&params.user_code.unwrap_or_default());
+    Html(format!(
+        r#"<!doctype html>
+<title>Mock OIDC device login</title>
+<h1>Device login</h1>
+<form method="post" action="/device">
+  <label>Code <input name="user_code" value="{user_code}"></label>
+  <label>Log in as <input name="login" value="PILOT" list="users"></label>
+  {users}
+  <button name="action" value="approve">Approve</button>
+  <button name="action" value="deny">Deny</button>
+</form>"#,
+        users = user_list(&state),
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct DeviceApproval {
+    user_code: String,
+    /// Login code of the user, as on the `/authorize` page.
+    #[serde(default)]
+    login: Option<String>,
+    #[serde(default)]
+    action: Option<String>,
+}
+
+/// `POST /device` — the user approved or denied a device login.
+pub async fn device_approve(State(state): State<Arc<AppState>>, Form(req): Form<DeviceApproval>) -> Response {
+    let user_code = req.user_code.trim().to_uppercase();
+    let login = req.login.filter(|l| !l.is_empty()).unwrap_or_else(|| "PILOT".to_string());
+    let Some(user) = state.users.find(&login) else {
+        return (StatusCode::NOT_FOUND, Html(format!("Unknown user {}", html_escape(&login)))).into_response();
+    };
+    let deny = req.action.as_deref() == Some("deny") || user.deny_consent || state.faults().deny_consent;
+
+    let mut logins = state.device_logins.lock().unwrap();
+    let Some(pending) = logins.values_mut().find(|l| l.user_code == user_code) else {
+        return (StatusCode::NOT_FOUND, Html("Unknown code".to_string())).into_response();
+    };
+    if deny {
+        pending.denied = true;
+        println!("MOCK-OIDC: Device login {} denied", user_code);
+        return Html("Login denied. You can close this window.".to_string()).into_response();
+    }
+    pending.approved_as = Some(login.clone());
+    println!("MOCK-OIDC: Device login {} approved as '{}'", user_code, login);
+    Html(format!("Logged in as {}. You can close this window.", html_escape(&login))).into_response()
+}
+
+/// Device code grant of `/token`.
+pub fn device_token(state: &AppState, device_code: &str) -> Response {
+    let mut logins = state.device_logins.lock().unwrap();
+    let Some(login) = logins.get(device_code) else {
+        return token_error("invalid_grant", "unknown device code");
+    };
+    if login.expires_at <= Utc::now().timestamp() {
+        logins.remove(device_code);
+        return token_error("expired_token", "the device code expired");
+    }
+    if login.denied {
+        logins.remove(device_code);
+        return token_error("access_denied", "the user denied the login");
+    }
+    let Some(approved_as) = login.approved_as.clone() else {
+        return token_error("authorization_pending", "the user has not approved the login yet");
+    };
+    let client_id = login.client_id.clone();
+    logins.remove(device_code);
+    drop(logins);
+    println!("MOCK-OIDC: Device Token Request approved as '{}'", approved_as);
+    issue_tokens(state, &approved_as, None, client_id.as_deref())
+}
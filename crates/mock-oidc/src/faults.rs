@@ -0,0 +1,147 @@
+//! Error injection.
+//!
+//! Faults come from the `[faults]` table of the fixture file and can be
+//! changed at runtime with `PUT /faults`, so a test can make the provider
+//! misbehave for a few requests and check how its clients cope.
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::{Json, Response},
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::AppState;
+
+/// Faults in effect. Every field is optional in the fixture file and in
+/// `PUT /faults`; missing ones take their default, no fault.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Faults {
+    /// Delay before every response, in milliseconds.
+    pub latency_ms: u64,
+    /// OAuth error answered by `/token` instead of tokens (`invalid_grant`,
+    /// `invalid_client`, `server_error`, `temporarily_unavailable`, ...).
+    pub token_error: Option<String>,
+    /// Number of token requests answered with `token_error`; all of them when unset.
+    pub token_error_count: Option<u32>,
+    /// Lifetime of authorization codes in seconds; `0` issues codes that
+    /// are already expired.
+    pub code_ttl_seconds: u64,
+    /// Issue `id_token`s that expired an hour ago.
+    pub expired_id_tokens: bool,
+    /// Deny every login, as if users refused consent.
+    pub deny_consent: bool,
+}
+
+impl Default for Faults {
+    fn default() -> Self {
+        Self {
+            latency_ms: 0,
+            token_error: None,
+            token_error_count: None,
+            code_ttl_seconds: 60,
+            expired_id_tokens: false,
+            deny_consent: false,
+        }
+    }
+}
+
+impl Faults {
+    /// The error to answer the current token request with, if any,
+    /// counting it against `token_error_count`.
+    pub fn take_token_error(&mut self) -> Option<String> {
+        let error = self.token_error.clone()?;
+        match self.token_error_count {
+            None => Some(error),
+            Some(0) => {
+                self.token_error = None;
+                self.token_error_count = None;
+                None
+            }
+            Some(remaining) => {
+                self.token_error_count = Some(remaining - 1);
+                if remaining == 1 {
+                    self.token_error = None;
+                    self.token_error_count = None;
+                }
+                Some(error)
+            }
+        }
+    }
+}
+
+/// Delay responses by `latency_ms`.
+pub async fn latency(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+    let latency_ms = state.faults().latency_ms;
+    if latency_ms > 0 {
+        tokio::time::sleep(Duration::from_millis(latency_ms)).await;
+    }
+    next.run(request).await
+}
+
+/// `GET /faults` — the faults in effect.
+pub async fn get_faults(State(state): State<Arc<AppState>>) -> Json<Faults> {
+    Json(state.faults().clone())
+}
+
+/// `PUT /faults` — replace the faults in effect; `{}` clears them.
+pub async fn put_faults(State(state): State<Arc<AppState>>, Json(faults): Json<Faults>) -> Json<Faults> {
+    println!("MOCK-OIDC: Faults set to {:?}", faults);
+    *state.faults() = faults.clone();
+    Json(faults)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failing(count: Option<u32>) -> Faults {
+        Faults {
+            token_error: Some("invalid_grant".to_string()),
+            token_error_count: count,
+            ..Faults::default()
+        }
+    }
+
+    #[test]
+    fn no_token_error_by_default() {
+        assert_eq!(Faults::default().take_token_error(), None);
+    }
+
+    #[test]
+    fn token_error_without_count_is_permanent() {
+        let mut faults = failing(None);
+        for _ in 0..3 {
+            assert_eq!(faults.take_token_error().as_deref(), Some("invalid_grant"));
+        }
+    }
+
+    #[test]
+    fn token_error_count_fails_that_many_requests() {
+        let mut faults = failing(Some(2));
+        assert_eq!(faults.take_token_error().as_deref(), Some("invalid_grant"));
+        assert_eq!(faults.token_error_count, Some(1));
+        assert_eq!(faults.take_token_error().as_deref(), Some("invalid_grant"));
+        assert_eq!(faults.take_token_error(), None);
+        assert_eq!(faults.token_error, None);
+        assert_eq!(faults.token_error_count, None);
+    }
+
+    #[test]
+    fn zero_token_error_count_clears_the_error() {
+        let mut faults = failing(Some(0));
+        assert_eq!(faults.take_token_error(), None);
+        assert_eq!(faults.token_error, None);
+        assert_eq!(faults.token_error_count, None);
+    }
+
+    #[test]
+    fn unknown_fault_is_refused() {
+        assert!(serde_json::from_str::<Faults>(r#"{"latency": 5}"#).is_err());
+        let faults: Faults = serde_json::from_str("{}").unwrap();
+        assert_eq!(faults.code_ttl_seconds, 60);
+    }
+}
@@ -1,66 +1,131 @@
+mod authorize;
+mod device;
+mod faults;
+mod token;
+mod users;
+
 use axum::{
-    extract::{Form, Query},
-    response::{Json, IntoResponse, Redirect},
+    extract::State,
+    middleware,
+    response::Json,
     routing::{get, post},
     Router,
 };
-use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::sync::OnceLock;
-use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
-use chrono::{Utc, Duration};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use jsonwebtoken::EncodingKey;
 use rsa::{RsaPrivateKey, RsaPublicKey, pkcs1::EncodeRsaPrivateKey};
 use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
 
+use crate::authorize::AuthorizationCode;
+use crate::device::DeviceLogin;
+use crate::faults::Faults;
+use crate::token::{AccessGrant, RefreshGrant};
+use crate::users::{Fixture, Users};
+
+/// Issuer of the tokens, and base URL of every endpoint.
+pub const ISSUER: &str = "http://localhost:4000";
+
+// Signing keys, generated at startup
+pub struct OidcKeys {
+    pub encoding_key: EncodingKey,
+    pub public_jwk: Value,
+}
+
+impl OidcKeys {
+    /// Generate an RSA key pair of `bits` bits.
+    pub fn generate(bits: usize) -> Self {
+        let mut rng = rand::thread_rng();
+        let priv_key = RsaPrivateKey::new(&mut rng, bits).expect("Failed to generate private key");
+        let pub_key = RsaPublicKey::from(&priv_key);
+
+        // Convert to PEM for jsonwebtoken
+        // jsonwebtoken EncodingKey::from_rsa_pem expects PKCS#1 or PKCS#8.
+        let priv_pem = priv_key.to_pkcs1_pem(rsa::pkcs8::LineEnding::LF).unwrap();
+        let encoding_key = EncodingKey::from_rsa_pem(priv_pem.as_bytes()).unwrap();
+
+        // Construct JWK for the public key (Naive construction)
+        // For proper JWK we need Modulus (n) and Exponent (e) in Base64URL
+        use rsa::traits::PublicKeyParts;
+        let n = base64_url_encode_bytes(&pub_key.n().to_bytes_be());
+        let e = base64_url_encode_bytes(&pub_key.e().to_bytes_be());
+
+        let public_jwk = json!({
+            "kty": "RSA",
+            "alg": "RS256",
+            "use": "sig",
+            "kid": "mock-key-1",
+            "n": n,
+            "e": e
+        });
+        Self { encoding_key, public_jwk }
+    }
+}
 
-// Global Keys
-struct OidcKeys {
-    encoding_key: EncodingKey,
-    public_jwk: Value,
+/// State shared by the endpoints.
+pub struct AppState {
+    pub keys: OidcKeys,
+    pub users: Users,
+    pub faults: Mutex<Faults>,
+    /// Codes issued by `/authorize`, keyed by code.
+    pub codes: Mutex<HashMap<String, AuthorizationCode>>,
+    /// Pending device logins, keyed by device code.
+    pub device_logins: Mutex<HashMap<String, DeviceLogin>>,
+    pub access_tokens: Mutex<HashMap<String, AccessGrant>>,
+    pub refresh_tokens: Mutex<HashMap<String, RefreshGrant>>,
 }
 
-static KEYS: OnceLock<OidcKeys> = OnceLock::new();
+impl AppState {
+    pub fn new(keys: OidcKeys, fixture: Fixture) -> Self {
+        Self {
+            keys,
+            users: Users::new(fixture.dynamic_users, fixture.users),
+            faults: Mutex::new(fixture.faults),
+            codes: Mutex::default(),
+            device_logins: Mutex::default(),
+            access_tokens: Mutex::default(),
+            refresh_tokens: Mutex::default(),
+        }
+    }
+
+    pub fn faults(&self) -> MutexGuard<'_, Faults> {
+        self.faults.lock().unwrap()
+    }
+}
 
 #[tokio::main]
 async fn main() {
-    // 1. Generate RSA Key Pair on Startup
+    // 1. Load the users, from MOCK_OIDC_USERS when set
+    let fixture = match std::env::var_os("MOCK_OIDC_USERS") {
+        Some(path) => {
+            println!("MOCK-OIDC: Loading users from {}", path.to_string_lossy());
+            Fixture::load(path.as_ref()).unwrap_or_else(|e| panic!("Invalid user fixture: {}", e))
+        }
+        None => Fixture::builtin(),
+    };
+
+    // 2. Generate RSA Key Pair on Startup
     println!("MOCK-OIDC: Generating RSA-2048 keys...");
-    let mut rng = rand::thread_rng();
-    let bits = 2048;
-    let priv_key = RsaPrivateKey::new(&mut rng, bits).expect("Failed to generate private key");
-    let pub_key = RsaPublicKey::from(&priv_key);
-
-    // Convert to PEM for jsonwebtoken
-    // jsonwebtoken EncodingKey::from_rsa_pem expects PKCS#1 or PKCS#8.
-    let priv_pem = priv_key.to_pkcs1_pem(rsa::pkcs8::LineEnding::LF).unwrap();
-    let encoding_key = EncodingKey::from_rsa_pem(priv_pem.as_bytes()).unwrap();
-
-    // Construct JWK for the public key (Naive construction)
-    // For proper JWK we need Modulus (n) and Exponent (e) in Base64URL
-    use rsa::traits::PublicKeyParts;
-    let n = base64_url_encode_bytes(&pub_key.n().to_bytes_be());
-    let e = base64_url_encode_bytes(&pub_key.e().to_bytes_be());
-    
-    let public_jwk = json!({
-        "kty": "RSA",
-        "alg": "RS256",
-        "use": "sig",
-        "kid": "mock-key-1",
-        "n": n,
-        "e": e
-    });
-
-    KEYS.set(OidcKeys { encoding_key, public_jwk }).ok().unwrap();
-
-    // 2. Setup Routes
+    let keys = OidcKeys::generate(2048);
+
+    let state = Arc::new(AppState::new(keys, fixture));
+
+    // 3. Setup Routes; /faults is never slowed down
     let app = Router::new()
         .route("/.well-known/openid-configuration", get(openid_configuration))
         .route("/jwks", get(jwks))
-        .route("/authorize", get(authorize)) // Dummy
-        .route("/token", post(token));
+        .route("/authorize", get(authorize::authorize_page).post(authorize::consent))
+        .route("/device_authorization", post(device::device_authorization))
+        .route("/device", get(device::device_page).post(device::device_approve))
+        .route("/token", post(token::token))
+        .route("/userinfo", get(token::userinfo))
+        .route_layer(middleware::from_fn_with_state(state.clone(), faults::latency))
+        .route("/faults", get(faults::get_faults).put(faults::put_faults))
+        .with_state(state);
 
     let listener = tokio::net::TcpListener::bind("0.0.0.0:4000").await.unwrap();
-    println!("MOCK-OIDC: Listening on http://localhost:4000");
+    println!("MOCK-OIDC: Listening on {}", ISSUER);
     axum::serve(listener, app).await.unwrap();
 }
 
@@ -68,114 +133,61 @@ async fn main() {
 
 async fn openid_configuration() -> Json<Value> {
     Json(json!({
-        "issuer": "http://localhost:4000",
-        "authorization_endpoint": "http://localhost:4000/authorize",
-        "token_endpoint": "http://localhost:4000/token",
-        "jwks_uri": "http://localhost:4000/jwks",
-        "response_types_supported": ["code", "token", "id_token"],
+        "issuer": ISSUER,
+        "authorization_endpoint": format!("{}/authorize", ISSUER),
+        "token_endpoint": format!("{}/token", ISSUER),
+        "device_authorization_endpoint": format!("{}/device_authorization", ISSUER),
+        "userinfo_endpoint": format!("{}/userinfo", ISSUER),
+        "jwks_uri": format!("{}/jwks", ISSUER),
+        "response_types_supported": ["code"],
+        "grant_types_supported": ["authorization_code", "refresh_token", token::DEVICE_CODE_GRANT],
+        "code_challenge_methods_supported": ["S256", "plain"],
         "subject_types_supported": ["public"],
         "id_token_signing_alg_values_supported": ["RS256"]
     }))
 }
 
-async fn jwks() -> Json<Value> {
-    let keys = KEYS.get().unwrap();
+async fn jwks(State(state): State<Arc<AppState>>) -> Json<Value> {
     Json(json!({
-        "keys": [keys.public_jwk.clone()]
+        "keys": [state.keys.public_jwk.clone()]
     }))
 }
 
-#[derive(Deserialize)]
-struct AuthorizeParams {
-    client_id: Option<String>,
-    redirect_uri: String,
-    state: Option<String>,
-    #[allow(dead_code)]
-    response_type: Option<String>,
-}
+// --- Helpers ---
 
-async fn authorize(Query(params): Query<AuthorizeParams>) -> impl IntoResponse {
-    println!("MOCK-OIDC: Authorize request for client_id={:?}", params.client_id);
-    
-    // Auto-approve: Redirect strictly to the provided redirect_uri with a fixed code
-    // In a real app, we would show a login page here.
-    let code = "PILOT"; // Default to PILOT role for generic testing
-    let state = params.state.unwrap_or_default();
-    
-    // Check if redirect_uri already has params (basic append)
-    let separator = if params.redirect_uri.contains('?') { '&' } else { '?' };
-    let target = format!("{}{}{}code={}&state={}", params.redirect_uri, separator, if separator == '&' { "" } else { "" }, code, state);
-    
-    Redirect::to(&target)
-}
-
-#[derive(Deserialize)]
-struct TokenRequest {
-    code: String,
-    // grant_type: String, // optional here to be lenient
+fn base64_url_encode_bytes(input: &[u8]) -> String {
+    URL_SAFE_NO_PAD.encode(input)
 }
 
-#[derive(Serialize)]
-struct IdTokenClaims {
-    iss: String,
-    sub: String,
-    aud: String,
-    exp: i64,
-    iat: i64,
-    // Custom claims
-    name: String,
-    email: String,
-    demonetwork_cid: String, 
+/// Escape `value` for use in HTML text and attributes.
+pub fn html_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
 }
 
-async fn token(Form(req): Form<TokenRequest>) -> Json<Value> {
-    println!("MOCK-OIDC: Token Request code='{}'", req.code);
-
-    // Map Code to Identity
-    let (sub, name, email) = match req.code.as_str() {
-        "PILOT" => ("100000".to_string(), "Captain Smith".to_string(), "pilot@demonetwork.net".to_string()),
-        "ATC" => ("888888".to_string(), "Generic ATC".to_string(), "atc@demonetwork.net".to_string()),
-        "ATC_EGLL" => ("777777".to_string(), "Generic ATC".to_string(), "atc@demonetwork.net".to_string()),
-
-        // Dynamic Fallback: use the provided code as the identity
-        // This allows the CLI to request tokens for any network address
-        other => (other.to_string(), format!("User {}", other), format!("{}@demonetwork.net", other.to_lowercase())),
-    };
-
-    let now = Utc::now();
-    let exp = now + Duration::hours(1);
-
-    let claims = IdTokenClaims {
-        iss: "http://localhost:4000".to_string(),
-        sub: sub.to_string(),
-        aud: "openlink-auth".to_string(),
-        exp: exp.timestamp(),
-        iat: now.timestamp(),
-        name: name.to_string(),
-        email: email.to_string(),
-        demonetwork_cid: sub.to_string(), 
-    };
-
-    let keys = KEYS.get().unwrap();
-    let header = Header {
-        kid: Some("mock-key-1".to_string()),
-        alg: Algorithm::RS256,
-        ..Default::default()
-    };
-
-    let id_token = encode(&header, &claims, &keys.encoding_key).unwrap();
-    
-    // Access token format consumed by openlink-auth
-    let access_token = format!("demonetwork_{}", sub); 
-
-    Json(json!({
-        "access_token": access_token, 
-        "id_token": id_token,
-        "token_type": "Bearer",
-        "expires_in": 3600
-    }))
+/// `uri` with `params` appended to its query string.
+pub fn with_query(uri: &str, params: &[(&str, &str)]) -> String {
+    let mut target = uri.to_string();
+    for (i, (key, value)) in params.iter().enumerate() {
+        let separator = if i == 0 && !uri.contains('?') { '?' } else { '&' };
+        target.push(separator);
+        target.push_str(key);
+        target.push('=');
+        target.push_str(&percent_encode(value));
+    }
+    target
 }
 
-fn base64_url_encode_bytes(input: &[u8]) -> String {
-    URL_SAFE_NO_PAD.encode(input)
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
 }
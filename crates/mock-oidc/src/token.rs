@@ -0,0 +1,358 @@
+//! Token endpoint, refresh tokens and `/userinfo`.
+
+use axum::{
+    extract::{Form, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use chrono::{Duration, Utc};
+use jsonwebtoken::{encode, Algorithm, Header};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use crate::authorize::verify_pkce;
+use crate::device::device_token;
+use crate::{AppState, ISSUER};
+
+/// Grant type of device code token requests.
+pub const DEVICE_CODE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// Lifetime of access and ID tokens in seconds.
+const TOKEN_TTL: i64 = 3600;
+
+/// Audience of ID tokens when the token request names no client.
+const DEFAULT_AUDIENCE: &str = "openlink-auth";
+
+/// User an access token was issued for.
+pub struct AccessGrant {
+    login: String,
+    expires_at: i64,
+}
+
+/// Login a refresh token renews. Refresh tokens are single-use: every
+/// refresh returns a new one.
+pub struct RefreshGrant {
+    login: String,
+    client_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct TokenRequest {
+    #[serde(default)]
+    grant_type: Option<String>,
+    #[serde(default)]
+    code: Option<String>,
+    #[serde(default)]
+    device_code: Option<String>,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    #[serde(default)]
+    client_id: Option<String>,
+    #[serde(default)]
+    redirect_uri: Option<String>,
+    #[serde(default)]
+    code_verifier: Option<String>,
+}
+
+/// `POST /token`
+pub async fn token(State(state): State<Arc<AppState>>, Form(req): Form<TokenRequest>) -> Response {
+    let injected = state.faults().take_token_error();
+    if let Some(error) = injected {
+        println!("MOCK-OIDC: Token Request answered with injected error '{}'", error);
+        return token_error(&error, "injected fault");
+    }
+    match req.grant_type.as_deref() {
+        Some(DEVICE_CODE_GRANT) => device_token(&state, req.device_code.as_deref().unwrap_or_default()),
+        Some("refresh_token") => refresh(&state, req),
+        Some("authorization_code") | None => authorization_code(&state, req),
+        Some(other) => token_error("unsupported_grant_type", &format!("unsupported grant type '{}'", other)),
+    }
+}
+
+fn authorization_code(state: &AppState, req: TokenRequest) -> Response {
+    let Some(code) = req.code else {
+        return token_error("invalid_request", "missing code");
+    };
+    println!("MOCK-OIDC: Token Request code='{}'", code);
+
+    let mut codes = state.codes.lock().unwrap();
+    let Some(issued) = codes.get_mut(&code) else {
+        drop(codes);
+        // Not issued by /authorize: the code itself names the user, so
+        // tools can log in as anyone without a browser.
+        return match state.users.find(&code) {
+            Some(user) if user.deny_consent || state.faults().deny_consent => {
+                token_error("access_denied", "the user denied the login")
+            }
+            Some(_) => issue_tokens(state, &code, None, req.client_id.as_deref()),
+            None => token_error("invalid_grant", "unknown authorization code"),
+        };
+    };
+    if issued.redeemed {
+        return token_error("invalid_grant", "authorization code already used");
+    }
+    issued.redeemed = true;
+    if issued.expires_at <= Utc::now().timestamp() {
+        return token_error("invalid_grant", "authorization code expired");
+    }
+    if let (Some(sent), Some(expected)) = (req.client_id.as_deref(), issued.client_id.as_deref()) {
+        if sent != expected {
+            return token_error("invalid_grant", "authorization code issued to another client");
+        }
+    }
+    if req.redirect_uri.as_deref().is_some_and(|uri| uri != issued.redirect_uri) {
+        return token_error("invalid_grant", "redirect_uri does not match the authorization request");
+    }
+    if let Err(e) = verify_pkce(issued.pkce.as_ref(), req.code_verifier.as_deref()) {
+        return token_error("invalid_grant", e);
+    }
+    let login = issued.login.clone();
+    let nonce = issued.nonce.clone();
+    let client_id = req.client_id.or_else(|| issued.client_id.clone());
+    drop(codes);
+    issue_tokens(state, &login, nonce.as_deref(), client_id.as_deref())
+}
+
+fn refresh(state: &AppState, req: TokenRequest) -> Response {
+    let Some(refresh_token) = req.refresh_token else {
+        return token_error("invalid_request", "missing refresh_token");
+    };
+    let Some(grant) = state.refresh_tokens.lock().unwrap().remove(&refresh_token) else {
+        return token_error("invalid_grant", "unknown or already used refresh token");
+    };
+    if let (Some(sent), Some(expected)) = (req.client_id.as_deref(), grant.client_id.as_deref()) {
+        if sent != expected {
+            return token_error("invalid_grant", "refresh token issued to another client");
+        }
+    }
+    println!("MOCK-OIDC: Refresh Token Request for '{}'", grant.login);
+    issue_tokens(state, &grant.login, None, grant.client_id.as_deref())
+}
+
+/// OAuth error response of the token endpoint.
+pub fn token_error(error: &str, description: &str) -> Response {
+    let status = match error {
+        "invalid_client" => StatusCode::UNAUTHORIZED,
+        "server_error" => StatusCode::INTERNAL_SERVER_ERROR,
+        "temporarily_unavailable" => StatusCode::SERVICE_UNAVAILABLE,
+        _ => StatusCode::BAD_REQUEST,
+    };
+    (status, Json(json!({ "error": error, "error_description": description }))).into_response()
+}
+
+/// Sign an ID token for the user logging in with `login` and issue an
+/// access token and a refresh token along with it.
+pub fn issue_tokens(state: &AppState, login: &str, nonce: Option<&str>, client_id: Option<&str>) -> Response {
+    let Some(user) = state.users.find(login) else {
+        return token_error("invalid_grant", "unknown user");
+    };
+
+    let mut now = Utc::now();
+    if state.faults().expired_id_tokens {
+        now -= Duration::hours(2);
+    }
+    let mut claims = user.claims();
+    claims.insert("iss".into(), json!(ISSUER));
+    claims.insert("aud".into(), json!(client_id.unwrap_or(DEFAULT_AUDIENCE)));
+    claims.insert("iat".into(), json!(now.timestamp()));
+    claims.insert("exp".into(), json!((now + Duration::seconds(TOKEN_TTL)).timestamp()));
+    if let Some(nonce) = nonce {
+        claims.insert("nonce".into(), json!(nonce));
+    }
+
+    let header = Header {
+        kid: Some("mock-key-1".to_string()),
+        alg: Algorithm::RS256,
+        ..Default::default()
+    };
+    let id_token = encode(&header, &claims, &state.keys.encoding_key).unwrap();
+
+    // Opaque tokens: the identity is only available from /userinfo
+    let access_token = uuid::Uuid::new_v4().simple().to_string();
+    let refresh_token = uuid::Uuid::new_v4().simple().to_string();
+    let issued_at = Utc::now().timestamp();
+    let mut access_tokens = state.access_tokens.lock().unwrap();
+    access_tokens.retain(|_, grant| grant.expires_at > issued_at);
+    access_tokens.insert(access_token.clone(), AccessGrant {
+        login: login.to_string(),
+        expires_at: issued_at + TOKEN_TTL,
+    });
+    state.refresh_tokens.lock().unwrap().insert(refresh_token.clone(), RefreshGrant {
+        login: login.to_string(),
+        client_id: client_id.map(str::to_string),
+    });
+
+    Json(json!({
+        "access_token": access_token,
+        "id_token": id_token,
+        "refresh_token": refresh_token,
+        "token_type": "Bearer",
+        "expires_in": TOKEN_TTL
+    }))
+    .into_response()
+}
+
+/// `GET /userinfo` — claims of the user an access token was issued for.
+pub async fn userinfo(State(state): State<Arc<AppState>>, headers: HeaderMap) -> Response {
+    let token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    let login = token.and_then(|token| {
+        let tokens = state.access_tokens.lock().unwrap();
+        tokens
+            .get(token)
+            .filter(|grant| grant.expires_at > Utc::now().timestamp())
+            .map(|grant| grant.login.clone())
+    });
+    match login.and_then(|login| state.users.find(&login)) {
+        Some(user) => Json(Value::Object(user.claims())).into_response(),
+        None => (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, r#"Bearer error="invalid_token""#)],
+        )
+            .into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authorize::AuthorizationCode;
+    use crate::users::Fixture;
+    use crate::OidcKeys;
+
+    const REDIRECT_URI: &str = "http://localhost:8080/callback";
+
+    fn state() -> AppState {
+        // A short key: tests only need signatures, not strong ones
+        AppState::new(OidcKeys::generate(1024), Fixture::builtin())
+    }
+
+    /// Issue a code for `PILOT` to `openlink-cli`, as `/authorize` would.
+    fn issue_code(state: &AppState, expires_in: i64) -> String {
+        let code = uuid::Uuid::new_v4().simple().to_string();
+        state.codes.lock().unwrap().insert(code.clone(), AuthorizationCode {
+            login: "PILOT".to_string(),
+            client_id: Some("openlink-cli".to_string()),
+            redirect_uri: REDIRECT_URI.to_string(),
+            nonce: Some("n-0S6_WzA2Mj".to_string()),
+            pkce: None,
+            expires_at: Utc::now().timestamp() + expires_in,
+            redeemed: false,
+        });
+        code
+    }
+
+    fn code_request(code: &str, client_id: &str, redirect_uri: &str) -> TokenRequest {
+        TokenRequest {
+            grant_type: Some("authorization_code".to_string()),
+            code: Some(code.to_string()),
+            device_code: None,
+            refresh_token: None,
+            client_id: Some(client_id.to_string()),
+            redirect_uri: Some(redirect_uri.to_string()),
+            code_verifier: None,
+        }
+    }
+
+    fn refresh_request(refresh_token: &str) -> TokenRequest {
+        TokenRequest {
+            grant_type: Some("refresh_token".to_string()),
+            code: None,
+            device_code: None,
+            refresh_token: Some(refresh_token.to_string()),
+            client_id: None,
+            redirect_uri: None,
+            code_verifier: None,
+        }
+    }
+
+    async fn body(response: Response) -> (StatusCode, Value) {
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    async fn assert_invalid_grant(response: Response, description: &str) {
+        let (status, body) = body(response).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "invalid_grant");
+        assert_eq!(body["error_description"], description);
+    }
+
+    #[tokio::test]
+    async fn authorization_code_is_single_use() {
+        let state = state();
+        let code = issue_code(&state, 60);
+        let (status, tokens) = body(authorization_code(&state, code_request(&code, "openlink-cli", REDIRECT_URI))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(tokens["id_token"].is_string());
+        assert!(tokens["refresh_token"].is_string());
+
+        let replay = authorization_code(&state, code_request(&code, "openlink-cli", REDIRECT_URI));
+        assert_invalid_grant(replay, "authorization code already used").await;
+    }
+
+    #[tokio::test]
+    async fn expired_authorization_code_is_refused() {
+        let state = state();
+        let code = issue_code(&state, 0);
+        let response = authorization_code(&state, code_request(&code, "openlink-cli", REDIRECT_URI));
+        assert_invalid_grant(response, "authorization code expired").await;
+    }
+
+    #[tokio::test]
+    async fn authorization_code_is_bound_to_client_and_redirect_uri() {
+        let state = state();
+        let code = issue_code(&state, 60);
+        let response = authorization_code(&state, code_request(&code, "another-client", REDIRECT_URI));
+        assert_invalid_grant(response, "authorization code issued to another client").await;
+
+        let code = issue_code(&state, 60);
+        let response = authorization_code(&state, code_request(&code, "openlink-cli", "http://evil.example/callback"));
+        assert_invalid_grant(response, "redirect_uri does not match the authorization request").await;
+
+        // A refused attempt still redeems the code
+        let retry = authorization_code(&state, code_request(&code, "openlink-cli", REDIRECT_URI));
+        assert_invalid_grant(retry, "authorization code already used").await;
+    }
+
+    #[tokio::test]
+    async fn authorization_code_checks_the_code_verifier() {
+        let state = state();
+        let code = issue_code(&state, 60);
+        let mut request = code_request(&code, "openlink-cli", REDIRECT_URI);
+        request.code_verifier = Some("unexpected".to_string());
+        let response = authorization_code(&state, request);
+        assert_invalid_grant(response, "code_verifier sent for a code issued without code_challenge").await;
+    }
+
+    #[tokio::test]
+    async fn refresh_token_is_single_use() {
+        let state = state();
+        let code = issue_code(&state, 60);
+        let (_, tokens) = body(authorization_code(&state, code_request(&code, "openlink-cli", REDIRECT_URI))).await;
+        let first = tokens["refresh_token"].as_str().unwrap();
+
+        let (status, renewed) = body(refresh(&state, refresh_request(first))).await;
+        assert_eq!(status, StatusCode::OK);
+        let second = renewed["refresh_token"].as_str().unwrap();
+        assert_ne!(first, second);
+
+        assert_invalid_grant(refresh(&state, refresh_request(first)), "unknown or already used refresh token").await;
+        let (status, _) = body(refresh(&state, refresh_request(second))).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn refresh_token_is_bound_to_its_client() {
+        let state = state();
+        let code = issue_code(&state, 60);
+        let (_, tokens) = body(authorization_code(&state, code_request(&code, "openlink-cli", REDIRECT_URI))).await;
+        let mut request = refresh_request(tokens["refresh_token"].as_str().unwrap());
+        request.client_id = Some("another-client".to_string());
+        assert_invalid_grant(refresh(&state, request), "refresh token issued to another client").await;
+    }
+}
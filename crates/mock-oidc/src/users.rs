@@ -0,0 +1,200 @@
+//! Users the provider logs in.
+//!
+//! Built in are `PILOT`, `ATC` and `ATC_EGLL`, and any other login code
+//! becomes a user of its own. A fixture file (`MOCK_OIDC_USERS`, TOML)
+//! replaces them with its own users, their ratings, groups and extra
+//! claims, and sets the faults in effect at startup.
+
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::faults::Faults;
+
+/// Contents of a fixture file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Fixture {
+    /// Accept unknown login codes as users named after them.
+    #[serde(default = "default_dynamic_users")]
+    pub dynamic_users: bool,
+    #[serde(default)]
+    pub users: BTreeMap<String, User>,
+    #[serde(default)]
+    pub faults: Faults,
+}
+
+fn default_dynamic_users() -> bool {
+    true
+}
+
+impl Fixture {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        toml::from_str(&contents).map_err(|e| e.to_string())
+    }
+
+    /// The users known without a fixture file.
+    pub fn builtin() -> Self {
+        let user = |sub: &str, name: &str, email: &str, rating: &str| User {
+            sub: sub.to_string(),
+            name: Some(name.to_string()),
+            email: Some(email.to_string()),
+            rating: Some(rating.to_string()),
+            groups: Vec::new(),
+            claims: Map::new(),
+            deny_consent: false,
+        };
+        let users = BTreeMap::from([
+            ("PILOT".to_string(), user("100000", "Captain Smith", "pilot@demonetwork.net", "OBS")),
+            ("ATC".to_string(), user("888888", "Generic ATC", "atc@demonetwork.net", "C1")),
+            ("ATC_EGLL".to_string(), user("777777", "Generic ATC", "atc@demonetwork.net", "C1")),
+        ]);
+        Self {
+            dynamic_users: true,
+            users,
+            faults: Faults::default(),
+        }
+    }
+}
+
+/// Users by login code.
+pub struct Users {
+    dynamic_users: bool,
+    known: BTreeMap<String, User>,
+}
+
+impl Users {
+    pub fn new(dynamic_users: bool, known: BTreeMap<String, User>) -> Self {
+        Self { dynamic_users, known }
+    }
+
+    /// The user logging in with `login`.
+    pub fn find(&self, login: &str) -> Option<User> {
+        if let Some(user) = self.known.get(login) {
+            return Some(user.clone());
+        }
+        if !self.dynamic_users || login.is_empty() {
+            return None;
+        }
+        // Dynamic Fallback: use the provided code as the identity
+        // This allows the CLI to request tokens for any network address,
+        // acting as pilot or controller.
+        Some(User {
+            sub: login.to_string(),
+            name: Some(format!("User {}", login)),
+            email: Some(format!("{}@demonetwork.net", login.to_lowercase())),
+            rating: Some("C1".to_string()),
+            groups: Vec::new(),
+            claims: Map::new(),
+            deny_consent: false,
+        })
+    }
+
+    /// Login codes of the known users, for the login pages.
+    pub fn logins(&self) -> impl Iterator<Item = &str> {
+        self.known.keys().map(String::as_str)
+    }
+}
+
+/// A user of the fixture file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct User {
+    /// Subject, also sent as `demonetwork_cid`.
+    pub sub: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub email: Option<String>,
+    /// Controller rating (`demonetwork_rating`), mapped to OpenLink roles by openlink-auth.
+    #[serde(default)]
+    pub rating: Option<String>,
+    /// Group memberships (`groups`), for networks mapping roles from groups.
+    #[serde(default)]
+    pub groups: Vec<String>,
+    /// Further claims, overriding the ones above.
+    #[serde(default)]
+    pub claims: Map<String, Value>,
+    /// Refuse consent: logins as this user are denied.
+    #[serde(default)]
+    pub deny_consent: bool,
+}
+
+impl User {
+    /// Identity claims of the user, for the `id_token` and `/userinfo`.
+    pub fn claims(&self) -> Map<String, Value> {
+        let mut claims = Map::new();
+        claims.insert("sub".into(), json!(self.sub));
+        claims.insert("name".into(), json!(self.name.as_deref().unwrap_or(&self.sub)));
+        if let Some(ref email) = self.email {
+            claims.insert("email".into(), json!(email));
+        }
+        claims.insert("demonetwork_cid".into(), json!(self.sub));
+        if let Some(ref rating) = self.rating {
+            claims.insert("demonetwork_rating".into(), json!(rating));
+        }
+        if !self.groups.is_empty() {
+            claims.insert("groups".into(), json!(self.groups));
+        }
+        claims.extend(self.claims.clone());
+        claims
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example_fixture_parses() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("users.example.toml");
+        let fixture = Fixture::load(&path).unwrap();
+        assert!(fixture.dynamic_users);
+        let logins: Vec<_> = fixture.users.keys().map(String::as_str).collect();
+        assert_eq!(logins, ["AFRV_ATC", "ATC", "PILOT", "REFUSER", "SUPERVISOR"]);
+        assert!(fixture.users["REFUSER"].deny_consent);
+        assert_eq!(fixture.users["AFRV_ATC"].groups, ["pilots", "atc"]);
+        assert_eq!(fixture.faults.code_ttl_seconds, 60);
+
+        let claims = fixture.users["SUPERVISOR"].claims();
+        assert_eq!(claims["demonetwork_rating"], "SUP");
+        assert_eq!(claims["locale"], "fr");
+    }
+
+    #[test]
+    fn fixture_defaults() {
+        let fixture: Fixture = toml::from_str("[users.X]\nsub = \"1\"").unwrap();
+        assert!(fixture.dynamic_users);
+        let claims = fixture.users["X"].claims();
+        assert_eq!(claims["name"], "1");
+        assert_eq!(claims["demonetwork_cid"], "1");
+        assert!(!claims.contains_key("email"));
+    }
+
+    #[test]
+    fn unknown_fixture_keys_are_refused() {
+        for fixture in [
+            "dynamic_user = false",
+            "[users.X]\nsub = \"1\"\nratting = \"C1\"",
+            "[faults]\nlatency = 5",
+        ] {
+            assert!(toml::from_str::<Fixture>(fixture).is_err(), "{fixture}");
+        }
+        assert!(toml::from_str::<Fixture>("[users.X]\nname = \"no sub\"").is_err());
+    }
+
+    #[test]
+    fn unknown_logins_follow_dynamic_users() {
+        let known = Fixture::builtin().users;
+        let dynamic = Users::new(true, known.clone());
+        assert_eq!(dynamic.find("PILOT").unwrap().sub, "100000");
+        assert_eq!(dynamic.find("AFR123").unwrap().sub, "AFR123");
+        assert!(dynamic.find("").is_none());
+
+        let fixed = Users::new(false, known);
+        assert!(fixed.find("PILOT").is_some());
+        assert!(fixed.find("AFR123").is_none());
+    }
+}
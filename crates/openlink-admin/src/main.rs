@@ -0,0 +1,105 @@
+//! OpenLink admin CLI — wraps the server's admin HTTP API.
+
+use anyhow::{Result, bail};
+use clap::{Parser, Subcommand};
+use reqwest::{Method, RequestBuilder};
+use serde_json::{Value, json};
+
+#[derive(Parser, Debug)]
+#[command(name = "openlink-admin")]
+#[command(about = "Inspect and repair sessions and stations on a running OpenLink server")]
+struct Args {
+    /// Base URL of the server admin API.
+    #[arg(long, env = "OPENLINK_ADMIN_URL", default_value = "http://127.0.0.1:9465")]
+    url: String,
+
+    /// Bearer token (the server's `ADMIN_TOKEN`).
+    #[arg(long, env = "OPENLINK_ADMIN_TOKEN", hide_env_values = true)]
+    token: String,
+
+    #[arg(long, default_value = "demonetwork")]
+    network_id: String,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// List CPDLC sessions.
+    Sessions {
+        /// Only sessions involving this callsign (aircraft or station).
+        #[arg(long)]
+        callsign: Option<String>,
+    },
+    /// List station registry entries.
+    Stations,
+    /// Terminate the connection between an aircraft and a station.
+    Terminate { aircraft: String, station: String },
+    /// Delete an aircraft's session.
+    Purge { aircraft: String },
+    /// Force the station holding a callsign offline.
+    Offline { callsign: String },
+    /// Resend session snapshots to the station holding a callsign.
+    Resync { callsign: String },
+}
+
+struct AdminClient {
+    http: reqwest::Client,
+    base: String,
+    token: String,
+}
+
+impl AdminClient {
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        self.http
+            .request(method, format!("{}{path}", self.base))
+            .bearer_auth(&self.token)
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<Value> {
+        let response = request.send().await?;
+        let status = response.status();
+        let body: Value = response.json().await.unwrap_or(Value::Null);
+        if !status.is_success() {
+            let message = body["error"].as_str().unwrap_or("no details");
+            bail!("{status}: {message}");
+        }
+        Ok(body)
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    let client = AdminClient {
+        http: reqwest::Client::new(),
+        base: format!("{}/admin/v1/{}", args.url.trim_end_matches('/'), args.network_id),
+        token: args.token,
+    };
+
+    let request = match &args.command {
+        Command::Sessions { callsign } => {
+            let request = client.request(Method::GET, "/sessions");
+            match callsign {
+                Some(callsign) => request.query(&[("callsign", callsign)]),
+                None => request,
+            }
+        }
+        Command::Stations => client.request(Method::GET, "/stations"),
+        Command::Terminate { aircraft, station } => client
+            .request(Method::POST, &format!("/sessions/{aircraft}/terminate"))
+            .json(&json!({ "station": station })),
+        Command::Purge { aircraft } => client.request(Method::DELETE, &format!("/sessions/{aircraft}")),
+        Command::Offline { callsign } => {
+            client.request(Method::POST, &format!("/stations/{callsign}/offline"))
+        }
+        Command::Resync { callsign } => {
+            client.request(Method::POST, &format!("/stations/{callsign}/resync"))
+        }
+    };
+
+    let body = client.send(request).await?;
+    println!("{}", serde_json::to_string_pretty(&body)?);
+    Ok(())
+}
@@ -0,0 +1,180 @@
+//! Admin HTTP API.
+//!
+//! Served next to the public routes when `AUTH_ADMIN_TOKEN` is set. Every
+//! request must carry `Authorization: Bearer {AUTH_ADMIN_TOKEN}`.
+//!
+//! | Method | Path                              | Action |
+//! |--------|-----------------------------------|--------|
+//! | `POST` | `/admin/v1/{network}/revocations` | Revoke a user's credentials (`{"cid": "100000"}` or `{"user_nkey_public": "U…"}`, optional `reason`) |
+//! | `GET`  | `/admin/v1/{network}/revocations` | List the revocations in force |
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::extract::{ConnectInfo, Path, Request, State};
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use openlink_models::NetworkId;
+use openlink_sdk::Revocation;
+use serde::Deserialize;
+use utoipa::ToSchema;
+use subtle::ConstantTimeEq;
+use tracing::info;
+
+use crate::error::{AuthError, ErrorBody};
+use crate::revocation::RevocationStore;
+use crate::session::unix_now;
+use crate::{AUDIT, AppState};
+
+/// Body of `POST /admin/v1/{network}/revocations`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub(crate) struct RevokeRequest {
+    /// CID whose credentials are revoked.
+    #[serde(default)]
+    cid: Option<String>,
+    /// User NKey whose credentials are revoked.
+    #[serde(default)]
+    user_nkey_public: Option<String>,
+    /// Why, for the audit trail.
+    #[serde(default)]
+    reason: String,
+}
+
+/// Admin routes, guarded by the admin token.
+pub fn router(state: Arc<AppState>) -> Router<Arc<AppState>> {
+    Router::new()
+        .route(
+            "/admin/v1/{network}/revocations",
+            get(list_revocations).post(revoke),
+        )
+        .layer(middleware::from_fn_with_state(state, require_token))
+}
+
+async fn require_token(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+    let token = state.admin_token.as_deref().unwrap_or_default();
+    if authorized(request.headers(), token) {
+        next.run(request).await
+    } else {
+        let error = "missing or invalid admin token".to_string();
+        (StatusCode::UNAUTHORIZED, Json(ErrorBody { error })).into_response()
+    }
+}
+
+fn authorized(headers: &HeaderMap, token: &str) -> bool {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|presented| {
+            !token.is_empty() && bool::from(presented.trim().as_bytes().ct_eq(token.as_bytes()))
+        })
+}
+
+/// The revocation store, for a network the service serves.
+fn store<'s>(state: &'s AppState, network: &NetworkId) -> Result<&'s RevocationStore, AuthError> {
+    if state.provider(network).is_none() {
+        return Err(AuthError::UnknownNetwork(network.to_string()));
+    }
+    state
+        .revocations
+        .as_ref()
+        .ok_or_else(|| AuthError::RevocationStore("not connected to NATS".into()))
+}
+
+/// `POST /admin/v1/{network}/revocations` — revoke every JWT issued so far
+/// to a user NKey or CID, and close their refresh sessions.
+#[utoipa::path(
+    post,
+    path = "/admin/v1/{network}/revocations",
+    tag = "admin",
+    params(("network" = String, Path, description = "Network key")),
+    request_body = RevokeRequest,
+    security(("admin_token" = [])),
+    responses(
+        (status = 201, description = "Credentials revoked", body = crate::openapi::RevocationSchema),
+        (status = 400, description = "Unknown network, or neither `cid` nor `user_nkey_public`", body = ErrorBody),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorBody),
+        (status = 502, description = "Revocation store unreachable", body = ErrorBody),
+    )
+)]
+pub(crate) async fn revoke(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    Path(network): Path<String>,
+    Json(req): Json<RevokeRequest>,
+) -> Result<(StatusCode, Json<Revocation>), AuthError> {
+    let network = NetworkId::new(&network);
+    let store = store(&state, &network)?;
+    if req.cid.is_none() && req.user_nkey_public.is_none() {
+        return Err(AuthError::InvalidRequest(
+            "cid or user_nkey_public is required".into(),
+        ));
+    }
+
+    let owner = state
+        .sessions
+        .revoke(&network, req.cid.as_deref(), req.user_nkey_public.as_deref());
+    let revocation = Revocation {
+        cid: req.cid.or(owner),
+        user_nkey_public: req.user_nkey_public,
+        revoked_at: unix_now(),
+        reason: req.reason,
+    };
+    store.publish(&network, &revocation).await?;
+
+    info!(
+        target: AUDIT,
+        %peer,
+        network = %network,
+        cid = revocation.cid.as_deref().unwrap_or("-"),
+        user_nkey = revocation.user_nkey_public.as_deref().unwrap_or("-"),
+        reason = %revocation.reason,
+        "credentials revoked"
+    );
+    Ok((StatusCode::CREATED, Json(revocation)))
+}
+
+/// `GET /admin/v1/{network}/revocations` — list the revocations in force.
+#[utoipa::path(
+    get,
+    path = "/admin/v1/{network}/revocations",
+    tag = "admin",
+    params(("network" = String, Path, description = "Network key")),
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "Revocations in force", body = Vec<crate::openapi::RevocationSchema>),
+        (status = 400, description = "Unknown network", body = ErrorBody),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorBody),
+        (status = 502, description = "Revocation store unreachable", body = ErrorBody),
+    )
+)]
+pub(crate) async fn list_revocations(
+    State(state): State<Arc<AppState>>,
+    Path(network): Path<String>,
+) -> Result<Json<Vec<Revocation>>, AuthError> {
+    let network = NetworkId::new(&network);
+    Ok(Json(store(&state, &network)?.list(&network).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_authorization(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn bearer_token_must_match() {
+        assert!(authorized(&with_authorization("Bearer s3cret"), "s3cret"));
+        assert!(!authorized(&with_authorization("Bearer wrong"), "s3cret"));
+        assert!(!authorized(&with_authorization("s3cret"), "s3cret"));
+        assert!(!authorized(&HeaderMap::new(), "s3cret"));
+        assert!(!authorized(&with_authorization("Bearer "), ""));
+    }
+}
@@ -0,0 +1,273 @@
+//! Audit log of issued JWTs.
+//!
+//! Every JWT the service signs — user logins, refreshes, device logins,
+//! servers and service accounts — is recorded as one JSON object: what was
+//! granted is read back from the signed claims (CID, NKey, `jti`, expiry,
+//! tags with the network and role, publish/subscribe permissions), along
+//! with the grant, the authenticated server or account and the request
+//! source. Records go to the `openlink_auth::audit` tracing target and,
+//! when `audit_log` is configured, are appended to that file as JSON Lines.
+//!
+//! The file is opened in append mode and never rewritten; rotate it with
+//! copy-and-truncate or by renaming it and restarting the service.
+
+use std::fs::File;
+use std::io::Write;
+use std::net::{IpAddr, SocketAddr};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use axum::extract::{ConnectInfo, FromRequestParts};
+use axum::http::header;
+use axum::http::request::Parts;
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use openlink_models::NetworkId;
+use serde::Serialize;
+use serde_json::Value;
+use tracing::info;
+
+use crate::error::AuthError;
+use crate::session::unix_now;
+use crate::{AUDIT, AppState};
+
+/// Where a request came from.
+#[derive(Debug, Clone, Serialize)]
+pub struct RequestSource {
+    /// Client IP: the peer address, or the last `X-Forwarded-For` entry
+    /// when the service trusts its reverse proxy.
+    pub ip: IpAddr,
+    /// Peer address of the connection.
+    pub peer: SocketAddr,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+}
+
+impl RequestSource {
+    fn from_parts(parts: &Parts, trust_forwarded_for: bool) -> Option<Self> {
+        let ConnectInfo(peer) = parts.extensions.get::<ConnectInfo<SocketAddr>>()?;
+        let forwarded = trust_forwarded_for
+            .then(|| parts.headers.get_all("x-forwarded-for").iter().next_back())
+            .flatten()
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.rsplit(',').next())
+            .and_then(|ip| ip.trim().parse().ok());
+        Some(Self {
+            ip: forwarded.unwrap_or(peer.ip()),
+            peer: *peer,
+            user_agent: parts
+                .headers
+                .get(header::USER_AGENT)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string),
+        })
+    }
+}
+
+impl FromRequestParts<Arc<AppState>> for RequestSource {
+    type Rejection = AuthError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &Arc<AppState>) -> Result<Self, Self::Rejection> {
+        Self::from_parts(parts, state.rate_limits.trust_forwarded_for)
+            .ok_or_else(|| AuthError::InvalidRequest("no peer address".into()))
+    }
+}
+
+/// How a JWT was obtained.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Grant {
+    /// `POST /exchange`.
+    Exchange,
+    /// `POST /device/token`.
+    Device,
+    /// `POST /refresh`.
+    Refresh,
+    /// `POST /exchange-server`.
+    Server,
+    /// `POST /exchange-service`.
+    ServiceAccount,
+}
+
+/// One line of the audit log.
+#[derive(Debug, Serialize)]
+struct JwtIssued<'a> {
+    /// Unix time of the issuance.
+    time: u64,
+    event: &'static str,
+    grant: Grant,
+    network: &'a str,
+    /// Authenticated server or service account name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    principal: Option<&'a str>,
+    /// JWT `name`: the CID, or `openlink-server-{network}`.
+    cid: &'a Value,
+    /// JWT `sub`: the NKey the JWT is issued to.
+    user_nkey: &'a Value,
+    jti: &'a Value,
+    expires_at: &'a Value,
+    tags: &'a Value,
+    permissions: &'a Value,
+    source: &'a RequestSource,
+}
+
+/// Records issued JWTs.
+pub struct AuditLog {
+    file: Option<Mutex<File>>,
+}
+
+impl AuditLog {
+    /// Append to the file at `path`, created owner-readable only when
+    /// missing; without a path, records only go to tracing.
+    pub fn open(path: Option<&Path>) -> std::io::Result<Self> {
+        let file = match path {
+            Some(path) => {
+                let mut options = std::fs::OpenOptions::new();
+                options.append(true).create(true);
+                #[cfg(unix)]
+                {
+                    use std::os::unix::fs::OpenOptionsExt;
+                    options.mode(0o600);
+                }
+                Some(Mutex::new(options.open(path)?))
+            }
+            None => None,
+        };
+        Ok(Self { file })
+    }
+
+    /// Record the issuance of `jwt`.
+    ///
+    /// Fails when the record cannot be written: the JWT is then not handed
+    /// out, so nothing is granted without a trace.
+    pub fn jwt_issued(
+        &self,
+        grant: Grant,
+        jwt: &str,
+        network: &NetworkId,
+        principal: Option<&str>,
+        source: &RequestSource,
+    ) -> Result<(), AuthError> {
+        let claims = decode_claims(jwt)?;
+        let record = JwtIssued {
+            time: unix_now(),
+            event: "jwt_issued",
+            grant,
+            network: network.as_str(),
+            principal,
+            cid: &claims["name"],
+            user_nkey: &claims["sub"],
+            jti: &claims["jti"],
+            expires_at: &claims["exp"],
+            tags: &claims["nats"]["tags"],
+            permissions: &claims["nats"]["permissions"],
+            source,
+        };
+        let line = serde_json::to_string(&record)?;
+
+        info!(
+            target: AUDIT,
+            ?grant,
+            network = %network,
+            principal = principal.unwrap_or("-"),
+            cid = %record.cid,
+            jti = %record.jti,
+            expires_at = %record.expires_at,
+            ip = %source.ip,
+            "JWT issued"
+        );
+        if let Some(file) = &self.file {
+            let mut file = file.lock().expect("audit log lock poisoned");
+            file.write_all(format!("{line}\n").as_bytes())
+                .and_then(|()| file.flush())
+                .map_err(|e| AuthError::AuditLog(e.to_string()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Claims of a JWT the service signed itself.
+fn decode_claims(jwt: &str) -> Result<Value, AuthError> {
+    let body = jwt
+        .split('.')
+        .nth(1)
+        .ok_or_else(|| AuthError::AuditLog("malformed JWT".into()))?;
+    let body = URL_SAFE_NO_PAD
+        .decode(body)
+        .map_err(|e| AuthError::AuditLog(e.to_string()))?;
+    Ok(serde_json::from_slice(&body)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Request;
+    use nkeys::KeyPair;
+    use openlink_models::UserRole;
+
+    fn parts(forwarded_for: &[&str]) -> Parts {
+        let mut request = Request::builder().header(header::USER_AGENT, "openlink-sdk");
+        for value in forwarded_for {
+            request = request.header("x-forwarded-for", *value);
+        }
+        let (mut parts, ()) = request.body(()).unwrap().into_parts();
+        parts
+            .extensions
+            .insert(ConnectInfo(SocketAddr::from(([10, 0, 0, 1], 40000))));
+        parts
+    }
+
+    #[test]
+    fn forwarded_for_is_only_used_when_trusted() {
+        let parts = parts(&["203.0.113.9", "198.51.100.1, 192.0.2.7"]);
+        let direct = RequestSource::from_parts(&parts, false).unwrap();
+        assert_eq!(direct.ip, IpAddr::from([10, 0, 0, 1]));
+        assert_eq!(direct.user_agent.as_deref(), Some("openlink-sdk"));
+        let proxied = RequestSource::from_parts(&parts, true).unwrap();
+        assert_eq!(proxied.ip, IpAddr::from([192, 0, 2, 7]));
+        assert_eq!(proxied.peer, SocketAddr::from(([10, 0, 0, 1], 40000)));
+    }
+
+    #[test]
+    fn issued_jwts_are_appended_with_their_grants() {
+        let path = std::env::temp_dir().join(format!("openlink-auth-audit-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let log = AuditLog::open(Some(&path)).unwrap();
+        let account = KeyPair::new_account();
+        let network = NetworkId::new("demonetwork");
+        let jwt = crate::jwt::sign_user_jwt(&account, "UCLIENT", "100000", &network, UserRole::Pilot, 600)
+            .unwrap();
+        let source = RequestSource::from_parts(&parts(&[]), false).unwrap();
+
+        log.jwt_issued(Grant::Exchange, &jwt, &network, None, &source).unwrap();
+        log.jwt_issued(Grant::Refresh, &jwt, &network, None, &source).unwrap();
+        drop(log);
+        let log = AuditLog::open(Some(&path)).unwrap();
+        log.jwt_issued(Grant::ServiceAccount, &jwt, &network, Some("atc-bot"), &source).unwrap();
+
+        let records: Vec<Value> = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0]["grant"], "exchange");
+        assert_eq!(records[0]["cid"], "100000");
+        assert_eq!(records[0]["user_nkey"], "UCLIENT");
+        assert_eq!(records[0]["network"], "demonetwork");
+        assert_eq!(records[0]["source"]["ip"], "10.0.0.1");
+        assert!(records[0]["expires_at"].is_u64());
+        assert!(
+            records[0]["permissions"]["publish"]["allow"]
+                .as_array()
+                .unwrap()
+                .contains(&"openlink.v1.demonetwork.outbox.100000".into())
+        );
+        assert!(records[0]["tags"].as_array().unwrap().contains(&"openlink-role:pilot".into()));
+        assert_eq!(records[1]["grant"], "refresh");
+        assert_eq!(records[2]["grant"], "service_account");
+        assert_eq!(records[2]["principal"], "atc-bot");
+        assert!(records[0].get("principal").is_none());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
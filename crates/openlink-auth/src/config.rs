@@ -1,96 +1,1192 @@
 //! Auth service configuration.
 //!
-//! Maps each [`NetworkId`] to its OIDC provider parameters.  The mapping
-//! is built from environment variables at startup and injected into Axum
-//! handlers via [`axum::extract::State`].
+//! Maps each [`NetworkId`] to its OIDC provider parameters, and lists the
+//! OpenLink servers allowed to obtain network-wide JWTs and the service
+//! accounts allowed to obtain user JWTs without a login. All are declared
+//! in an optional TOML file (`AUTH_CONFIG`) and overlaid with environment
+//! variables, so one openlink-auth instance can front several communities
+//! with different identity providers:
+//!
+//! ```toml
+//! listen_port = 3001
+//!
+//! [networks.demonetwork]
+//! issuer = "http://localhost:4000"
+//! client_id = "openlink-auth"
+//! scopes = ["openid", "profile"]
+//! jwt_ttl_seconds = 3600
+//! refresh_ttl_seconds = 86400
+//!
+//! [networks.demonetwork.claims]
+//! cid = "demonetwork_cid"
+//!
+//! [networks.demonetwork.roles]
+//! claim = "demonetwork_rating"
+//! controller = ["C1", "C3"]
+//!
+//! [servers.paris-1]
+//! public_key = "UCRH6JUVKV5YRNFAOYKYTVYLHGDXMJDWZP2O3PA3NRO4EM3JEY6TTTU3"
+//! networks = ["demonetwork"]
+//!
+//! [service_accounts.atc-bot]
+//! network = "demonetwork"
+//! cid = "900001"
+//! callsigns = ["LFPG_TWR"]
+//! public_key = "UA4EDEL63JERZCEFSCP3RI76BW2NVZLPSG5QW52DZVXDTDZ3BW2HGWIN"
+//!
+//! [rate_limits]
+//! ip_per_minute = 60
+//! identity_per_minute = 20
+//! ```
+//!
+//! The result is validated before use; [`AppConfig::load`] is called again
+//! on `SIGHUP` to reload the networks, servers and service accounts without
+//! a restart. The listen port, rate limits and audit log are only read at
+//! startup.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 
-use openlink_models::NetworkId;
+use openlink_models::{NetworkId, UserRole};
+use serde::{Deserialize, Deserializer};
+
+const DEFAULT_LISTEN_PORT: u16 = 3001;
+const DEFAULT_NATS_URL: &str = "nats://localhost:4222";
+const DEFAULT_NETWORK: &str = "demonetwork";
+const DEFAULT_ISSUER: &str = "http://localhost:4000";
+const DEFAULT_JWT_TTL_SECONDS: u64 = 3600;
+const MIN_JWT_TTL_SECONDS: u64 = 60;
+const MAX_JWT_TTL_SECONDS: u64 = 86_400;
+/// Longest user JWT lifetime while the admin API is enabled. NATS keeps
+/// accepting a revoked JWT until it expires (the service cannot add it to
+/// the account's revocations, signed by the operator), so this bounds how
+/// long revoked credentials still connect.
+const MAX_REVOCABLE_JWT_TTL_SECONDS: u64 = 3600;
+const DEFAULT_REFRESH_TTL_SECONDS: u64 = 86_400;
+const MAX_REFRESH_TTL_SECONDS: u64 = 30 * 86_400;
+const DEFAULT_SERVER_NAME: &str = "default";
+const DEFAULT_SERVER_SECRET: &str = "openlink-dev-secret";
+const DEFAULT_SERVER_JWT_TTL_SECONDS: u64 = 3600;
+const DEFAULT_IP_REQUESTS_PER_MINUTE: u32 = 60;
+const DEFAULT_IDENTITY_REQUESTS_PER_MINUTE: u32 = 20;
+
+/// Errors raised while loading or validating the configuration.
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    /// The configuration file could not be read.
+    #[error("cannot read {path}: {source}")]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    /// The configuration file is not valid TOML for [`ConfigFile`].
+    #[error("cannot parse configuration: {0}")]
+    Parse(#[from] toml::de::Error),
+
+    /// One or more values are invalid.
+    #[error("invalid configuration: {}", .0.join("; "))]
+    Invalid(Vec<String>),
+}
+
+/// Mapping of `id_token` claims to OpenLink identity fields.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClaimMappings {
+    /// Claim holding the user's CID (default `sub`).
+    #[serde(default = "default_cid_claim")]
+    pub cid: String,
+}
+
+impl Default for ClaimMappings {
+    fn default() -> Self {
+        Self {
+            cid: default_cid_claim(),
+        }
+    }
+}
+
+/// Mapping of `id_token` claim values to the [`UserRole`]s a user may take.
+///
+/// A user is granted the `default` roles, plus each role one of whose values
+/// appears in `claim` (a string, a number, or an array of them). `observer`
+/// is always granted.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RoleMappings {
+    /// Claim holding the user's rating or groups.
+    #[serde(default)]
+    pub claim: Option<String>,
+    /// Roles granted to every user of the network.
+    #[serde(default = "default_roles", deserialize_with = "deserialize_roles")]
+    pub default: Vec<UserRole>,
+    /// Claim values granting [`UserRole::Controller`].
+    #[serde(default)]
+    pub controller: Vec<String>,
+    /// Claim values granting [`UserRole::Bot`].
+    #[serde(default)]
+    pub bot: Vec<String>,
+    /// Claim values granting [`UserRole::Pilot`].
+    #[serde(default)]
+    pub pilot: Vec<String>,
+}
+
+impl Default for RoleMappings {
+    fn default() -> Self {
+        Self {
+            claim: None,
+            default: default_roles(),
+            controller: Vec::new(),
+            bot: Vec::new(),
+            pilot: Vec::new(),
+        }
+    }
+}
+
+impl RoleMappings {
+    /// Claim values granting `role`.
+    pub fn values_for(&self, role: UserRole) -> &[String] {
+        match role {
+            UserRole::Controller => &self.controller,
+            UserRole::Bot => &self.bot,
+            UserRole::Pilot => &self.pilot,
+            UserRole::Observer => &[],
+        }
+    }
+}
 
 /// OIDC provider parameters for a single network.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct OidcProviderConfig {
-    /// Base URL of the OIDC token endpoint (e.g. `http://localhost:4000/token`).
-    pub token_url: String,
+    /// Issuer URL; the discovery document is read from
+    /// `{issuer}/.well-known/openid-configuration`.
+    #[serde(default)]
+    pub issuer: String,
+    /// Client ID registered with the provider, also the expected `aud`.
+    #[serde(default = "default_client_id")]
+    pub client_id: String,
+    /// Client secret, for providers that require confidential clients.
+    #[serde(default)]
+    pub client_secret: Option<String>,
+    /// Override of the discovered token endpoint.
+    #[serde(default)]
+    pub token_url: Option<String>,
+    /// Override of the discovered device authorization endpoint.
+    #[serde(default)]
+    pub device_authorization_url: Option<String>,
+    /// Scopes clients must request in the authorization request.
+    #[serde(default = "default_scopes")]
+    pub scopes: Vec<String>,
+    /// Claim mappings.
+    #[serde(default)]
+    pub claims: ClaimMappings,
+    /// Role mappings.
+    #[serde(default)]
+    pub roles: RoleMappings,
+    /// Lifetime of the NATS user JWTs issued for this network.
+    #[serde(default = "default_jwt_ttl_seconds")]
+    pub jwt_ttl_seconds: u64,
+    /// How long a login can be renewed through `POST /refresh` before the
+    /// user must authenticate again; `0` issues no refresh tokens.
+    #[serde(default = "default_refresh_ttl_seconds")]
+    pub refresh_ttl_seconds: u64,
 }
 
-/// Global configuration shared across all handlers.
+impl Default for OidcProviderConfig {
+    fn default() -> Self {
+        Self {
+            issuer: String::new(),
+            client_id: default_client_id(),
+            client_secret: None,
+            token_url: None,
+            device_authorization_url: None,
+            scopes: default_scopes(),
+            claims: ClaimMappings::default(),
+            roles: RoleMappings::default(),
+            jwt_ttl_seconds: default_jwt_ttl_seconds(),
+            refresh_ttl_seconds: default_refresh_ttl_seconds(),
+        }
+    }
+}
+
+impl OidcProviderConfig {
+    /// Apply `OIDC_{NETWORK}_*` overrides.
+    fn overlay_env(&mut self, prefix: &str, env: &impl Fn(&str) -> Option<String>) -> Vec<String> {
+        let var = |name: &str| env(&format!("{prefix}_{name}")).filter(|v| !v.is_empty());
+        let mut problems = Vec::new();
+        if let Some(issuer) = var("ISSUER") {
+            self.issuer = issuer;
+        }
+        if let Some(client_id) = var("CLIENT_ID") {
+            self.client_id = client_id;
+        }
+        if let Some(secret) = var("CLIENT_SECRET") {
+            self.client_secret = Some(secret);
+        }
+        if let Some(token_url) = var("TOKEN_URL") {
+            self.token_url = Some(token_url);
+        }
+        if let Some(url) = var("DEVICE_AUTHORIZATION_URL") {
+            self.device_authorization_url = Some(url);
+        }
+        if let Some(scopes) = var("SCOPES") {
+            self.scopes = split_list(&scopes).map(str::to_string).collect();
+        }
+        if let Some(claim) = var("CID_CLAIM") {
+            self.claims.cid = claim;
+        }
+        if let Some(claim) = var("ROLE_CLAIM") {
+            self.roles.claim = Some(claim);
+        }
+        if let Some(roles) = var("DEFAULT_ROLES") {
+            match split_list(&roles).map(|r| r.parse()).collect() {
+                Ok(roles) => self.roles.default = roles,
+                Err(_) => problems.push(format!("{prefix}_DEFAULT_ROLES: unknown role in {roles}")),
+            }
+        }
+        if let Some(values) = var("CONTROLLER_VALUES") {
+            self.roles.controller = split_list(&values).map(str::to_string).collect();
+        }
+        if let Some(values) = var("BOT_VALUES") {
+            self.roles.bot = split_list(&values).map(str::to_string).collect();
+        }
+        if let Some(values) = var("PILOT_VALUES") {
+            self.roles.pilot = split_list(&values).map(str::to_string).collect();
+        }
+        if let Some(ttl) = var("JWT_TTL_SECONDS") {
+            match ttl.parse() {
+                Ok(ttl) => self.jwt_ttl_seconds = ttl,
+                Err(_) => problems.push(format!("{prefix}_JWT_TTL_SECONDS: not a number: {ttl}")),
+            }
+        }
+        if let Some(ttl) = var("REFRESH_TTL_SECONDS") {
+            match ttl.parse() {
+                Ok(ttl) => self.refresh_ttl_seconds = ttl,
+                Err(_) => problems.push(format!("{prefix}_REFRESH_TTL_SECONDS: not a number: {ttl}")),
+            }
+        }
+        problems
+    }
+
+    fn validate(&self, network: &str) -> Vec<String> {
+        let mut problems = Vec::new();
+        let mut problem = |message: String| problems.push(format!("{network}: {message}"));
+        if self.issuer.is_empty() {
+            problem("issuer is required".into());
+        } else if !is_http_url(&self.issuer) {
+            problem(format!("issuer is not an http(s) URL: {}", self.issuer));
+        }
+        if let Some(ref token_url) = self.token_url
+            && !is_http_url(token_url)
+        {
+            problem(format!("token_url is not an http(s) URL: {token_url}"));
+        }
+        if let Some(ref url) = self.device_authorization_url
+            && !is_http_url(url)
+        {
+            problem(format!("device_authorization_url is not an http(s) URL: {url}"));
+        }
+        if self.client_id.is_empty() {
+            problem("client_id is empty".into());
+        }
+        if !self.scopes.iter().any(|s| s == "openid") {
+            problem("scopes must include \"openid\"".into());
+        }
+        if self.claims.cid.is_empty() {
+            problem("claims.cid is empty".into());
+        }
+        let maps_values = [UserRole::Controller, UserRole::Bot, UserRole::Pilot]
+            .iter()
+            .any(|role| !self.roles.values_for(*role).is_empty());
+        if maps_values && self.roles.claim.is_none() {
+            problem("roles map claim values but roles.claim is not set".into());
+        }
+        if !(MIN_JWT_TTL_SECONDS..=MAX_JWT_TTL_SECONDS).contains(&self.jwt_ttl_seconds) {
+            problem(format!(
+                "jwt_ttl_seconds must be between {MIN_JWT_TTL_SECONDS} and {MAX_JWT_TTL_SECONDS}"
+            ));
+        }
+        if self.refresh_ttl_seconds > MAX_REFRESH_TTL_SECONDS {
+            problem(format!("refresh_ttl_seconds must be at most {MAX_REFRESH_TTL_SECONDS}"));
+        }
+        problems
+    }
+}
+
+/// An OpenLink server allowed to obtain network-wide JWTs at
+/// `POST /exchange-server`.
+///
+/// The server proves its identity with an assertion signed by the NKey
+/// registered as `public_key`, or — for deployments without keys — with a
+/// shared `secret`. Exactly one of the two is set.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ServerCredentialConfig {
+    /// NKey public key (`U…`) the server signs its assertions with.
+    #[serde(default)]
+    pub public_key: Option<String>,
+    /// Shared secret.
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// Networks the server may obtain JWTs for; `"*"` allows any.
+    #[serde(default)]
+    pub networks: Vec<String>,
+    /// Refuse the credential. JWTs already issued stay valid until they
+    /// expire.
+    #[serde(default)]
+    pub revoked: bool,
+    /// Lifetime of the server JWTs issued to this server.
+    #[serde(default = "default_server_jwt_ttl_seconds")]
+    pub jwt_ttl_seconds: u64,
+}
+
+impl ServerCredentialConfig {
+    /// Whether the server may obtain JWTs for `network`.
+    pub fn allows(&self, network: &NetworkId) -> bool {
+        self.networks
+            .iter()
+            .any(|allowed| allowed == "*" || allowed == network.as_str())
+    }
+
+    /// Apply `AUTH_SERVER_{NAME}_*` overrides.
+    fn overlay_env(&mut self, prefix: &str, env: &impl Fn(&str) -> Option<String>) -> Vec<String> {
+        let var = |name: &str| env(&format!("{prefix}_{name}")).filter(|v| !v.is_empty());
+        let mut problems = Vec::new();
+        if let Some(public_key) = var("PUBLIC_KEY") {
+            self.public_key = Some(public_key);
+        }
+        if let Some(secret) = var("SECRET") {
+            self.secret = Some(secret);
+        }
+        if let Some(networks) = var("NETWORKS") {
+            self.networks = split_list(&networks).map(str::to_string).collect();
+        }
+        if let Some(revoked) = var("REVOKED") {
+            match revoked.parse() {
+                Ok(revoked) => self.revoked = revoked,
+                Err(_) => problems.push(format!("{prefix}_REVOKED: not a boolean: {revoked}")),
+            }
+        }
+        if let Some(ttl) = var("JWT_TTL_SECONDS") {
+            match ttl.parse() {
+                Ok(ttl) => self.jwt_ttl_seconds = ttl,
+                Err(_) => problems.push(format!("{prefix}_JWT_TTL_SECONDS: not a number: {ttl}")),
+            }
+        }
+        problems
+    }
+
+    fn validate(&self, name: &str) -> Vec<String> {
+        let mut problems = Vec::new();
+        let mut problem = |message: String| problems.push(format!("servers.{name}: {message}"));
+        match (&self.public_key, &self.secret) {
+            (Some(_), Some(_)) => problem("set either public_key or secret, not both".into()),
+            (None, None) => problem("public_key or secret is required".into()),
+            (Some(public_key), None) => {
+                if !public_key.starts_with('U') || nkeys::KeyPair::from_public_key(public_key).is_err() {
+                    problem(format!("public_key is not an NKey user public key: {public_key}"));
+                }
+            }
+            (None, Some(secret)) => {
+                if secret.len() < 16 {
+                    problem("secret must be at least 16 characters".into());
+                }
+            }
+        }
+        if self.networks.is_empty() {
+            problem("networks is empty".into());
+        }
+        for network in &self.networks {
+            if network != "*" && !is_valid_key(network) {
+                problem(format!("networks: invalid network key {network}"));
+            }
+        }
+        if !(MIN_JWT_TTL_SECONDS..=MAX_JWT_TTL_SECONDS).contains(&self.jwt_ttl_seconds) {
+            problem(format!(
+                "jwt_ttl_seconds must be between {MIN_JWT_TTL_SECONDS} and {MAX_JWT_TTL_SECONDS}"
+            ));
+        }
+        problems
+    }
+}
+
+/// A service account (bot, bridge, automation) allowed to obtain user JWTs
+/// at `POST /exchange-service` without an interactive login.
 ///
-/// Constructed once at startup and passed as Axum shared state.
+/// The account acts under a fixed CID on one network, and its JWTs only let
+/// it register and send as the listed callsigns. It proves its identity with
+/// an assertion signed by the NKey registered as `public_key`, or with an
+/// `api_key`. Exactly one of the two is set.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ServiceAccountConfig {
+    /// Network the account acts on.
+    pub network: String,
+    /// CID the account's JWTs are issued for.
+    pub cid: String,
+    /// Callsigns the account may register and send as; `"*"` for any.
+    #[serde(default)]
+    pub callsigns: Vec<String>,
+    /// Roles the account may act under.
+    #[serde(default = "default_service_account_roles", deserialize_with = "deserialize_roles")]
+    pub roles: Vec<UserRole>,
+    /// NKey public key (`U…`) the account signs its assertions with.
+    #[serde(default)]
+    pub public_key: Option<String>,
+    /// API key.
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// End of the account's validity (Unix seconds); no JWT outlives it.
+    #[serde(default)]
+    pub expires_at: Option<u64>,
+    /// Refuse the credential. JWTs already issued stay valid until they
+    /// expire unless revoked through the admin API.
+    #[serde(default)]
+    pub revoked: bool,
+    /// Lifetime of the JWTs issued to this account.
+    #[serde(default = "default_jwt_ttl_seconds")]
+    pub jwt_ttl_seconds: u64,
+}
+
+impl ServiceAccountConfig {
+    /// Whether the account's JWTs may name any callsign.
+    pub fn any_callsign(&self) -> bool {
+        self.callsigns.iter().any(|callsign| callsign == "*")
+    }
+
+    /// Apply `AUTH_SERVICE_ACCOUNT_{NAME}_*` overrides.
+    fn overlay_env(&mut self, prefix: &str, env: &impl Fn(&str) -> Option<String>) -> Vec<String> {
+        let var = |name: &str| env(&format!("{prefix}_{name}")).filter(|v| !v.is_empty());
+        let mut problems = Vec::new();
+        if let Some(api_key) = var("API_KEY") {
+            self.api_key = Some(api_key);
+        }
+        if let Some(revoked) = var("REVOKED") {
+            match revoked.parse() {
+                Ok(revoked) => self.revoked = revoked,
+                Err(_) => problems.push(format!("{prefix}_REVOKED: not a boolean: {revoked}")),
+            }
+        }
+        problems
+    }
+
+    fn validate(&self, name: &str, networks: &BTreeMap<String, OidcProviderConfig>) -> Vec<String> {
+        let mut problems = Vec::new();
+        let mut problem =
+            |message: String| problems.push(format!("service_accounts.{name}: {message}"));
+        match (&self.public_key, &self.api_key) {
+            (Some(_), Some(_)) => problem("set either public_key or api_key, not both".into()),
+            (None, None) => problem("public_key or api_key is required".into()),
+            (Some(public_key), None) => {
+                if !public_key.starts_with('U') || nkeys::KeyPair::from_public_key(public_key).is_err() {
+                    problem(format!("public_key is not an NKey user public key: {public_key}"));
+                }
+            }
+            (None, Some(api_key)) => {
+                if api_key.len() < 32 {
+                    problem("api_key must be at least 32 characters".into());
+                }
+            }
+        }
+        if !networks.contains_key(&self.network) {
+            problem(format!("network {} is not declared", self.network));
+        }
+        if !is_subject_token(&self.cid) {
+            problem(format!("cid may only contain letters, digits, '-' and '_': {}", self.cid));
+        }
+        if self.callsigns.is_empty() {
+            problem("callsigns is empty (use \"*\" for any)".into());
+        }
+        for callsign in &self.callsigns {
+            if callsign != "*" && !is_subject_token(callsign) {
+                problem(format!("callsigns: invalid callsign {callsign}"));
+            }
+        }
+        if self.roles.is_empty() {
+            problem("roles is empty".into());
+        }
+        if !(MIN_JWT_TTL_SECONDS..=MAX_JWT_TTL_SECONDS).contains(&self.jwt_ttl_seconds) {
+            problem(format!(
+                "jwt_ttl_seconds must be between {MIN_JWT_TTL_SECONDS} and {MAX_JWT_TTL_SECONDS}"
+            ));
+        }
+        problems
+    }
+}
+
+/// Limits on the exchange endpoints (`/exchange`, `/refresh`, `/device/*`,
+/// `/exchange-server`, `/exchange-service`).
+///
+/// Each limit is a token bucket of that many requests refilled over a
+/// minute; `0` disables it.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimitConfig {
+    /// Requests per minute from one client IP.
+    #[serde(default = "default_ip_requests_per_minute")]
+    pub ip_per_minute: u32,
+    /// Requests per minute for one identity: a CID on a network, a server
+    /// or a service account.
+    #[serde(default = "default_identity_requests_per_minute")]
+    pub identity_per_minute: u32,
+    /// Take the client IP from the last `X-Forwarded-For` entry, for a
+    /// service behind a reverse proxy that appends it. Otherwise the header
+    /// is ignored: clients could set it to dodge the per-IP limit.
+    #[serde(default)]
+    pub trust_forwarded_for: bool,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            ip_per_minute: DEFAULT_IP_REQUESTS_PER_MINUTE,
+            identity_per_minute: DEFAULT_IDENTITY_REQUESTS_PER_MINUTE,
+            trust_forwarded_for: false,
+        }
+    }
+}
+
+impl RateLimitConfig {
+    /// Apply `AUTH_RATE_LIMIT_*` overrides.
+    fn overlay_env(&mut self, env: &impl Fn(&str) -> Option<String>) -> Vec<String> {
+        let var = |name: &str| env(&format!("AUTH_RATE_LIMIT_{name}")).filter(|v| !v.is_empty());
+        let mut problems = Vec::new();
+        for (name, limit) in [
+            ("IP_PER_MINUTE", &mut self.ip_per_minute),
+            ("IDENTITY_PER_MINUTE", &mut self.identity_per_minute),
+        ] {
+            if let Some(value) = var(name) {
+                match value.parse() {
+                    Ok(value) => *limit = value,
+                    Err(_) => problems.push(format!("AUTH_RATE_LIMIT_{name}: not a number: {value}")),
+                }
+            }
+        }
+        if let Some(trust) = var("TRUST_FORWARDED_FOR") {
+            match trust.parse() {
+                Ok(trust) => self.trust_forwarded_for = trust,
+                Err(_) => problems.push(format!(
+                    "AUTH_RATE_LIMIT_TRUST_FORWARDED_FOR: not a boolean: {trust}"
+                )),
+            }
+        }
+        problems
+    }
+}
+
+/// On-disk layout of the configuration file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    listen_port: Option<u16>,
+    audit_log: Option<PathBuf>,
+    #[serde(default)]
+    rate_limits: RateLimitConfig,
+    #[serde(default)]
+    networks: BTreeMap<String, OidcProviderConfig>,
+    #[serde(default)]
+    servers: BTreeMap<String, ServerCredentialConfig>,
+    #[serde(default)]
+    service_accounts: BTreeMap<String, ServiceAccountConfig>,
+}
+
+/// Global configuration shared across all handlers.
 #[derive(Debug, Clone)]
 pub struct AppConfig {
     /// Mapping of network key → OIDC provider.
     pub networks: HashMap<NetworkId, OidcProviderConfig>,
+    /// Servers allowed to call `POST /exchange-server`, by name.
+    pub servers: HashMap<String, ServerCredentialConfig>,
+    /// Service accounts allowed to call `POST /exchange-service`, by name.
+    pub service_accounts: HashMap<String, ServiceAccountConfig>,
     /// Port to listen on (default `3001`).
     pub listen_port: u16,
+    /// Bearer token of the admin API; the API is disabled without one.
+    pub admin_token: Option<String>,
+    /// NATS server revocations are recorded on.
+    pub nats_url: String,
+    /// Limits on the exchange endpoints.
+    pub rate_limits: RateLimitConfig,
+    /// File every issued JWT is appended to; only the tracing audit
+    /// target records them without one.
+    pub audit_log: Option<PathBuf>,
 }
 
 impl AppConfig {
-    /// Build the configuration from environment variables.
+    /// Load and validate the configuration from `AUTH_CONFIG` (if set) and
+    /// the environment.
     ///
     /// | Variable              | Default                          | Description                     |
     /// |-----------------------|----------------------------------|---------------------------------|
+    /// | `AUTH_CONFIG`          | —                                | Path of the TOML configuration file |
     /// | `AUTH_PORT`            | `3001`                           | HTTP listen port                |
-    /// | `OIDC_DEMONETWORK_TOKEN_URL` | `http://localhost:4000/token`  | OIDC token endpoint for demonetwork  |
+    /// | `AUTH_NETWORKS`        | —                                | Extra networks declared from env only (comma-separated) |
+    /// | `OIDC_{NETWORK}_ISSUER` | `http://localhost:4000` for demonetwork | OIDC issuer             |
+    /// | `OIDC_{NETWORK}_CLIENT_ID` | `openlink-auth`              | Client ID and expected `aud`    |
+    /// | `OIDC_{NETWORK}_CLIENT_SECRET` | —                        | Client secret, if required      |
+    /// | `OIDC_{NETWORK}_TOKEN_URL` | discovered                   | Token endpoint override         |
+    /// | `OIDC_{NETWORK}_DEVICE_AUTHORIZATION_URL` | discovered    | Device authorization endpoint override |
+    /// | `OIDC_{NETWORK}_SCOPES` | `openid profile`                | Scopes to request               |
+    /// | `OIDC_{NETWORK}_CID_CLAIM` | `sub`                        | `id_token` claim holding the CID |
+    /// | `OIDC_{NETWORK}_JWT_TTL_SECONDS` | `3600`                 | NATS user JWT lifetime          |
+    /// | `OIDC_{NETWORK}_REFRESH_TTL_SECONDS` | `86400`            | Renewable login lifetime (`0`: no refresh) |
+    /// | `OIDC_{NETWORK}_ROLE_CLAIM` | —                           | Claim mapped to roles           |
+    /// | `OIDC_{NETWORK}_DEFAULT_ROLES` | `pilot`                  | Roles granted to every user     |
+    /// | `OIDC_{NETWORK}_{CONTROLLER,BOT,PILOT}_VALUES` | —        | Claim values granting each role |
+    /// | `AUTH_SERVERS`         | —                                | Extra servers declared from env only (comma-separated) |
+    /// | `AUTH_SERVER_{NAME}_{PUBLIC_KEY,SECRET,NETWORKS,REVOKED,JWT_TTL_SECONDS}` | — | Server credential overrides |
+    /// | `AUTH_SERVICE_ACCOUNT_{NAME}_{API_KEY,REVOKED}` | —       | Service account overrides       |
+    /// | `SERVER_SECRET`        | `openlink-dev-secret`            | Secret of the `default` server, declared when no server is |
+    /// | `AUTH_ADMIN_TOKEN`     | —                                | Bearer token of the admin API (disabled when unset); user JWT lifetimes are then limited to an hour |
+    /// | `NATS_URL`             | `nats://localhost:4222`          | NATS server revocations are recorded on |
+    /// | `AUTH_RATE_LIMIT_{IP,IDENTITY}_PER_MINUTE` | `60`, `20`   | Exchange rate limits (`0`: unlimited) |
+    /// | `AUTH_RATE_LIMIT_TRUST_FORWARDED_FOR` | `false`           | Client IP from `X-Forwarded-For` |
+    /// | `AUTH_AUDIT_LOG`       | —                                | JSON Lines file issued JWTs are appended to |
     ///
-    /// Additional networks can be added by setting
-    /// `OIDC_{NETWORK}_TOKEN_URL` where `{NETWORK}` is upper-cased.
-    pub fn from_env() -> Self {
-        let listen_port: u16 = std::env::var("AUTH_PORT")
-            .ok()
-            .and_then(|v| v.parse().ok())
-            .unwrap_or(3001);
-
-        let mut networks = HashMap::new();
-
-        // demonetwork — always present with a default
-        let demonetwork_token_url = std::env::var("OIDC_DEMONETWORK_TOKEN_URL")
-            .unwrap_or_else(|_| "http://localhost:4000/token".to_string());
-        networks.insert(
-            NetworkId::new("demonetwork"),
-            OidcProviderConfig {
-                token_url: demonetwork_token_url,
-            },
-        );
+    /// `{NETWORK}` and `{NAME}` are the keys upper-cased, with `-` replaced
+    /// by `_`. Without a file or `AUTH_NETWORKS`, `demonetwork` is served
+    /// from `mock-oidc`; without a declared server, a `default` server may
+    /// obtain JWTs for any network with `SERVER_SECRET`.
+    pub fn load() -> Result<Self, ConfigError> {
+        let file = match std::env::var_os("AUTH_CONFIG") {
+            Some(path) => Some(read_file(Path::new(&path))?),
+            None => None,
+        };
+        Self::from_sources(file.as_deref(), &|name| std::env::var(name).ok())
+    }
 
-        Self {
-            networks,
-            listen_port,
+    /// Build the configuration from file contents and an environment lookup.
+    fn from_sources(
+        file: Option<&str>,
+        env: &impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, ConfigError> {
+        let file: ConfigFile = match file {
+            Some(contents) => toml::from_str(contents)?,
+            None => ConfigFile::default(),
+        };
+        let mut problems = Vec::new();
+
+        let mut networks = file.networks;
+        for network in env("AUTH_NETWORKS").unwrap_or_default().split(',') {
+            let network = network.trim();
+            if !network.is_empty() {
+                networks.entry(network.to_string()).or_default();
+            }
+        }
+        if networks.is_empty() {
+            // demonetwork — present by default, backed by mock-oidc
+            networks.insert(
+                DEFAULT_NETWORK.to_string(),
+                OidcProviderConfig {
+                    issuer: DEFAULT_ISSUER.to_string(),
+                    roles: RoleMappings {
+                        claim: Some("demonetwork_rating".to_string()),
+                        controller: ["C1", "C3", "I1", "I3", "SUP", "ADM"]
+                            .map(str::to_string)
+                            .to_vec(),
+                        ..RoleMappings::default()
+                    },
+                    ..OidcProviderConfig::default()
+                },
+            );
+        }
+
+        for (network, provider) in &mut networks {
+            if !is_valid_key(network) {
+                problems.push(format!(
+                    "{network}: network keys may only contain a-z, 0-9, '-' and '_'"
+                ));
+            }
+            problems.extend(provider.overlay_env(&env_prefix(network), env));
+            problems.extend(provider.validate(network));
         }
-    }
 
-    /// Look up the OIDC provider for the given network.
-    pub fn provider_for(&self, network: &NetworkId) -> Option<&OidcProviderConfig> {
-        self.networks.get(network)
+        let mut servers = file.servers;
+        for name in env("AUTH_SERVERS").unwrap_or_default().split(',') {
+            let name = name.trim();
+            if !name.is_empty() {
+                servers.entry(name.to_string()).or_insert_with(|| ServerCredentialConfig {
+                    public_key: None,
+                    secret: None,
+                    networks: vec!["*".to_string()],
+                    revoked: false,
+                    jwt_ttl_seconds: DEFAULT_SERVER_JWT_TTL_SECONDS,
+                });
+            }
+        }
+        if servers.is_empty() {
+            // Development fallback: any network, with the shared secret
+            servers.insert(
+                DEFAULT_SERVER_NAME.to_string(),
+                ServerCredentialConfig {
+                    public_key: None,
+                    secret: Some(
+                        env("SERVER_SECRET").unwrap_or_else(|| DEFAULT_SERVER_SECRET.to_string()),
+                    ),
+                    networks: vec!["*".to_string()],
+                    revoked: false,
+                    jwt_ttl_seconds: DEFAULT_SERVER_JWT_TTL_SECONDS,
+                },
+            );
+        }
+
+        for (name, server) in &mut servers {
+            if !is_valid_key(name) {
+                problems.push(format!(
+                    "servers.{name}: server names may only contain a-z, 0-9, '-' and '_'"
+                ));
+            }
+            problems.extend(server.overlay_env(&server_env_prefix(name), env));
+            problems.extend(server.validate(name));
+        }
+
+        let mut service_accounts = file.service_accounts;
+        for (name, account) in &mut service_accounts {
+            if !is_valid_key(name) {
+                problems.push(format!(
+                    "service_accounts.{name}: account names may only contain a-z, 0-9, '-' and '_'"
+                ));
+            }
+            problems.extend(account.overlay_env(&service_account_env_prefix(name), env));
+            problems.extend(account.validate(name, &networks));
+        }
+
+        let listen_port = match env("AUTH_PORT") {
+            Some(port) => port.parse().unwrap_or_else(|_| {
+                problems.push(format!("AUTH_PORT: not a port number: {port}"));
+                DEFAULT_LISTEN_PORT
+            }),
+            None => file.listen_port.unwrap_or(DEFAULT_LISTEN_PORT),
+        };
+
+        let mut rate_limits = file.rate_limits;
+        problems.extend(rate_limits.overlay_env(env));
+
+        let admin_token = env("AUTH_ADMIN_TOKEN").filter(|token| !token.trim().is_empty());
+        if admin_token.is_some() {
+            let ttls = networks
+                .iter()
+                .map(|(network, provider)| (network.clone(), provider.jwt_ttl_seconds))
+                .chain(
+                    service_accounts
+                        .iter()
+                        .map(|(name, account)| (format!("service_accounts.{name}"), account.jwt_ttl_seconds)),
+                );
+            for (name, ttl) in ttls {
+                if ttl > MAX_REVOCABLE_JWT_TTL_SECONDS {
+                    problems.push(format!(
+                        "{name}: jwt_ttl_seconds must be at most {MAX_REVOCABLE_JWT_TTL_SECONDS} \
+                         with the admin API enabled, as NATS accepts revoked JWTs until they expire"
+                    ));
+                }
+            }
+        }
+
+        if !problems.is_empty() {
+            return Err(ConfigError::Invalid(problems));
+        }
+        Ok(Self {
+            networks: networks
+                .into_iter()
+                .map(|(network, provider)| (NetworkId::new(&network), provider))
+                .collect(),
+            servers: servers.into_iter().collect(),
+            service_accounts: service_accounts.into_iter().collect(),
+            listen_port,
+            admin_token,
+            nats_url: env("NATS_URL").unwrap_or_else(|| DEFAULT_NATS_URL.to_string()),
+            rate_limits,
+            audit_log: env("AUTH_AUDIT_LOG")
+                .filter(|path| !path.is_empty())
+                .map(PathBuf::from)
+                .or(file.audit_log),
+        })
     }
 }
 
+fn split_list(list: &str) -> impl Iterator<Item = &str> {
+    list.split([',', ' ']).filter(|s| !s.is_empty())
+}
+
+fn read_file(path: &Path) -> Result<String, ConfigError> {
+    std::fs::read_to_string(path).map_err(|source| ConfigError::Read {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// `OIDC_{NETWORK}` prefix of a network's environment variables.
+fn env_prefix(network: &str) -> String {
+    format!("OIDC_{}", network.to_uppercase().replace('-', "_"))
+}
+
+/// `AUTH_SERVER_{NAME}` prefix of a server's environment variables.
+fn server_env_prefix(name: &str) -> String {
+    format!("AUTH_SERVER_{}", name.to_uppercase().replace('-', "_"))
+}
+
+/// `AUTH_SERVICE_ACCOUNT_{NAME}` prefix of a service account's environment
+/// variables.
+fn service_account_env_prefix(name: &str) -> String {
+    format!("AUTH_SERVICE_ACCOUNT_{}", name.to_uppercase().replace('-', "_"))
+}
+
+/// CIDs and callsigns end up in NATS subjects and JWT tags.
+fn is_subject_token(token: &str) -> bool {
+    !token.is_empty()
+        && token
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Network keys end up in NATS subjects and server names in assertions, so
+/// wildcards and separators are rejected.
+pub fn is_valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
+fn is_http_url(url: &str) -> bool {
+    ["http://", "https://"]
+        .iter()
+        .any(|scheme| url.strip_prefix(scheme).is_some_and(|rest| !rest.is_empty()))
+}
+
+fn default_client_id() -> String {
+    "openlink-auth".to_string()
+}
+
+fn default_scopes() -> Vec<String> {
+    vec!["openid".to_string(), "profile".to_string()]
+}
+
+fn default_cid_claim() -> String {
+    "sub".to_string()
+}
+
+fn default_jwt_ttl_seconds() -> u64 {
+    DEFAULT_JWT_TTL_SECONDS
+}
+
+fn default_refresh_ttl_seconds() -> u64 {
+    DEFAULT_REFRESH_TTL_SECONDS
+}
+
+fn default_server_jwt_ttl_seconds() -> u64 {
+    DEFAULT_SERVER_JWT_TTL_SECONDS
+}
+
+fn default_ip_requests_per_minute() -> u32 {
+    DEFAULT_IP_REQUESTS_PER_MINUTE
+}
+
+fn default_identity_requests_per_minute() -> u32 {
+    DEFAULT_IDENTITY_REQUESTS_PER_MINUTE
+}
+
+fn default_roles() -> Vec<UserRole> {
+    vec![UserRole::Pilot]
+}
+
+fn default_service_account_roles() -> Vec<UserRole> {
+    vec![UserRole::Bot]
+}
+
+/// Roles are written in lowercase (`"pilot"`), as in JWT tags.
+fn deserialize_roles<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<UserRole>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|role| role.parse().map_err(serde::de::Error::custom))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
     #[test]
     fn default_config_has_demonetwork() {
-        let cfg = AppConfig::from_env();
+        let cfg = AppConfig::from_sources(None, &env(&[])).unwrap();
         let demonetwork = NetworkId::new("demonetwork");
-        assert!(cfg.provider_for(&demonetwork).is_some());
-        assert!(cfg
-            .provider_for(&demonetwork)
-            .unwrap()
-            .token_url
-            .contains("/token"));
+        let provider = &cfg.networks[&demonetwork];
+        assert_eq!(provider.issuer, "http://localhost:4000");
+        assert_eq!(provider.client_id, "openlink-auth");
+        assert_eq!(provider.claims.cid, "sub");
+        assert_eq!(provider.jwt_ttl_seconds, 3600);
+        assert_eq!(provider.refresh_ttl_seconds, 86_400);
+        assert!(provider.token_url.is_none());
+        assert!(provider.device_authorization_url.is_none());
     }
 
     #[test]
     fn default_listen_port() {
-        let cfg = AppConfig::from_env();
+        let cfg = AppConfig::from_sources(None, &env(&[])).unwrap();
         assert_eq!(cfg.listen_port, 3001);
     }
 
     #[test]
     fn unknown_network_returns_none() {
-        let cfg = AppConfig::from_env();
-        assert!(cfg.provider_for(&NetworkId::new("unknown")).is_none());
+        let cfg = AppConfig::from_sources(None, &env(&[])).unwrap();
+        assert!(!cfg.networks.contains_key(&NetworkId::new("unknown")));
+    }
+
+    #[test]
+    fn file_declares_networks_and_env_overlays_them() {
+        let file = r#"
+            listen_port = 4001
+
+            [networks.demonetwork]
+            issuer = "http://localhost:4000"
+
+            [networks.afrv]
+            issuer = "https://auth.afrv.example"
+            client_id = "openlink"
+            scopes = ["openid", "email"]
+            jwt_ttl_seconds = 900
+
+            [networks.afrv.claims]
+            cid = "afrv_cid"
+
+            [networks.afrv.roles]
+            claim = "rating"
+            default = ["observer"]
+            controller = ["C1"]
+        "#;
+        let cfg = AppConfig::from_sources(
+            Some(file),
+            &env(&[
+                ("AUTH_NETWORKS", "ivao-test"),
+                ("OIDC_IVAO_TEST_ISSUER", "https://sso.ivao.example"),
+                ("OIDC_AFRV_CLIENT_SECRET", "s3cret"),
+                ("OIDC_AFRV_REFRESH_TTL_SECONDS", "0"),
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(cfg.listen_port, 4001);
+        assert_eq!(cfg.networks.len(), 3);
+        let afrv = &cfg.networks[&NetworkId::new("afrv")];
+        assert_eq!(afrv.client_id, "openlink");
+        assert_eq!(afrv.client_secret.as_deref(), Some("s3cret"));
+        assert_eq!(afrv.claims.cid, "afrv_cid");
+        assert_eq!(afrv.jwt_ttl_seconds, 900);
+        assert_eq!(afrv.refresh_ttl_seconds, 0);
+        assert_eq!(afrv.roles.default, vec![UserRole::Observer]);
+        assert_eq!(afrv.roles.values_for(UserRole::Controller), ["C1".to_string()]);
+        assert_eq!(
+            cfg.networks[&NetworkId::new("ivao-test")].issuer,
+            "https://sso.ivao.example"
+        );
+    }
+
+    #[test]
+    fn invalid_networks_are_all_reported() {
+        let file = r#"
+            [networks.afrv]
+            scopes = ["profile"]
+            jwt_ttl_seconds = 5
+            refresh_ttl_seconds = 99999999
+
+            [networks.afrv.roles]
+            controller = ["C1"]
+
+            [networks."bad.key"]
+            issuer = "ftp://example"
+        "#;
+        let Err(ConfigError::Invalid(problems)) = AppConfig::from_sources(Some(file), &env(&[]))
+        else {
+            panic!("expected validation errors");
+        };
+        assert_eq!(problems.len(), 7, "{problems:?}");
+    }
+
+    #[test]
+    fn admin_api_limits_jwt_lifetime() {
+        let file = r#"
+            [networks.demonetwork]
+            issuer = "http://localhost:4000"
+            jwt_ttl_seconds = 7200
+        "#;
+        assert!(AppConfig::from_sources(Some(file), &env(&[])).is_ok());
+        let Err(ConfigError::Invalid(problems)) =
+            AppConfig::from_sources(Some(file), &env(&[("AUTH_ADMIN_TOKEN", "admin")]))
+        else {
+            panic!("expected validation errors");
+        };
+        assert_eq!(problems.len(), 1, "{problems:?}");
+        assert!(problems[0].starts_with("demonetwork: jwt_ttl_seconds must be at most 3600"));
+
+        let cfg = AppConfig::from_sources(None, &env(&[("AUTH_ADMIN_TOKEN", "admin")])).unwrap();
+        assert_eq!(cfg.admin_token.as_deref(), Some("admin"));
+    }
+
+    #[test]
+    fn default_server_uses_server_secret() {
+        let cfg = AppConfig::from_sources(None, &env(&[("SERVER_SECRET", "a-long-enough-secret")]))
+            .unwrap();
+        let server = &cfg.servers["default"];
+        assert_eq!(server.secret.as_deref(), Some("a-long-enough-secret"));
+        assert!(server.allows(&NetworkId::new("anything")));
+    }
+
+    #[test]
+    fn servers_are_declared_overlaid_and_validated() {
+        let file = r#"
+            [servers.paris-1]
+            public_key = "UCRH6JUVKV5YRNFAOYKYTVYLHGDXMJDWZP2O3PA3NRO4EM3JEY6TTTU3"
+            networks = ["demonetwork", "afrv"]
+            jwt_ttl_seconds = 900
+        "#;
+        let cfg = AppConfig::from_sources(
+            Some(file),
+            &env(&[
+                ("AUTH_SERVERS", "legacy"),
+                ("AUTH_SERVER_LEGACY_SECRET", "a-long-enough-secret"),
+                ("AUTH_SERVER_LEGACY_NETWORKS", "demonetwork"),
+                ("AUTH_SERVER_PARIS_1_REVOKED", "true"),
+                ("SERVER_SECRET", "ignored-once-servers-are-declared"),
+            ]),
+        )
+        .unwrap();
+        assert_eq!(cfg.servers.len(), 2);
+        let paris = &cfg.servers["paris-1"];
+        assert!(paris.revoked);
+        assert_eq!(paris.jwt_ttl_seconds, 900);
+        assert!(paris.allows(&NetworkId::new("afrv")));
+        let legacy = &cfg.servers["legacy"];
+        assert!(legacy.allows(&NetworkId::new("demonetwork")));
+        assert!(!legacy.allows(&NetworkId::new("afrv")));
+
+        let file = r#"
+            [servers.both]
+            public_key = "UCRH6JUVKV5YRNFAOYKYTVYLHGDXMJDWZP2O3PA3NRO4EM3JEY6TTTU3"
+            secret = "a-long-enough-secret"
+            networks = ["demonetwork"]
+
+            [servers.weak]
+            secret = "short"
+            networks = []
+
+            [servers.badkey]
+            public_key = "not-a-key"
+            networks = ["demo.>"]
+        "#;
+        let Err(ConfigError::Invalid(problems)) = AppConfig::from_sources(Some(file), &env(&[]))
+        else {
+            panic!("expected validation errors");
+        };
+        assert_eq!(problems.len(), 5, "{problems:?}");
+    }
+
+    #[test]
+    fn service_accounts_are_declared_overlaid_and_validated() {
+        let file = r#"
+            [networks.demonetwork]
+            issuer = "http://localhost:4000"
+
+            [service_accounts.atc-bot]
+            network = "demonetwork"
+            cid = "900001"
+            callsigns = ["LFPG_TWR", "LFPG_GND"]
+            public_key = "UCRH6JUVKV5YRNFAOYKYTVYLHGDXMJDWZP2O3PA3NRO4EM3JEY6TTTU3"
+            expires_at = 1800000000
+
+            [service_accounts.hoppie]
+            network = "demonetwork"
+            cid = "900002"
+            callsigns = ["*"]
+            roles = ["bot", "pilot"]
+        "#;
+        let cfg = AppConfig::from_sources(
+            Some(file),
+            &env(&[
+                ("AUTH_SERVICE_ACCOUNT_HOPPIE_API_KEY", "0123456789abcdef0123456789abcdef"),
+                ("AUTH_SERVICE_ACCOUNT_ATC_BOT_REVOKED", "true"),
+            ]),
+        )
+        .unwrap();
+        let bot = &cfg.service_accounts["atc-bot"];
+        assert_eq!(bot.roles, vec![UserRole::Bot]);
+        assert_eq!(bot.expires_at, Some(1_800_000_000));
+        assert!(bot.revoked);
+        assert!(!bot.any_callsign());
+        let hoppie = &cfg.service_accounts["hoppie"];
+        assert!(hoppie.any_callsign());
+        assert_eq!(hoppie.roles, vec![UserRole::Bot, UserRole::Pilot]);
+        assert!(hoppie.api_key.is_some());
+
+        let file = r#"
+            [service_accounts.orphan]
+            network = "afrv"
+            cid = "9.1"
+            callsigns = []
+            roles = []
+            api_key = "short"
+            jwt_ttl_seconds = 10
+        "#;
+        let Err(ConfigError::Invalid(problems)) = AppConfig::from_sources(Some(file), &env(&[]))
+        else {
+            panic!("expected validation errors");
+        };
+        assert_eq!(problems.len(), 6, "{problems:?}");
+    }
+
+    #[test]
+    fn rate_limits_and_audit_log_are_read_from_file_and_env() {
+        let cfg = AppConfig::from_sources(None, &env(&[])).unwrap();
+        assert_eq!(cfg.rate_limits, RateLimitConfig::default());
+        assert!(cfg.audit_log.is_none());
+
+        let file = r#"
+            audit_log = "/var/log/openlink-auth/audit.jsonl"
+
+            [rate_limits]
+            ip_per_minute = 120
+            trust_forwarded_for = true
+        "#;
+        let cfg = AppConfig::from_sources(
+            Some(file),
+            &env(&[("AUTH_RATE_LIMIT_IDENTITY_PER_MINUTE", "0")]),
+        )
+        .unwrap();
+        assert_eq!(
+            cfg.rate_limits,
+            RateLimitConfig {
+                ip_per_minute: 120,
+                identity_per_minute: 0,
+                trust_forwarded_for: true,
+            }
+        );
+        assert_eq!(
+            cfg.audit_log.as_deref(),
+            Some(Path::new("/var/log/openlink-auth/audit.jsonl"))
+        );
+
+        let cfg = AppConfig::from_sources(Some(file), &env(&[("AUTH_AUDIT_LOG", "audit.jsonl")])).unwrap();
+        assert_eq!(cfg.audit_log.as_deref(), Some(Path::new("audit.jsonl")));
+
+        let err = AppConfig::from_sources(None, &env(&[("AUTH_RATE_LIMIT_IP_PER_MINUTE", "many")]))
+            .unwrap_err();
+        assert!(err.to_string().contains("AUTH_RATE_LIMIT_IP_PER_MINUTE"));
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected() {
+        let file = "[networks.afrv]\nissuer = \"http://x\"\nclient = \"typo\"\n";
+        assert!(matches!(
+            AppConfig::from_sources(Some(file), &env(&[])),
+            Err(ConfigError::Parse(_))
+        ));
     }
 }
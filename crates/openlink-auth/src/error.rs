@@ -3,10 +3,11 @@
 //! [`AuthError`] unifies all failure modes and implements [`axum::response::IntoResponse`]
 //! so handlers can return `Result<…, AuthError>` directly.
 
-use axum::http::StatusCode;
+use axum::http::{StatusCode, header};
 use axum::response::{IntoResponse, Response};
 use axum::Json;
-use serde_json::json;
+use serde::Serialize;
+use utoipa::ToSchema;
 
 /// Errors that can occur during the authentication flow.
 #[derive(Debug, thiserror::Error)]
@@ -19,6 +20,58 @@ pub enum AuthError {
     #[error("OIDC authentication failed: {0}")]
     OidcExchangeFailed(String),
 
+    /// A device code is not approved yet or no longer usable; carries the
+    /// RFC 8628 error code (`authorization_pending`, `slow_down`,
+    /// `expired_token` or `access_denied`).
+    #[error("{0}")]
+    DeviceFlow(String),
+
+    /// The `id_token` failed signature or claim validation.
+    #[error("invalid id_token: {0}")]
+    InvalidIdToken(String),
+
+    /// The refresh token is unknown, used, expired or bound to another key.
+    #[error("invalid refresh token: {0}")]
+    InvalidRefreshToken(String),
+
+    /// The server credential is unknown, invalid or revoked.
+    #[error("server authentication failed: {0}")]
+    ServerRejected(String),
+
+    /// The authenticated server is not registered for the network.
+    #[error("server {server} may not serve network {network}")]
+    ServerNotAllowed { server: String, network: String },
+
+    /// The service account credential is unknown, invalid, expired or
+    /// revoked.
+    #[error("service account authentication failed: {0}")]
+    ServiceAccountRejected(String),
+
+    /// The authenticated service account acts on another network.
+    #[error("service account {account} may not act on network {network}")]
+    ServiceAccountNotAllowed { account: String, network: String },
+
+    /// The request is missing a field or has an invalid one.
+    #[error("invalid request: {0}")]
+    InvalidRequest(String),
+
+    /// The revocation bucket could not be reached or written.
+    #[error("revocation store unavailable: {0}")]
+    RevocationStore(String),
+
+    /// The requested role is not granted by the user's identity claims.
+    #[error("role {0} is not granted to this user")]
+    RoleNotGranted(openlink_models::UserRole),
+
+    /// The client IP or the identity exceeded its rate limit; carries the
+    /// seconds to wait before retrying.
+    #[error("too many requests, retry in {0} s")]
+    RateLimited(u64),
+
+    /// An issued JWT could not be written to the audit log.
+    #[error("audit log unavailable: {0}")]
+    AuditLog(String),
+
     /// The HTTP call to the OIDC provider failed at the transport level.
     #[error("failed to reach identity provider: {0}")]
     HttpError(#[from] reqwest::Error),
@@ -32,17 +85,48 @@ pub enum AuthError {
     Serialization(#[from] serde_json::Error),
 }
 
+/// Body of every error response.
+#[derive(Serialize, ToSchema)]
+pub struct ErrorBody {
+    /// What went wrong.
+    pub error: String,
+}
+
 impl IntoResponse for AuthError {
     fn into_response(self) -> Response {
         let (status, message) = match &self {
             Self::UnknownNetwork(_) => (StatusCode::BAD_REQUEST, self.to_string()),
             Self::OidcExchangeFailed(_) => (StatusCode::UNAUTHORIZED, self.to_string()),
+            Self::DeviceFlow(_) => (StatusCode::BAD_REQUEST, self.to_string()),
+            Self::InvalidIdToken(_) => (StatusCode::UNAUTHORIZED, self.to_string()),
+            Self::InvalidRefreshToken(_) => (StatusCode::UNAUTHORIZED, self.to_string()),
+            Self::ServerRejected(_) => (StatusCode::UNAUTHORIZED, self.to_string()),
+            Self::ServerNotAllowed { .. } => (StatusCode::FORBIDDEN, self.to_string()),
+            Self::ServiceAccountRejected(_) => (StatusCode::UNAUTHORIZED, self.to_string()),
+            Self::ServiceAccountNotAllowed { .. } => (StatusCode::FORBIDDEN, self.to_string()),
+            Self::InvalidRequest(_) => (StatusCode::BAD_REQUEST, self.to_string()),
+            Self::RevocationStore(_) => (StatusCode::BAD_GATEWAY, self.to_string()),
+            Self::RoleNotGranted(_) => (StatusCode::FORBIDDEN, self.to_string()),
+            Self::RateLimited(_) => (StatusCode::TOO_MANY_REQUESTS, self.to_string()),
+            Self::AuditLog(_) => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
             Self::HttpError(_) => (StatusCode::BAD_GATEWAY, self.to_string()),
             Self::NKeyError(_) => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
             Self::Serialization(_) => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
         };
 
-        tracing::error!(%status, error = %message, "request failed");
-        (status, Json(json!({ "error": message }))).into_response()
+        match self {
+            // Expected while the user approves the device login.
+            Self::DeviceFlow(_) => tracing::debug!(%status, error = %message, "device code not redeemed"),
+            // Already in the audit trail.
+            Self::RateLimited(_) => {}
+            _ => tracing::error!(%status, error = %message, "request failed"),
+        }
+        let mut response = (status, Json(ErrorBody { error: message })).into_response();
+        if let Self::RateLimited(retry_after) = self {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, retry_after.into());
+        }
+        response
     }
 }
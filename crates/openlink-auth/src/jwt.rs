@@ -1,13 +1,14 @@
 //! NATS JWT generation.
 //!
 //! Signs a NATS user JWT that encodes the CID, station NKey public key,
+//! the user's [`UserRole`] — and for service accounts, their callsigns —
 //! and scoped publish/subscribe permissions derived from [`NatsSubjects`].
 
 use std::time::SystemTime;
 
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
 use nkeys::KeyPair;
-use openlink_models::{NetworkAddress, NetworkId};
+use openlink_models::{NetworkAddress, NetworkId, UserRole};
 use openlink_sdk::NatsSubjects;
 use serde::Serialize;
 
@@ -33,6 +34,9 @@ struct NatsUserClaims {
 #[derive(Serialize)]
 struct NatsClaims {
     permissions: NatsPermissions,
+    /// Reported in NATS connect/disconnect advisories as `client.tags`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
     #[serde(rename = "type")]
     claim_type: String,
     version: i32,
@@ -57,9 +61,13 @@ struct NatsPermissionList {
 
 /// Sign a NATS user JWT for the given CID on a specific network.
 ///
-/// The JWT grants the user:
-/// - **publish** on their outbox subject
-/// - **subscribe** on their inbox subject
+/// The JWT is tagged with the network and `role`, and grants the user:
+/// - **publish** on their outbox subject (unless `role` is
+///   [`UserRole::Observer`]) and the directory request subject
+/// - **subscribe** on their inbox subject, their request/reply prefix and
+///   directory change events
+/// - **read** their inbox through a durable JetStream consumer (create,
+///   pull and ack on their own consumer only)
 ///
 /// # Arguments
 ///
@@ -68,15 +76,54 @@ struct NatsPermissionList {
 /// * `cid` — The authenticated CID (becomes the JWT `name` and is used
 ///   for subject scoping).
 /// * `network` — The network this JWT authorises access to.
+/// * `role` — The role the user acts under, checked again by the server on
+///   every envelope.
 /// * `ttl_secs` — Lifetime of the token in seconds.
 pub fn sign_user_jwt(
     account_kp: &KeyPair,
     user_nkey_public: &str,
     cid: &str,
     network: &NetworkId,
+    role: UserRole,
+    ttl_secs: u64,
+) -> Result<String, AuthError> {
+    sign_station_jwt(account_kp, user_nkey_public, cid, network, role, &[], ttl_secs)
+}
+
+/// Sign a NATS user JWT for a service account.
+///
+/// Same permissions as [`sign_user_jwt`], with one `openlink-callsign` tag
+/// per entry of `callsigns`: the server only lets the holder register and
+/// send as those callsigns. An empty list leaves the callsigns
+/// unrestricted.
+pub fn sign_service_account_jwt(
+    account_kp: &KeyPair,
+    user_nkey_public: &str,
+    cid: &str,
+    network: &NetworkId,
+    role: UserRole,
+    callsigns: &[String],
+    ttl_secs: u64,
+) -> Result<String, AuthError> {
+    sign_station_jwt(account_kp, user_nkey_public, cid, network, role, callsigns, ttl_secs)
+}
+
+fn sign_station_jwt(
+    account_kp: &KeyPair,
+    user_nkey_public: &str,
+    cid: &str,
+    network: &NetworkId,
+    role: UserRole,
+    callsigns: &[String],
     ttl_secs: u64,
 ) -> Result<String, AuthError> {
     let address = NetworkAddress::new(cid);
+    let mut publish = Vec::new();
+    if role.may_publish() {
+        publish.push(NatsSubjects::outbox(network, &address));
+    }
+    publish.push(NatsSubjects::directory_query(network));
+    publish.extend(NatsSubjects::durable_inbox_api(network, &address));
     let now = SystemTime::now()
         .duration_since(SystemTime::UNIX_EPOCH)
         .expect("system clock before epoch")
@@ -92,12 +139,18 @@ pub fn sign_user_jwt(
         nats: NatsClaims {
             claim_type: "user".to_string(),
             version: 2,
+            tags: [NatsSubjects::network_tag(network), NatsSubjects::role_tag(role)]
+                .into_iter()
+                .chain(callsigns.iter().map(|callsign| NatsSubjects::callsign_tag(callsign)))
+                .collect(),
             permissions: NatsPermissions {
-                publish: NatsPermissionList {
-                    allow: vec![NatsSubjects::outbox(network, &address)],
-                },
+                publish: NatsPermissionList { allow: publish },
                 subscribe: NatsPermissionList {
-                    allow: vec![NatsSubjects::inbox(network, &address)],
+                    allow: vec![
+                        NatsSubjects::inbox(network, &address),
+                        format!("{}.>", NatsSubjects::replies(network, &address)),
+                        NatsSubjects::directory_events(network),
+                    ],
                 },
             },
         },
@@ -109,8 +162,12 @@ pub fn sign_user_jwt(
 /// Sign a NATS JWT granting **server-level** permissions on a network.
 ///
 /// The server JWT can:
-/// - **subscribe** to all outbox messages (`outbox.>`)
-/// - **publish** to any station inbox (`inbox.>`)
+/// - **subscribe** to all outbox messages (`outbox.>`), directory requests
+///   and relays from peer networks (`federation.*`)
+/// - **publish** to any station inbox (`inbox.>`), request replies
+///   (`replies.>`), directory events, store-and-forward queues
+///   (`pending.>`) and federation relays to peer networks
+///   (`*.federation.{network}`)
 /// - **access** JetStream KV buckets (`$JS.API.>`, `_INBOX.>`)
 ///
 /// # Arguments
@@ -140,10 +197,15 @@ pub fn sign_server_jwt(
         nats: NatsClaims {
             claim_type: "user".to_string(),
             version: 2,
+            tags: vec![NatsSubjects::network_tag(network)],
             permissions: NatsPermissions {
                 publish: NatsPermissionList {
                     allow: vec![
                         NatsSubjects::inbox_wildcard(network),
+                        NatsSubjects::replies_wildcard(network),
+                        NatsSubjects::directory_events(network),
+                        NatsSubjects::pending_wildcard(network),
+                        NatsSubjects::federation_outbound_wildcard(network),
                         "$JS.API.>".to_string(),
                         "_INBOX.>".to_string(),
                     ],
@@ -151,10 +213,59 @@ pub fn sign_server_jwt(
                 subscribe: NatsPermissionList {
                     allow: vec![
                         NatsSubjects::outbox_wildcard(network),
+                        NatsSubjects::directory_query(network),
+                        NatsSubjects::federation_inbound_wildcard(network),
+                        "$JS.API.>".to_string(),
+                        "_INBOX.>".to_string(),
+                    ],
+                },
+            },
+        },
+    };
+
+    encode_and_sign(account_kp, &claims)
+}
+
+/// Sign the JWT the auth service itself connects to NATS with, to record
+/// revocations in the networks' JetStream KV buckets.
+///
+/// # Arguments
+///
+/// * `account_kp` — The NATS account key-pair used to sign the JWT.
+/// * `user_nkey_public` — The service's ephemeral NKey public key.
+/// * `ttl_secs` — Lifetime of the token in seconds.
+pub fn sign_service_jwt(
+    account_kp: &KeyPair,
+    user_nkey_public: &str,
+    ttl_secs: u64,
+) -> Result<String, AuthError> {
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .expect("system clock before epoch")
+        .as_secs();
+
+    let claims = NatsUserClaims {
+        jti: uuid::Uuid::new_v4().to_string(),
+        iat: now,
+        exp: now + ttl_secs,
+        iss: account_kp.public_key(),
+        name: "openlink-auth".to_string(),
+        sub: user_nkey_public.to_string(),
+        nats: NatsClaims {
+            claim_type: "user".to_string(),
+            version: 2,
+            tags: Vec::new(),
+            permissions: NatsPermissions {
+                publish: NatsPermissionList {
+                    allow: vec![
                         "$JS.API.>".to_string(),
+                        "$KV.>".to_string(),
                         "_INBOX.>".to_string(),
                     ],
                 },
+                subscribe: NatsPermissionList {
+                    allow: vec!["_INBOX.>".to_string()],
+                },
             },
         },
     };
@@ -200,7 +311,15 @@ mod tests {
     #[test]
     fn jwt_has_three_parts() {
         let kp = test_account_kp();
-        let jwt = sign_user_jwt(&kp, "UABC123", "42", &NetworkId::new("demonetwork"), 3600).unwrap();
+        let jwt = sign_user_jwt(
+            &kp,
+            "UABC123",
+            "42",
+            &NetworkId::new("demonetwork"),
+            UserRole::Pilot,
+            3600,
+        )
+        .unwrap();
         assert_eq!(jwt.split('.').count(), 3);
     }
 
@@ -208,7 +327,7 @@ mod tests {
     fn jwt_body_contains_correct_permissions() {
         let kp = test_account_kp();
         let net = NetworkId::new("demonetwork");
-        let jwt = sign_user_jwt(&kp, "UABC123", "42", &net, 3600).unwrap();
+        let jwt = sign_user_jwt(&kp, "UABC123", "42", &net, UserRole::Pilot, 3600).unwrap();
 
         // Decode the body (second part)
         let body_b64 = jwt.split('.').nth(1).unwrap();
@@ -226,13 +345,54 @@ mod tests {
             subscribe_allow[0].as_str().unwrap(),
             "openlink.v1.demonetwork.inbox.42"
         );
+        assert_eq!(
+            publish_allow[1].as_str().unwrap(),
+            "openlink.v1.demonetwork.directory.query"
+        );
+        assert_eq!(
+            subscribe_allow[1].as_str().unwrap(),
+            "openlink.v1.demonetwork.replies.42.>"
+        );
+        let publish_allow: Vec<&str> = publish_allow
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_str().unwrap())
+            .collect();
+        assert!(publish_allow.contains(
+            &"$JS.API.CONSUMER.MSG.NEXT.openlink-v1-demonetwork-inboxes.inbox-42"
+        ));
+        assert!(!publish_allow.contains(&"$JS.API.>"));
+    }
+
+    #[test]
+    fn observer_jwt_cannot_publish_to_outbox() {
+        let kp = test_account_kp();
+        let net = NetworkId::new("demonetwork");
+        let jwt = sign_user_jwt(&kp, "UABC123", "42", &net, UserRole::Observer, 3600).unwrap();
+
+        let body_b64 = jwt.split('.').nth(1).unwrap();
+        let body_bytes = URL_SAFE_NO_PAD.decode(body_b64).unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+
+        let publish_allow = body["nats"]["permissions"]["publish"]["allow"].as_array().unwrap();
+        assert!(!publish_allow.contains(&"openlink.v1.demonetwork.outbox.42".into()));
+        assert_eq!(body["nats"]["tags"][1].as_str().unwrap(), "openlink-role:observer");
     }
 
     #[test]
     fn jwt_sub_matches_user_nkey() {
         let kp = test_account_kp();
         let user_pub = "UTEST_PUBLIC_KEY";
-        let jwt = sign_user_jwt(&kp, user_pub, "99", &NetworkId::new("icao"), 3600).unwrap();
+        let jwt = sign_user_jwt(
+            &kp,
+            user_pub,
+            "99",
+            &NetworkId::new("icao"),
+            UserRole::Pilot,
+            3600,
+        )
+        .unwrap();
 
         let body_b64 = jwt.split('.').nth(1).unwrap();
         let body_bytes = URL_SAFE_NO_PAD.decode(body_b64).unwrap();
@@ -240,14 +400,46 @@ mod tests {
 
         assert_eq!(body["sub"].as_str().unwrap(), user_pub);
         assert_eq!(body["name"].as_str().unwrap(), "99");
+        assert_eq!(body["nats"]["tags"][0].as_str().unwrap(), "openlink-network:icao");
+    }
+
+    #[test]
+    fn service_account_jwt_carries_callsign_tags() {
+        let kp = test_account_kp();
+        let net = NetworkId::new("demonetwork");
+        let callsigns = ["LFPG_TWR".to_string(), "lfpg_gnd".to_string()];
+        let jwt =
+            sign_service_account_jwt(&kp, "UABC123", "900001", &net, UserRole::Bot, &callsigns, 3600)
+                .unwrap();
+
+        let body_b64 = jwt.split('.').nth(1).unwrap();
+        let body: serde_json::Value =
+            serde_json::from_slice(&URL_SAFE_NO_PAD.decode(body_b64).unwrap()).unwrap();
+        assert_eq!(
+            body["nats"]["tags"],
+            serde_json::json!([
+                "openlink-network:demonetwork",
+                "openlink-role:bot",
+                "openlink-callsign:LFPG_TWR",
+                "openlink-callsign:LFPG_GND",
+            ])
+        );
+        assert_eq!(body["name"], "900001");
     }
 
     #[test]
     fn jwt_issuer_is_account_public_key() {
         let kp = test_account_kp();
         let expected_issuer = kp.public_key();
-        let jwt =
-            sign_user_jwt(&kp, "UKEY", "1", &NetworkId::new("demonetwork"), 3600).unwrap();
+        let jwt = sign_user_jwt(
+            &kp,
+            "UKEY",
+            "1",
+            &NetworkId::new("demonetwork"),
+            UserRole::Pilot,
+            3600,
+        )
+        .unwrap();
 
         let body_b64 = jwt.split('.').nth(1).unwrap();
         let body_bytes = URL_SAFE_NO_PAD.decode(body_b64).unwrap();
@@ -260,7 +452,15 @@ mod tests {
     fn jwt_expiry_matches_ttl() {
         let kp = test_account_kp();
         let ttl = 7200_u64;
-        let jwt = sign_user_jwt(&kp, "UKEY", "1", &NetworkId::new("demonetwork"), ttl).unwrap();
+        let jwt = sign_user_jwt(
+            &kp,
+            "UKEY",
+            "1",
+            &NetworkId::new("demonetwork"),
+            UserRole::Pilot,
+            ttl,
+        )
+        .unwrap();
 
         let body_b64 = jwt.split('.').nth(1).unwrap();
         let body_bytes = URL_SAFE_NO_PAD.decode(body_b64).unwrap();
@@ -298,10 +498,34 @@ mod tests {
 
         assert!(pub_allow.contains(&"openlink.v1.demonetwork.inbox.>"));
         assert!(pub_allow.contains(&"$JS.API.>"));
+        assert!(pub_allow.contains(&"openlink.v1.demonetwork.replies.>"));
         assert!(sub_allow.contains(&"openlink.v1.demonetwork.outbox.>"));
+        assert!(sub_allow.contains(&"openlink.v1.demonetwork.directory.query"));
+        assert!(pub_allow.contains(&"openlink.v1.*.federation.demonetwork"));
+        assert!(sub_allow.contains(&"openlink.v1.demonetwork.federation.*"));
         assert!(sub_allow.contains(&"$JS.API.>"));
     }
 
+    #[test]
+    fn service_jwt_only_reaches_jetstream() {
+        let kp = test_account_kp();
+        let jwt = sign_service_jwt(&kp, "UAUTH", 600).unwrap();
+
+        let body_b64 = jwt.split('.').nth(1).unwrap();
+        let body_bytes = URL_SAFE_NO_PAD.decode(body_b64).unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+
+        assert_eq!(body["name"], "openlink-auth");
+        assert_eq!(
+            body["nats"]["permissions"]["publish"]["allow"],
+            serde_json::json!(["$JS.API.>", "$KV.>", "_INBOX.>"])
+        );
+        assert_eq!(
+            body["nats"]["permissions"]["subscribe"]["allow"],
+            serde_json::json!(["_INBOX.>"])
+        );
+    }
+
     #[test]
     fn server_jwt_name_contains_network() {
         let kp = test_account_kp();
@@ -3,27 +3,78 @@
 //! The service is configured with a mapping of network keys (e.g. `demonetwork`)
 //! to OIDC provider parameters.  On each request it:
 //!
-//! 1. Validates the OIDC code against the identity provider.
-//! 2. Signs a scoped NATS user JWT (publish outbox / subscribe inbox).
-//! 3. Returns the JWT and authenticated CID to the caller.
+//! 1. Exchanges the OIDC code at the identity provider and verifies the
+//!    returned `id_token` against the provider's JWKS.
+//! 2. Picks the user's role among those granted by their claims.
+//! 3. Signs a scoped NATS user JWT (publish outbox / subscribe inbox).
+//! 4. Returns the JWT, authenticated CID and role to the caller, with a
+//!    refresh token the client redeems at `POST /refresh` for a new JWT
+//!    before this one expires.
+//!
+//! Terminals and headless tools without a browser redirect use the device
+//! authorization grant instead: `POST /device/authorize` starts it at the
+//! identity provider and `POST /device/token` is polled until the user
+//! approves, then answers like `POST /exchange`.
+//!
+//! OpenLink servers obtain network-wide JWTs at `POST /exchange-server` with
+//! per-server credentials (see [`server_auth`]), and service accounts (bots,
+//! bridges) obtain user JWTs restricted to their callsigns at
+//! `POST /exchange-service` (see [`service_account`]); every issuance and
+//! refusal is logged under the `openlink_auth::audit` target, and issued
+//! JWTs are appended to the [`audit`] log.
+//!
+//! The exchange endpoints are [rate limited](rate_limit) per client IP and
+//! per identity. `GET /openapi.json` describes every endpoint (see
+//! [`openapi`]).
+//!
+//! With `AUTH_ADMIN_TOKEN` set, the [`admin`] routes revoke a user's
+//! credentials before they expire.
 
+mod admin;
+mod audit;
 mod config;
 mod error;
 mod jwt;
 mod oidc;
+mod openapi;
+mod rate_limit;
+mod revocation;
+mod server_auth;
+mod service_account;
+mod session;
 
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
 
-use axum::extract::{Json, State};
+use axum::extract::{Json, Request, State};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
 use axum::routing::{get, post};
 use axum::Router;
 use nkeys::KeyPair;
-use openlink_models::NetworkId;
+use openlink_models::{NetworkId, UserRole};
+use openlink_sdk::DeviceAuthorization;
 use serde::{Deserialize, Serialize};
-use tracing::info;
+use tracing::{error, info, warn};
+use utoipa::ToSchema;
+
+use crate::audit::{AuditLog, Grant, RequestSource};
+use crate::config::{
+    AppConfig, OidcProviderConfig, RateLimitConfig, ServerCredentialConfig, ServiceAccountConfig,
+};
+use crate::error::{AuthError, ErrorBody};
+use crate::oidc::{OidcProvider, VerifiedUser};
+use crate::openapi::Role;
+use crate::rate_limit::RateLimiter;
+use crate::revocation::RevocationStore;
+use crate::server_auth::ServerProof;
+use crate::service_account::ServiceAccountProof;
+use crate::session::{RefreshSession, RefreshSessions, unix_now};
 
-use crate::config::AppConfig;
-use crate::error::AuthError;
+/// Tracing target of the server JWT audit trail.
+const AUDIT: &str = "openlink_auth::audit";
 
 // ---------------------------------------------------------------------------
 // Shared application state
@@ -33,10 +84,161 @@ use crate::error::AuthError;
 struct AppState {
     /// NATS account key-pair used to sign user JWTs.
     account_kp: KeyPair,
-    /// Global configuration (network → OIDC mapping).
-    config: AppConfig,
-    /// Shared secret that server instances present to obtain a master JWT.
-    server_secret: String,
+    /// OIDC providers by network, with their discovery and keys cached.
+    /// Replaced as a whole when the configuration is reloaded.
+    providers: RwLock<HashMap<NetworkId, Arc<OidcProvider>>>,
+    /// Servers allowed to obtain network-wide JWTs, by name. Replaced as a
+    /// whole when the configuration is reloaded.
+    servers: RwLock<HashMap<String, ServerCredentialConfig>>,
+    /// Service accounts, by name. Replaced as a whole when the
+    /// configuration is reloaded.
+    service_accounts: RwLock<HashMap<String, ServiceAccountConfig>>,
+    /// Open refresh sessions.
+    sessions: RefreshSessions,
+    /// Bearer token of the admin API.
+    admin_token: Option<String>,
+    /// Where revocations are recorded; `None` while the admin API is
+    /// disabled.
+    revocations: Option<RevocationStore>,
+    /// Rate limits read at startup.
+    rate_limits: RateLimitConfig,
+    /// Exchange requests per client IP.
+    ip_limiter: RateLimiter,
+    /// Exchange requests per identity.
+    identity_limiter: RateLimiter,
+    /// Record of every issued JWT.
+    audit: AuditLog,
+}
+
+impl AppState {
+    fn provider(&self, network: &NetworkId) -> Option<Arc<OidcProvider>> {
+        self.providers
+            .read()
+            .expect("providers lock poisoned")
+            .get(network)
+            .cloned()
+    }
+
+    /// Take a request for `identity` (`user:{network}:{cid}`,
+    /// `key:{network}:{user_nkey_public}`, `server:{name}` or
+    /// `service_account:{name}`) from its rate limit.
+    fn limit_identity(&self, identity: &str, source: &RequestSource) -> Result<(), AuthError> {
+        self.identity_limiter
+            .check(identity, Instant::now())
+            .map_err(|retry_after| {
+                warn!(target: AUDIT, ip = %source.ip, identity, "identity rate limit exceeded");
+                AuthError::RateLimited(retry_after.as_secs_f64().ceil() as u64)
+            })
+    }
+}
+
+/// Refuse exchange requests from client IPs over their rate limit.
+async fn limit_per_ip(
+    State(state): State<Arc<AppState>>,
+    source: RequestSource,
+    request: Request,
+    next: Next,
+) -> Response {
+    match state.ip_limiter.check(&source.ip.to_string(), Instant::now()) {
+        Ok(()) => next.run(request).await,
+        Err(retry_after) => {
+            warn!(target: AUDIT, ip = %source.ip, path = %request.uri().path(), "IP rate limit exceeded");
+            AuthError::RateLimited(retry_after.as_secs_f64().ceil() as u64).into_response()
+        }
+    }
+}
+
+/// Build one provider per network, keeping the existing provider (and its
+/// cached discovery and keys) when a network's configuration is unchanged.
+fn build_providers(
+    networks: HashMap<NetworkId, OidcProviderConfig>,
+    previous: &HashMap<NetworkId, Arc<OidcProvider>>,
+) -> HashMap<NetworkId, Arc<OidcProvider>> {
+    networks
+        .into_iter()
+        .map(|(network, config)| {
+            let provider = match previous.get(&network) {
+                Some(existing) if *existing.config() == config => existing.clone(),
+                _ => {
+                    info!(
+                        network = %network,
+                        issuer = %config.issuer,
+                        client_id = %config.client_id,
+                        "OIDC provider registered"
+                    );
+                    Arc::new(OidcProvider::new(config))
+                }
+            };
+            (network, provider)
+        })
+        .collect()
+}
+
+/// Log the registered servers, warning about the development secret.
+fn log_servers(servers: &HashMap<String, ServerCredentialConfig>) {
+    for (name, server) in servers {
+        let credential = if server.public_key.is_some() { "key" } else { "secret" };
+        info!(
+            server = %name,
+            credential,
+            networks = ?server.networks,
+            revoked = server.revoked,
+            "server registered"
+        );
+        if server.secret.as_deref() == Some("openlink-dev-secret") {
+            warn!(server = %name, "server uses the development secret; set SERVER_SECRET or register servers");
+        }
+    }
+}
+
+/// Log the registered service accounts.
+fn log_service_accounts(accounts: &HashMap<String, ServiceAccountConfig>) {
+    for (name, account) in accounts {
+        let credential = if account.public_key.is_some() { "key" } else { "api_key" };
+        info!(
+            account = %name,
+            credential,
+            network = %account.network,
+            cid = %account.cid,
+            callsigns = ?account.callsigns,
+            roles = ?account.roles,
+            expires_at = ?account.expires_at,
+            revoked = account.revoked,
+            "service account registered"
+        );
+    }
+}
+
+/// Reload the network configuration on `SIGHUP`. An invalid configuration
+/// is logged and the running one kept.
+#[cfg(unix)]
+async fn reload_on_sighup(state: Arc<AppState>) {
+    use tokio::signal::unix::{SignalKind, signal};
+
+    let mut hangup = match signal(SignalKind::hangup()) {
+        Ok(hangup) => hangup,
+        Err(e) => {
+            error!(error = %e, "cannot listen for SIGHUP; configuration reload disabled");
+            return;
+        }
+    };
+    while hangup.recv().await.is_some() {
+        match AppConfig::load() {
+            Ok(config) => {
+                log_servers(&config.servers);
+                log_service_accounts(&config.service_accounts);
+                *state.servers.write().expect("servers lock poisoned") = config.servers;
+                *state
+                    .service_accounts
+                    .write()
+                    .expect("service accounts lock poisoned") = config.service_accounts;
+                let mut providers = state.providers.write().expect("providers lock poisoned");
+                *providers = build_providers(config.networks, &providers);
+                info!(networks = providers.len(), "configuration reloaded");
+            }
+            Err(e) => error!(error = %e, "configuration reload failed; keeping current networks"),
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -44,7 +246,7 @@ struct AppState {
 // ---------------------------------------------------------------------------
 
 /// Body of `POST /exchange`.
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 struct ExchangeRequest {
     /// OIDC authorization code received from the identity provider.
     oidc_code: String,
@@ -53,6 +255,19 @@ struct ExchangeRequest {
     /// Network the user wants to authenticate against (e.g. `"demonetwork"`).
     #[serde(default = "default_network")]
     network: String,
+    /// Nonce sent in the authorization request, checked against the
+    /// `id_token` when present.
+    #[serde(default)]
+    nonce: Option<String>,
+    /// PKCE code verifier of the authorization request, forwarded to the
+    /// identity provider.
+    #[serde(default)]
+    code_verifier: Option<String>,
+    /// Role to act under; must be granted by the user's claims. Defaults
+    /// to pilot when granted, else the most privileged granted role.
+    #[serde(default)]
+    #[schema(value_type = Option<Role>)]
+    role: Option<UserRole>,
 }
 
 fn default_network() -> String {
@@ -60,21 +275,112 @@ fn default_network() -> String {
 }
 
 /// Response of `POST /exchange`.
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 struct ExchangeResponse {
     /// Signed NATS user JWT.
     jwt: String,
     /// Authenticated CID.
     cid: String,
+    /// Role embedded in the JWT.
+    #[schema(value_type = Role)]
+    role: UserRole,
     /// Network the JWT was issued for.
     network: String,
+    /// Expiry of the JWT (Unix seconds).
+    expires_at: u64,
+    /// Single-use token for `POST /refresh`; absent when the network
+    /// issues no refresh tokens or the session ends with this JWT.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    refresh_token: Option<String>,
+}
+
+/// Body of `POST /device/authorize`.
+#[derive(Deserialize, ToSchema)]
+struct DeviceAuthorizeRequest {
+    /// Network the user wants to authenticate against.
+    #[serde(default = "default_network")]
+    network: String,
+}
+
+/// Body of `POST /device/token`.
+#[derive(Deserialize, ToSchema)]
+struct DeviceTokenRequest {
+    /// Device code from `POST /device/authorize`.
+    device_code: String,
+    /// Client-generated NKey public key to embed in the JWT.
+    user_nkey_public: String,
+    /// Network the device authorization was started for.
+    #[serde(default = "default_network")]
+    network: String,
+    /// Role to act under, as for `POST /exchange`.
+    #[serde(default)]
+    #[schema(value_type = Option<Role>)]
+    role: Option<UserRole>,
+}
+
+/// Body of `POST /refresh`.
+#[derive(Deserialize, ToSchema)]
+struct RefreshRequest {
+    /// Refresh token from the previous exchange or refresh.
+    refresh_token: String,
+    /// NKey public key the session was opened with.
+    user_nkey_public: String,
+}
+
+/// Body of `POST /exchange-service`.
+#[derive(Deserialize, ToSchema)]
+struct ExchangeServiceRequest {
+    /// Name the service account is registered under.
+    account: String,
+    /// API key of an API-key account.
+    #[serde(default)]
+    api_key: Option<String>,
+    /// Unix time the assertion was signed at.
+    #[serde(default)]
+    timestamp: Option<u64>,
+    /// Base64url signature of the assertion by the account's registered key.
+    #[serde(default)]
+    signature: Option<String>,
+    /// Client-generated NKey public key to embed in the JWT.
+    user_nkey_public: String,
+    /// Network the account acts on.
+    network: String,
+    /// Role to act under; must be one of the account's. Defaults to bot
+    /// when allowed, else the account's first role.
+    #[serde(default)]
+    #[schema(value_type = Option<Role>)]
+    role: Option<UserRole>,
+}
+
+/// Entry of `GET /networks`.
+#[derive(Serialize, ToSchema)]
+struct NetworkInfo {
+    /// Network key.
+    network: String,
+    /// OIDC issuer users authenticate with.
+    issuer: String,
+    /// Client ID to use in the authorization request.
+    client_id: String,
+    /// Scopes to request.
+    scopes: Vec<String>,
 }
 
 /// Body of `POST /exchange-server`.
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 struct ExchangeServerRequest {
-    /// Pre-shared secret proving the caller is a legitimate server.
-    server_secret: String,
+    /// Name the server is registered under; optional for secret-registered
+    /// servers.
+    #[serde(default)]
+    server_name: Option<String>,
+    /// Shared secret of a secret-registered server.
+    #[serde(default)]
+    server_secret: Option<String>,
+    /// Unix time the assertion was signed at.
+    #[serde(default)]
+    timestamp: Option<u64>,
+    /// Base64url signature of the assertion by the server's registered key.
+    #[serde(default)]
+    signature: Option<String>,
     /// Client-generated NKey public key to embed in the JWT.
     user_nkey_public: String,
     /// Network the server needs master access to.
@@ -82,12 +388,16 @@ struct ExchangeServerRequest {
 }
 
 /// Response of `POST /exchange-server`.
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 struct ExchangeServerResponse {
     /// Signed NATS server JWT with wildcard permissions.
     jwt: String,
     /// Network the JWT was issued for.
     network: String,
+    /// Name of the authenticated server.
+    server_name: String,
+    /// Expiry of the JWT (Unix seconds).
+    expires_at: u64,
 }
 
 // ---------------------------------------------------------------------------
@@ -98,76 +408,495 @@ struct ExchangeServerResponse {
 ///
 /// Clients or monitoring tools can use this to verify they are talking
 /// to the expected auth service.
+#[utoipa::path(
+    get,
+    path = "/public-key",
+    tag = "service",
+    responses((status = 200, description = "NATS account public key (`A…`)", body = String, content_type = "text/plain"))
+)]
 async fn get_public_key(State(state): State<Arc<AppState>>) -> String {
     state.account_kp.public_key()
 }
 
+/// `GET /networks` — list the served networks and how to log in to them.
+#[utoipa::path(
+    get,
+    path = "/networks",
+    tag = "service",
+    responses((status = 200, description = "Served networks, sorted by key", body = Vec<NetworkInfo>))
+)]
+async fn list_networks(State(state): State<Arc<AppState>>) -> Json<Vec<NetworkInfo>> {
+    let providers = state.providers.read().expect("providers lock poisoned");
+    let mut networks: Vec<NetworkInfo> = providers
+        .iter()
+        .map(|(network, provider)| {
+            let config = provider.config();
+            NetworkInfo {
+                network: network.to_string(),
+                issuer: config.issuer.clone(),
+                client_id: config.client_id.clone(),
+                scopes: config.scopes.clone(),
+            }
+        })
+        .collect();
+    networks.sort_by(|a, b| a.network.cmp(&b.network));
+    Json(networks)
+}
+
 /// `POST /exchange` — exchange an OIDC code for a NATS JWT.
+#[utoipa::path(
+    post,
+    path = "/exchange",
+    tag = "users",
+    request_body = ExchangeRequest,
+    responses(
+        (status = 200, description = "JWT issued", body = ExchangeResponse),
+        (status = 400, description = "Unknown network", body = ErrorBody),
+        (status = 401, description = "Code exchange failed or invalid `id_token`", body = ErrorBody),
+        (status = 403, description = "Requested role not granted", body = ErrorBody),
+        (status = 429, description = "Rate limited", body = ErrorBody, headers(("Retry-After" = u64, description = "Seconds to wait"))),
+        (status = 502, description = "Identity provider unreachable", body = ErrorBody),
+    )
+)]
 async fn exchange_token(
     State(state): State<Arc<AppState>>,
+    source: RequestSource,
     Json(req): Json<ExchangeRequest>,
 ) -> Result<Json<ExchangeResponse>, AuthError> {
     let network = NetworkId::new(&req.network);
 
     // 1. Resolve OIDC provider for the requested network
     let provider = state
-        .config
-        .provider_for(&network)
+        .provider(&network)
         .ok_or_else(|| AuthError::UnknownNetwork(req.network.clone()))?;
 
     info!(network = %network, "exchange request received");
 
-    // 2. Exchange the OIDC code for a CID
-    let cid = oidc::exchange_code(provider, &req.oidc_code).await?;
-    info!(network = %network, cid = %cid, "OIDC authentication successful");
+    // 2. Limit the requesting key before the code is spent at the provider;
+    //    the CID is only known once it is
+    state.limit_identity(&format!("key:{network}:{}", req.user_nkey_public), &source)?;
 
-    // 3. Sign a scoped NATS JWT
-    let jwt_ttl_secs = 3600;
-    let jwt_token =
-        jwt::sign_user_jwt(&state.account_kp, &req.user_nkey_public, &cid, &network, jwt_ttl_secs)?;
+    // 3. Exchange the OIDC code for a CID
+    let user = provider
+        .exchange_code(&req.oidc_code, req.nonce.as_deref(), req.code_verifier.as_deref())
+        .await?;
+    info!(network = %network, cid = %user.cid, "OIDC authentication successful");
 
-    info!(network = %network, cid = %cid, "JWT issued");
+    // 4. Pick the role, sign the JWT and open a refresh session
+    let login = Login {
+        grant: Grant::Exchange,
+        source: &source,
+        user_nkey_public: &req.user_nkey_public,
+        role: req.role,
+    };
+    issue_user_jwt(&state, &provider, &network, user, login).map(Json)
+}
 
-    Ok(Json(ExchangeResponse {
+/// `POST /device/authorize` — start a device login at the network's
+/// identity provider.
+///
+/// Returns the provider's `device_code`, `user_code`, `verification_uri`
+/// (and `verification_uri_complete`), `expires_in` and `interval`: the
+/// client shows the URI and code to the user and polls `POST /device/token`.
+#[utoipa::path(
+    post,
+    path = "/device/authorize",
+    tag = "users",
+    request_body = DeviceAuthorizeRequest,
+    responses(
+        (status = 200, description = "Device login started", body = crate::openapi::DeviceAuthorizationSchema),
+        (status = 400, description = "Unknown network, or no device login at its identity provider", body = ErrorBody),
+        (status = 401, description = "Identity provider refused the request", body = ErrorBody),
+        (status = 429, description = "Rate limited", body = ErrorBody, headers(("Retry-After" = u64, description = "Seconds to wait"))),
+        (status = 502, description = "Identity provider unreachable", body = ErrorBody),
+    )
+)]
+async fn device_authorize(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<DeviceAuthorizeRequest>,
+) -> Result<Json<DeviceAuthorization>, AuthError> {
+    let network = NetworkId::new(&req.network);
+    let provider = state
+        .provider(&network)
+        .ok_or_else(|| AuthError::UnknownNetwork(req.network.clone()))?;
+    let authorization = provider.start_device_authorization().await?;
+    info!(network = %network, user_code = %authorization.user_code, "device login started");
+    Ok(Json(authorization))
+}
+
+/// `POST /device/token` — redeem an approved device code for a NATS JWT.
+///
+/// Answers like `POST /exchange` once the user approved the login; until
+/// then, 400 with `{"error": "authorization_pending"}` (or `slow_down`,
+/// `expired_token`, `access_denied`) as returned by the provider.
+#[utoipa::path(
+    post,
+    path = "/device/token",
+    tag = "users",
+    request_body = DeviceTokenRequest,
+    responses(
+        (status = 200, description = "Login approved, JWT issued", body = ExchangeResponse),
+        (status = 400, description = "`authorization_pending`, `slow_down`, `expired_token`, `access_denied` or unknown network", body = ErrorBody),
+        (status = 401, description = "Invalid device code or `id_token`", body = ErrorBody),
+        (status = 403, description = "Requested role not granted", body = ErrorBody),
+        (status = 429, description = "Rate limited", body = ErrorBody, headers(("Retry-After" = u64, description = "Seconds to wait"))),
+        (status = 502, description = "Identity provider unreachable", body = ErrorBody),
+    )
+)]
+async fn device_token(
+    State(state): State<Arc<AppState>>,
+    source: RequestSource,
+    Json(req): Json<DeviceTokenRequest>,
+) -> Result<Json<ExchangeResponse>, AuthError> {
+    let network = NetworkId::new(&req.network);
+    let provider = state
+        .provider(&network)
+        .ok_or_else(|| AuthError::UnknownNetwork(req.network.clone()))?;
+    let user = provider.poll_device_code(&req.device_code).await?;
+    info!(network = %network, cid = %user.cid, "device login approved");
+    let login = Login {
+        grant: Grant::Device,
+        source: &source,
+        user_nkey_public: &req.user_nkey_public,
+        role: req.role,
+    };
+    issue_user_jwt(&state, &provider, &network, user, login).map(Json)
+}
+
+/// How an authenticated user asked for a JWT.
+struct Login<'a> {
+    grant: Grant,
+    source: &'a RequestSource,
+    user_nkey_public: &'a str,
+    role: Option<UserRole>,
+}
+
+/// Sign a user JWT for an authenticated `user` under the requested role,
+/// record it and open their refresh session.
+fn issue_user_jwt(
+    state: &AppState,
+    provider: &OidcProvider,
+    network: &NetworkId,
+    user: VerifiedUser,
+    login: Login<'_>,
+) -> Result<ExchangeResponse, AuthError> {
+    let cid = user.cid;
+    let user_nkey_public = login.user_nkey_public;
+    state.limit_identity(&format!("user:{network}:{cid}"), login.source)?;
+
+    // 1. Pick the role
+    let role = select_role(login.role, &user.granted_roles)?;
+
+    // 2. Sign a scoped NATS JWT
+    let now = unix_now();
+    let jwt_ttl_secs = provider.config().jwt_ttl_seconds;
+    let jwt_token = jwt::sign_user_jwt(
+        &state.account_kp,
+        user_nkey_public,
+        &cid,
+        network,
+        role,
+        jwt_ttl_secs,
+    )?;
+    state
+        .audit
+        .jwt_issued(login.grant, &jwt_token, network, None, login.source)?;
+
+    // 3. Open a refresh session
+    let refresh_ttl_secs = provider.config().refresh_ttl_seconds;
+    let refresh_token = (refresh_ttl_secs > 0).then(|| {
+        state.sessions.open(
+            RefreshSession {
+                cid: cid.clone(),
+                network: network.clone(),
+                role,
+                user_nkey_public: user_nkey_public.to_string(),
+                expires_at: now + refresh_ttl_secs,
+            },
+            now,
+        )
+    });
+
+    Ok(ExchangeResponse {
         jwt: jwt_token,
         cid,
+        role,
+        network: network.to_string(),
+        expires_at: now + jwt_ttl_secs,
+        refresh_token,
+    })
+}
+
+/// `POST /refresh` — renew a NATS JWT with a refresh token.
+///
+/// The new JWT keeps the CID, network and role of the login and never
+/// outlives its session. The refresh token is rotated.
+#[utoipa::path(
+    post,
+    path = "/refresh",
+    tag = "users",
+    request_body = RefreshRequest,
+    responses(
+        (status = 200, description = "JWT renewed; `refresh_token` absent when the session ends with it", body = ExchangeResponse),
+        (status = 400, description = "The session's network is no longer served", body = ErrorBody),
+        (status = 401, description = "Unknown, used or expired refresh token, or another key", body = ErrorBody),
+        (status = 429, description = "Rate limited", body = ErrorBody, headers(("Retry-After" = u64, description = "Seconds to wait"))),
+    )
+)]
+async fn refresh_token(
+    State(state): State<Arc<AppState>>,
+    source: RequestSource,
+    Json(req): Json<RefreshRequest>,
+) -> Result<Json<ExchangeResponse>, AuthError> {
+    let now = unix_now();
+    let session = state
+        .sessions
+        .check(&req.refresh_token, &req.user_nkey_public, now)?;
+    state.limit_identity(&format!("user:{}:{}", session.network, session.cid), &source)?;
+    let provider = state
+        .provider(&session.network)
+        .ok_or_else(|| AuthError::UnknownNetwork(session.network.to_string()))?;
+
+    let jwt_ttl_secs = provider
+        .config()
+        .jwt_ttl_seconds
+        .min(session.expires_at - now);
+    let jwt_token = jwt::sign_user_jwt(
+        &state.account_kp,
+        &session.user_nkey_public,
+        &session.cid,
+        &session.network,
+        session.role,
+        jwt_ttl_secs,
+    )?;
+    let expires_at = now + jwt_ttl_secs;
+    state
+        .audit
+        .jwt_issued(Grant::Refresh, &jwt_token, &session.network, None, &source)?;
+    // The token is spent only once the JWT is recorded; the session ends
+    // with this refresh once the JWT reaches its end.
+    let refresh_token = state
+        .sessions
+        .rotate(&req.refresh_token, expires_at < session.expires_at)?;
+
+    Ok(Json(ExchangeResponse {
+        jwt: jwt_token,
+        cid: session.cid,
+        role: session.role,
+        network: session.network.to_string(),
+        expires_at,
+        refresh_token,
+    }))
+}
+
+/// `POST /exchange-service` — exchange a service account credential for a
+/// NATS JWT.
+///
+/// The JWT is issued for the account's CID, restricted to its callsigns and
+/// never outlives the account. It is refused while a revocation of the CID
+/// or of the requesting key is in force, so revoking an account through the
+/// admin API also stops its renewals. Every issuance and refusal is written
+/// to the audit log.
+#[utoipa::path(
+    post,
+    path = "/exchange-service",
+    tag = "services",
+    request_body = ExchangeServiceRequest,
+    responses(
+        (status = 200, description = "JWT issued for the account's CID; no `refresh_token`", body = ExchangeResponse),
+        (status = 400, description = "Unknown network", body = ErrorBody),
+        (status = 401, description = "Unknown, invalid, expired or revoked credential", body = ErrorBody),
+        (status = 403, description = "Other network, or role not allowed to the account", body = ErrorBody),
+        (status = 429, description = "Rate limited", body = ErrorBody, headers(("Retry-After" = u64, description = "Seconds to wait"))),
+        (status = 502, description = "Revocation store unreachable", body = ErrorBody),
+    )
+)]
+async fn exchange_service_token(
+    State(state): State<Arc<AppState>>,
+    source: RequestSource,
+    Json(req): Json<ExchangeServiceRequest>,
+) -> Result<Json<ExchangeResponse>, AuthError> {
+    let network = NetworkId::new(&req.network);
+    let now = unix_now();
+    let refused = |e: &AuthError| {
+        warn!(
+            target: AUDIT,
+            ip = %source.ip,
+            account = %req.account,
+            network = %req.network,
+            error = %e,
+            "service account JWT refused"
+        );
+    };
+
+    // 1. Authenticate the account and pick the role
+    let authenticated = {
+        let accounts = state
+            .service_accounts
+            .read()
+            .expect("service accounts lock poisoned");
+        let proof = ServiceAccountProof {
+            api_key: req.api_key.as_deref(),
+            timestamp: req.timestamp,
+            signature: req.signature.as_deref(),
+        };
+        service_account::authenticate(
+            &accounts,
+            &req.account,
+            &proof,
+            &network,
+            &req.user_nkey_public,
+            now,
+        )
+        .and_then(|account| {
+            let role = match req.role {
+                None if account.roles.contains(&UserRole::Bot) => UserRole::Bot,
+                requested => select_role(requested, &account.roles)?,
+            };
+            Ok((account.clone(), role))
+        })
+    };
+    let (account, role) = authenticated.inspect_err(refused)?;
+    state.limit_identity(&format!("service_account:{}", req.account), &source)?;
+
+    // 2. Refuse revoked credentials
+    if let Some(store) = &state.revocations
+        && let Some(revocation) = store
+            .find(&network, &account.cid, &req.user_nkey_public)
+            .await?
+    {
+        let e = AuthError::ServiceAccountRejected(match revocation.reason.as_str() {
+            "" => "credentials revoked".to_string(),
+            reason => format!("credentials revoked: {reason}"),
+        });
+        refused(&e);
+        return Err(e);
+    }
+
+    // 3. Sign a NATS JWT restricted to the account's callsigns
+    let jwt_ttl_secs = account
+        .expires_at
+        .map_or(account.jwt_ttl_seconds, |expires_at| {
+            account.jwt_ttl_seconds.min(expires_at - now)
+        });
+    let callsigns: &[String] = if account.any_callsign() {
+        &[]
+    } else {
+        &account.callsigns
+    };
+    let jwt_token = jwt::sign_service_account_jwt(
+        &state.account_kp,
+        &req.user_nkey_public,
+        &account.cid,
+        &network,
+        role,
+        callsigns,
+        jwt_ttl_secs,
+    )?;
+    let expires_at = now + jwt_ttl_secs;
+    state.audit.jwt_issued(
+        Grant::ServiceAccount,
+        &jwt_token,
+        &network,
+        Some(&req.account),
+        &source,
+    )?;
+
+    Ok(Json(ExchangeResponse {
+        jwt: jwt_token,
+        cid: account.cid,
+        role,
         network: req.network,
+        expires_at,
+        refresh_token: None,
     }))
 }
 
-/// `POST /exchange-server` — exchange a server secret for a master NATS JWT.
+/// The requested role if granted; without a request, pilot when granted,
+/// else the most privileged granted role.
+fn select_role(requested: Option<UserRole>, granted: &[UserRole]) -> Result<UserRole, AuthError> {
+    match requested {
+        Some(role) if granted.contains(&role) => Ok(role),
+        Some(role) => Err(AuthError::RoleNotGranted(role)),
+        None if granted.contains(&UserRole::Pilot) => Ok(UserRole::Pilot),
+        None => Ok(granted.first().copied().unwrap_or(UserRole::Observer)),
+    }
+}
+
+/// `POST /exchange-server` — exchange a server credential for a master NATS
+/// JWT.
 ///
 /// The server JWT grants wildcard publish/subscribe on all outbox and inbox
-/// subjects of the requested network, plus JetStream API access for KV stores.
+/// subjects of the requested network, plus JetStream API access for KV
+/// stores, so every issuance and refusal is written to the audit log.
+#[utoipa::path(
+    post,
+    path = "/exchange-server",
+    tag = "services",
+    request_body = ExchangeServerRequest,
+    responses(
+        (status = 200, description = "Server JWT issued", body = ExchangeServerResponse),
+        (status = 400, description = "Invalid network key", body = ErrorBody),
+        (status = 401, description = "Unknown, invalid or revoked credential", body = ErrorBody),
+        (status = 403, description = "Server not registered for the network", body = ErrorBody),
+        (status = 429, description = "Rate limited", body = ErrorBody, headers(("Retry-After" = u64, description = "Seconds to wait"))),
+    )
+)]
 async fn exchange_server_token(
     State(state): State<Arc<AppState>>,
+    source: RequestSource,
     Json(req): Json<ExchangeServerRequest>,
 ) -> Result<Json<ExchangeServerResponse>, AuthError> {
-    // 1. Verify the shared secret
-    if req.server_secret != state.server_secret {
-        return Err(AuthError::OidcExchangeFailed(
-            "invalid server secret".into(),
-        ));
-    }
-
     let network = NetworkId::new(&req.network);
+    let now = unix_now();
 
-    info!(network = %network, "server token request");
+    // 1. Authenticate the server and check its scope
+    let authenticated = if config::is_valid_key(&req.network) {
+        let servers = state.servers.read().expect("servers lock poisoned");
+        let proof = ServerProof {
+            server_name: req.server_name.as_deref(),
+            secret: req.server_secret.as_deref(),
+            timestamp: req.timestamp,
+            signature: req.signature.as_deref(),
+        };
+        server_auth::authenticate(&servers, &proof, &network, &req.user_nkey_public, now)
+            .map(|(name, server)| (name.to_string(), server.jwt_ttl_seconds))
+    } else {
+        Err(AuthError::UnknownNetwork(req.network.clone()))
+    };
+    let (server_name, jwt_ttl_secs) = match authenticated {
+        Ok(authenticated) => authenticated,
+        Err(e) => {
+            warn!(
+                target: AUDIT,
+                ip = %source.ip,
+                server = req.server_name.as_deref().unwrap_or("-"),
+                network = %req.network,
+                error = %e,
+                "server JWT refused"
+            );
+            return Err(e);
+        }
+    };
+    state.limit_identity(&format!("server:{server_name}"), &source)?;
 
-    // 2. Sign a server-scoped NATS JWT (long-lived: 24h)
-    let jwt_ttl_secs = 86_400;
+    // 2. Sign a server-scoped NATS JWT
     let jwt_token = jwt::sign_server_jwt(
         &state.account_kp,
         &req.user_nkey_public,
         &network,
         jwt_ttl_secs,
     )?;
-
-    info!(network = %network, "server JWT issued");
+    let expires_at = now + jwt_ttl_secs;
+    state
+        .audit
+        .jwt_issued(Grant::Server, &jwt_token, &network, Some(&server_name), &source)?;
 
     Ok(Json(ExchangeServerResponse {
         jwt: jwt_token,
         network: req.network,
+        server_name,
+        expires_at,
     }))
 }
 
@@ -186,7 +915,13 @@ async fn main() {
         .init();
 
     // Configuration
-    let config = AppConfig::from_env();
+    let config = match AppConfig::load() {
+        Ok(config) => config,
+        Err(e) => {
+            error!(error = %e, "invalid configuration");
+            std::process::exit(1);
+        }
+    };
 
     // NATS account key-pair (in production this would be loaded from a vault)
     let account_kp = KeyPair::new_account();
@@ -195,31 +930,78 @@ async fn main() {
         "NATS account key generated"
     );
 
-    for (network, provider) in &config.networks {
-        info!(
-            network = %network,
-            token_url = %provider.token_url,
-            "OIDC provider registered"
-        );
-    }
+    let listen_port = config.listen_port;
+    let providers = build_providers(config.networks, &HashMap::new());
 
-    let server_secret = std::env::var("SERVER_SECRET")
-        .unwrap_or_else(|_| "openlink-dev-secret".to_string());
-    info!("server secret configured (use SERVER_SECRET env var in production)");
+    log_servers(&config.servers);
+    log_service_accounts(&config.service_accounts);
 
-    let listen_port = config.listen_port;
+    let revocations = match config.admin_token {
+        Some(_) => match RevocationStore::connect(&config.nats_url, &account_kp).await {
+            Ok(store) => {
+                info!(nats_url = %config.nats_url, "admin API enabled; revocations recorded on NATS");
+                Some(store)
+            }
+            Err(e) => {
+                error!(error = %e, "cannot connect to NATS; revocations unavailable");
+                None
+            }
+        },
+        None => {
+            info!("admin API disabled (set AUTH_ADMIN_TOKEN to enable it)");
+            None
+        }
+    };
+
+    let audit = match AuditLog::open(config.audit_log.as_deref()) {
+        Ok(audit) => audit,
+        Err(e) => {
+            error!(error = %e, path = ?config.audit_log, "cannot open audit log");
+            std::process::exit(1);
+        }
+    };
+    info!(
+        ip_per_minute = config.rate_limits.ip_per_minute,
+        identity_per_minute = config.rate_limits.identity_per_minute,
+        trust_forwarded_for = config.rate_limits.trust_forwarded_for,
+        audit_log = ?config.audit_log,
+        "exchange rate limits and audit log"
+    );
 
     let state = Arc::new(AppState {
         account_kp,
-        config,
-        server_secret,
+        providers: RwLock::new(providers),
+        servers: RwLock::new(config.servers),
+        service_accounts: RwLock::new(config.service_accounts),
+        sessions: RefreshSessions::default(),
+        admin_token: config.admin_token,
+        revocations,
+        ip_limiter: RateLimiter::new(config.rate_limits.ip_per_minute),
+        identity_limiter: RateLimiter::new(config.rate_limits.identity_per_minute),
+        rate_limits: config.rate_limits,
+        audit,
     });
 
-    let app = Router::new()
+    #[cfg(unix)]
+    tokio::spawn(reload_on_sighup(state.clone()));
+
+    let exchanges = Router::new()
         .route("/exchange", post(exchange_token))
+        .route("/refresh", post(refresh_token))
+        .route("/device/authorize", post(device_authorize))
+        .route("/device/token", post(device_token))
         .route("/exchange-server", post(exchange_server_token))
+        .route("/exchange-service", post(exchange_service_token))
+        .route_layer(middleware::from_fn_with_state(state.clone(), limit_per_ip));
+    let mut app = Router::new()
+        .merge(exchanges)
         .route("/public-key", get(get_public_key))
-        .with_state(state);
+        .route("/networks", get(list_networks))
+        .route("/openapi.json", get(openapi::openapi_json));
+    if state.admin_token.is_some() {
+        app = app.merge(admin::router(state.clone()));
+    }
+    let app = app.with_state(state);
 
     let addr = format!("0.0.0.0:{listen_port}");
     let listener = tokio::net::TcpListener::bind(&addr)
@@ -227,5 +1009,28 @@ async fn main() {
         .expect("failed to bind listener");
 
     info!(address = %addr, "auth service listening");
-    axum::serve(listener, app).await.expect("server error");
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .expect("server error");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn select_role_defaults_to_pilot_and_refuses_ungranted_roles() {
+        let controller = [UserRole::Controller, UserRole::Pilot, UserRole::Observer];
+        assert_eq!(select_role(None, &controller).unwrap(), UserRole::Pilot);
+        assert_eq!(
+            select_role(Some(UserRole::Controller), &controller).unwrap(),
+            UserRole::Controller
+        );
+        assert!(select_role(Some(UserRole::Bot), &controller).is_err());
+        let bot = [UserRole::Bot, UserRole::Observer];
+        assert_eq!(select_role(None, &bot).unwrap(), UserRole::Bot);
+    }
 }
@@ -0,0 +1,142 @@
+//! OpenAPI description of the HTTP API.
+//!
+//! Generated from the `#[utoipa::path]` annotations on the handlers and the
+//! request/response types, and served at `GET /openapi.json` so SDKs in
+//! other languages can be generated from it. The admin routes are
+//! described even when the admin API is disabled.
+
+use axum::Json;
+use utoipa::openapi::security::{Http, HttpAuthScheme, SecurityScheme};
+use utoipa::{Modify, OpenApi, ToSchema};
+
+/// Role a JWT is issued for, as sent in requests and responses.
+#[derive(ToSchema)]
+#[schema(as = UserRole)]
+#[allow(dead_code)] // Describes openlink_models::UserRole.
+pub enum Role {
+    Pilot,
+    Controller,
+    Bot,
+    Observer,
+}
+
+/// A revocation in force: JWTs issued to the CID or NKey before
+/// `revoked_at` are refused.
+#[derive(ToSchema)]
+#[schema(as = Revocation)]
+#[allow(dead_code)] // Describes openlink_sdk::Revocation.
+pub struct RevocationSchema {
+    /// Revoked CID.
+    cid: Option<String>,
+    /// Revoked user NKey.
+    user_nkey_public: Option<String>,
+    /// Unix time of the revocation.
+    revoked_at: u64,
+    /// Why, for the audit trail.
+    reason: String,
+}
+
+/// A device login started at the identity provider: the user approves
+/// `user_code` at `verification_uri` while the client polls with
+/// `device_code`.
+#[derive(ToSchema)]
+#[schema(as = DeviceAuthorization)]
+#[allow(dead_code)] // Describes openlink_sdk::DeviceAuthorization.
+pub struct DeviceAuthorizationSchema {
+    device_code: String,
+    user_code: String,
+    verification_uri: String,
+    verification_uri_complete: Option<String>,
+    /// Lifetime of the codes in seconds.
+    expires_in: u64,
+    /// Minimum delay between two polls in seconds.
+    interval: u64,
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "OpenLink auth service",
+        description = "Exchanges OIDC logins and server or service account credentials for scoped NATS JWTs."
+    ),
+    paths(
+        crate::exchange_token,
+        crate::device_authorize,
+        crate::device_token,
+        crate::refresh_token,
+        crate::exchange_server_token,
+        crate::exchange_service_token,
+        crate::get_public_key,
+        crate::list_networks,
+        crate::admin::revoke,
+        crate::admin::list_revocations,
+    ),
+    modifiers(&AdminToken),
+    tags(
+        (name = "users", description = "User logins and their renewal"),
+        (name = "services", description = "OpenLink servers and service accounts"),
+        (name = "service", description = "Information about this auth service"),
+        (name = "admin", description = "Revocations; served when `AUTH_ADMIN_TOKEN` is set"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Declares the admin bearer token.
+struct AdminToken;
+
+impl Modify for AdminToken {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "admin_token",
+            SecurityScheme::Http(Http::new(HttpAuthScheme::Bearer)),
+        );
+    }
+}
+
+/// `GET /openapi.json` — the OpenAPI description of this service.
+pub async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_route_is_described() {
+        let doc = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        let paths = doc["paths"].as_object().unwrap();
+        for (path, method) in [
+            ("/exchange", "post"),
+            ("/refresh", "post"),
+            ("/device/authorize", "post"),
+            ("/device/token", "post"),
+            ("/exchange-server", "post"),
+            ("/exchange-service", "post"),
+            ("/public-key", "get"),
+            ("/networks", "get"),
+            ("/admin/v1/{network}/revocations", "get"),
+            ("/admin/v1/{network}/revocations", "post"),
+        ] {
+            assert!(paths[path][method].is_object(), "{method} {path} is not described");
+        }
+
+        let schemas = &doc["components"]["schemas"];
+        assert_eq!(
+            schemas["UserRole"]["enum"],
+            serde_json::json!(["Pilot", "Controller", "Bot", "Observer"])
+        );
+        assert!(schemas["DeviceAuthorization"]["properties"]["user_code"].is_object());
+        let exchange = &schemas["ExchangeRequest"];
+        assert!(exchange["properties"]["oidc_code"].is_object());
+        assert!(
+            exchange["required"]
+                .as_array()
+                .unwrap()
+                .contains(&"user_nkey_public".into())
+        );
+        assert!(paths["/exchange"]["post"]["responses"]["429"]["headers"]["Retry-After"].is_object());
+        assert!(doc["components"]["securitySchemes"]["admin_token"].is_object());
+    }
+}
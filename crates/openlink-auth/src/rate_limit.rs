@@ -0,0 +1,112 @@
+//! Rate limiting of the exchange endpoints.
+//!
+//! Each client IP and each identity (a CID on a network, a server, a
+//! service account) gets a token bucket of `per_minute` requests, refilled
+//! continuously over a minute. The IP limit is checked before the request
+//! is handled, so it also slows down guessing of codes, secrets and API
+//! keys; the identity limit once the identity is known. Exhausted buckets
+//! answer 429 with `Retry-After`.
+//!
+//! Buckets live in memory: every instance of the service limits on its
+//! own, and a restart starts from full buckets.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Number of buckets above which full ones are dropped.
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// Token buckets by key.
+pub struct RateLimiter {
+    per_minute: u32,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    /// A limiter allowing `per_minute` requests per key; `0` allows all.
+    pub fn new(per_minute: u32) -> Self {
+        Self {
+            per_minute,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Take a request for `key` at `now`, or return how long to wait before
+    /// the next one is allowed.
+    pub fn check(&self, key: &str, now: Instant) -> Result<(), Duration> {
+        if self.per_minute == 0 {
+            return Ok(());
+        }
+        let capacity = f64::from(self.per_minute);
+        let refill_per_sec = capacity / 60.0;
+
+        let mut buckets = self.buckets.lock().expect("rate limiter lock poisoned");
+        if buckets.len() >= PRUNE_THRESHOLD {
+            buckets.retain(|_, bucket| {
+                bucket.tokens + elapsed_secs(bucket.updated, now) * refill_per_sec < capacity
+            });
+        }
+        let bucket = buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: capacity,
+            updated: now,
+        });
+        bucket.tokens =
+            (bucket.tokens + elapsed_secs(bucket.updated, now) * refill_per_sec).min(capacity);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - bucket.tokens) / refill_per_sec,
+            ))
+        }
+    }
+}
+
+fn elapsed_secs(since: Instant, now: Instant) -> f64 {
+    now.saturating_duration_since(since).as_secs_f64()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buckets_drain_per_key_and_refill_over_a_minute() {
+        let limiter = RateLimiter::new(3);
+        let start = Instant::now();
+        for _ in 0..3 {
+            assert!(limiter.check("10.0.0.1", start).is_ok());
+        }
+        let retry_after = limiter.check("10.0.0.1", start).unwrap_err();
+        assert_eq!(retry_after, Duration::from_secs(20));
+        // Other keys have their own bucket.
+        assert!(limiter.check("10.0.0.2", start).is_ok());
+
+        // One request per 20 seconds comes back, never above the capacity.
+        assert!(limiter.check("10.0.0.1", start + Duration::from_secs(20)).is_ok());
+        assert!(limiter.check("10.0.0.1", start + Duration::from_secs(20)).is_err());
+        let later = start + Duration::from_secs(3600);
+        for _ in 0..3 {
+            assert!(limiter.check("10.0.0.1", later).is_ok());
+        }
+        assert!(limiter.check("10.0.0.1", later).is_err());
+    }
+
+    #[test]
+    fn zero_disables_the_limit() {
+        let limiter = RateLimiter::new(0);
+        let now = Instant::now();
+        for _ in 0..1000 {
+            assert!(limiter.check("10.0.0.1", now).is_ok());
+        }
+    }
+}
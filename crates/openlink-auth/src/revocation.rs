@@ -0,0 +1,138 @@
+//! Revocation of user credentials.
+//!
+//! Revocations are recorded as [`Revocation`] entries in each network's
+//! JetStream KV bucket ([`NatsSubjects::kv_revocations`]), where every
+//! OpenLink server of the network watches them: envelopes carrying a
+//! revoked JWT are rejected, the user's NATS connections are closed and
+//! their stations marked offline.
+//!
+//! The service connects to NATS with a JWT it signs itself with the
+//! account key, issued anew on every (re)connection.
+
+use async_nats::jetstream::{self, kv};
+use async_nats::ConnectOptions;
+use futures::StreamExt;
+use nkeys::KeyPair;
+use openlink_models::NetworkId;
+use openlink_sdk::revocation::REVOCATION_RETENTION;
+use openlink_sdk::{NatsSubjects, Revocation};
+use tracing::debug;
+
+use crate::error::AuthError;
+use crate::jwt;
+
+/// Lifetime of the service's own NATS JWT; a reconnection signs a new one.
+const SERVICE_JWT_TTL_SECONDS: u64 = 3600;
+
+/// Writes and lists revocations in the networks' KV buckets.
+pub struct RevocationStore {
+    js: jetstream::Context,
+}
+
+impl RevocationStore {
+    /// Connect to NATS at `nats_url` as the service. The connection is
+    /// retried in the background until NATS is reachable.
+    pub async fn connect(nats_url: &str, account_kp: &KeyPair) -> Result<Self, AuthError> {
+        let account_seed = account_kp
+            .seed()
+            .map_err(|e| AuthError::NKeyError(e.to_string()))?;
+        let options = ConnectOptions::with_auth_callback(move |nonce| {
+            let account_seed = account_seed.clone();
+            async move {
+                let account = KeyPair::from_seed(&account_seed).map_err(async_nats::AuthError::new)?;
+                let user = KeyPair::new_user();
+                let jwt = jwt::sign_service_jwt(&account, &user.public_key(), SERVICE_JWT_TTL_SECONDS)
+                    .map_err(async_nats::AuthError::new)?;
+                let mut auth = async_nats::Auth::new();
+                auth.jwt = Some(jwt);
+                auth.signature = Some(user.sign(&nonce).map_err(async_nats::AuthError::new)?);
+                Ok(auth)
+            }
+        })
+        .retry_on_initial_connect();
+        let client = async_nats::connect_with_options(nats_url, options)
+            .await
+            .map_err(|e| AuthError::RevocationStore(e.to_string()))?;
+        Ok(Self {
+            js: jetstream::new(client),
+        })
+    }
+
+    /// Record `revocation` for `network`.
+    pub async fn publish(&self, network: &NetworkId, revocation: &Revocation) -> Result<(), AuthError> {
+        let key = revocation
+            .kv_key()
+            .ok_or_else(|| AuthError::InvalidRequest("cid or user_nkey_public is invalid".into()))?;
+        let value = serde_json::to_vec(revocation)?;
+        self.bucket(network)
+            .await?
+            .put(&key, value.into())
+            .await
+            .map_err(|e| AuthError::RevocationStore(e.to_string()))?;
+        Ok(())
+    }
+
+    /// The revocation in force on `network` for `cid` or `user_nkey_public`,
+    /// if any.
+    pub async fn find(
+        &self,
+        network: &NetworkId,
+        cid: &str,
+        user_nkey_public: &str,
+    ) -> Result<Option<Revocation>, AuthError> {
+        let store = self.bucket(network).await?;
+        for key in [format!("cid.{cid}"), format!("nkey.{user_nkey_public}")] {
+            let value = store
+                .get(&key)
+                .await
+                .map_err(|e| AuthError::RevocationStore(e.to_string()))?;
+            if let Some(value) = value {
+                return Ok(Some(serde_json::from_slice(&value)?));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Revocations in force on `network`.
+    pub async fn list(&self, network: &NetworkId) -> Result<Vec<Revocation>, AuthError> {
+        let store = self.bucket(network).await?;
+        let mut keys = store
+            .keys()
+            .await
+            .map_err(|e| AuthError::RevocationStore(e.to_string()))?;
+        let mut revocations = Vec::new();
+        while let Some(key) = keys.next().await {
+            let key = key.map_err(|e| AuthError::RevocationStore(e.to_string()))?;
+            let value = store
+                .get(&key)
+                .await
+                .map_err(|e| AuthError::RevocationStore(e.to_string()))?;
+            if let Some(value) = value {
+                revocations.push(serde_json::from_slice(&value)?);
+            }
+        }
+        revocations.sort_by_key(|r: &Revocation| r.revoked_at);
+        Ok(revocations)
+    }
+
+    /// Create or bind to the network's bucket.
+    async fn bucket(&self, network: &NetworkId) -> Result<kv::Store, AuthError> {
+        let bucket = NatsSubjects::kv_revocations(network);
+        let config = kv::Config {
+            bucket: bucket.clone(),
+            history: 1,
+            max_age: REVOCATION_RETENTION,
+            ..Default::default()
+        };
+        match self.js.create_key_value(config).await {
+            Ok(store) => Ok(store),
+            Err(e) => {
+                debug!(%bucket, error = %e, "bucket exists, binding");
+                self.js
+                    .get_key_value(&bucket)
+                    .await
+                    .map_err(|e| AuthError::RevocationStore(e.to_string()))
+            }
+        }
+    }
+}
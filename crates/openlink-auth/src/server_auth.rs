@@ -0,0 +1,235 @@
+//! Authentication of OpenLink servers at `POST /exchange-server`.
+//!
+//! Each server is registered under a name in the configuration (see
+//! [`ServerCredentialConfig`]), with either an NKey public key or a shared
+//! secret, and the networks it may serve. A key-registered server signs
+//! [`server_assertion`]`(name, network, user_nkey_public, timestamp)` with
+//! its seed; the timestamp must be within [`ASSERTION_MAX_SKEW_SECONDS`] of
+//! the service's clock, and binding the requested key makes a replayed
+//! assertion worthless. Shared secrets are compared in constant time.
+//!
+//! Requests without a `server_name` (SDKs predating named servers) are
+//! matched against the registered secrets.
+
+use std::collections::HashMap;
+
+use base64::Engine as _;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use nkeys::KeyPair;
+use openlink_models::NetworkId;
+use openlink_sdk::server_assertion;
+use subtle::ConstantTimeEq;
+
+use crate::config::ServerCredentialConfig;
+use crate::error::AuthError;
+
+/// Maximum distance between an assertion's timestamp and the current time.
+pub const ASSERTION_MAX_SKEW_SECONDS: u64 = 60;
+
+/// What a server presents to prove its identity.
+#[derive(Debug, Default)]
+pub struct ServerProof<'a> {
+    pub server_name: Option<&'a str>,
+    pub secret: Option<&'a str>,
+    pub timestamp: Option<u64>,
+    pub signature: Option<&'a str>,
+}
+
+/// Authenticate a server asking for a JWT issued to `user_nkey_public` on
+/// `network`, returning its registered name and configuration.
+pub fn authenticate<'s>(
+    servers: &'s HashMap<String, ServerCredentialConfig>,
+    proof: &ServerProof<'_>,
+    network: &NetworkId,
+    user_nkey_public: &str,
+    now: u64,
+) -> Result<(&'s str, &'s ServerCredentialConfig), AuthError> {
+    let (name, server) = match proof.server_name {
+        Some(name) => {
+            let (name, server) = servers
+                .get_key_value(name)
+                .ok_or_else(|| rejected("unknown server"))?;
+            verify(name, server, proof, network, user_nkey_public, now)?;
+            (name, server)
+        }
+        None => {
+            let secret = proof
+                .secret
+                .ok_or_else(|| rejected("server_name or server_secret is required"))?;
+            servers
+                .iter()
+                .find(|(_, server)| {
+                    server
+                        .secret
+                        .as_deref()
+                        .is_some_and(|expected| secret_matches(expected, secret))
+                })
+                .ok_or_else(|| rejected("invalid server secret"))?
+        }
+    };
+    if server.revoked {
+        return Err(rejected("credential revoked"));
+    }
+    if !server.allows(network) {
+        return Err(AuthError::ServerNotAllowed {
+            server: name.clone(),
+            network: network.to_string(),
+        });
+    }
+    Ok((name, server))
+}
+
+/// Check the proof against the server's registered credential.
+fn verify(
+    name: &str,
+    server: &ServerCredentialConfig,
+    proof: &ServerProof<'_>,
+    network: &NetworkId,
+    user_nkey_public: &str,
+    now: u64,
+) -> Result<(), AuthError> {
+    match (&server.public_key, &server.secret) {
+        (Some(public_key), _) => {
+            verify_assertion(public_key, proof.timestamp, proof.signature, now, |timestamp| {
+                server_assertion(name, network, user_nkey_public, timestamp)
+            })
+            .map_err(rejected)
+        }
+        (None, Some(expected)) => match proof.secret {
+            Some(secret) if secret_matches(expected, secret) => Ok(()),
+            _ => Err(rejected("invalid server secret")),
+        },
+        (None, None) => Err(rejected("no credential registered")),
+    }
+}
+
+/// Check an assertion signed with the seed of `public_key` at `timestamp`,
+/// built by `assertion`. Returns why it is refused.
+pub fn verify_assertion(
+    public_key: &str,
+    timestamp: Option<u64>,
+    signature: Option<&str>,
+    now: u64,
+    assertion: impl FnOnce(u64) -> String,
+) -> Result<(), &'static str> {
+    let (Some(timestamp), Some(signature)) = (timestamp, signature) else {
+        return Err("signed assertion required");
+    };
+    if now.abs_diff(timestamp) > ASSERTION_MAX_SKEW_SECONDS {
+        return Err("assertion timestamp out of range");
+    }
+    let signature = URL_SAFE_NO_PAD
+        .decode(signature)
+        .map_err(|_| "malformed assertion signature")?;
+    KeyPair::from_public_key(public_key)
+        .and_then(|kp| kp.verify(assertion(timestamp).as_bytes(), &signature))
+        .map_err(|_| "invalid assertion signature")
+}
+
+/// Constant-time comparison; only the length of the secret can leak.
+pub fn secret_matches(expected: &str, presented: &str) -> bool {
+    expected.as_bytes().ct_eq(presented.as_bytes()).into()
+}
+
+fn rejected(reason: &str) -> AuthError {
+    AuthError::ServerRejected(reason.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn servers(key: &KeyPair) -> HashMap<String, ServerCredentialConfig> {
+        let entry = |public_key: Option<String>, secret: Option<&str>, networks: &[&str]| {
+            ServerCredentialConfig {
+                public_key,
+                secret: secret.map(str::to_string),
+                networks: networks.iter().map(|n| n.to_string()).collect(),
+                revoked: false,
+                jwt_ttl_seconds: 3600,
+            }
+        };
+        HashMap::from([
+            (
+                "paris-1".to_string(),
+                entry(Some(key.public_key()), None, &["demonetwork"]),
+            ),
+            (
+                "legacy".to_string(),
+                entry(None, Some("legacy-secret-0123"), &["*"]),
+            ),
+        ])
+    }
+
+    fn signed<'a>(key: &KeyPair, network: &str, timestamp: u64, signature: &'a mut String) -> ServerProof<'a> {
+        let assertion = server_assertion("paris-1", &NetworkId::new(network), "UCLIENT", timestamp);
+        *signature = URL_SAFE_NO_PAD.encode(key.sign(assertion.as_bytes()).unwrap());
+        ServerProof {
+            server_name: Some("paris-1"),
+            timestamp: Some(timestamp),
+            signature: Some(signature),
+            ..ServerProof::default()
+        }
+    }
+
+    #[test]
+    fn signed_assertions_are_verified_and_scoped() {
+        let key = KeyPair::new_user();
+        let servers = servers(&key);
+        let demonetwork = NetworkId::new("demonetwork");
+        let mut signature = String::new();
+
+        let proof = signed(&key, "demonetwork", NOW - 30, &mut signature);
+        let (name, _) = authenticate(&servers, &proof, &demonetwork, "UCLIENT", NOW).unwrap();
+        assert_eq!(name, "paris-1");
+
+        // Signed for another key, stale, by another key, or for a network
+        // the server is not registered for.
+        assert!(authenticate(&servers, &proof, &demonetwork, "UOTHER", NOW).is_err());
+        assert!(authenticate(&servers, &proof, &demonetwork, "UCLIENT", NOW + 120).is_err());
+        let proof = signed(&KeyPair::new_user(), "demonetwork", NOW, &mut signature);
+        assert!(authenticate(&servers, &proof, &demonetwork, "UCLIENT", NOW).is_err());
+        let proof = signed(&key, "afrv", NOW, &mut signature);
+        assert!(matches!(
+            authenticate(&servers, &proof, &NetworkId::new("afrv"), "UCLIENT", NOW),
+            Err(AuthError::ServerNotAllowed { .. })
+        ));
+        // A key-registered server cannot fall back to a secret.
+        let proof = ServerProof {
+            server_name: Some("paris-1"),
+            secret: Some("legacy-secret-0123"),
+            ..ServerProof::default()
+        };
+        assert!(authenticate(&servers, &proof, &demonetwork, "UCLIENT", NOW).is_err());
+    }
+
+    #[test]
+    fn secrets_match_named_or_unnamed_and_revocation_applies() {
+        let mut servers = servers(&KeyPair::new_user());
+        let network = NetworkId::new("afrv");
+        let unnamed = ServerProof {
+            secret: Some("legacy-secret-0123"),
+            ..ServerProof::default()
+        };
+        let named = ServerProof {
+            server_name: Some("legacy"),
+            ..unnamed
+        };
+        assert_eq!(authenticate(&servers, &unnamed, &network, "U", NOW).unwrap().0, "legacy");
+        assert_eq!(authenticate(&servers, &named, &network, "U", NOW).unwrap().0, "legacy");
+        let wrong = ServerProof {
+            secret: Some("legacy-secret-0124"),
+            ..ServerProof::default()
+        };
+        assert!(authenticate(&servers, &wrong, &network, "U", NOW).is_err());
+
+        servers.get_mut("legacy").unwrap().revoked = true;
+        let proof = ServerProof {
+            secret: Some("legacy-secret-0123"),
+            ..ServerProof::default()
+        };
+        assert!(authenticate(&servers, &proof, &network, "U", NOW).is_err());
+    }
+}
@@ -0,0 +1,154 @@
+//! Authentication of service accounts at `POST /exchange-service`.
+//!
+//! Each account is registered under a name in the configuration (see
+//! [`ServiceAccountConfig`]) with either an NKey public key or an API key.
+//! A key-registered account signs
+//! [`service_account_assertion`]`(name, network, user_nkey_public, timestamp)`
+//! with its seed, checked like a server's assertion (see
+//! [`server_auth`](crate::server_auth)); API keys are compared in constant
+//! time.
+
+use std::collections::HashMap;
+
+use openlink_models::NetworkId;
+use openlink_sdk::service_account_assertion;
+
+use crate::config::ServiceAccountConfig;
+use crate::error::AuthError;
+use crate::server_auth::{secret_matches, verify_assertion};
+
+/// What a service account presents to prove its identity.
+#[derive(Debug, Default)]
+pub struct ServiceAccountProof<'a> {
+    pub api_key: Option<&'a str>,
+    pub timestamp: Option<u64>,
+    pub signature: Option<&'a str>,
+}
+
+/// Authenticate the service account `name` asking for a JWT issued to
+/// `user_nkey_public` on `network`, returning its configuration.
+pub fn authenticate<'s>(
+    accounts: &'s HashMap<String, ServiceAccountConfig>,
+    name: &str,
+    proof: &ServiceAccountProof<'_>,
+    network: &NetworkId,
+    user_nkey_public: &str,
+    now: u64,
+) -> Result<&'s ServiceAccountConfig, AuthError> {
+    let account = accounts
+        .get(name)
+        .ok_or_else(|| rejected("unknown service account"))?;
+    match (&account.public_key, &account.api_key) {
+        (Some(public_key), _) => {
+            verify_assertion(public_key, proof.timestamp, proof.signature, now, |timestamp| {
+                service_account_assertion(name, network, user_nkey_public, timestamp)
+            })
+            .map_err(rejected)?;
+        }
+        (None, Some(expected)) => match proof.api_key {
+            Some(api_key) if secret_matches(expected, api_key) => {}
+            _ => return Err(rejected("invalid API key")),
+        },
+        (None, None) => return Err(rejected("no credential registered")),
+    }
+    if account.revoked {
+        return Err(rejected("credential revoked"));
+    }
+    if account.expires_at.is_some_and(|expires_at| expires_at <= now) {
+        return Err(rejected("account expired"));
+    }
+    if account.network != network.as_str() {
+        return Err(AuthError::ServiceAccountNotAllowed {
+            account: name.to_string(),
+            network: network.to_string(),
+        });
+    }
+    Ok(account)
+}
+
+fn rejected(reason: &str) -> AuthError {
+    AuthError::ServiceAccountRejected(reason.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::Engine as _;
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use nkeys::KeyPair;
+    use openlink_models::UserRole;
+
+    const NOW: u64 = 1_700_000_000;
+    const API_KEY: &str = "0123456789abcdef0123456789abcdef";
+
+    fn accounts(key: &KeyPair) -> HashMap<String, ServiceAccountConfig> {
+        let entry = |public_key: Option<String>, api_key: Option<&str>| ServiceAccountConfig {
+            network: "demonetwork".into(),
+            cid: "900001".into(),
+            callsigns: vec!["LFPG_TWR".into()],
+            roles: vec![UserRole::Bot],
+            public_key,
+            api_key: api_key.map(str::to_string),
+            expires_at: Some(NOW + 3600),
+            revoked: false,
+            jwt_ttl_seconds: 3600,
+        };
+        HashMap::from([
+            ("atc-bot".to_string(), entry(Some(key.public_key()), None)),
+            ("hoppie".to_string(), entry(None, Some(API_KEY))),
+        ])
+    }
+
+    #[test]
+    fn signed_assertions_are_verified_and_scoped() {
+        let key = KeyPair::new_user();
+        let accounts = accounts(&key);
+        let network = NetworkId::new("demonetwork");
+        let assertion = service_account_assertion("atc-bot", &network, "UCLIENT", NOW);
+        let signature = URL_SAFE_NO_PAD.encode(key.sign(assertion.as_bytes()).unwrap());
+        let proof = ServiceAccountProof {
+            timestamp: Some(NOW),
+            signature: Some(&signature),
+            ..ServiceAccountProof::default()
+        };
+
+        let account = authenticate(&accounts, "atc-bot", &proof, &network, "UCLIENT", NOW).unwrap();
+        assert_eq!(account.cid, "900001");
+        assert!(authenticate(&accounts, "atc-bot", &proof, &network, "UOTHER", NOW).is_err());
+        assert!(authenticate(&accounts, "hoppie", &proof, &network, "UCLIENT", NOW).is_err());
+
+        // A server assertion of the same name is not an account's.
+        let assertion = openlink_sdk::server_assertion("atc-bot", &network, "UCLIENT", NOW);
+        let signature = URL_SAFE_NO_PAD.encode(key.sign(assertion.as_bytes()).unwrap());
+        let proof = ServiceAccountProof {
+            timestamp: Some(NOW),
+            signature: Some(&signature),
+            ..ServiceAccountProof::default()
+        };
+        assert!(authenticate(&accounts, "atc-bot", &proof, &network, "UCLIENT", NOW).is_err());
+    }
+
+    #[test]
+    fn api_keys_expiry_revocation_and_network_apply() {
+        let mut accounts = accounts(&KeyPair::new_user());
+        let network = NetworkId::new("demonetwork");
+        let proof = ServiceAccountProof {
+            api_key: Some(API_KEY),
+            ..ServiceAccountProof::default()
+        };
+        assert!(authenticate(&accounts, "hoppie", &proof, &network, "U", NOW).is_ok());
+        let wrong = ServiceAccountProof {
+            api_key: Some("0123456789abcdef0123456789abcdeF"),
+            ..ServiceAccountProof::default()
+        };
+        assert!(authenticate(&accounts, "hoppie", &wrong, &network, "U", NOW).is_err());
+        assert!(authenticate(&accounts, "hoppie", &proof, &network, "U", NOW + 3600).is_err());
+        assert!(matches!(
+            authenticate(&accounts, "hoppie", &proof, &NetworkId::new("afrv"), "U", NOW),
+            Err(AuthError::ServiceAccountNotAllowed { .. })
+        ));
+
+        accounts.get_mut("hoppie").unwrap().revoked = true;
+        assert!(authenticate(&accounts, "hoppie", &proof, &network, "U", NOW).is_err());
+    }
+}
@@ -0,0 +1,198 @@
+//! Refresh sessions.
+//!
+//! A successful `/exchange` opens a session and returns an opaque refresh
+//! token. `POST /refresh` redeems it for a new NATS JWT with the same CID,
+//! network and role, and rotates the token: each one is usable once, and
+//! only a redemption with the session's key consumes it. The
+//! session is bound to the NKey it was opened with — a stolen refresh token
+//! only yields JWTs nobody else can sign connections for — and ends
+//! `refresh_ttl_seconds` after the login, after which the user has to
+//! authenticate with the identity provider again.
+//!
+//! Sessions live in memory, like the account key: restarting the service
+//! invalidates both.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use openlink_models::{NetworkId, UserRole};
+
+use crate::error::AuthError;
+
+/// What a refresh token renews.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RefreshSession {
+    pub cid: String,
+    pub network: NetworkId,
+    pub role: UserRole,
+    /// NKey public key every JWT of the session is issued to.
+    pub user_nkey_public: String,
+    /// End of the session (Unix seconds); no JWT outlives it.
+    pub expires_at: u64,
+}
+
+/// Open refresh sessions by token.
+#[derive(Default)]
+pub struct RefreshSessions {
+    sessions: Mutex<HashMap<String, RefreshSession>>,
+}
+
+impl RefreshSessions {
+    /// Open a session and return its first refresh token.
+    pub fn open(&self, session: RefreshSession, now: u64) -> String {
+        let mut sessions = self.sessions.lock().expect("sessions lock poisoned");
+        sessions.retain(|_, s| s.expires_at > now);
+        let token = new_token();
+        sessions.insert(token.clone(), session);
+        token
+    }
+
+    /// The session of `token`, when it is open and bound to
+    /// `user_nkey_public`. The token is left in place: a wrong key or an
+    /// expired session does not end the owner's session.
+    pub fn check(&self, token: &str, user_nkey_public: &str, now: u64) -> Result<RefreshSession, AuthError> {
+        let sessions = self.sessions.lock().expect("sessions lock poisoned");
+        let session = sessions
+            .get(token)
+            .ok_or_else(|| AuthError::InvalidRefreshToken("unknown or already used".into()))?;
+        if session.expires_at <= now {
+            return Err(AuthError::InvalidRefreshToken("session expired".into()));
+        }
+        if session.user_nkey_public != user_nkey_public {
+            return Err(AuthError::InvalidRefreshToken("issued to another key".into()));
+        }
+        Ok(session.clone())
+    }
+
+    /// Consume `token`, checked with [`Self::check`], and return its
+    /// replacement, or `None` when the session ends with this refresh.
+    ///
+    /// Fails when the token was consumed in the meantime, so that each
+    /// token is redeemed once.
+    pub fn rotate(&self, token: &str, continues: bool) -> Result<Option<String>, AuthError> {
+        let mut sessions = self.sessions.lock().expect("sessions lock poisoned");
+        let session = sessions
+            .remove(token)
+            .ok_or_else(|| AuthError::InvalidRefreshToken("unknown or already used".into()))?;
+        if !continues {
+            return Ok(None);
+        }
+        let next = new_token();
+        sessions.insert(next.clone(), session);
+        Ok(Some(next))
+    }
+
+    /// Close the sessions on `network` of `user_nkey_public` or, without a
+    /// key, of `cid`. Returns the CID the key's sessions belonged to.
+    pub fn revoke(
+        &self,
+        network: &NetworkId,
+        cid: Option<&str>,
+        user_nkey_public: Option<&str>,
+    ) -> Option<String> {
+        let mut sessions = self.sessions.lock().expect("sessions lock poisoned");
+        let mut owner = None;
+        sessions.retain(|_, session| {
+            let revoked = session.network == *network
+                && match user_nkey_public {
+                    Some(key) => session.user_nkey_public == key,
+                    None => cid == Some(session.cid.as_str()),
+                };
+            if revoked && user_nkey_public.is_some() {
+                owner = Some(session.cid.clone());
+            }
+            !revoked
+        });
+        owner
+    }
+}
+
+fn new_token() -> String {
+    format!(
+        "{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    )
+}
+
+/// Current time in Unix seconds.
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .expect("system clock before epoch")
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn session() -> RefreshSession {
+        RefreshSession {
+            cid: "100000".into(),
+            network: NetworkId::new("demonetwork"),
+            role: UserRole::Pilot,
+            user_nkey_public: "UKEY".into(),
+            expires_at: NOW + 3600,
+        }
+    }
+
+    fn redeem(sessions: &RefreshSessions, token: &str, now: u64) -> Result<Option<String>, AuthError> {
+        sessions.check(token, "UKEY", now)?;
+        sessions.rotate(token, true)
+    }
+
+    #[test]
+    fn tokens_rotate_and_are_single_use() {
+        let sessions = RefreshSessions::default();
+        let first = sessions.open(session(), NOW);
+
+        assert_eq!(sessions.check(&first, "UKEY", NOW + 60).unwrap(), session());
+        let second = redeem(&sessions, &first, NOW + 60).unwrap().unwrap();
+        assert_ne!(first, second);
+        assert!(redeem(&sessions, &first, NOW + 60).is_err());
+        assert!(sessions.rotate(&first, true).is_err());
+        assert!(redeem(&sessions, &second, NOW + 120).is_ok());
+    }
+
+    #[test]
+    fn last_refresh_ends_the_session() {
+        let sessions = RefreshSessions::default();
+        let token = sessions.open(session(), NOW);
+        assert_eq!(sessions.rotate(&token, false).unwrap(), None);
+        assert!(sessions.check(&token, "UKEY", NOW).is_err());
+        assert!(sessions.sessions.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn wrong_key_and_expired_sessions_are_refused() {
+        let sessions = RefreshSessions::default();
+        let token = sessions.open(session(), NOW);
+        assert!(sessions.check(&token, "UOTHER", NOW).is_err());
+        // The failed attempt left the owner's session alone.
+        assert!(redeem(&sessions, &token, NOW).is_ok());
+
+        let token = sessions.open(session(), NOW);
+        assert!(sessions.check(&token, "UKEY", NOW + 3600).is_err());
+    }
+
+    #[test]
+    fn revocation_closes_the_sessions_of_a_key_or_cid() {
+        let sessions = RefreshSessions::default();
+        let network = NetworkId::new("demonetwork");
+        let token = sessions.open(session(), NOW);
+        assert_eq!(sessions.revoke(&NetworkId::new("afrv"), None, Some("UKEY")), None);
+        assert_eq!(
+            sessions.revoke(&network, None, Some("UKEY")),
+            Some("100000".to_string())
+        );
+        assert!(sessions.check(&token, "UKEY", NOW).is_err());
+
+        let token = sessions.open(session(), NOW);
+        assert_eq!(sessions.revoke(&network, Some("100000"), None), None);
+        assert!(sessions.check(&token, "UKEY", NOW).is_err());
+    }
+}
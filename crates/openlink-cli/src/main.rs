@@ -3,8 +3,8 @@
 // mod ui;
 
 use clap::{Parser, Subcommand};
-use openlink_models::{AcarsEndpointAddress, AcarsEndpointCallsign, AcarsEnvelope, AcarsMessage, AcarsRouting, AcarsRoutingEndpoint, ArgType, CpdlcArgument, CpdlcEnvelope, CpdlcMessageType, CpdlcMetaMessage, FlightLevel, ICAOAirportCode, MessageBuilder, MessageDirection, MessageElement, MetaMessage, NetworkAddress, NetworkId, OpenLinkEnvelope, OpenLinkMessage, SerializedMessagePayload, StationId, find_definition};
-use openlink_sdk::OpenLinkClient;
+use openlink_models::{AcarsEndpointAddress, AcarsEndpointCallsign, AcarsEnvelope, AcarsMessage, AcarsRouting, AcarsRoutingEndpoint, ArgType, CpdlcArgument, CpdlcEnvelope, CpdlcMessageType, CpdlcMetaMessage, FlightLevel, ICAOAirportCode, MessageBuilder, MessageDirection, MessageElement, MetaMessage, NetworkAddress, NetworkId, OpenLinkEnvelope, OpenLinkMessage, SerializedMessagePayload, StationId, StationMetadata, UserRole, find_definition};
+use openlink_sdk::{CredentialCache, OpenLinkClient};
 use std::io;
 // use crate::tui::{EventHandler, init, restore};
 // use crate::app_state::{AppController};
@@ -32,6 +32,7 @@ async fn send_station_status(
         station_id.clone(),
         status,
         endpoint.clone(),
+        StationMetadata::default(),
     ));
 
     if let Err(e) = client.send_to_server(meta_msg).await {
@@ -86,6 +87,15 @@ pub struct Cli {
     #[arg(long)]
     pub network_address: NetworkAddress,
 
+    /// Rôle demandé au service d'authentification (pilot, controller, bot, observer)
+    #[arg(long, default_value = "pilot")]
+    pub role: UserRole,
+
+    /// Se connecter via le flux "device authorization" (URL + code à valider
+    /// dans un navigateur) ; les identifiants sont mis en cache sur disque
+    #[arg(long, default_value_t = false)]
+    pub device_login: bool,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -299,14 +309,26 @@ async fn main() -> io::Result<()> {
 
     println!("Starting for {:?}:{:?}", network_id,network_address);
 
-    println!("DEBUG: Acquired OIDC Token for {:?}: {}", cli.network_address, network_address.to_string());
-
-    let client = OpenLinkClient::connect_with_authorization_code(
-        &nats_url, 
-        "http://localhost:3001", 
-        &network_address.to_string(),
-        &network_id,
-    ).await.expect("Failed to connect");
+    let client = if cli.device_login {
+        OpenLinkClient::connect_with_device_login(
+            &nats_url,
+            "http://localhost:3001",
+            &network_id,
+            Some(cli.role),
+            CredentialCache::for_network(&network_id).as_ref(),
+            openlink_sdk::print_device_prompt,
+        ).await.expect("Failed to connect")
+    } else {
+        println!("DEBUG: Acquired OIDC Token for {:?}: {}", cli.network_address, network_address.to_string());
+
+        OpenLinkClient::connect_with_authorization_code_as(
+            &nats_url, 
+            "http://localhost:3001", 
+            &network_address.to_string(),
+            &network_id,
+            cli.role,
+        ).await.expect("Failed to connect")
+    };
     
     let cid = client.cid().to_string();
     println!("DEBUG: Connected. Resolved CID: '{}'", cid);
@@ -354,6 +376,14 @@ async fn main() -> io::Result<()> {
                                         };
                                         match serde_json::from_slice::<OpenLinkEnvelope>(&message.payload) {
                                             Ok(envelope) => {
+                                                match client.accept_incoming(&envelope).await {
+                                                    Ok(true) => {}
+                                                    Ok(false) => {
+                                                        println!("⚠ Discarded stale message {}", envelope.id);
+                                                        continue;
+                                                    }
+                                                    Err(e) => println!("⚠ Failed to report stale message: {e}"),
+                                                }
                                                 // Extract source and display text from CPDLC payload
                                                 let (source, display) = match &envelope.payload {
                                                     OpenLinkMessage::Acars(acars_env) => {
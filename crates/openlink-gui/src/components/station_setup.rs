@@ -225,7 +225,7 @@ pub fn StationSetup(
                             let status_err_label = tr.status_send_error;
                             let conn_err_label = tr.connection_failed;
                             spawn(async move {
-                                match nats_client::connect_nats(&setup.network_id, &setup.network_address).await {
+                                match nats_client::connect_nats(&setup.network_id, &setup.network_address, &setup.station_type).await {
                                     Ok(client) => {
                                         // Send online status
                                         if let Err(e) = nats_client::send_online_status(
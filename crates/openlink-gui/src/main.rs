@@ -222,6 +222,17 @@ async fn spawn_inbox_listener(
 
         let envelope = serde_json::from_slice::<openlink_models::OpenLinkEnvelope>(&message.payload).ok();
 
+        if let Some(ref env) = envelope {
+            match client.accept_incoming(env).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    eprintln!("[GUI RECV] tab={} discarded stale message {}", tab_id, env.id);
+                    continue;
+                }
+                Err(e) => eprintln!("[GUI RECV] tab={} failed to report stale message: {e}", tab_id),
+            }
+        }
+
         if let Some(ref env) = envelope {
             if let Some((cpdlc, app, _)) = nats_client::extract_cpdlc_application(env) {
                 let ids = app
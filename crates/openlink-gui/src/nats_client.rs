@@ -1,24 +1,34 @@
 use openlink_models::{
     AcarsEndpointAddress, CpdlcApplicationMessage, CpdlcEnvelope, CpdlcMessageType,
-    CpdlcMetaMessage, NetworkId, OpenLinkEnvelope, OpenLinkMessage,
+    CpdlcMetaMessage, NetworkId, OpenLinkEnvelope, OpenLinkMessage, UserRole,
 };
 use openlink_sdk::{MessageBuilder, OpenLinkClient};
 
+use crate::state::StationType;
+
 /// Connect to NATS via the OpenLink SDK and return the client.
+///
+/// ATC stations request the controller role, aircraft the pilot role.
 pub async fn connect_nats(
     network_id: &str,
     network_address: &str,
+    station_type: &StationType,
 ) -> Result<OpenLinkClient, String> {
     let nats_url =
         std::env::var("NATS_URL").unwrap_or_else(|_| "nats://localhost:4222".to_string());
     let auth_url =
         std::env::var("AUTH_URL").unwrap_or_else(|_| "http://localhost:3001".to_string());
 
-    OpenLinkClient::connect_with_authorization_code(
+    let role = match station_type {
+        StationType::Atc => UserRole::Controller,
+        StationType::Aircraft => UserRole::Pilot,
+    };
+    OpenLinkClient::connect_with_authorization_code_as(
         &nats_url,
         &auth_url,
         network_address,
         &NetworkId::new(network_id),
+        role,
     )
         .await
         .map_err(|e| format!("{e}"))
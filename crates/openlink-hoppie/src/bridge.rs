@@ -29,13 +29,31 @@ pub async fn run_bridge(config: BridgeConfig) -> Result<()> {
 
     // Connect to OpenLink
     let network = NetworkId::new(&config.network_id);
-    let client = OpenLinkClient::connect_with_authorization_code(
-        &config.nats_url,
-        &config.auth_url,
-        &config.auth_code,
-        &network,
-    )
-    .await
+    let credential = config
+        .service_account_credential()
+        .map_err(anyhow::Error::msg)?;
+    let client = match (&credential, &config.auth_code) {
+        (Some(credential), _) => {
+            OpenLinkClient::connect_as_service_account(
+                &config.nats_url,
+                &config.auth_url,
+                credential,
+                &network,
+                None,
+            )
+            .await
+        }
+        (None, Some(auth_code)) => {
+            OpenLinkClient::connect_with_authorization_code(
+                &config.nats_url,
+                &config.auth_url,
+                auth_code,
+                &network,
+            )
+            .await
+        }
+        (None, None) => anyhow::bail!("--auth-code or --service-account is required"),
+    }
     .context("failed to connect to OpenLink")?;
 
     info!("connected to OpenLink network: {}", config.network_id);
@@ -235,6 +253,12 @@ async fn handle_openlink_message(
     }
     tracker.mark_openlink_seen(&msg_id);
 
+    // Expired or delayed beyond the max uplink delay: never relay.
+    if !client.accept_incoming(envelope).await? {
+        info!(id = %msg_id, "discarding stale OpenLink message");
+        return Ok(());
+    }
+
     // Extract CPDLC envelope from the payload
     let cpdlc_env = match &envelope.payload {
         OpenLinkMessage::Acars(acars) => match &acars.message {
@@ -264,7 +288,7 @@ async fn handle_openlink_message(
         return Ok(());
     }
 
-    // ── Handle Meta messages (LogonResponse → auto-connect + relay) ──
+    // ── Handle Meta messages (LogonResponse relay, ConnectionRequest auto-accept) ──
     if let CpdlcMessageType::Meta(ref meta) = cpdlc_env.message {
         return handle_openlink_meta(meta, &source, &dest, hoppie, client, bridge_callsigns).await;
     }
@@ -304,10 +328,13 @@ async fn handle_openlink_message(
 
 /// Handle an OpenLink meta message.
 ///
-/// When the server routes a LogonResponse to the bridge (because the Hoppie
-/// aircraft is registered with the bridge's CID), we:
-/// 1. Auto-inject ConnectionRequest + ConnectionResponse to establish the CPDLC session
-/// 2. Relay the logon acceptance to the Hoppie aircraft
+/// The server routes station → aircraft meta messages to the bridge because the
+/// Hoppie aircraft is registered with the bridge's CID:
+/// - LogonResponse is relayed to the Hoppie aircraft.
+/// - ConnectionRequest is auto-accepted on behalf of the aircraft (avionics
+///   behaviour), since Hoppie has no connection concept. The bridge only
+///   speaks for the aircraft it registered — the server rejects envelopes
+///   claiming a station callsign owned by another address.
 async fn handle_openlink_meta(
     meta: &CpdlcMetaMessage,
     source: &str,
@@ -321,27 +348,6 @@ async fn handle_openlink_meta(
             // source = station (ATC), dest = aircraft (Hoppie)
             let station_cs = source;
             let aircraft_cs = dest;
-            let aircraft_address = AcarsEndpointAddress::new(aircraft_cs);
-
-            if *accepted {
-                info!(
-                    station = %station_cs,
-                    aircraft = %aircraft_cs,
-                    "LogonResponse(accepted) → auto-connect + relay to Hoppie"
-                );
-
-                // 1. Auto-inject ConnectionRequest (station → aircraft)
-                let msg = client.cpdlc_connection_request(station_cs, aircraft_cs, &aircraft_address);
-                client.send_to_server(msg).await
-                    .context("auto-connect: connection request")?;
-
-                // 2. Auto-inject ConnectionResponse (aircraft → station, accepted)
-                let msg = client.cpdlc_connection_response(aircraft_cs, &aircraft_address, station_cs, true);
-                client.send_to_server(msg).await
-                    .context("auto-connect: connection response")?;
-
-                info!(station = %station_cs, aircraft = %aircraft_cs, "auto-connect: session established");
-            }
 
             // Relay logon response to Hoppie
             let body = if *accepted { "LOGON ACCEPTED" } else { "LOGON REJECTED" };
@@ -369,6 +375,19 @@ async fn handle_openlink_meta(
 
             Ok(())
         }
+        CpdlcMetaMessage::ConnectionRequest => {
+            // source = station (ATC), dest = aircraft (Hoppie)
+            let station_cs = source;
+            let aircraft_cs = dest;
+            let aircraft_address = AcarsEndpointAddress::new(aircraft_cs);
+
+            let msg = client.cpdlc_connection_response(aircraft_cs, &aircraft_address, station_cs, true);
+            client.send_to_server(msg).await
+                .context("auto-connect: connection response")?;
+
+            info!(station = %station_cs, aircraft = %aircraft_cs, "auto-connect: ConnectionRequest accepted on behalf of Hoppie aircraft");
+            Ok(())
+        }
         CpdlcMetaMessage::SessionUpdate { .. } => {
             debug!("ignoring SessionUpdate from server");
             Ok(())
@@ -1,6 +1,7 @@
 //! Bridge configuration.
 
 use clap::Parser;
+use openlink_sdk::ServiceAccountCredential;
 
 /// Hoppie ↔ OpenLink CPDLC bridge.
 #[derive(Parser, Debug, Clone)]
@@ -41,8 +42,46 @@ pub struct BridgeConfig {
     pub auth_url: String,
 
     /// OIDC authorization code for OpenLink authentication.
-    #[arg(long)]
-    pub auth_code: String,
+    #[arg(long, required_unless_present = "service_account")]
+    pub auth_code: Option<String>,
+
+    /// Service account to authenticate as instead of an authorization
+    /// code, for unattended restarts.
+    #[arg(long, conflicts_with = "auth_code")]
+    pub service_account: Option<String>,
+
+    /// API key of the service account.
+    #[arg(long, env = "OPENLINK_API_KEY", requires = "service_account")]
+    pub api_key: Option<String>,
+
+    /// NKey seed (`SU…`) of a key-registered service account.
+    #[arg(
+        long,
+        env = "OPENLINK_SERVICE_ACCOUNT_SEED",
+        requires = "service_account",
+        conflicts_with = "api_key"
+    )]
+    pub service_account_seed: Option<String>,
+}
+
+impl BridgeConfig {
+    /// The service account credential, when the bridge authenticates as one.
+    pub fn service_account_credential(&self) -> Result<Option<ServiceAccountCredential>, String> {
+        let Some(name) = self.service_account.clone() else {
+            return Ok(None);
+        };
+        match (&self.api_key, &self.service_account_seed) {
+            (Some(api_key), _) => Ok(Some(ServiceAccountCredential::ApiKey {
+                name,
+                api_key: api_key.clone(),
+            })),
+            (None, Some(seed)) => Ok(Some(ServiceAccountCredential::Key {
+                name,
+                seed: seed.clone(),
+            })),
+            (None, None) => Err("--service-account needs --api-key or --service-account-seed".into()),
+        }
+    }
 }
 
 /// Bridge operating mode.
@@ -1,3 +1,4 @@
+use std::path::PathBuf;
 use std::sync::{Arc, atomic::{AtomicU64, Ordering}};
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
@@ -8,9 +9,9 @@ use openlink_models::{
     AcarsEndpointAddress, AcarsEndpointCallsign, AcarsMessage, CpdlcArgument, CpdlcMessageType,
     MessageBuilder, MessageElement, MetaMessage, NetworkAddress, NetworkId, OpenLinkEnvelope,
     OpenLinkMessage,
-    StationId, StationStatus,
+    StationId, StationMetadata, StationStatus, UserRole,
 };
-use openlink_sdk::{NatsSubjects, OpenLinkClient};
+use openlink_sdk::{CredentialCache, DeviceAuthorization, NatsSubjects, OpenLinkClient};
 use tokio::sync::Mutex;
 
 #[derive(Parser, Debug, Clone)]
@@ -52,6 +53,16 @@ struct Args {
 
     #[arg(long, default_value_t = false)]
     skip_preflight: bool,
+
+    /// Log every station in with the device authorization grant instead of
+    /// the mock identity provider's code-as-CID shortcut.
+    #[arg(long, default_value_t = false)]
+    device_login: bool,
+
+    /// Directory of the per-station credential caches used with
+    /// `--device-login`.
+    #[arg(long, default_value = "loadtest-credentials")]
+    credential_dir: PathBuf,
 }
 
 #[derive(Clone, Copy, Debug, ValueEnum, PartialEq, Eq)]
@@ -109,13 +120,7 @@ async fn main() -> Result<()> {
     for i in 0..atc_count {
         let atc_cid = format!("LT-ATC-{i:05}");
 
-        let atc_client = OpenLinkClient::connect_with_authorization_code(
-            &args.nats_url,
-            &args.auth_url,
-            &atc_cid,
-            &network,
-        )
-        .await?;
+        let atc_client = connect(&args, &network, &atc_cid, Some(UserRole::Controller)).await?;
 
         let atc = Endpoint {
             cid: atc_client.cid().to_string(),
@@ -133,13 +138,7 @@ async fn main() -> Result<()> {
             let pilot_idx = i * pilots_per_atc + j;
             let pilot_cid = format!("LT-PILOT-{pilot_idx:05}");
 
-            let pilot_client = OpenLinkClient::connect_with_authorization_code(
-                &args.nats_url,
-                &args.auth_url,
-                &pilot_cid,
-                &network,
-            )
-            .await?;
+            let pilot_client = connect(&args, &network, &pilot_cid, None).await?;
 
             let pilot = Endpoint {
                 cid: pilot_client.cid().to_string(),
@@ -234,6 +233,54 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// Connect the station `label` under `role` (the default role when `None`).
+///
+/// With `--device-login` each station has its own credential cache, so the
+/// logins approved on a first run are reused by the next ones.
+async fn connect(
+    args: &Args,
+    network: &NetworkId,
+    label: &str,
+    role: Option<UserRole>,
+) -> Result<OpenLinkClient> {
+    let client = if args.device_login {
+        let cache = CredentialCache::new(
+            args.credential_dir
+                .join(format!("{}-{label}.json", network.as_str())),
+        );
+        OpenLinkClient::connect_with_device_login(
+            &args.nats_url,
+            &args.auth_url,
+            network,
+            role,
+            Some(&cache),
+            |authorization: &DeviceAuthorization| {
+                eprintln!("Login for {label}:");
+                openlink_sdk::print_device_prompt(authorization);
+            },
+        )
+        .await?
+    } else if let Some(role) = role {
+        OpenLinkClient::connect_with_authorization_code_as(
+            &args.nats_url,
+            &args.auth_url,
+            label,
+            network,
+            role,
+        )
+        .await?
+    } else {
+        OpenLinkClient::connect_with_authorization_code(
+            &args.nats_url,
+            &args.auth_url,
+            label,
+            network,
+        )
+        .await?
+    };
+    Ok(client)
+}
+
 async fn register_online(network: &NetworkId, endpoint: &Endpoint) -> Result<()> {
     let msg = OpenLinkMessage::Meta(MetaMessage::StationStatus(
         StationId::new(&endpoint.cid),
@@ -242,6 +289,7 @@ async fn register_online(network: &NetworkId, endpoint: &Endpoint) -> Result<()>
             endpoint.callsign.to_string(),
             endpoint.address.to_string(),
         ),
+        StationMetadata::default(),
     ));
     endpoint.client.send_to_server(msg).await?;
     // tiny delay to let registry/index settle before high-rate sends
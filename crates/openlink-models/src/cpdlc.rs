@@ -878,6 +878,48 @@ pub fn closes_dialogue_response_elements(elements: &[MessageElement]) -> bool {
     has_closing_response && !has_standby
 }
 
+/// Free-text prefix of the "SET MAX UPLINK DELAY VALUE TO [delay] SEC"
+/// uplink.
+///
+/// FANS 1/A has no dedicated element for it, so it is sent as `UM169`
+/// free text. `0` seconds turns the check off.
+pub const MAX_UPLINK_DELAY_PREFIX: &str = "SET MAX UPLINK DELAY VALUE TO";
+
+/// Error text sent back when an uplink is discarded as delayed or expired.
+pub const UPLINK_DELAYED_TEXT: &str =
+    "UPLINK DELAYED IN NETWORK AND REJECTED - RESEND OR CONTACT BY VOICE";
+
+/// Error text sent back when a downlink is discarded as expired.
+pub const DOWNLINK_DELAYED_TEXT: &str =
+    "DOWNLINK DELAYED IN NETWORK AND REJECTED - RESEND OR CONTACT BY VOICE";
+
+/// Build the `UM169` element setting the aircraft max uplink delay.
+pub fn set_max_uplink_delay_element(seconds: u16) -> MessageElement {
+    MessageElement::new(
+        "UM169",
+        vec![CpdlcArgument::FreeText(format!(
+            "{MAX_UPLINK_DELAY_PREFIX} {seconds} SEC"
+        ))],
+    )
+}
+
+/// Max uplink delay (in seconds) set by a "SET MAX UPLINK DELAY" element,
+/// if the message contains one.
+pub fn max_uplink_delay_seconds(elements: &[MessageElement]) -> Option<u16> {
+    elements
+        .iter()
+        .filter(|e| e.id == "UM169")
+        .flat_map(|e| e.args.iter())
+        .find_map(|arg| {
+            let CpdlcArgument::FreeText(text) = arg else {
+                return None;
+            };
+            let text = text.trim().to_ascii_uppercase();
+            let rest = text.strip_prefix(MAX_UPLINK_DELAY_PREFIX)?;
+            rest.split_whitespace().next()?.parse().ok()
+        })
+}
+
 impl From<CpdlcApplicationMessage> for SerializedMessagePayload {
     fn from(value: CpdlcApplicationMessage) -> Self {
         SerializedMessagePayload(value.render())
@@ -953,6 +995,46 @@ pub struct CpdlcConnectionView {
     pub phase: CpdlcConnectionPhase,
 }
 
+/// Kind of voice transfer instructed by the current data authority.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CpdlcTransferKind {
+    /// `UM117 CONTACT [unit name] [frequency]`.
+    Contact,
+    /// `UM120 MONITOR [unit name] [frequency]`.
+    Monitor,
+}
+
+impl fmt::Display for CpdlcTransferKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CpdlcTransferKind::Contact => write!(f, "CONTACT"),
+            CpdlcTransferKind::Monitor => write!(f, "MONITOR"),
+        }
+    }
+}
+
+/// A handoff in progress between the current data authority and the next unit.
+///
+/// Created when the current data authority uplinks a CONTACT / MONITOR
+/// instruction, acknowledged by the pilot's WILCO (or MONITORING report), and
+/// cleared once the connection has moved to the next data authority or the
+/// instruction was refused.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CpdlcTransferView {
+    /// Instruction that started the transfer.
+    pub kind: CpdlcTransferKind,
+    /// Ground station handing the aircraft over.
+    pub from: AcarsEndpointCallsign,
+    /// Unit name given in the instruction.
+    pub unit: String,
+    /// Voice frequency given in the instruction.
+    pub frequency: String,
+    /// Next Data Authority the CPDLC connection will move to, if designated.
+    pub next_data_authority: Option<AcarsEndpointCallsign>,
+    /// `true` once the pilot has accepted the instruction.
+    pub acknowledged: bool,
+}
+
 /// Server-authoritative view of a CPDLC session for a given participant.
 ///
 /// Broadcast by the server after every session-mutating meta-message so that
@@ -973,6 +1055,9 @@ pub struct CpdlcSessionView {
     pub inactive_connection: Option<CpdlcConnectionView>,
     /// Next Data Authority, if designated.
     pub next_data_authority: Option<AcarsEndpointCallsign>,
+    /// Transfer to the next unit currently in progress, if any.
+    #[serde(default)]
+    pub transfer: Option<CpdlcTransferView>,
 }
 
 // ---------------------------------------------------------------------------
@@ -1116,7 +1201,18 @@ impl From<CpdlcMetaMessage> for SerializedMessagePayload {
                     .as_ref()
                     .map(|c| format!("{} ({})", c.peer, c.phase))
                     .unwrap_or_else(|| "NONE".to_string());
-                format!("SESSION UPDATE ACTIVE {} INACTIVE {}", active, inactive)
+                match session.transfer {
+                    Some(ref transfer) => format!(
+                        "SESSION UPDATE ACTIVE {} INACTIVE {} TRANSFER {} {} {}{}",
+                        active,
+                        inactive,
+                        transfer.kind,
+                        transfer.unit,
+                        transfer.frequency,
+                        if transfer.acknowledged { " (WILCO)" } else { "" }
+                    ),
+                    None => format!("SESSION UPDATE ACTIVE {} INACTIVE {}", active, inactive),
+                }
             }
         };
         SerializedMessagePayload(text)
@@ -1529,6 +1625,42 @@ mod tests {
         assert_eq!(meta, back);
     }
 
+    #[test]
+    fn session_view_without_transfer_field_deserializes() {
+        let json = r#"{"aircraft":"AFR1234","aircraft_address":"39401A","active_connection":null,"inactive_connection":null,"next_data_authority":null}"#;
+        let view: CpdlcSessionView = serde_json::from_str(json).unwrap();
+        assert!(view.transfer.is_none());
+    }
+
+    #[test]
+    fn session_update_renders_transfer() {
+        let meta = CpdlcMetaMessage::SessionUpdate {
+            session: CpdlcSessionView {
+                aircraft: Some("AFR1234".into()),
+                aircraft_address: Some("39401A".into()),
+                active_connection: Some(CpdlcConnectionView {
+                    peer: "LFPG".into(),
+                    phase: CpdlcConnectionPhase::Connected,
+                }),
+                inactive_connection: None,
+                next_data_authority: Some("LFFF".into()),
+                transfer: Some(CpdlcTransferView {
+                    kind: CpdlcTransferKind::Contact,
+                    from: "LFPG".into(),
+                    unit: "PARIS CONTROL".into(),
+                    frequency: "128.300".into(),
+                    next_data_authority: Some("LFFF".into()),
+                    acknowledged: true,
+                }),
+            },
+        };
+        let payload: SerializedMessagePayload = meta.into();
+        assert_eq!(
+            payload.to_string(),
+            "SESSION UPDATE ACTIVE LFPG (CONNECTED) INACTIVE NONE TRANSFER CONTACT PARIS CONTROL 128.300 (WILCO)"
+        );
+    }
+
     #[test]
     fn flight_level_serde_roundtrip() {
         let fl = FlightLevel::new(350);
@@ -1537,4 +1669,23 @@ mod tests {
         let back: FlightLevel = serde_json::from_str(&json).unwrap();
         assert_eq!(fl, back);
     }
+
+    #[test]
+    fn max_uplink_delay_roundtrip() {
+        let element = set_max_uplink_delay_element(120);
+        assert_eq!(
+            find_definition("UM169").unwrap().render(&element.args),
+            "SET MAX UPLINK DELAY VALUE TO 120 SEC"
+        );
+        assert_eq!(max_uplink_delay_seconds(&[element]), Some(120));
+
+        let lowercase = MessageElement::new(
+            "UM169",
+            vec![CpdlcArgument::FreeText("set max uplink delay value to 0 sec".into())],
+        );
+        assert_eq!(max_uplink_delay_seconds(&[lowercase]), Some(0));
+
+        let other = MessageElement::new("UM169", vec![CpdlcArgument::FreeText("HELLO".into())]);
+        assert_eq!(max_uplink_delay_seconds(&[other]), None);
+    }
 }
@@ -0,0 +1,137 @@
+//! Station directory types.
+//!
+//! The directory lists reachable stations so that clients can discover
+//! who is online (e.g. to offer a logon picker) without knowing callsigns
+//! in advance. It is served over NATS request/reply and kept up to date by
+//! change events; see `openlink_sdk::NatsSubjects::directory_query`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::acars::AcarsEndpointCallsign;
+use crate::station::{DatalinkApplication, StationInfo, StationRole};
+
+/// Filters for a directory request. An empty query matches every station.
+///
+/// # Examples
+///
+/// ```
+/// use openlink_models::{DirectoryQuery, StationRole};
+///
+/// let query = DirectoryQuery {
+///     role: Some(StationRole::Atc),
+///     callsign_prefix: Some("LF".to_string()),
+///     ..Default::default()
+/// };
+/// assert!(query.callsign_prefix.is_some());
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct DirectoryQuery {
+    /// Only stations advertising this role.
+    pub role: Option<StationRole>,
+    /// Only stations advertising this application.
+    pub application: Option<DatalinkApplication>,
+    /// Only callsigns starting with this prefix (case-insensitive).
+    pub callsign_prefix: Option<String>,
+}
+
+impl DirectoryQuery {
+    /// Whether `station` passes every filter of this query.
+    pub fn matches(&self, station: &StationInfo) -> bool {
+        let role_ok = self
+            .role
+            .is_none_or(|role| station.metadata.role == Some(role));
+        let application_ok = self
+            .application
+            .is_none_or(|app| station.metadata.applications.contains(&app));
+        let prefix_ok = self.callsign_prefix.as_ref().is_none_or(|prefix| {
+            station
+                .callsign
+                .to_string()
+                .to_uppercase()
+                .starts_with(&prefix.to_uppercase())
+        });
+        role_ok && application_ok && prefix_ok
+    }
+}
+
+/// Reply to a [`DirectoryQuery`].
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct DirectoryResponse {
+    /// Matching reachable stations, sorted by callsign.
+    pub stations: Vec<StationInfo>,
+}
+
+/// Live directory change, published by the server.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum DirectoryEvent {
+    /// A station became reachable or changed its status or metadata.
+    Changed(StationInfo),
+    /// The station holding this callsign is no longer reachable.
+    Removed(AcarsEndpointCallsign),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acars::AcarsEndpointAddress;
+    use crate::station::{StationMetadata, StationStatus};
+    use chrono::Utc;
+
+    fn station(callsign: &str, role: StationRole, applications: Vec<DatalinkApplication>) -> StationInfo {
+        StationInfo {
+            callsign: AcarsEndpointCallsign::new(callsign),
+            acars_address: AcarsEndpointAddress::new("ADDR1"),
+            status: StationStatus::Online,
+            metadata: StationMetadata {
+                role: Some(role),
+                applications,
+                ..Default::default()
+            },
+            last_updated: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn empty_query_matches_everything() {
+        let lfpg = station("LFPG", StationRole::Atc, vec![]);
+        assert!(DirectoryQuery::default().matches(&lfpg));
+    }
+
+    #[test]
+    fn query_filters_role_application_and_prefix() {
+        let lfpg = station("LFPG", StationRole::Atc, vec![DatalinkApplication::CpdlcFans]);
+        let afr = station("AFR123", StationRole::Aircraft, vec![DatalinkApplication::CpdlcFans]);
+
+        let atc = DirectoryQuery {
+            role: Some(StationRole::Atc),
+            ..Default::default()
+        };
+        assert!(atc.matches(&lfpg));
+        assert!(!atc.matches(&afr));
+
+        let dcl = DirectoryQuery {
+            application: Some(DatalinkApplication::Dcl),
+            ..Default::default()
+        };
+        assert!(!dcl.matches(&lfpg));
+
+        let prefix = DirectoryQuery {
+            callsign_prefix: Some("lf".to_string()),
+            ..Default::default()
+        };
+        assert!(prefix.matches(&lfpg));
+        assert!(!prefix.matches(&afr));
+    }
+
+    #[test]
+    fn directory_event_serde_roundtrip() {
+        let event = DirectoryEvent::Changed(station("LFPG", StationRole::Atc, vec![]));
+        let json = serde_json::to_string(&event).unwrap();
+        let back: DirectoryEvent = serde_json::from_str(&json).unwrap();
+        assert_eq!(event, back);
+
+        let query: DirectoryQuery = serde_json::from_str("{}").unwrap();
+        assert_eq!(query, DirectoryQuery::default());
+    }
+}
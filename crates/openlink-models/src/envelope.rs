@@ -6,6 +6,7 @@
 //! - A unique message id.
 //! - A UTC timestamp.
 //! - An optional correlation id for request/response pairing.
+//! - An optional expiry, after which the message must not be delivered.
 //! - Network-level routing information ([`OpenLinkRouting`]).
 //! - An authentication token.
 //! - The actual payload ([`OpenLinkMessage`]).
@@ -40,6 +41,17 @@ pub struct OpenLinkEnvelope {
     pub payload: OpenLinkMessage,
     /// Bearer / JWT token for authentication.
     pub token: String,
+    /// Instant after which the message is stale and must be discarded
+    /// instead of delivered (e.g. a clearance that is no longer safe).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl OpenLinkEnvelope {
+    /// Whether the envelope carries an expiry that is at or before `now`.
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -102,6 +114,7 @@ mod tests {
                 }),
             }),
             token: "tok".to_string(),
+            expires_at: None,
         }
     }
 
@@ -122,13 +135,30 @@ mod tests {
         assert_eq!(back.correlation_id, Some("corr-42".to_string()));
     }
 
+    #[test]
+    fn envelope_expiry() {
+        let mut env = sample_envelope();
+        let now = Utc::now();
+        assert!(!env.is_expired(now));
+        let json = serde_json::to_string(&env).unwrap();
+        assert!(!json.contains("expires_at"));
+
+        env.expires_at = Some(now - chrono::Duration::seconds(1));
+        assert!(env.is_expired(now));
+        env.expires_at = Some(now + chrono::Duration::seconds(30));
+        assert!(!env.is_expired(now));
+        let back: OpenLinkEnvelope = serde_json::from_str(&serde_json::to_string(&env).unwrap()).unwrap();
+        assert_eq!(back.expires_at, env.expires_at);
+    }
+
     #[test]
     fn meta_message_variant() {
-        use crate::station::{StationId, StationStatus};
+        use crate::station::{StationId, StationMetadata, StationStatus};
         let msg = OpenLinkMessage::Meta(MetaMessage::StationStatus(
             StationId::new("stn-1"),
             StationStatus::Online,
             AcarsRoutingEndpoint::new("LFPG", "ADDR001"),
+            StationMetadata::default(),
         ));
         let json = serde_json::to_string(&msg).unwrap();
         let back: OpenLinkMessage = serde_json::from_str(&json).unwrap();
@@ -14,7 +14,9 @@
 //! │       └── CpdlcMessageType::Meta(CpdlcMetaMessage)
 //! │           └── Logon / Connection / SessionUpdate / Transfer
 //! └── OpenLinkMessage::Meta(MetaMessage)
-//!     └── StationStatus
+//!     ├── StationStatus / StationLookup / StationInfo
+//!     ├── EnvelopeRejected
+//!     └── ServerNotice
 //! ```
 //!
 //! ## Module layout
@@ -24,11 +26,13 @@
 //! | [`network`] | Network-level addressing (`NetworkId`, `NetworkAddress`, routing) |
 //! | [`acars`] | ACARS envelope, routing, callsigns, addresses |
 //! | [`cpdlc`] | CPDLC messages, meta-messages, serialisation |
+//! | [`directory`] | Station directory queries, responses and change events |
 //! | [`envelope`] | Top-level `OpenLinkEnvelope` and `OpenLinkMessage` |
-//! | [`station`] | Ground-station identity and status |
+//! | [`station`] | Station identity, status and metadata, rejections and server notices |
 
 pub mod acars;
 pub mod cpdlc;
+pub mod directory;
 pub mod envelope;
 pub mod error;
 pub mod message_builder;
@@ -39,6 +43,7 @@ pub mod station;
 // Downstream crates can use `openlink_models::NetworkId` directly.
 pub use acars::*;
 pub use cpdlc::*;
+pub use directory::*;
 pub use envelope::*;
 pub use error::*;
 pub use message_builder::*;
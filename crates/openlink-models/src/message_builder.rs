@@ -35,7 +35,7 @@
 //!     .build();
 //! ```
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use uuid::Uuid;
 
 use crate::acars::{
@@ -44,10 +44,14 @@ use crate::acars::{
 use crate::cpdlc::{
     CpdlcApplicationMessage, CpdlcArgument, CpdlcEnvelope, CpdlcMessageType, CpdlcMetaMessage,
     CpdlcSessionView, FlightLevel, ICAOAirportCode, MessageElement,
+    set_max_uplink_delay_element,
 };
 use crate::envelope::{OpenLinkEnvelope, OpenLinkMessage};
 use crate::network::{NetworkAddress, NetworkId, OpenLinkRouting, OpenLinkRoutingEndpoint};
-use crate::station::{MetaMessage, StationId, StationStatus};
+use crate::station::{
+    EnvelopeRejection, MetaMessage, NoticeCode, RejectionCode, ServerNotice, StationId,
+    StationMetadata, StationStatus,
+};
 
 // ─── CPDLC Message Builder ───────────────────────────────────────────
 
@@ -375,6 +379,14 @@ impl CpdlcMessageBuilder {
         self.downlink("DM6", vec![CpdlcArgument::Level(level)])
     }
 
+    /// (Convenience) Uplink: SET MAX UPLINK DELAY VALUE TO `seconds` SEC.
+    ///
+    /// Aircraft discard uplinks that took longer than this to arrive.
+    pub fn set_max_uplink_delay(self, seconds: u16) -> Self {
+        let element = set_max_uplink_delay_element(seconds);
+        self.uplink(element.id, element.args)
+    }
+
     /// Set a raw [`CpdlcMessageType`] directly for advanced / future message types.
     pub fn raw_message(mut self, msg: CpdlcMessageType) -> Self {
         self.message_type = Some(msg);
@@ -431,6 +443,7 @@ pub struct StationStatusBuilder {
     callsign: String,
     acars_address: String,
     status: Option<StationStatus>,
+    metadata: StationMetadata,
 }
 
 impl StationStatusBuilder {
@@ -444,6 +457,7 @@ impl StationStatusBuilder {
             callsign: callsign.into(),
             acars_address: acars_address.into(),
             status: None,
+            metadata: StationMetadata::default(),
         }
     }
 
@@ -459,20 +473,33 @@ impl StationStatusBuilder {
         self
     }
 
+    /// Set an explicit status (e.g. [`StationStatus::Away`]).
+    pub fn status(mut self, status: StationStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Attach advertised station metadata.
+    pub fn metadata(mut self, metadata: StationMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
     /// Consume the builder and produce an [`OpenLinkMessage`].
     ///
     /// # Panics
     ///
-    /// Panics if neither `online()` nor `offline()` has been called.
+    /// Panics if none of `online()`, `offline()` or `status()` has been called.
     pub fn build(self) -> OpenLinkMessage {
         let status = self.status.expect(
-            "StationStatusBuilder: `online()` or `offline()` must be called before `build()`",
+            "StationStatusBuilder: `online()`, `offline()` or `status()` must be called before `build()`",
         );
 
         OpenLinkMessage::Meta(MetaMessage::StationStatus(
             StationId::new(&self.network_address),
             status,
             AcarsRoutingEndpoint::new(self.callsign.as_str(), self.acars_address.as_str()),
+            self.metadata,
         ))
     }
 
@@ -498,10 +525,13 @@ impl StationStatusBuilder {
 /// - `timestamp` → `Utc::now()`
 /// - `token` → `""` (empty)
 /// - `correlation_id` → `None`
+/// - `expires_at` → `None` (never expires)
 pub struct EnvelopeBuilder {
     id: Option<Uuid>,
     timestamp: Option<DateTime<Utc>>,
     correlation_id: Option<String>,
+    expires_at: Option<DateTime<Utc>>,
+    time_to_live: Option<Duration>,
     token: Option<String>,
     source: Option<OpenLinkRoutingEndpoint>,
     destination: Option<OpenLinkRoutingEndpoint>,
@@ -514,6 +544,8 @@ impl EnvelopeBuilder {
             id: None,
             timestamp: None,
             correlation_id: None,
+            expires_at: None,
+            time_to_live: None,
             token: None,
             source: None,
             destination: None,
@@ -539,6 +571,21 @@ impl EnvelopeBuilder {
         self
     }
 
+    /// Discard the message if it cannot be delivered before `expires_at`.
+    pub fn expires_at(mut self, expires_at: DateTime<Utc>) -> Self {
+        self.expires_at = Some(expires_at);
+        self.time_to_live = None;
+        self
+    }
+
+    /// Discard the message if it cannot be delivered within `ttl` of its
+    /// timestamp.
+    pub fn time_to_live(mut self, ttl: Duration) -> Self {
+        self.time_to_live = Some(ttl);
+        self.expires_at = None;
+        self
+    }
+
     /// Set the authentication / authorization token.
     pub fn token(mut self, token: impl Into<String>) -> Self {
         self.token = Some(token.into());
@@ -622,11 +669,17 @@ impl EnvelopeBuilder {
             .destination
             .expect("EnvelopeBuilder: a destination must be set (e.g. `destination_server()`)");
 
+        let timestamp = self.timestamp.unwrap_or_else(Utc::now);
+        let expires_at = self
+            .expires_at
+            .or_else(|| self.time_to_live.map(|ttl| timestamp + ttl));
+
         OpenLinkEnvelope {
             id: self.id.unwrap_or_else(Uuid::new_v4),
-            timestamp: self.timestamp.unwrap_or_else(Utc::now),
+            timestamp,
             correlation_id: self.correlation_id,
             token: self.token.unwrap_or_default(),
+            expires_at,
             routing: OpenLinkRouting {
                 source,
                 destination,
@@ -679,6 +732,34 @@ impl MessageBuilder {
     ) -> StationStatusBuilder {
         StationStatusBuilder::new(network_address, callsign, acars_address)
     }
+
+    /// Build a client → server lookup of the station holding `callsign`.
+    pub fn station_lookup(callsign: impl Into<String>) -> OpenLinkMessage {
+        OpenLinkMessage::Meta(MetaMessage::StationLookup(AcarsEndpointCallsign::new(
+            &callsign.into(),
+        )))
+    }
+
+    /// Build a server → client notice that envelope `envelope_id` was rejected.
+    pub fn envelope_rejected(
+        envelope_id: Uuid,
+        code: RejectionCode,
+        reason: impl Into<String>,
+    ) -> OpenLinkMessage {
+        OpenLinkMessage::Meta(MetaMessage::EnvelopeRejected(EnvelopeRejection {
+            envelope_id,
+            code,
+            reason: reason.into(),
+        }))
+    }
+
+    /// Build an unsolicited server → client notice.
+    pub fn server_notice(code: NoticeCode, message: impl Into<String>) -> OpenLinkMessage {
+        OpenLinkMessage::Meta(MetaMessage::ServerNotice(ServerNotice {
+            code,
+            message: message.into(),
+        }))
+    }
 }
 
 // ─── Tests ───────────────────────────────────────────────────────────
@@ -786,11 +867,36 @@ mod tests {
             .build();
 
         match msg {
-            OpenLinkMessage::Meta(MetaMessage::StationStatus(id, status, endpoint)) => {
+            OpenLinkMessage::Meta(MetaMessage::StationStatus(id, status, endpoint, metadata)) => {
                 assert_eq!(id.to_string(), "1234");
                 assert_eq!(status, StationStatus::Online);
                 assert_eq!(endpoint.callsign.to_string(), "LFPG");
                 assert_eq!(endpoint.address.to_string(), "39401A");
+                assert_eq!(metadata, StationMetadata::default());
+            }
+            other => panic!("Expected StationStatus, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn build_station_away_with_metadata() {
+        use crate::station::{DatalinkApplication, StationRole};
+        let msg = MessageBuilder::station_status("1234", "LFPG", "39401A")
+            .status(StationStatus::Away)
+            .metadata(StationMetadata {
+                role: Some(StationRole::Atc),
+                facility: Some("PARIS CONTROL".to_string()),
+                applications: vec![DatalinkApplication::CpdlcFans],
+                accepting_logons: Some(false),
+                ..Default::default()
+            })
+            .build();
+
+        match msg {
+            OpenLinkMessage::Meta(MetaMessage::StationStatus(_, status, _, metadata)) => {
+                assert_eq!(status, StationStatus::Away);
+                assert_eq!(metadata.role, Some(StationRole::Atc));
+                assert_eq!(metadata.accepting_logons, Some(false));
             }
             other => panic!("Expected StationStatus, got {:?}", other),
         }
@@ -886,7 +992,7 @@ mod tests {
             other => panic!("Expected Address source, got {:?}", other),
         }
         match &envelope.payload {
-            OpenLinkMessage::Meta(MetaMessage::StationStatus(id, status, _)) => {
+            OpenLinkMessage::Meta(MetaMessage::StationStatus(id, status, _, _)) => {
                 assert_eq!(id.to_string(), "1234");
                 assert_eq!(*status, StationStatus::Online);
             }
@@ -894,6 +1000,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn build_envelope_rejected() {
+        let id = Uuid::new_v4();
+        let msg = MessageBuilder::envelope_rejected(id, RejectionCode::SenderMismatch, "nope");
+        match msg {
+            OpenLinkMessage::Meta(MetaMessage::EnvelopeRejected(rejection)) => {
+                assert_eq!(rejection.envelope_id, id);
+                assert_eq!(rejection.code, RejectionCode::SenderMismatch);
+                assert_eq!(rejection.reason, "nope");
+            }
+            other => panic!("Expected EnvelopeRejected, got {:?}", other),
+        }
+    }
+
     #[test]
     fn build_envelope_from_prebuilt_message() {
         let msg = MessageBuilder::cpdlc("AFR123", "394A0B")
@@ -7,7 +7,7 @@
 //!
 //! A [`NetworkId`] identifies a network (e.g. "demonetwork", "icao") on which
 //! stations are registered. Each station within a network is identified by
-//! its [`NetworkAddress`].
+//! its [`NetworkAddress`], and the user behind it holds a [`UserRole`].
 
 use std::convert::Infallible;
 use std::fmt;
@@ -136,6 +136,70 @@ impl FromStr for NetworkAddress {
     }
 }
 
+// ---------------------------------------------------------------------------
+// UserRole
+// ---------------------------------------------------------------------------
+
+/// What an authenticated user may do on a network.
+///
+/// Issued by the auth service in the user's NATS JWT and enforced by the
+/// server on every envelope.
+///
+/// | Role         | Publish | Uplinks | Downlinks | Reserved callsigns |
+/// |--------------|---------|---------|-----------|--------------------|
+/// | `pilot`      | yes     | no      | yes       | no                 |
+/// | `controller` | yes     | yes     | no        | yes                |
+/// | `bot`        | yes     | yes     | no        | no                 |
+/// | `observer`   | no      | no      | no        | no                 |
+///
+/// # Examples
+///
+/// ```
+/// use openlink_models::UserRole;
+///
+/// let role: UserRole = "controller".parse().unwrap();
+/// assert!(role.may_uplink());
+/// assert_eq!(UserRole::Observer.to_string(), "observer");
+/// ```
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, strum::Display, strum::EnumString,
+)]
+#[strum(serialize_all = "lowercase")]
+pub enum UserRole {
+    /// Flies an aircraft: registers its callsign and sends downlinks.
+    Pilot,
+    /// Rated controller: may claim reserved ground-station callsigns and
+    /// send uplinks.
+    Controller,
+    /// Automated ground service (ATIS, DCL…): sends uplinks from
+    /// unreserved callsigns.
+    Bot,
+    /// Read-only access: directory and own inbox, no publishing.
+    Observer,
+}
+
+impl UserRole {
+    /// Whether the role may publish envelopes at all.
+    pub fn may_publish(self) -> bool {
+        self != UserRole::Observer
+    }
+
+    /// Whether the role may send ground-to-air messages.
+    pub fn may_uplink(self) -> bool {
+        matches!(self, UserRole::Controller | UserRole::Bot)
+    }
+
+    /// Whether the role may send air-to-ground messages.
+    pub fn may_downlink(self) -> bool {
+        self == UserRole::Pilot
+    }
+
+    /// Whether the role may claim callsigns reserved to controllers.
+    pub fn may_claim_reserved(self) -> bool {
+        self == UserRole::Controller
+    }
+}
+
 // ---------------------------------------------------------------------------
 // OpenLinkRoutingEndpoint
 // ---------------------------------------------------------------------------
@@ -224,6 +288,15 @@ mod tests {
         assert_eq!(routing, back);
     }
 
+    #[test]
+    fn user_role_permissions() {
+        assert!(UserRole::Pilot.may_downlink() && !UserRole::Pilot.may_uplink());
+        assert!(UserRole::Controller.may_uplink() && UserRole::Controller.may_claim_reserved());
+        assert!(UserRole::Bot.may_uplink() && !UserRole::Bot.may_claim_reserved());
+        assert!(!UserRole::Observer.may_publish());
+        assert_eq!("bot".parse::<UserRole>().unwrap(), UserRole::Bot);
+    }
+
     #[test]
     fn network_id_hash_usable_in_collections() {
         use std::collections::HashSet;
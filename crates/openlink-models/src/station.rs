@@ -2,15 +2,17 @@
 //!
 //! These types represent system-level messages that are not part of the ACARS
 //! or CPDLC protocols but are used by the OpenLink infrastructure to track
-//! ground-station availability.
+//! ground-station availability and to report routing failures back to clients.
 
 use std::convert::Infallible;
 use std::fmt;
 use std::str::FromStr;
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
-use crate::acars::AcarsRoutingEndpoint;
+use crate::acars::{AcarsEndpointAddress, AcarsEndpointCallsign, AcarsRoutingEndpoint};
 
 // ---------------------------------------------------------------------------
 // StationId
@@ -79,6 +81,160 @@ pub enum StationStatus {
     Online,
     /// The station is offline.
     Offline,
+    /// The station is logged in but temporarily unattended; existing
+    /// sessions are kept but new logons may be slow to be answered.
+    Away,
+    /// The station is about to close and is handing its traffic over.
+    Closing,
+}
+
+impl StationStatus {
+    /// Whether the station still holds its callsign and can be routed to.
+    ///
+    /// Every status but [`StationStatus::Offline`] is reachable.
+    pub fn is_reachable(self) -> bool {
+        !matches!(self, StationStatus::Offline)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// StationMetadata
+// ---------------------------------------------------------------------------
+
+/// The kind of participant behind a station.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, strum::Display, strum::EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum StationRole {
+    /// Air traffic control unit.
+    Atc,
+    /// Aircraft (pilot client).
+    Aircraft,
+    /// Airline operations centre.
+    Aoc,
+}
+
+/// A datalink application a station supports.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, strum::Display, strum::EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum DatalinkApplication {
+    /// FANS 1/A CPDLC.
+    CpdlcFans,
+    /// ATN Baseline 1 CPDLC.
+    CpdlcAtnB1,
+    /// Departure clearance.
+    Dcl,
+    /// Datalink ATIS.
+    Atis,
+    /// ADS-Contract.
+    AdsC,
+}
+
+/// Optional details a station advertises alongside its status.
+///
+/// Every field is optional so that older clients, which send no metadata,
+/// keep working.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct StationMetadata {
+    /// Kind of participant.
+    pub role: Option<StationRole>,
+    /// Sector or facility name (e.g. `"PARIS CONTROL"`).
+    pub facility: Option<String>,
+    /// Primary voice frequency (e.g. `"127.775"`).
+    pub frequency: Option<String>,
+    /// Supported datalink applications.
+    pub applications: Vec<DatalinkApplication>,
+    /// Whether the station currently accepts new logons, when it says so.
+    pub accepting_logons: Option<bool>,
+}
+
+/// Public view of a registered station, as returned to clients.
+///
+/// Network addresses are deliberately not exposed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StationInfo {
+    /// Callsign the station is registered under.
+    pub callsign: AcarsEndpointCallsign,
+    /// ACARS address of the station.
+    pub acars_address: AcarsEndpointAddress,
+    /// Current status.
+    pub status: StationStatus,
+    /// Advertised metadata.
+    pub metadata: StationMetadata,
+    /// Last time the station refreshed its status.
+    pub last_updated: DateTime<Utc>,
+}
+
+// ---------------------------------------------------------------------------
+// EnvelopeRejection
+// ---------------------------------------------------------------------------
+
+/// Why the server refused to route an envelope.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, strum::Display, strum::EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum RejectionCode {
+    /// The envelope's routing source does not match the outbox it was published on.
+    SenderMismatch,
+    /// The claimed callsign is not registered to the sending network address.
+    CallsignNotOwned,
+    /// The claimed callsign is registered to the sender but not online.
+    CallsignOffline,
+    /// The ACARS aircraft routing does not match the sender's registration.
+    AircraftMismatch,
+    /// The callsign is leased by another online station.
+    CallsignInUse,
+    /// The callsign matches a pattern reserved to controllers on this network.
+    CallsignReserved,
+    /// The recipient stayed offline until the store-and-forward delay ran out.
+    DeliveryExpired,
+    /// The envelope's own `expires_at` passed before it could be delivered.
+    Expired,
+    /// The sender exceeded its rate limit; retry later.
+    RateLimited,
+    /// The envelope's token is missing, expired, revoked, or not the sender's.
+    Unauthenticated,
+    /// The sender's role does not allow this message (e.g. an uplink from a
+    /// pilot, or any envelope from an observer).
+    RoleNotPermitted,
+}
+
+/// Server → client notice that an envelope was not routed.
+///
+/// Sent to the inbox of the network address that published the rejected
+/// envelope, with the envelope id as correlation.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EnvelopeRejection {
+    /// Id of the rejected envelope.
+    pub envelope_id: Uuid,
+    /// Machine-readable reason.
+    pub code: RejectionCode,
+    /// Human-readable detail.
+    pub reason: String,
+}
+
+// ---------------------------------------------------------------------------
+// ServerNotice
+// ---------------------------------------------------------------------------
+
+/// Kind of unsolicited server → client notice.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, strum::Display, strum::EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum NoticeCode {
+    /// Another network address took over the recipient's callsign; the
+    /// recipient has been marked offline.
+    CallsignTakenOver,
+    /// The server is shutting down (restart or maintenance). Session state
+    /// is kept; reconnect and publish your status again shortly.
+    Maintenance,
+}
+
+/// Unsolicited server → client notice about the recipient's own state.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ServerNotice {
+    /// Machine-readable notice kind.
+    pub code: NoticeCode,
+    /// Human-readable detail.
+    pub message: String,
 }
 
 // ---------------------------------------------------------------------------
@@ -86,12 +242,24 @@ pub enum StationStatus {
 // ---------------------------------------------------------------------------
 
 /// System-level messages exchanged on the OpenLink network.
-///
-/// Currently only station-status updates are defined.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum MetaMessage {
-    /// A station announces or updates its status.
-    StationStatus(StationId, StationStatus, AcarsRoutingEndpoint),
+    /// A station announces or updates its status and advertised metadata.
+    StationStatus(
+        StationId,
+        StationStatus,
+        AcarsRoutingEndpoint,
+        #[serde(default)] StationMetadata,
+    ),
+    /// Client → server: look up a station by callsign.
+    StationLookup(AcarsEndpointCallsign),
+    /// Server → client: answer to a [`MetaMessage::StationLookup`]; `None`
+    /// when no reachable station holds the callsign.
+    StationInfo(AcarsEndpointCallsign, Option<StationInfo>),
+    /// Server → client: an envelope published by the recipient was rejected.
+    EnvelopeRejected(EnvelopeRejection),
+    /// Server → client: unsolicited notice about the recipient's state.
+    ServerNotice(ServerNotice),
 }
 
 // ---------------------------------------------------------------------------
@@ -116,6 +284,8 @@ mod tests {
     fn station_status_display() {
         assert_eq!(StationStatus::Online.to_string(), "online");
         assert_eq!(StationStatus::Offline.to_string(), "offline");
+        assert_eq!(StationStatus::Away.to_string(), "away");
+        assert_eq!(StationStatus::Closing.to_string(), "closing");
     }
 
     #[test]
@@ -131,12 +301,77 @@ mod tests {
             StationId::new("LFPG-APP"),
             StationStatus::Online,
             AcarsRoutingEndpoint::new("LFPG", "ADDR001"),
+            StationMetadata::default(),
         );
         let json = serde_json::to_string(&msg).unwrap();
         let back: MetaMessage = serde_json::from_str(&json).unwrap();
         assert_eq!(msg, back);
     }
 
+    #[test]
+    fn station_status_without_metadata_deserializes() {
+        let json = r#"{"StationStatus":["LFPG-APP","Online",{"callsign":"LFPG","address":"ADDR001"}]}"#;
+        let msg: MetaMessage = serde_json::from_str(json).unwrap();
+        match msg {
+            MetaMessage::StationStatus(_, status, _, metadata) => {
+                assert_eq!(status, StationStatus::Online);
+                assert_eq!(metadata, StationMetadata::default());
+            }
+            other => panic!("Expected StationStatus, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn station_metadata_serde_roundtrip() {
+        let metadata = StationMetadata {
+            role: Some(StationRole::Atc),
+            facility: Some("PARIS CONTROL".to_string()),
+            frequency: Some("127.775".to_string()),
+            applications: vec![DatalinkApplication::CpdlcFans, DatalinkApplication::Dcl],
+            accepting_logons: Some(true),
+        };
+        let json = serde_json::to_string(&metadata).unwrap();
+        let back: StationMetadata = serde_json::from_str(&json).unwrap();
+        assert_eq!(metadata, back);
+        let partial: StationMetadata = serde_json::from_str(r#"{"role":"Aircraft"}"#).unwrap();
+        assert_eq!(partial.role, Some(StationRole::Aircraft));
+        assert!(partial.applications.is_empty());
+    }
+
+    #[test]
+    fn station_status_reachability() {
+        assert!(StationStatus::Online.is_reachable());
+        assert!(StationStatus::Away.is_reachable());
+        assert!(StationStatus::Closing.is_reachable());
+        assert!(!StationStatus::Offline.is_reachable());
+    }
+
+    #[test]
+    fn envelope_rejected_serde_roundtrip() {
+        let msg = MetaMessage::EnvelopeRejected(EnvelopeRejection {
+            envelope_id: Uuid::new_v4(),
+            code: RejectionCode::CallsignNotOwned,
+            reason: "callsign LFPG is registered to another address".to_string(),
+        });
+        let json = serde_json::to_string(&msg).unwrap();
+        assert!(json.contains("\"CallsignNotOwned\""));
+        let back: MetaMessage = serde_json::from_str(&json).unwrap();
+        assert_eq!(msg, back);
+        assert_eq!(RejectionCode::CallsignNotOwned.to_string(), "callsign_not_owned");
+    }
+
+    #[test]
+    fn server_notice_serde_roundtrip() {
+        let msg = MetaMessage::ServerNotice(ServerNotice {
+            code: NoticeCode::CallsignTakenOver,
+            message: "callsign LFPG taken over by 888888".to_string(),
+        });
+        let json = serde_json::to_string(&msg).unwrap();
+        let back: MetaMessage = serde_json::from_str(&json).unwrap();
+        assert_eq!(msg, back);
+        assert_eq!(NoticeCode::Maintenance.to_string(), "maintenance");
+    }
+
     #[test]
     fn station_id_hash_usable() {
         use std::collections::HashSet;
@@ -159,7 +394,15 @@ mod tests {
     fn station_status_enum_iter() {
         use strum::IntoEnumIterator;
         let variants: Vec<_> = StationStatus::iter().collect();
-        assert_eq!(variants, vec![StationStatus::Online, StationStatus::Offline]);
+        assert_eq!(
+            variants,
+            vec![
+                StationStatus::Online,
+                StationStatus::Offline,
+                StationStatus::Away,
+                StationStatus::Closing
+            ]
+        );
     }
 
     #[test]
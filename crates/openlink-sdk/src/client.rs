@@ -22,20 +22,59 @@
 //! # Ok(())
 //! # }
 //! ```
+//!
+//! # Credential renewal
+//!
+//! Clients connected through the auth service renew their JWT before it
+//! expires — users with the refresh token from their login, servers and
+//! service accounts by presenting their credential again — then reconnect to NATS with it. The
+//! reconnection is transparent: subscriptions are restored by the NATS
+//! client and the same [`OpenLinkClient`] keeps its MIN sequences.
+//! Users logged in with
+//! [`connect_with_device_login`](OpenLinkClient::connect_with_device_login)
+//! also have the renewed credentials written back to their
+//! [`CredentialCache`].
 
 use async_nats::ConnectOptions;
+use futures::{Stream, StreamExt};
 use nkeys::KeyPair;
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
 use openlink_models::{
-    AcarsEndpointAddress, MessageBuilder, MessageElement, NetworkAddress, NetworkId,
-    OpenLinkEnvelope, OpenLinkMessage,
+    max_uplink_delay_seconds, AcarsEndpointAddress, AcarsMessage, CpdlcArgument,
+    CpdlcMessageType, DirectoryEvent, DirectoryQuery, DirectoryResponse, MessageBuilder,
+    MessageElement, NetworkAddress, NetworkId, OpenLinkEnvelope, OpenLinkMessage, StationInfo, UserRole,
+    DOWNLINK_DELAYED_TEXT, UPLINK_DELAYED_TEXT,
 };
 
-use crate::credentials::OpenLinkCredentials;
+use crate::credentials::{OpenLinkCredentials, ServerCredential, ServiceAccountCredential};
+use crate::device::{device_login, CredentialCache, DeviceAuthorization};
 use crate::error::SdkError;
 use crate::subjects::NatsSubjects;
-use crate::{LOGICAL_ACK_DOWNLINK_ID, LOGICAL_ACK_UPLINK_ID};
+use crate::cpdlc_runtime::{should_report_discarded, uplink_exceeds_max_delay};
+use crate::{ERROR_DOWNLINK_ID, ERROR_UPLINK_ID, LOGICAL_ACK_DOWNLINK_ID, LOGICAL_ACK_UPLINK_ID};
+
+/// Attempts made by [`OpenLinkClient::publish_envelope`] before giving up.
+pub const PUBLISH_ATTEMPTS: u32 = 3;
+/// Base delay between publish attempts, multiplied by the attempt number.
+pub const PUBLISH_RETRY_DELAY: std::time::Duration = std::time::Duration::from_millis(200);
+
+/// Delay before retrying a credential renewal that failed for another
+/// reason than the auth service refusing it.
+pub const RENEWAL_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Minimum remaining lifetime of cached credentials reused by
+/// [`OpenLinkClient::connect_with_device_login`] without renewal.
+pub const CACHED_JWT_MIN_VALIDITY: Duration = Duration::from_secs(60);
+
+/// Delay before an unacknowledged durable inbox message is redelivered.
+pub const DURABLE_INBOX_ACK_WAIT: std::time::Duration = std::time::Duration::from_secs(30);
+/// Delivery attempts per durable inbox message before it is given up.
+pub const DURABLE_INBOX_MAX_DELIVER: i64 = 10;
+/// Idle time after which an unused durable inbox consumer is removed.
+pub const DURABLE_INBOX_INACTIVE_THRESHOLD: std::time::Duration =
+    std::time::Duration::from_secs(24 * 3600);
 
 /// A connected OpenLink participant.
 ///
@@ -43,16 +82,56 @@ use crate::{LOGICAL_ACK_DOWNLINK_ID, LOGICAL_ACK_UPLINK_ID};
 /// publish and subscribe to the correct subjects.
 ///
 /// A client can represent a **station / aircraft** (connected via
-/// [`connect_with_authorization_code`](Self::connect_with_authorization_code))
+/// [`connect_with_authorization_code`](Self::connect_with_authorization_code)
+/// or, for unattended bots, via
+/// [`connect_as_service_account`](Self::connect_as_service_account))
 /// or a **server** (connected via [`connect_as_server`](Self::connect_as_server))
 /// with wildcard permissions.
 #[derive(Clone)]
 pub struct OpenLinkClient {
     nats_client: async_nats::Client,
-    creds: OpenLinkCredentials,
+    /// Current credentials, replaced on renewal and read by the NATS auth
+    /// callback on every (re)connection.
+    creds: Arc<RwLock<OpenLinkCredentials>>,
     network: NetworkId,
     address: NetworkAddress,
     min_sequences: Arc<Mutex<HashMap<String, u8>>>,
+    /// Max uplink delay (seconds) last set by ATC, per aircraft callsign.
+    max_uplink_delays: Arc<Mutex<HashMap<String, u16>>>,
+    /// Background renewal, stopped when the last clone is dropped.
+    _renewal: Option<Arc<RenewalTask>>,
+}
+
+/// How a client renews its credentials.
+#[derive(Clone)]
+enum Renewal {
+    /// Redeem the refresh token at `{auth_url}/refresh`, storing the
+    /// renewed credentials in `cache` when set.
+    User {
+        auth_url: String,
+        cache: Option<(CredentialCache, Option<UserRole>)>,
+    },
+    /// Present the server credential again at `{auth_url}/exchange-server`.
+    Server {
+        auth_url: String,
+        credential: ServerCredential,
+    },
+    /// Present the service account credential again at
+    /// `{auth_url}/exchange-service`.
+    ServiceAccount {
+        auth_url: String,
+        credential: ServiceAccountCredential,
+        role: Option<UserRole>,
+    },
+}
+
+/// Aborts the renewal task on drop.
+struct RenewalTask(tokio::task::AbortHandle);
+
+impl Drop for RenewalTask {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
 }
 
 impl OpenLinkClient {
@@ -67,11 +146,41 @@ impl OpenLinkClient {
     /// 1. Generates an ephemeral NKey pair.
     /// 2. Exchanges the code + public key for a signed NATS JWT.
     /// 3. Connects to the NATS server using JWT + NKey challenge.
+    ///
+    /// The auth service picks the role (pilot when granted); use
+    /// [`connect_with_authorization_code_as`](Self::connect_with_authorization_code_as)
+    /// to ask for a specific one.
     pub async fn connect_with_authorization_code(
         nats_url: &str,
         auth_url: &str,
         authorization_code: &str,
         network: &NetworkId,
+    ) -> Result<Self, SdkError> {
+        Self::exchange_and_connect(nats_url, auth_url, authorization_code, network, None).await
+    }
+
+    /// Like [`connect_with_authorization_code`](Self::connect_with_authorization_code),
+    /// requesting `role` in the issued JWT.
+    ///
+    /// Fails with [`SdkError::Auth`] when the user's identity provider
+    /// claims do not grant that role.
+    pub async fn connect_with_authorization_code_as(
+        nats_url: &str,
+        auth_url: &str,
+        authorization_code: &str,
+        network: &NetworkId,
+        role: UserRole,
+    ) -> Result<Self, SdkError> {
+        Self::exchange_and_connect(nats_url, auth_url, authorization_code, network, Some(role))
+            .await
+    }
+
+    async fn exchange_and_connect(
+        nats_url: &str,
+        auth_url: &str,
+        authorization_code: &str,
+        network: &NetworkId,
+        role: Option<UserRole>,
     ) -> Result<Self, SdkError> {
         // 1. Generate ephemeral user key-pair
         let user_kp = KeyPair::new(nkeys::KeyPairType::User);
@@ -88,6 +197,7 @@ impl OpenLinkClient {
                 "oidc_code": authorization_code,
                 "user_nkey_public": public_key,
                 "network": network.as_str(),
+                "role": role,
             }))
             .send()
             .await?;
@@ -106,38 +216,147 @@ impl OpenLinkClient {
             .as_str()
             .ok_or_else(|| SdkError::Auth("missing `cid` in auth response".into()))?
             .to_string();
+        let refresh_token = body["refresh_token"].as_str().map(str::to_string);
 
-        let creds = OpenLinkCredentials { seed, jwt, cid };
+        let creds = OpenLinkCredentials {
+            seed,
+            jwt,
+            cid,
+            refresh_token,
+        };
 
         // 3. Connect
-        Self::connect(nats_url, creds, network).await
+        Self::connect_with_credentials(nats_url, auth_url, creds, network).await
     }
 
     /// Connect to NATS using pre-existing credentials.
     ///
     /// Supports both TCP (`nats://`) and WebSocket (`ws://`, `wss://`).
+    /// Request/reply inboxes are scoped under [`NatsSubjects::replies`], the
+    /// only reply subjects a station JWT may subscribe to.
+    ///
+    /// The credentials are not renewed: the connection stops working when
+    /// the JWT expires. Use
+    /// [`connect_with_credentials`](Self::connect_with_credentials) to
+    /// renew them through the auth service.
     pub async fn connect(
         nats_url: &str,
         creds: OpenLinkCredentials,
         network: &NetworkId,
+    ) -> Result<Self, SdkError> {
+        let address = NetworkAddress::new(&creds.cid);
+        Self::connect_renewing(nats_url, creds, network, address, None).await
+    }
+
+    /// Connect with stored credentials (e.g. saved from
+    /// [`credentials`](Self::credentials)), renewing them through the auth
+    /// service at `auth_url` with their refresh token before the JWT
+    /// expires.
+    pub async fn connect_with_credentials(
+        nats_url: &str,
+        auth_url: &str,
+        creds: OpenLinkCredentials,
+        network: &NetworkId,
+    ) -> Result<Self, SdkError> {
+        let address = NetworkAddress::new(&creds.cid);
+        let renewal = Renewal::User {
+            auth_url: auth_url.to_string(),
+            cache: None,
+        };
+        Self::connect_renewing(nats_url, creds, network, address, Some(renewal)).await
+    }
+
+    /// Log in from a terminal or headless tool with the device
+    /// authorization grant, then connect to NATS.
+    ///
+    /// 1. Reuses the credentials in `cache` when they were obtained for the
+    ///    same auth service, network and role and their JWT is valid for
+    ///    at least [`CACHED_JWT_MIN_VALIDITY`].
+    /// 2. Otherwise renews them with their refresh token.
+    /// 3. Otherwise runs [`device_login`]: `prompt` shows the verification
+    ///    URL and user code (see
+    ///    [`print_device_prompt`](crate::print_device_prompt)) and the
+    ///    call returns once the user approved the login in a browser.
+    ///
+    /// The credentials in use, including every later renewal, are written
+    /// back to `cache`.
+    pub async fn connect_with_device_login(
+        nats_url: &str,
+        auth_url: &str,
+        network: &NetworkId,
+        role: Option<UserRole>,
+        cache: Option<&CredentialCache>,
+        prompt: impl FnOnce(&DeviceAuthorization),
+    ) -> Result<Self, SdkError> {
+        let renewal = Renewal::User {
+            auth_url: auth_url.to_string(),
+            cache: cache.map(|cache| (cache.clone(), role)),
+        };
+        let cached = cache.and_then(|cache| cache.load(auth_url, network, role));
+
+        let creds = match cached {
+            Some(login) if login.is_valid_at(unix_now(), CACHED_JWT_MIN_VALIDITY) => {
+                login.credentials
+            }
+            Some(login) if login.credentials.refresh_token.is_some() => {
+                match renew(&reqwest::Client::new(), &renewal, &login.credentials, network).await {
+                    Ok(creds) => creds,
+                    Err(SdkError::Auth(_)) => device_login(auth_url, network, role, prompt).await?,
+                    Err(e) => return Err(e),
+                }
+            }
+            _ => device_login(auth_url, network, role, prompt).await?,
+        };
+        if let Some(cache) = cache {
+            cache.store(auth_url, network, role, &creds)?;
+        }
+
+        let address = NetworkAddress::new(&creds.cid);
+        Self::connect_renewing(nats_url, creds, network, address, Some(renewal)).await
+    }
+
+    async fn connect_renewing(
+        nats_url: &str,
+        creds: OpenLinkCredentials,
+        network: &NetworkId,
+        address: NetworkAddress,
+        renewal: Option<Renewal>,
     ) -> Result<Self, SdkError> {
         // Sanity-check the seed
         let _ = KeyPair::from_seed(&creds.seed)
             .map_err(|e| SdkError::Config(format!("invalid NKey seed: {e}")))?;
 
-        let jwt = creds.jwt.clone();
-        let seed_for_sign = creds.seed.clone();
-        let address = NetworkAddress::new(&creds.cid);
-
-        let options = ConnectOptions::with_jwt(jwt, move |nonce| {
-            let seed = seed_for_sign.clone();
+        let is_server = matches!(renewal, Some(Renewal::Server { .. }));
+        let creds = Arc::new(RwLock::new(creds));
+        let creds_for_auth = creds.clone();
+        let mut options = ConnectOptions::with_auth_callback(move |nonce| {
+            let creds = creds_for_auth.clone();
             async move {
+                let (jwt, seed) = {
+                    let creds = creds.read().expect("credentials lock poisoned");
+                    (creds.jwt.clone(), creds.seed.clone())
+                };
                 let kp = KeyPair::from_seed(&seed).map_err(async_nats::AuthError::new)?;
-                kp.sign(&nonce).map_err(async_nats::AuthError::new)
+                let mut auth = async_nats::Auth::new();
+                auth.jwt = Some(jwt);
+                auth.signature = Some(kp.sign(&nonce).map_err(async_nats::AuthError::new)?);
+                Ok(auth)
             }
         });
+        if !is_server {
+            options = options.custom_inbox_prefix(NatsSubjects::replies(network, &address));
+        }
 
         let nats_client = async_nats::connect_with_options(nats_url, options).await?;
+        let renewal = renewal.map(|renewal| {
+            let task = tokio::spawn(renew_until_refused(
+                nats_client.clone(),
+                creds.clone(),
+                network.clone(),
+                renewal,
+            ));
+            Arc::new(RenewalTask(task.abort_handle()))
+        });
 
         Ok(Self {
             nats_client,
@@ -145,23 +364,27 @@ impl OpenLinkClient {
             network: network.clone(),
             address,
             min_sequences: Arc::new(Mutex::new(HashMap::new())),
+            max_uplink_delays: Arc::new(Mutex::new(HashMap::new())),
+            _renewal: renewal,
         })
     }
 
     /// Connect to NATS as an **OpenLink server** with wildcard permissions.
     ///
     /// 1. Generates an ephemeral NKey pair.
-    /// 2. Exchanges the server secret for a master NATS JWT via
-    ///    `POST /exchange-server`.
+    /// 2. Exchanges the server credential (a signed assertion or a shared
+    ///    secret) for a master NATS JWT via `POST /exchange-server`.
     /// 3. Connects to NATS with JWT + NKey challenge.
     ///
+    /// The JWT is renewed with the same credential before it expires.
+    ///
     /// The returned client has publish access to all inboxes and subscribe
     /// access to all outboxes on the given `network`, as well as JetStream
     /// KV access.
     pub async fn connect_as_server(
         nats_url: &str,
         auth_url: &str,
-        server_secret: &str,
+        credential: &ServerCredential,
         network: &NetworkId,
     ) -> Result<Self, SdkError> {
         // 1. Generate ephemeral user key-pair
@@ -169,60 +392,75 @@ impl OpenLinkClient {
         let seed = user_kp
             .seed()
             .map_err(|e| SdkError::Config(e.to_string()))?;
-        let public_key = user_kp.public_key();
-
-        // 2. Exchange server secret for a master NATS JWT
-        let http = reqwest::Client::new();
-        let res = http
-            .post(format!("{auth_url}/exchange-server"))
-            .json(&serde_json::json!({
-                "server_secret": server_secret,
-                "user_nkey_public": public_key,
-                "network": network.as_str(),
-            }))
-            .send()
-            .await?;
-
-        if !res.status().is_success() {
-            let text = res.text().await?;
-            return Err(SdkError::Auth(text));
-        }
-
-        let body: serde_json::Value = res.json().await?;
-        let jwt = body["jwt"]
-            .as_str()
-            .ok_or_else(|| SdkError::Auth("missing `jwt` in auth response".into()))?
-            .to_string();
 
+        // 2. Exchange the server credential for a master NATS JWT
         let server_name = format!("openlink-server-{network}");
         let creds = OpenLinkCredentials {
             seed,
-            jwt,
+            jwt: String::new(),
             cid: server_name.clone(),
+            refresh_token: None,
+        };
+        let renewal = Renewal::Server {
+            auth_url: auth_url.to_string(),
+            credential: credential.clone(),
         };
-        let address = NetworkAddress::new(&server_name);
+        let creds = renew(&reqwest::Client::new(), &renewal, &creds, network).await?;
 
         // 3. Connect
-        let jwt_for_connect = creds.jwt.clone();
-        let seed_for_sign = creds.seed.clone();
+        Self::connect_renewing(
+            nats_url,
+            creds,
+            network,
+            NetworkAddress::new(&server_name),
+            Some(renewal),
+        )
+        .await
+    }
 
-        let options = ConnectOptions::with_jwt(jwt_for_connect, move |nonce| {
-            let seed = seed_for_sign.clone();
-            async move {
-                let kp = KeyPair::from_seed(&seed).map_err(async_nats::AuthError::new)?;
-                kp.sign(&nonce).map_err(async_nats::AuthError::new)
-            }
-        });
+    /// Connect to NATS as a **service account** (bot, bridge, automation).
+    ///
+    /// 1. Generates an ephemeral NKey pair.
+    /// 2. Exchanges the account credential (a signed assertion or an API
+    ///    key) for a NATS JWT via `POST /exchange-service`, under `role`
+    ///    or, when `None`, a role the auth service picks among the
+    ///    account's.
+    /// 3. Connects to NATS with JWT + NKey challenge.
+    ///
+    /// The client's address is the account's fixed CID, and it may only
+    /// register and send as the account's callsigns. The JWT is renewed
+    /// with the same credential before it expires; renewal stops once the
+    /// account is revoked or expired.
+    pub async fn connect_as_service_account(
+        nats_url: &str,
+        auth_url: &str,
+        credential: &ServiceAccountCredential,
+        network: &NetworkId,
+        role: Option<UserRole>,
+    ) -> Result<Self, SdkError> {
+        // 1. Generate ephemeral user key-pair
+        let user_kp = KeyPair::new(nkeys::KeyPairType::User);
+        let seed = user_kp
+            .seed()
+            .map_err(|e| SdkError::Config(e.to_string()))?;
 
-        let nats_client = async_nats::connect_with_options(nats_url, options).await?;
+        // 2. Exchange the account credential for a NATS JWT
+        let creds = OpenLinkCredentials {
+            seed,
+            jwt: String::new(),
+            cid: String::new(),
+            refresh_token: None,
+        };
+        let renewal = Renewal::ServiceAccount {
+            auth_url: auth_url.to_string(),
+            credential: credential.clone(),
+            role,
+        };
+        let creds = renew(&reqwest::Client::new(), &renewal, &creds, network).await?;
 
-        Ok(Self {
-            nats_client,
-            creds,
-            network: network.clone(),
-            address,
-            min_sequences: Arc::new(Mutex::new(HashMap::new())),
-        })
+        // 3. Connect
+        let address = NetworkAddress::new(&creds.cid);
+        Self::connect_renewing(nats_url, creds, network, address, Some(renewal)).await
     }
 
     /// Advance and return the next MIN for a sender/receiver CPDLC session key.
@@ -259,7 +497,7 @@ impl OpenLinkClient {
     /// published on the client's **outbox** subject.
     pub async fn send_to_server(&self, msg: OpenLinkMessage) -> Result<(), SdkError> {
         let envelope = MessageBuilder::envelope(msg)
-            .source_address(self.network.as_str(), self.creds.cid.as_str())
+            .source_address(self.network.as_str(), self.cid())
             .destination_server(self.network.as_str())
             .build();
 
@@ -474,20 +712,168 @@ impl OpenLinkClient {
     }
 
     /// Low-level: serialize an envelope and publish it on a raw subject.
+    ///
+    /// A failed publish or flush is retried up to [`PUBLISH_ATTEMPTS`]
+    /// times with the same payload, so every attempt carries the same
+    /// envelope id and the server drops the ones it already processed.
+    /// Callers retrying on their own should likewise resend the same
+    /// envelope rather than build a new one.
+    ///
+    /// Envelopes published on this client's outbox without a `token` carry
+    /// the client's JWT, which the server verifies to learn its role.
     pub async fn publish_envelope(
         &self,
         subject: &str,
         envelope: &OpenLinkEnvelope,
     ) -> Result<(), SdkError> {
-        let bytes = serde_json::to_vec(envelope)?;
+        let bytes = if envelope.token.is_empty()
+            && subject == NatsSubjects::outbox(&self.network, &self.address)
+        {
+            serde_json::to_vec(&OpenLinkEnvelope {
+                token: self.credentials().jwt,
+                ..envelope.clone()
+            })?
+        } else {
+            serde_json::to_vec(envelope)?
+        };
+        let mut attempt = 1;
+        loop {
+            match self.try_publish(subject, bytes.clone()).await {
+                Ok(()) => return Ok(()),
+                Err(e) if attempt >= PUBLISH_ATTEMPTS => return Err(e),
+                Err(_) => {
+                    tokio::time::sleep(PUBLISH_RETRY_DELAY * attempt).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    async fn try_publish(&self, subject: &str, payload: Vec<u8>) -> Result<(), SdkError> {
         self.nats_client
-            .publish(subject.to_string(), bytes.into())
+            .publish(subject.to_string(), payload.into())
             .await?;
         self.nats_client
             .flush()
             .await
+            .map_err(|e| SdkError::Nats(e.to_string()))
+    }
+
+    // ------------------------------------------------------------------
+    // Receiving
+    // ------------------------------------------------------------------
+
+    /// Screen a received envelope before handing it to the application.
+    ///
+    /// Returns `false` when the envelope must be discarded:
+    /// - it is past its `expires_at`, or
+    /// - it is a CPDLC uplink that took longer to arrive than the max
+    ///   uplink delay last set for that aircraft ("SET MAX UPLINK DELAY").
+    ///
+    /// A discarded CPDLC application message is answered with an ERROR
+    /// (`DM62` / `UM159`) so that the sender can resend or use voice.
+    /// Max uplink delay settings are recorded as they are received, so
+    /// every incoming envelope should go through this method.
+    pub async fn accept_incoming(&self, envelope: &OpenLinkEnvelope) -> Result<bool, SdkError> {
+        let now = chrono::Utc::now();
+        let expired = envelope.is_expired(now);
+        let OpenLinkMessage::Acars(acars) = &envelope.payload else {
+            return Ok(!expired);
+        };
+        let AcarsMessage::CPDLC(cpdlc) = &acars.message;
+        let CpdlcMessageType::Application(app) = &cpdlc.message else {
+            return Ok(!expired);
+        };
+
+        let aircraft = &acars.routing.aircraft;
+        let uplink = cpdlc.destination == aircraft.callsign;
+        let max_delay = self
+            .max_uplink_delays
+            .lock()
+            .expect("max uplink delay mutex poisoned")
+            .get(aircraft.callsign.as_str())
+            .copied();
+        let delayed = uplink && uplink_exceeds_max_delay(app.timestamp, now, max_delay);
+
+        if expired || delayed {
+            if should_report_discarded(&app.elements) {
+                let (id, text) = if uplink {
+                    (ERROR_DOWNLINK_ID, UPLINK_DELAYED_TEXT)
+                } else {
+                    (ERROR_UPLINK_ID, DOWNLINK_DELAYED_TEXT)
+                };
+                let sender = cpdlc.destination.to_string();
+                let receiver = cpdlc.source.to_string();
+                let min = self.next_min_for_session(&sender, &receiver);
+                let error = MessageBuilder::cpdlc(aircraft.callsign.to_string(), aircraft.address.to_string())
+                    .from(sender)
+                    .to(receiver)
+                    .application_message_with_min_and_mrn(
+                        vec![MessageElement::new(id, vec![CpdlcArgument::ErrorInfo(text.to_string())])],
+                        min,
+                        Some(app.min),
+                    )
+                    .build();
+                self.send_to_server(error).await?;
+            }
+            return Ok(false);
+        }
+
+        let setting = if uplink {
+            max_uplink_delay_seconds(&app.elements)
+        } else {
+            None
+        };
+        if let Some(seconds) = setting {
+            let mut guard = self
+                .max_uplink_delays
+                .lock()
+                .expect("max uplink delay mutex poisoned");
+            if seconds == 0 {
+                guard.remove(aircraft.callsign.as_str());
+            } else {
+                guard.insert(aircraft.callsign.to_string(), seconds);
+            }
+        }
+        Ok(true)
+    }
+
+    // ------------------------------------------------------------------
+    // Station directory
+    // ------------------------------------------------------------------
+
+    /// Ask the server for the reachable stations matching `query`.
+    ///
+    /// Stations are returned sorted by callsign.
+    pub async fn query_directory(
+        &self,
+        query: &DirectoryQuery,
+    ) -> Result<Vec<StationInfo>, SdkError> {
+        let payload = serde_json::to_vec(query)?;
+        let reply = self
+            .nats_client
+            .request(NatsSubjects::directory_query(&self.network), payload.into())
+            .await
             .map_err(|e| SdkError::Nats(e.to_string()))?;
-        Ok(())
+        let response: DirectoryResponse = serde_json::from_slice(&reply.payload)?;
+        Ok(response.stations)
+    }
+
+    /// Subscribe to live directory changes.
+    ///
+    /// Combine with [`query_directory`](Self::query_directory) to keep a
+    /// local picker up to date: subscribe first, then query, then apply
+    /// events. Malformed payloads are skipped.
+    pub async fn watch_directory(
+        &self,
+    ) -> Result<impl Stream<Item = DirectoryEvent> + Unpin, SdkError> {
+        let sub = self
+            .nats_client
+            .subscribe(NatsSubjects::directory_events(&self.network))
+            .await?;
+        Ok(sub.filter_map(|msg| {
+            futures::future::ready(serde_json::from_slice::<DirectoryEvent>(&msg.payload).ok())
+        }))
     }
 
     // ------------------------------------------------------------------
@@ -504,6 +890,51 @@ impl OpenLinkClient {
         Ok(sub)
     }
 
+    /// Read this client's inbox through a durable JetStream consumer.
+    ///
+    /// Unlike [`subscribe_inbox`](Self::subscribe_inbox), messages published
+    /// while the client is disconnected are not lost: the consumer keeps a
+    /// per-address cursor on the server, so a later call (even from a new
+    /// process) resumes after the last acknowledged message. Each message
+    /// must be acknowledged with `message.ack()` once handled; unacknowledged
+    /// messages are redelivered after [`DURABLE_INBOX_ACK_WAIT`].
+    ///
+    /// Requires the server to run with the inbox stream enabled.
+    pub async fn subscribe_inbox_durable(
+        &self,
+    ) -> Result<async_nats::jetstream::consumer::pull::Stream, SdkError> {
+        use async_nats::jetstream::consumer::{AckPolicy, DeliverPolicy, pull};
+
+        let js = async_nats::jetstream::new(self.nats_client.clone());
+        let stream = js
+            .get_stream(NatsSubjects::stream_inboxes(&self.network))
+            .await
+            .map_err(|e| SdkError::Nats(e.to_string()))?;
+        let name = NatsSubjects::inbox_consumer(&self.address);
+        let consumer: async_nats::jetstream::consumer::Consumer<pull::Config> = stream
+            .get_or_create_consumer(
+                &name,
+                pull::Config {
+                    durable_name: Some(name.clone()),
+                    filter_subject: NatsSubjects::inbox(&self.network, &self.address),
+                    // Only the first creation picks the start point; the
+                    // cursor is kept across reconnects afterwards.
+                    deliver_policy: DeliverPolicy::New,
+                    ack_policy: AckPolicy::Explicit,
+                    ack_wait: DURABLE_INBOX_ACK_WAIT,
+                    max_deliver: DURABLE_INBOX_MAX_DELIVER,
+                    inactive_threshold: DURABLE_INBOX_INACTIVE_THRESHOLD,
+                    ..Default::default()
+                },
+            )
+            .await
+            .map_err(|e| SdkError::Nats(e.to_string()))?;
+        consumer
+            .messages()
+            .await
+            .map_err(|e| SdkError::Nats(e.to_string()))
+    }
+
     /// Subscribe to the **outbox wildcard** subject for this network.
     ///
     /// This receives every message published by any client on the network.
@@ -531,12 +962,16 @@ impl OpenLinkClient {
 
     /// The connection identifier (CID) from the auth service.
     pub fn cid(&self) -> &str {
-        &self.creds.cid
+        self.address.as_str()
     }
 
-    /// The underlying credentials.
-    pub fn credentials(&self) -> &OpenLinkCredentials {
-        &self.creds
+    /// The current credentials, including the latest renewed JWT and
+    /// refresh token.
+    pub fn credentials(&self) -> OpenLinkCredentials {
+        self.creds
+            .read()
+            .expect("credentials lock poisoned")
+            .clone()
     }
 
     /// Access the raw NATS client for advanced operations.
@@ -548,6 +983,115 @@ impl OpenLinkClient {
     }
 }
 
+/// Renew `creds` before each JWT expires and reconnect with the new one,
+/// until the auth service refuses a renewal or the credentials cannot be
+/// renewed (no expiry, no refresh token).
+async fn renew_until_refused(
+    nats_client: async_nats::Client,
+    creds: Arc<RwLock<OpenLinkCredentials>>,
+    network: NetworkId,
+    renewal: Renewal,
+) {
+    let http = reqwest::Client::new();
+    loop {
+        let current = creds.read().expect("credentials lock poisoned").clone();
+        let Some(expires_at) = current.expires_at() else {
+            return;
+        };
+        tokio::time::sleep(renewal_delay(unix_now(), expires_at)).await;
+        match renew(&http, &renewal, &current, &network).await {
+            Ok(renewed) => {
+                if let Renewal::User {
+                    auth_url,
+                    cache: Some((cache, role)),
+                } = &renewal
+                {
+                    // A stale cache would hold an already redeemed refresh
+                    // token; the connection goes on regardless.
+                    let _ = cache.store(auth_url, &network, *role, &renewed);
+                }
+                *creds.write().expect("credentials lock poisoned") = renewed;
+                // Present the new JWT now rather than when the server
+                // drops the connection at expiry.
+                let _ = nats_client.force_reconnect().await;
+            }
+            Err(SdkError::Auth(_)) => return,
+            Err(_) => tokio::time::sleep(RENEWAL_RETRY_DELAY).await,
+        }
+    }
+}
+
+/// Obtain new credentials for the same key pair.
+async fn renew(
+    http: &reqwest::Client,
+    renewal: &Renewal,
+    current: &OpenLinkCredentials,
+    network: &NetworkId,
+) -> Result<OpenLinkCredentials, SdkError> {
+    let public_key = KeyPair::from_seed(&current.seed)
+        .map_err(|e| SdkError::Config(format!("invalid NKey seed: {e}")))?
+        .public_key();
+    let request = match renewal {
+        Renewal::User { auth_url, .. } => {
+            let refresh_token = current
+                .refresh_token
+                .as_deref()
+                .ok_or_else(|| SdkError::Auth("no refresh token".into()))?;
+            http.post(format!("{auth_url}/refresh")).json(&serde_json::json!({
+                "refresh_token": refresh_token,
+                "user_nkey_public": public_key,
+            }))
+        }
+        Renewal::Server {
+            auth_url,
+            credential,
+        } => http
+            .post(format!("{auth_url}/exchange-server"))
+            .json(&credential.exchange_request(&public_key, network, unix_now())?),
+        Renewal::ServiceAccount {
+            auth_url,
+            credential,
+            role,
+        } => http
+            .post(format!("{auth_url}/exchange-service"))
+            .json(&credential.exchange_request(&public_key, network, *role, unix_now())?),
+    };
+
+    let res = request.send().await?;
+    // Rate limited: an HTTP error, retried after a delay.
+    if res.status().is_client_error() && res.status() != reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return Err(SdkError::Auth(res.text().await?));
+    }
+    let body: serde_json::Value = res.error_for_status()?.json().await?;
+    let jwt = body["jwt"]
+        .as_str()
+        .ok_or_else(|| SdkError::Auth("missing `jwt` in auth response".into()))?
+        .to_string();
+    Ok(OpenLinkCredentials {
+        jwt,
+        cid: body["cid"]
+            .as_str()
+            .map_or_else(|| current.cid.clone(), str::to_string),
+        refresh_token: body["refresh_token"].as_str().map(str::to_string),
+        seed: current.seed.clone(),
+    })
+}
+
+/// Time to wait before renewing a JWT expiring at `expires_at`: a fifth of
+/// its remaining lifetime ahead of expiry, between 10 s and 5 min.
+fn renewal_delay(now: u64, expires_at: u64) -> Duration {
+    let remaining = expires_at.saturating_sub(now);
+    let lead = (remaining / 5).clamp(10, 300);
+    Duration::from_secs(remaining.saturating_sub(lead))
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("system clock before epoch")
+        .as_secs()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -569,4 +1113,16 @@ mod tests {
         assert_eq!(network.as_str(), "demonetwork");
         assert_eq!(address.as_str(), "12345");
     }
+
+    #[test]
+    fn renewal_happens_ahead_of_expiry() {
+        let now = 1_700_000_000;
+        // One hour left: renew five minutes before expiry.
+        assert_eq!(renewal_delay(now, now + 3600), Duration::from_secs(3300));
+        // Ten minutes left: renew two minutes before expiry.
+        assert_eq!(renewal_delay(now, now + 600), Duration::from_secs(480));
+        // Short-lived or already expired: renew right away.
+        assert_eq!(renewal_delay(now, now + 5), Duration::ZERO);
+        assert_eq!(renewal_delay(now, now - 60), Duration::ZERO);
+    }
 }
@@ -3,6 +3,7 @@
 //! This module centralizes protocol decisions that should behave the same
 //! across Rust and TypeScript SDKs.
 
+use chrono::{DateTime, Duration, Utc};
 use openlink_models::{
     closes_dialogue_response_elements as model_closes_dialogue_response_elements,
     find_definition, CpdlcResponseIntent, MessageElement, ResponseAttribute,
@@ -13,6 +14,11 @@ pub const LOGICAL_ACK_DOWNLINK_ID: &str = "DM100";
 /// Logical acknowledgement uplink message ID (station sender).
 pub const LOGICAL_ACK_UPLINK_ID: &str = "UM227";
 
+/// Error element ID sent by an aircraft.
+pub const ERROR_DOWNLINK_ID: &str = "DM62";
+/// Error element ID sent by a station.
+pub const ERROR_UPLINK_ID: &str = "UM159";
+
 /// Returns true if the message element id is a logical acknowledgement.
 pub fn is_logical_ack_element_id(id: &str) -> bool {
     matches!(id, LOGICAL_ACK_DOWNLINK_ID | LOGICAL_ACK_UPLINK_ID)
@@ -49,6 +55,30 @@ pub fn logical_ack_matches_outgoing(
         && message_contains_logical_ack(incoming_elements)
 }
 
+/// Returns true if an uplink sent at `sent_at` arrived later than the
+/// aircraft's max uplink delay.
+///
+/// No delay, or a delay of `0`, disables the check.
+pub fn uplink_exceeds_max_delay(
+    sent_at: DateTime<Utc>,
+    now: DateTime<Utc>,
+    max_delay_seconds: Option<u16>,
+) -> bool {
+    max_delay_seconds
+        .filter(|seconds| *seconds > 0)
+        .is_some_and(|seconds| now - sent_at > Duration::seconds(seconds.into()))
+}
+
+/// Returns true if a discarded message should be answered with an ERROR.
+///
+/// Errors and logical acknowledgements are never answered, to avoid loops.
+pub fn should_report_discarded(elements: &[MessageElement]) -> bool {
+    !message_contains_logical_ack(elements)
+        && !elements
+            .iter()
+            .any(|e| matches!(e.id.as_str(), ERROR_DOWNLINK_ID | ERROR_UPLINK_ID))
+}
+
 /// Returns true if response elements close the referenced dialogue.
 ///
 /// Delegates to the canonical model-level implementation.
@@ -161,6 +191,23 @@ mod tests {
         assert!(!should_auto_send_logical_ack(&ack, 12));
     }
 
+    #[test]
+    fn max_uplink_delay_rule() {
+        let now = Utc::now();
+        let sent = now - Duration::seconds(90);
+        assert!(!uplink_exceeds_max_delay(sent, now, None));
+        assert!(!uplink_exceeds_max_delay(sent, now, Some(0)));
+        assert!(!uplink_exceeds_max_delay(sent, now, Some(120)));
+        assert!(uplink_exceeds_max_delay(sent, now, Some(60)));
+    }
+
+    #[test]
+    fn discarded_errors_are_not_reported() {
+        assert!(should_report_discarded(&[MessageElement::new("UM20", vec![])]));
+        assert!(!should_report_discarded(&[MessageElement::new("DM62", vec![])]));
+        assert!(!should_report_discarded(&[MessageElement::new("UM227", vec![])]));
+    }
+
     #[test]
     fn logical_ack_matching_rule() {
         let ack = vec![MessageElement::new("DM100", vec![])];
@@ -1,4 +1,11 @@
-//! Authentication credentials returned by the OpenLink auth service.
+//! Authentication credentials returned by the OpenLink auth service, and
+//! the credentials OpenLink servers and service accounts present to it.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use nkeys::KeyPair;
+use openlink_models::{NetworkId, UserRole};
+
+use crate::error::SdkError;
 
 /// Credentials obtained after a successful OAuth / authorization-code exchange.
 ///
@@ -7,6 +14,7 @@
 /// * `seed`  – NKey seed (private key) used to sign the server challenge.
 /// * `jwt`   – User JWT that authorises the connection with specific permissions.
 /// * `cid`   – Unique connection identifier assigned by the auth service.
+/// * `refresh_token` – Single-use token renewing `jwt` at `POST /refresh`.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct OpenLinkCredentials {
     /// NKey seed for NATS authentication.
@@ -15,4 +23,281 @@ pub struct OpenLinkCredentials {
     pub jwt: String,
     /// Connection ID.
     pub cid: String,
+    /// Refresh token, when the auth service issued one. Replaced on every
+    /// renewal.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+}
+
+impl OpenLinkCredentials {
+    /// Expiry of [`jwt`](Self::jwt) in Unix seconds, read from its `exp`
+    /// claim. `None` when the JWT cannot be decoded or does not expire.
+    pub fn expires_at(&self) -> Option<u64> {
+        let body = self.jwt.split('.').nth(1)?;
+        let body = URL_SAFE_NO_PAD.decode(body).ok()?;
+        let claims: serde_json::Value = serde_json::from_slice(&body).ok()?;
+        claims["exp"].as_u64()
+    }
+}
+
+/// How an OpenLink server authenticates to `POST /exchange-server`.
+#[derive(Clone)]
+pub enum ServerCredential {
+    /// Server registered with an NKey public key: each request carries an
+    /// assertion (see [`server_assertion`]) signed with the matching seed.
+    Key {
+        /// Name the server is registered under.
+        name: String,
+        /// NKey seed (`SU…`) of the registered key.
+        seed: String,
+    },
+    /// Server registered with a shared secret.
+    Secret {
+        /// Name the server is registered under; `None` lets the auth
+        /// service find the entry by secret.
+        name: Option<String>,
+        /// The shared secret.
+        secret: String,
+    },
+}
+
+impl std::fmt::Debug for ServerCredential {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Key { name, .. } => f.debug_struct("Key").field("name", name).finish_non_exhaustive(),
+            Self::Secret { name, .. } => {
+                f.debug_struct("Secret").field("name", name).finish_non_exhaustive()
+            }
+        }
+    }
+}
+
+impl ServerCredential {
+    /// Registered server name, if any.
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            Self::Key { name, .. } => Some(name),
+            Self::Secret { name, .. } => name.as_deref(),
+        }
+    }
+
+    /// Body of a `POST /exchange-server` request for a JWT issued to
+    /// `user_nkey_public` on `network`, at Unix time `now`.
+    pub fn exchange_request(
+        &self,
+        user_nkey_public: &str,
+        network: &NetworkId,
+        now: u64,
+    ) -> Result<serde_json::Value, SdkError> {
+        let mut body = serde_json::json!({
+            "user_nkey_public": user_nkey_public,
+            "network": network.as_str(),
+        });
+        match self {
+            Self::Key { name, seed } => {
+                let kp = KeyPair::from_seed(seed)
+                    .map_err(|e| SdkError::Config(format!("invalid server NKey seed: {e}")))?;
+                let assertion = server_assertion(name, network, user_nkey_public, now);
+                let signature = kp
+                    .sign(assertion.as_bytes())
+                    .map_err(|e| SdkError::Config(e.to_string()))?;
+                body["server_name"] = name.as_str().into();
+                body["timestamp"] = now.into();
+                body["signature"] = URL_SAFE_NO_PAD.encode(signature).into();
+            }
+            Self::Secret { name, secret } => {
+                if let Some(name) = name {
+                    body["server_name"] = name.as_str().into();
+                }
+                body["server_secret"] = secret.as_str().into();
+            }
+        }
+        Ok(body)
+    }
+}
+
+/// How a service account (bot, bridge, automation) authenticates to
+/// `POST /exchange-service`.
+///
+/// Service accounts are declared in the auth service configuration with a
+/// fixed CID, the callsigns and roles they may use and an optional expiry;
+/// unlike users they need no interactive login.
+#[derive(Clone)]
+pub enum ServiceAccountCredential {
+    /// Account registered with an NKey public key: each request carries an
+    /// assertion (see [`service_account_assertion`]) signed with the
+    /// matching seed.
+    Key {
+        /// Name the account is registered under.
+        name: String,
+        /// NKey seed (`SU…`) of the registered key.
+        seed: String,
+    },
+    /// Account registered with an API key.
+    ApiKey {
+        /// Name the account is registered under.
+        name: String,
+        /// The API key.
+        api_key: String,
+    },
+}
+
+impl std::fmt::Debug for ServiceAccountCredential {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Key { name, .. } => f.debug_struct("Key").field("name", name).finish_non_exhaustive(),
+            Self::ApiKey { name, .. } => {
+                f.debug_struct("ApiKey").field("name", name).finish_non_exhaustive()
+            }
+        }
+    }
+}
+
+impl ServiceAccountCredential {
+    /// Registered account name.
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Key { name, .. } | Self::ApiKey { name, .. } => name,
+        }
+    }
+
+    /// Body of a `POST /exchange-service` request for a JWT issued to
+    /// `user_nkey_public` on `network`, at Unix time `now`, under `role`
+    /// (the auth service picks one of the account's roles when `None`).
+    pub fn exchange_request(
+        &self,
+        user_nkey_public: &str,
+        network: &NetworkId,
+        role: Option<UserRole>,
+        now: u64,
+    ) -> Result<serde_json::Value, SdkError> {
+        let mut body = serde_json::json!({
+            "account": self.name(),
+            "user_nkey_public": user_nkey_public,
+            "network": network.as_str(),
+            "role": role,
+        });
+        match self {
+            Self::Key { name, seed } => {
+                let kp = KeyPair::from_seed(seed).map_err(|e| {
+                    SdkError::Config(format!("invalid service account NKey seed: {e}"))
+                })?;
+                let assertion = service_account_assertion(name, network, user_nkey_public, now);
+                let signature = kp
+                    .sign(assertion.as_bytes())
+                    .map_err(|e| SdkError::Config(e.to_string()))?;
+                body["timestamp"] = now.into();
+                body["signature"] = URL_SAFE_NO_PAD.encode(signature).into();
+            }
+            Self::ApiKey { api_key, .. } => {
+                body["api_key"] = api_key.as_str().into();
+            }
+        }
+        Ok(body)
+    }
+}
+
+/// Message a [`ServiceAccountCredential::Key`] account signs to obtain a
+/// JWT for `user_nkey_public` on `network` at Unix time `timestamp`.
+///
+/// Distinct from [`server_assertion`], so a server's assertion cannot be
+/// presented as an account's of the same name, or the reverse.
+pub fn service_account_assertion(
+    account: &str,
+    network: &NetworkId,
+    user_nkey_public: &str,
+    timestamp: u64,
+) -> String {
+    format!("openlink-service-assertion:{account}:{network}:{user_nkey_public}:{timestamp}")
+}
+
+/// Message a [`ServerCredential::Key`] server signs to obtain a JWT for
+/// `user_nkey_public` on `network` at Unix time `timestamp`.
+///
+/// Binding the assertion to the requested key makes a captured one
+/// useless: it only yields JWTs nobody else can sign connections for.
+pub fn server_assertion(
+    server_name: &str,
+    network: &NetworkId,
+    user_nkey_public: &str,
+    timestamp: u64,
+) -> String {
+    format!("openlink-server-assertion:{server_name}:{network}:{user_nkey_public}:{timestamp}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_credentials_sign_an_assertion() {
+        let server_kp = KeyPair::new_user();
+        let credential = ServerCredential::Key {
+            name: "paris-1".into(),
+            seed: server_kp.seed().unwrap(),
+        };
+        let network = NetworkId::new("demonetwork");
+        let body = credential
+            .exchange_request("UCLIENT", &network, 1_700_000_000)
+            .unwrap();
+
+        assert_eq!(body["server_name"], "paris-1");
+        assert_eq!(body["timestamp"], 1_700_000_000);
+        assert!(body.get("server_secret").is_none());
+        let signature = URL_SAFE_NO_PAD
+            .decode(body["signature"].as_str().unwrap())
+            .unwrap();
+        let assertion = server_assertion("paris-1", &network, "UCLIENT", 1_700_000_000);
+        assert!(server_kp.verify(assertion.as_bytes(), &signature).is_ok());
+    }
+
+    #[test]
+    fn service_account_credentials_sign_or_carry_the_api_key() {
+        let account_kp = KeyPair::new_user();
+        let network = NetworkId::new("demonetwork");
+        let credential = ServiceAccountCredential::Key {
+            name: "atc-bot".into(),
+            seed: account_kp.seed().unwrap(),
+        };
+        let body = credential
+            .exchange_request("UCLIENT", &network, Some(UserRole::Bot), 1_700_000_000)
+            .unwrap();
+        assert_eq!(body["account"], "atc-bot");
+        assert_eq!(body["role"], "Bot");
+        assert!(body.get("api_key").is_none());
+        let signature = URL_SAFE_NO_PAD
+            .decode(body["signature"].as_str().unwrap())
+            .unwrap();
+        let assertion = service_account_assertion("atc-bot", &network, "UCLIENT", 1_700_000_000);
+        assert!(account_kp.verify(assertion.as_bytes(), &signature).is_ok());
+
+        let credential = ServiceAccountCredential::ApiKey {
+            name: "hoppie".into(),
+            api_key: "k3y".into(),
+        };
+        let body = credential
+            .exchange_request("UCLIENT", &network, None, 1_700_000_000)
+            .unwrap();
+        assert_eq!(body["api_key"], "k3y");
+        assert!(body["role"].is_null());
+        assert!(body.get("signature").is_none());
+    }
+
+    #[test]
+    fn expiry_is_read_from_the_jwt() {
+        let body = URL_SAFE_NO_PAD.encode(r#"{"exp":1700000000,"name":"100000"}"#);
+        let creds = OpenLinkCredentials {
+            seed: String::new(),
+            jwt: format!("e30.{body}.c2ln"),
+            cid: "100000".into(),
+            refresh_token: None,
+        };
+        assert_eq!(creds.expires_at(), Some(1_700_000_000));
+
+        let creds = OpenLinkCredentials {
+            jwt: "not-a-jwt".into(),
+            ..creds
+        };
+        assert_eq!(creds.expires_at(), None);
+    }
 }
@@ -0,0 +1,342 @@
+//! Device login for terminals and headless tools.
+//!
+//! [`device_login`] runs the OAuth device authorization grant through the
+//! auth service: it starts a login at `POST /device/authorize`, hands the
+//! verification URL and user code to a prompt (see [`print_device_prompt`]),
+//! then polls `POST /device/token` until the user approves it in a browser.
+//!
+//! [`CredentialCache`] keeps the resulting [`OpenLinkCredentials`] on disk
+//! so the next run reuses them until they expire, or renews them with
+//! their refresh token, instead of asking the user again. See
+//! [`OpenLinkClient::connect_with_device_login`](crate::OpenLinkClient::connect_with_device_login).
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use nkeys::KeyPair;
+use openlink_models::{NetworkId, UserRole};
+use serde::{Deserialize, Serialize};
+
+use crate::credentials::OpenLinkCredentials;
+use crate::error::SdkError;
+
+/// Seconds added to the polling interval when the auth service answers
+/// `slow_down`.
+const SLOW_DOWN_STEP: u64 = 5;
+
+/// A device login started at the auth service.
+///
+/// The user opens [`verification_uri`](Self::verification_uri) and enters
+/// [`user_code`](Self::user_code), or opens
+/// [`verification_uri_complete`](Self::verification_uri_complete) directly.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceAuthorization {
+    /// Code the client polls with.
+    pub device_code: String,
+    /// Code the user enters on the verification page.
+    pub user_code: String,
+    /// Verification page.
+    pub verification_uri: String,
+    /// Verification page with the user code filled in, when provided.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verification_uri_complete: Option<String>,
+    /// Lifetime of the codes in seconds.
+    pub expires_in: u64,
+    /// Minimum delay between two polls in seconds.
+    #[serde(default = "default_poll_interval")]
+    pub interval: u64,
+}
+
+fn default_poll_interval() -> u64 {
+    5
+}
+
+/// Print the verification URL and user code on standard error.
+pub fn print_device_prompt(authorization: &DeviceAuthorization) {
+    eprintln!(
+        "To log in, open {} and enter the code {}",
+        authorization.verification_uri, authorization.user_code
+    );
+    if let Some(ref complete) = authorization.verification_uri_complete {
+        eprintln!("or open {complete}");
+    }
+}
+
+/// Log in with the device authorization grant and return fresh
+/// credentials for a new NKey pair.
+///
+/// `prompt` is called once with the login to show to the user. Polling
+/// stops with [`SdkError::Auth`] when the user denies the login or the
+/// codes expire.
+pub async fn device_login(
+    auth_url: &str,
+    network: &NetworkId,
+    role: Option<UserRole>,
+    prompt: impl FnOnce(&DeviceAuthorization),
+) -> Result<OpenLinkCredentials, SdkError> {
+    // 1. Generate ephemeral user key-pair
+    let user_kp = KeyPair::new(nkeys::KeyPairType::User);
+    let seed = user_kp
+        .seed()
+        .map_err(|e| SdkError::Config(e.to_string()))?;
+    let public_key = user_kp.public_key();
+
+    // 2. Start the login and show it to the user
+    let http = reqwest::Client::new();
+    let res = http
+        .post(format!("{auth_url}/device/authorize"))
+        .json(&serde_json::json!({ "network": network.as_str() }))
+        .send()
+        .await?;
+    if !res.status().is_success() {
+        return Err(SdkError::Auth(res.text().await?));
+    }
+    let authorization: DeviceAuthorization = res.json().await?;
+    prompt(&authorization);
+
+    // 3. Poll until approved
+    let mut interval = authorization.interval.max(1);
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(authorization.expires_in);
+    loop {
+        tokio::time::sleep(Duration::from_secs(interval)).await;
+        if tokio::time::Instant::now() >= deadline {
+            return Err(SdkError::Auth("device login expired".into()));
+        }
+        let res = http
+            .post(format!("{auth_url}/device/token"))
+            .json(&serde_json::json!({
+                "device_code": authorization.device_code,
+                "user_nkey_public": public_key,
+                "network": network.as_str(),
+                "role": role,
+            }))
+            .send()
+            .await?;
+        if res.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            interval += SLOW_DOWN_STEP;
+            continue;
+        }
+        let success = res.status().is_success();
+        let body: serde_json::Value = res.json().await?;
+        if !success {
+            match body["error"].as_str() {
+                Some("authorization_pending") => continue,
+                Some("slow_down") => {
+                    interval += SLOW_DOWN_STEP;
+                    continue;
+                }
+                _ => return Err(SdkError::Auth(body.to_string())),
+            }
+        }
+
+        let jwt = body["jwt"]
+            .as_str()
+            .ok_or_else(|| SdkError::Auth("missing `jwt` in auth response".into()))?
+            .to_string();
+        let cid = body["cid"]
+            .as_str()
+            .ok_or_else(|| SdkError::Auth("missing `cid` in auth response".into()))?
+            .to_string();
+        return Ok(OpenLinkCredentials {
+            seed,
+            jwt,
+            cid,
+            refresh_token: body["refresh_token"].as_str().map(str::to_string),
+        });
+    }
+}
+
+/// On-disk cache of the credentials of one login.
+///
+/// The file records the auth service, network and requested role along
+/// with the credentials and their expiry; [`load`](Self::load) only returns
+/// credentials obtained for the same ones. On Unix the file is only
+/// readable by its owner.
+#[derive(Debug, Clone)]
+pub struct CredentialCache {
+    path: PathBuf,
+}
+
+/// Contents of a cache file.
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedCredentials {
+    auth_url: String,
+    network: String,
+    #[serde(default)]
+    role: Option<UserRole>,
+    /// Expiry of the JWT in Unix seconds.
+    #[serde(default)]
+    expires_at: Option<u64>,
+    credentials: OpenLinkCredentials,
+}
+
+/// Credentials read back from a [`CredentialCache`].
+#[derive(Debug, Clone)]
+pub struct CachedLogin {
+    /// The stored credentials.
+    pub credentials: OpenLinkCredentials,
+    /// Expiry of their JWT in Unix seconds, `None` when unknown.
+    pub expires_at: Option<u64>,
+}
+
+impl CachedLogin {
+    /// Whether the JWT is still valid for at least `margin` at `now`.
+    pub fn is_valid_at(&self, now: u64, margin: Duration) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at > now + margin.as_secs())
+    }
+}
+
+impl CredentialCache {
+    /// Cache stored in the file at `path`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Cache for `network` in the user's cache directory
+    /// (`~/.cache/openlink/credentials/{network}.json` on Linux). `None`
+    /// when the platform has no such directory.
+    pub fn for_network(network: &NetworkId) -> Option<Self> {
+        let dir = dirs::cache_dir()?.join("openlink").join("credentials");
+        Some(Self::new(dir.join(format!("{}.json", network.as_str()))))
+    }
+
+    /// Path of the cache file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Credentials stored for `auth_url`, `network` and `role`, if any.
+    ///
+    /// A missing, unreadable or mismatching file is reported as `None`:
+    /// the caller logs in again and overwrites it.
+    pub fn load(
+        &self,
+        auth_url: &str,
+        network: &NetworkId,
+        role: Option<UserRole>,
+    ) -> Option<CachedLogin> {
+        let contents = std::fs::read(&self.path).ok()?;
+        let cached: CachedCredentials = serde_json::from_slice(&contents).ok()?;
+        (cached.auth_url == auth_url && cached.network == network.as_str() && cached.role == role)
+            .then_some(CachedLogin {
+                credentials: cached.credentials,
+                expires_at: cached.expires_at,
+            })
+    }
+
+    /// Store `credentials` obtained for `auth_url`, `network` and `role`,
+    /// replacing the previous ones.
+    pub fn store(
+        &self,
+        auth_url: &str,
+        network: &NetworkId,
+        role: Option<UserRole>,
+        credentials: &OpenLinkCredentials,
+    ) -> Result<(), SdkError> {
+        let cached = CachedCredentials {
+            auth_url: auth_url.to_string(),
+            network: network.to_string(),
+            role,
+            expires_at: credentials.expires_at(),
+            credentials: credentials.clone(),
+        };
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let contents = serde_json::to_vec_pretty(&cached)?;
+
+        // Write next to the file then rename, so a crash never leaves a
+        // truncated cache behind.
+        let tmp = self.path.with_extension("json.tmp");
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        std::io::Write::write_all(&mut options.open(&tmp)?, &contents)?;
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+
+    /// Remove the cached credentials, e.g. on logout.
+    pub fn clear(&self) -> Result<(), SdkError> {
+        match std::fs::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+
+    const AUTH_URL: &str = "http://localhost:3001";
+
+    fn credentials(exp: u64) -> OpenLinkCredentials {
+        let claims = URL_SAFE_NO_PAD.encode(serde_json::json!({ "exp": exp }).to_string());
+        OpenLinkCredentials {
+            seed: "SUSEED".into(),
+            jwt: format!("e30.{claims}.sig"),
+            cid: "100000".into(),
+            refresh_token: Some("refresh".into()),
+        }
+    }
+
+    fn temp_cache(name: &str) -> CredentialCache {
+        let dir = std::env::temp_dir().join(format!("openlink-sdk-{name}-{}", std::process::id()));
+        CredentialCache::new(dir.join("demonetwork.json"))
+    }
+
+    #[test]
+    fn cached_credentials_round_trip_for_the_same_login() {
+        let cache = temp_cache("round-trip");
+        let network = NetworkId::new("demonetwork");
+        let role = Some(UserRole::Pilot);
+        assert!(cache.load(AUTH_URL, &network, role).is_none());
+
+        cache.store(AUTH_URL, &network, role, &credentials(2_000)).unwrap();
+        let login = cache.load(AUTH_URL, &network, role).unwrap();
+        assert_eq!(login.credentials.cid, "100000");
+        assert_eq!(login.credentials.refresh_token.as_deref(), Some("refresh"));
+        assert_eq!(login.expires_at, Some(2_000));
+
+        // Another service, network or role logs in again.
+        assert!(cache.load("http://auth.example.com", &network, role).is_none());
+        assert!(cache.load(AUTH_URL, &NetworkId::new("afrv"), role).is_none());
+        assert!(cache.load(AUTH_URL, &network, Some(UserRole::Controller)).is_none());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(cache.path()).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        cache.clear().unwrap();
+        assert!(cache.load(AUTH_URL, &network, role).is_none());
+        cache.clear().unwrap();
+    }
+
+    #[test]
+    fn cached_login_expires_with_a_margin() {
+        let login = CachedLogin {
+            credentials: credentials(1_000),
+            expires_at: Some(1_000),
+        };
+        let margin = Duration::from_secs(60);
+        assert!(login.is_valid_at(900, margin));
+        assert!(!login.is_valid_at(940, margin));
+        assert!(!login.is_valid_at(1_200, margin));
+
+        let unknown = CachedLogin {
+            expires_at: None,
+            ..login
+        };
+        assert!(!unknown.is_valid_at(0, margin));
+    }
+}
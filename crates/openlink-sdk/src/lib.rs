@@ -16,6 +16,8 @@
 //! * [`SdkError`] — unified error type for all SDK operations.
 //! * [`OpenLinkCredentials`] — portable credential struct (seed,
 //!   JWT, CID).
+//! * [`device`] — device login for terminals and headless tools, with an
+//!   on-disk [`CredentialCache`].
 //!
 //! Builders from [`openlink_models`] are re-exported for convenience.
 //!
@@ -43,18 +45,26 @@
 pub mod client;
 pub mod cpdlc_runtime;
 pub mod credentials;
+pub mod device;
 pub mod error;
+pub mod revocation;
 pub mod subjects;
 
 pub use client::OpenLinkClient;
-pub use credentials::OpenLinkCredentials;
+pub use credentials::{
+    server_assertion, service_account_assertion, OpenLinkCredentials, ServerCredential,
+    ServiceAccountCredential,
+};
+pub use device::{device_login, print_device_prompt, CredentialCache, DeviceAuthorization};
 pub use error::SdkError;
+pub use revocation::Revocation;
 pub use subjects::NatsSubjects;
 
 pub use cpdlc_runtime::{
     choose_short_response_intents, closes_dialogue_response_elements,
     is_logical_ack_element_id, logical_ack_matches_outgoing, message_contains_logical_ack,
-    response_attr_to_intents, should_auto_send_logical_ack, LOGICAL_ACK_DOWNLINK_ID,
+    response_attr_to_intents, should_auto_send_logical_ack, should_report_discarded,
+    uplink_exceeds_max_delay, ERROR_DOWNLINK_ID, ERROR_UPLINK_ID, LOGICAL_ACK_DOWNLINK_ID,
     LOGICAL_ACK_UPLINK_ID,
 };
 
@@ -0,0 +1,102 @@
+//! Credential revocations.
+//!
+//! The auth service records revocations in the network's
+//! [`kv_revocations`](crate::NatsSubjects::kv_revocations) bucket and
+//! OpenLink servers watch it. A revocation invalidates every JWT issued up
+//! to `revoked_at` to a user NKey, or to a CID when no key is named; JWTs
+//! issued afterwards (e.g. a new login) are not affected, as with the
+//! revocations of a NATS account.
+//!
+//! Entries are kept for [`REVOCATION_RETENTION`], the longest lifetime of
+//! a JWT the auth service issues: by then every revoked JWT has expired.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// How long a revocation is kept.
+pub const REVOCATION_RETENTION: Duration = Duration::from_secs(86_400);
+
+/// One revoked user key or CID.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Revocation {
+    /// CID whose JWTs are revoked; with `user_nkey_public`, the CID the key
+    /// was issued to, when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cid: Option<String>,
+    /// User NKey whose JWTs are revoked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_nkey_public: Option<String>,
+    /// JWTs issued at or before this time (Unix seconds) are revoked.
+    pub revoked_at: u64,
+    /// Why, for the audit trail.
+    #[serde(default)]
+    pub reason: String,
+}
+
+impl Revocation {
+    /// Key of the revocation in the bucket: `nkey.{key}` or `cid.{cid}`.
+    ///
+    /// `None` when neither is set, or the CID contains characters KV keys
+    /// do not allow.
+    pub fn kv_key(&self) -> Option<String> {
+        match (&self.user_nkey_public, &self.cid) {
+            (Some(key), _) => Some(format!("nkey.{key}")),
+            (None, Some(cid)) if is_kv_token(cid) => Some(format!("cid.{cid}")),
+            _ => None,
+        }
+    }
+
+    /// Whether a JWT issued at `issued_at` to `user_nkey_public` for `cid`
+    /// is revoked.
+    pub fn revokes(&self, cid: &str, user_nkey_public: &str, issued_at: u64) -> bool {
+        let subject = match (&self.user_nkey_public, &self.cid) {
+            (Some(key), _) => key == user_nkey_public,
+            (None, Some(revoked)) => revoked == cid,
+            (None, None) => false,
+        };
+        subject && issued_at <= self.revoked_at
+    }
+}
+
+fn is_kv_token(token: &str) -> bool {
+    !token.is_empty()
+        && token
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '=')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn revokes_earlier_jwts_of_the_key_or_cid() {
+        let by_cid = Revocation {
+            cid: Some("100000".into()),
+            user_nkey_public: None,
+            revoked_at: 1_700_000_000,
+            reason: "abuse".into(),
+        };
+        assert_eq!(by_cid.kv_key().as_deref(), Some("cid.100000"));
+        assert!(by_cid.revokes("100000", "UANY", 1_700_000_000));
+        assert!(!by_cid.revokes("100000", "UANY", 1_700_000_001));
+        assert!(!by_cid.revokes("100001", "UANY", 1_699_000_000));
+
+        let by_key = Revocation {
+            user_nkey_public: Some("UKEY".into()),
+            ..by_cid
+        };
+        assert_eq!(by_key.kv_key().as_deref(), Some("nkey.UKEY"));
+        assert!(by_key.revokes("100000", "UKEY", 1_699_000_000));
+        assert!(!by_key.revokes("100000", "UOTHER", 1_699_000_000));
+
+        let bad_cid = Revocation {
+            cid: Some("a.b".into()),
+            user_nkey_public: None,
+            revoked_at: 0,
+            reason: String::new(),
+        };
+        assert_eq!(bad_cid.kv_key(), None);
+    }
+}
@@ -11,6 +11,11 @@
 //! openlink.v1.{network}.inbox.{address}    ← clients SUBSCRIBE here
 //! openlink.v1.{network}.outbox.>           ← server wildcard (receives all client messages)
 //! openlink.v1.{network}.inbox.>            ← server wildcard (all inboxes)
+//! openlink.v1.{network}.directory.query    ← clients REQUEST the station directory
+//! openlink.v1.{network}.directory.events   ← server PUBLISHES directory changes
+//! openlink.v1.{network}.replies.{address}  ← per-client request/reply inbox prefix
+//! openlink.v1.{network}.pending.{callsign} ← server store-and-forward queue
+//! openlink.v1.{network}.federation.{origin} ← envelopes relayed from a peer network's server
 //! ```
 //!
 //! # KV bucket names
@@ -19,9 +24,18 @@
 //! openlink-v1-{network}-cpdlc-sessions     ← CPDLC session store
 //! openlink-v1-{network}-station-registry    ← station registry store
 //! openlink-v1-{network}-station-callsign-index ← station callsign reverse index
+//! openlink-v1-{network}-seen-envelopes      ← recently processed envelope ids
+//! openlink-v1-{network}-banned-addresses    ← temporary ban list
+//! ```
+//!
+//! # Stream names
+//!
+//! ```text
+//! openlink-v1-{network}-pending-deliveries ← messages for offline recipients
+//! openlink-v1-{network}-inboxes            ← inbox copies for durable consumers
 //! ```
 
-use openlink_models::{NetworkAddress, NetworkId};
+use openlink_models::{NetworkAddress, NetworkId, UserRole};
 
 /// Current subject version prefix.
 const VERSION: &str = "v1";
@@ -90,6 +104,108 @@ impl NatsSubjects {
         format!("openlink.{VERSION}.{network}.inbox.>")
     }
 
+    /// Request/reply subject answered by the server with the station
+    /// directory.
+    pub fn directory_query(network: &NetworkId) -> String {
+        format!("openlink.{VERSION}.{network}.directory.query")
+    }
+
+    /// Subject on which the server publishes live directory changes.
+    pub fn directory_events(network: &NetworkId) -> String {
+        format!("openlink.{VERSION}.{network}.directory.events")
+    }
+
+    /// Inbox prefix a client uses for request/reply.
+    ///
+    /// Replies are scoped per address so that a client can only subscribe
+    /// to answers to its own requests.
+    pub fn replies(network: &NetworkId, address: &NetworkAddress) -> String {
+        format!("openlink.{VERSION}.{network}.replies.{address}")
+    }
+
+    /// Wildcard subject that matches every client reply subject.
+    ///
+    /// Intended for the OpenLink server to answer requests.
+    pub fn replies_wildcard(network: &NetworkId) -> String {
+        format!("openlink.{VERSION}.{network}.replies.>")
+    }
+
+    /// Subject on which the server queues messages for an offline recipient.
+    ///
+    /// The callsign is upper-cased, then every byte other than an ASCII
+    /// letter, digit or `-` is escaped as `_XX` (hex), so that any callsign
+    /// maps to a single subject token and distinct callsigns never share
+    /// a queue.
+    pub fn pending(network: &NetworkId, callsign: &str) -> String {
+        let mut token = String::with_capacity(callsign.len());
+        for byte in callsign.to_uppercase().bytes() {
+            if byte.is_ascii_alphanumeric() || byte == b'-' {
+                token.push(char::from(byte));
+            } else {
+                token.push_str(&format!("_{byte:02X}"));
+            }
+        }
+        format!("openlink.{VERSION}.{network}.pending.{token}")
+    }
+
+    /// Wildcard subject that matches every store-and-forward queue.
+    pub fn pending_wildcard(network: &NetworkId) -> String {
+        format!("openlink.{VERSION}.{network}.pending.>")
+    }
+
+    /// Subject on which the server of `origin` relays envelopes to the
+    /// server of `network` (federation).
+    pub fn federation(network: &NetworkId, origin: &NetworkId) -> String {
+        format!("openlink.{VERSION}.{network}.federation.{origin}")
+    }
+
+    /// Wildcard subject on which a server receives relays from every peer.
+    pub fn federation_inbound_wildcard(network: &NetworkId) -> String {
+        format!("openlink.{VERSION}.{network}.federation.*")
+    }
+
+    /// Wildcard subject on which the server of `origin` may relay to any
+    /// peer. The origin token is fixed so a server cannot impersonate
+    /// another network.
+    pub fn federation_outbound_wildcard(origin: &NetworkId) -> String {
+        format!("openlink.{VERSION}.*.federation.{origin}")
+    }
+
+    // ------------------------------------------------------------------
+    // JWT tags
+    // ------------------------------------------------------------------
+
+    /// Tag carried by NATS JWTs issued for `network`.
+    ///
+    /// NATS reports it in connect/disconnect advisories, which lets a server
+    /// ignore connections belonging to other networks.
+    pub fn network_tag(network: &NetworkId) -> String {
+        format!("openlink-network:{network}")
+    }
+
+    /// Tag carrying the user's [`UserRole`] in NATS JWTs.
+    pub fn role_tag(role: UserRole) -> String {
+        format!("openlink-role:{role}")
+    }
+
+    /// Parse a role tag produced by [`role_tag`](Self::role_tag).
+    pub fn parse_role_tag(tag: &str) -> Option<UserRole> {
+        tag.strip_prefix("openlink-role:")?.parse().ok()
+    }
+
+    /// Tag restricting a service account's JWT to `callsign`.
+    ///
+    /// A JWT carrying callsign tags may only register and send as those
+    /// callsigns; one without any is not restricted.
+    pub fn callsign_tag(callsign: &str) -> String {
+        format!("openlink-callsign:{}", callsign.to_uppercase())
+    }
+
+    /// Parse a callsign tag produced by [`callsign_tag`](Self::callsign_tag).
+    pub fn parse_callsign_tag(tag: &str) -> Option<&str> {
+        tag.strip_prefix("openlink-callsign:")
+    }
+
     // ------------------------------------------------------------------
     // JetStream KV bucket names
     // ------------------------------------------------------------------
@@ -111,6 +227,64 @@ impl NatsSubjects {
         format!("openlink-{VERSION}-{network}-station-callsign-index")
     }
 
+    /// KV bucket name for the envelope ids processed within the dedup window.
+    ///
+    /// Key: `{sender address}.{envelope id}`, Value: receipt timestamp.
+    pub fn kv_seen_envelopes(network: &NetworkId) -> String {
+        format!("openlink-{VERSION}-{network}-seen-envelopes")
+    }
+
+    /// KV bucket name for the ban list.
+    ///
+    /// Key: network address, Value: ban reason and optional end time.
+    pub fn kv_banned_addresses(network: &NetworkId) -> String {
+        format!("openlink-{VERSION}-{network}-banned-addresses")
+    }
+
+    /// KV bucket name for credential revocations.
+    ///
+    /// Key: `nkey.{user key}` or `cid.{cid}`, Value: [`Revocation`](crate::Revocation).
+    pub fn kv_revocations(network: &NetworkId) -> String {
+        format!("openlink-{VERSION}-{network}-revocations")
+    }
+
+    // ------------------------------------------------------------------
+    // JetStream stream names
+    // ------------------------------------------------------------------
+
+    /// Stream holding messages queued for temporarily offline recipients.
+    pub fn stream_pending_deliveries(network: &NetworkId) -> String {
+        format!("openlink-{VERSION}-{network}-pending-deliveries")
+    }
+
+    /// Stream capturing every inbox subject, read by durable inbox consumers.
+    pub fn stream_inboxes(network: &NetworkId) -> String {
+        format!("openlink-{VERSION}-{network}-inboxes")
+    }
+
+    /// Durable consumer name for `address` on [`Self::stream_inboxes`].
+    pub fn inbox_consumer(address: &NetworkAddress) -> String {
+        format!("inbox-{address}")
+    }
+
+    /// JetStream API subjects a client needs to read its own inbox through
+    /// a durable consumer.
+    ///
+    /// Consumer creation is only granted with the client's own inbox as
+    /// filter subject, so it cannot read another inbox.
+    pub fn durable_inbox_api(network: &NetworkId, address: &NetworkAddress) -> Vec<String> {
+        let stream = Self::stream_inboxes(network);
+        let consumer = Self::inbox_consumer(address);
+        let inbox = Self::inbox(network, address);
+        vec![
+            format!("$JS.API.STREAM.INFO.{stream}"),
+            format!("$JS.API.CONSUMER.CREATE.{stream}.{consumer}.{inbox}"),
+            format!("$JS.API.CONSUMER.INFO.{stream}.{consumer}"),
+            format!("$JS.API.CONSUMER.MSG.NEXT.{stream}.{consumer}"),
+            format!("$JS.ACK.{stream}.{consumer}.>"),
+        ]
+    }
+
     // ------------------------------------------------------------------
     // Parsing helpers
     // ------------------------------------------------------------------
@@ -128,6 +302,18 @@ impl NatsSubjects {
         }
     }
 
+    /// Extract the origin network from a federation subject.
+    ///
+    /// Given `"openlink.v1.demonetwork.federation.afrv"` returns `Some("afrv")`.
+    pub fn parse_federation_origin(subject: &str) -> Option<&str> {
+        let parts: Vec<&str> = subject.splitn(5, '.').collect();
+        if parts.len() == 5 && parts[0] == "openlink" && parts[3] == "federation" {
+            Some(parts[4])
+        } else {
+            None
+        }
+    }
+
     /// Extract the recipient address from an inbox subject.
     ///
     /// Given `"openlink.v1.demonetwork.inbox.AFR123"` returns `Some("AFR123")`.
@@ -167,6 +353,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn federation_subjects() {
+        let afrv = NetworkId::new("afrv");
+        let subject = NatsSubjects::federation(&net(), &afrv);
+        assert_eq!(subject, "openlink.v1.demonetwork.federation.afrv");
+        assert_eq!(NatsSubjects::parse_federation_origin(&subject), Some("afrv"));
+        assert_eq!(
+            NatsSubjects::federation_inbound_wildcard(&net()),
+            "openlink.v1.demonetwork.federation.*",
+        );
+        assert_eq!(
+            NatsSubjects::federation_outbound_wildcard(&afrv),
+            "openlink.v1.*.federation.afrv",
+        );
+        assert_eq!(
+            NatsSubjects::parse_federation_origin("openlink.v1.demonetwork.outbox.LFPG"),
+            None,
+        );
+    }
+
     #[test]
     fn inbox_subject() {
         assert_eq!(
@@ -191,6 +397,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn directory_subjects() {
+        assert_eq!(
+            NatsSubjects::directory_query(&net()),
+            "openlink.v1.demonetwork.directory.query",
+        );
+        assert_eq!(
+            NatsSubjects::directory_events(&net()),
+            "openlink.v1.demonetwork.directory.events",
+        );
+        assert_eq!(
+            NatsSubjects::replies(&net(), &addr("AFR123")),
+            "openlink.v1.demonetwork.replies.AFR123",
+        );
+        assert_eq!(
+            NatsSubjects::replies_wildcard(&net()),
+            "openlink.v1.demonetwork.replies.>",
+        );
+    }
+
+    #[test]
+    fn network_tag_value() {
+        assert_eq!(NatsSubjects::network_tag(&net()), "openlink-network:demonetwork");
+    }
+
+    #[test]
+    fn role_tag_roundtrip() {
+        let tag = NatsSubjects::role_tag(UserRole::Controller);
+        assert_eq!(tag, "openlink-role:controller");
+        assert_eq!(NatsSubjects::parse_role_tag(&tag), Some(UserRole::Controller));
+        assert_eq!(NatsSubjects::parse_role_tag("openlink-network:controller"), None);
+    }
+
+    #[test]
+    fn callsign_tag_roundtrip() {
+        let tag = NatsSubjects::callsign_tag("lfpg_twr");
+        assert_eq!(tag, "openlink-callsign:LFPG_TWR");
+        assert_eq!(NatsSubjects::parse_callsign_tag(&tag), Some("LFPG_TWR"));
+        assert_eq!(NatsSubjects::parse_callsign_tag("openlink-role:bot"), None);
+    }
+
     // -- KV bucket names ----------------------------------------------------
 
     #[test]
@@ -201,6 +448,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn kv_seen_envelopes_bucket() {
+        assert_eq!(
+            NatsSubjects::kv_seen_envelopes(&net()),
+            "openlink-v1-demonetwork-seen-envelopes",
+        );
+    }
+
+    #[test]
+    fn kv_banned_addresses_bucket() {
+        assert_eq!(
+            NatsSubjects::kv_banned_addresses(&net()),
+            "openlink-v1-demonetwork-banned-addresses",
+        );
+    }
+
+    #[test]
+    fn kv_revocations_bucket() {
+        assert_eq!(
+            NatsSubjects::kv_revocations(&net()),
+            "openlink-v1-demonetwork-revocations",
+        );
+    }
+
     #[test]
     fn kv_station_registry_bucket() {
         assert_eq!(
@@ -217,6 +488,53 @@ mod tests {
         );
     }
 
+    #[test]
+    fn pending_subjects_and_stream() {
+        assert_eq!(
+            NatsSubjects::pending(&net(), "afr123"),
+            "openlink.v1.demonetwork.pending.AFR123",
+        );
+        assert_eq!(
+            NatsSubjects::pending(&net(), "LF.P*G"),
+            "openlink.v1.demonetwork.pending.LF_2EP_2AG",
+        );
+        // Escaping is injective: callsigns differing only in separators
+        // or wildcards keep their own queues.
+        let subjects: std::collections::HashSet<_> =
+            ["LF.P*G", "LF_P_G", "LF P>G", "LF_2EP_2AG", "LFFF_N", "LFFF-N"]
+                .iter()
+                .map(|callsign| NatsSubjects::pending(&net(), callsign))
+                .collect();
+        assert_eq!(subjects.len(), 6);
+        assert_eq!(
+            NatsSubjects::pending(&net(), "lfff_n"),
+            "openlink.v1.demonetwork.pending.LFFF_5FN",
+        );
+        assert_eq!(
+            NatsSubjects::pending_wildcard(&net()),
+            "openlink.v1.demonetwork.pending.>",
+        );
+        assert_eq!(
+            NatsSubjects::stream_pending_deliveries(&net()),
+            "openlink-v1-demonetwork-pending-deliveries",
+        );
+    }
+
+    #[test]
+    fn durable_inbox_names_and_api() {
+        assert_eq!(
+            NatsSubjects::stream_inboxes(&net()),
+            "openlink-v1-demonetwork-inboxes",
+        );
+        assert_eq!(NatsSubjects::inbox_consumer(&addr("42")), "inbox-42");
+        let api = NatsSubjects::durable_inbox_api(&net(), &addr("42"));
+        assert!(api.contains(
+            &"$JS.API.CONSUMER.CREATE.openlink-v1-demonetwork-inboxes.inbox-42.openlink.v1.demonetwork.inbox.42"
+                .to_string()
+        ));
+        assert!(api.contains(&"$JS.ACK.openlink-v1-demonetwork-inboxes.inbox-42.>".to_string()));
+    }
+
     // -- parsing helpers ----------------------------------------------------
 
     #[test]
@@ -12,8 +12,8 @@ use futures::TryStreamExt;
 use openlink_models::{
     AcarsEndpointCallsign, AcarsEnvelope, AcarsMessage, AcarsRoutingEndpoint,
     CpdlcApplicationMessage, CpdlcArgument, CpdlcConnectionPhase, CpdlcConnectionView,
-    CpdlcEnvelope, CpdlcMessageType, CpdlcMetaMessage, CpdlcSessionView,
-    NetworkId, OpenLinkEnvelope, OpenLinkMessage, find_definition,
+    CpdlcEnvelope, CpdlcMessageType, CpdlcMetaMessage, CpdlcSessionView, CpdlcTransferKind,
+    CpdlcTransferView, MessageElement, NetworkId, OpenLinkEnvelope, OpenLinkMessage, find_definition,
 };
 use tracing::{debug, info, warn};
 
@@ -27,6 +27,23 @@ pub struct CPDLCSession {
     pub active_connection: Option<CPDLCConnection>,
     pub inactive_connection: Option<CPDLCConnection>,
     pub next_data_authority: Option<AcarsRoutingEndpoint>,
+    #[serde(default)]
+    pub pending_transfer: Option<CPDLCTransfer>,
+}
+
+/// A CONTACT / MONITOR instruction issued by the current data authority.
+///
+/// Kept on the session until the pilot refuses it, or until the handoff to
+/// the next data authority has completed.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct CPDLCTransfer {
+    pub kind: CpdlcTransferKind,
+    pub from: AcarsEndpointCallsign,
+    pub unit: String,
+    pub frequency: String,
+    /// MIN of the uplink, used to match the pilot's WILCO / UNABLE.
+    pub min: u8,
+    pub acknowledged: bool,
 }
 
 /// A single CPDLC connection to a ground station within a session.
@@ -110,6 +127,7 @@ impl CPDLCSession {
             active_connection: None,
             inactive_connection: None,
             next_data_authority: None,
+            pending_transfer: None,
         }
     }
 
@@ -189,7 +207,7 @@ impl CPDLCSession {
             && conn.station.callsign == *station
         {
             conn.connect()?;
-            return Ok(());
+            return self.try_complete_transfer();
         }
         warn!(station = ?station, aircraft = ?self.aircraft, "no matching connection for connection acceptance");
         Ok(())
@@ -206,6 +224,13 @@ impl CPDLCSession {
     /// the inactive one (if any) gets promoted.
     pub fn termination_request(&mut self, station: &AcarsEndpointCallsign) -> Result<()> {
         debug!(station = ?station, aircraft = ?self.aircraft, "termination requested");
+        if self
+            .pending_transfer
+            .as_ref()
+            .is_some_and(|t| t.from == *station)
+        {
+            self.pending_transfer = None;
+        }
         if self.active_connection.as_ref().is_some_and(|c| c.station.callsign == *station) {
             self.active_connection = self.inactive_connection.take();
             return Ok(());
@@ -217,6 +242,96 @@ impl CPDLCSession {
         warn!(station = ?station, aircraft = ?self.aircraft, "no matching connection for termination");
         Ok(())
     }
+
+    /// Record a CONTACT / MONITOR instruction uplinked by `station`.
+    ///
+    /// Only the current data authority can hand the aircraft over.
+    pub fn transfer_instruction(
+        &mut self,
+        station: &AcarsEndpointCallsign,
+        kind: CpdlcTransferKind,
+        unit: String,
+        frequency: String,
+        min: u8,
+    ) -> Result<()> {
+        if !self
+            .active_connection
+            .as_ref()
+            .is_some_and(|c| c.station.callsign == *station)
+        {
+            return Err(anyhow::anyhow!(
+                "{kind} instruction from {station} which is not the current data authority"
+            ));
+        }
+        debug!(station = ?station, aircraft = ?self.aircraft, %kind, %unit, "transfer instructed");
+        self.pending_transfer = Some(CPDLCTransfer {
+            kind,
+            from: station.clone(),
+            unit,
+            frequency,
+            min,
+            acknowledged: false,
+        });
+        Ok(())
+    }
+
+    /// Pilot accepted the pending transfer (WILCO or MONITORING report).
+    ///
+    /// `mrn` must reference the instruction when present. The handoff is
+    /// completed immediately if the NDA connection is already established.
+    pub fn transfer_acknowledged(&mut self, mrn: Option<u8>) -> Result<()> {
+        let Some(ref mut transfer) = self.pending_transfer else {
+            return Ok(());
+        };
+        if mrn.is_some_and(|mrn| mrn != transfer.min) {
+            return Ok(());
+        }
+        debug!(aircraft = ?self.aircraft, from = ?transfer.from, "transfer acknowledged");
+        transfer.acknowledged = true;
+        self.try_complete_transfer()
+    }
+
+    /// Pilot refused the pending transfer (UNABLE referencing the instruction).
+    pub fn transfer_refused(&mut self, mrn: Option<u8>) -> Result<()> {
+        if self
+            .pending_transfer
+            .as_ref()
+            .is_some_and(|t| mrn == Some(t.min))
+        {
+            debug!(aircraft = ?self.aircraft, "transfer refused");
+            self.pending_transfer = None;
+        }
+        Ok(())
+    }
+
+    /// Move the aircraft to the next data authority once the transfer is
+    /// acknowledged and the NDA connection is up.
+    ///
+    /// The transferring station is terminated (which promotes the NDA
+    /// connection) and the NDA designation is consumed.
+    pub fn try_complete_transfer(&mut self) -> Result<()> {
+        let Some(ref transfer) = self.pending_transfer else {
+            return Ok(());
+        };
+        if !transfer.acknowledged {
+            return Ok(());
+        }
+        let Some(ref nda) = self.next_data_authority else {
+            return Ok(());
+        };
+        let nda_ready = self
+            .inactive_connection
+            .as_ref()
+            .is_some_and(|c| c.station.callsign == nda.callsign && c.ready_exchange());
+        if !nda_ready {
+            return Ok(());
+        }
+        let from = transfer.from.clone();
+        info!(aircraft = ?self.aircraft, from = %from, to = %nda.callsign, "completing automatic transfer");
+        self.termination_request(&from)?;
+        self.next_data_authority = None;
+        Ok(())
+    }
 }
 
 impl CPDLCConnection {
@@ -240,6 +355,20 @@ impl CPDLCConnection {
     }
 }
 
+impl CPDLCTransfer {
+    /// Convert to a client-visible `CpdlcTransferView`.
+    pub fn to_view(&self, next_data_authority: Option<&AcarsRoutingEndpoint>) -> CpdlcTransferView {
+        CpdlcTransferView {
+            kind: self.kind,
+            from: self.from.clone(),
+            unit: self.unit.clone(),
+            frequency: self.frequency.clone(),
+            next_data_authority: next_data_authority.map(|nda| nda.callsign.clone()),
+            acknowledged: self.acknowledged,
+        }
+    }
+}
+
 impl CPDLCSession {
     /// Build the session view from the aircraft's perspective.
     ///
@@ -255,9 +384,16 @@ impl CPDLCSession {
                 c.to_view(&c.station.callsign)
             }),
             next_data_authority: self.next_data_authority.as_ref().map(|nda| nda.callsign.clone()),
+            transfer: self.transfer_view(),
         }
     }
 
+    fn transfer_view(&self) -> Option<CpdlcTransferView> {
+        self.pending_transfer
+            .as_ref()
+            .map(|t| t.to_view(self.next_data_authority.as_ref()))
+    }
+
     /// Build the session view from a specific ground station's perspective.
     ///
     /// In the station view, `peer` is the aircraft callsign.
@@ -276,6 +412,7 @@ impl CPDLCSession {
             active_connection: self.active_connection.as_ref().and_then(conn_to_view),
             inactive_connection: self.inactive_connection.as_ref().and_then(conn_to_view),
             next_data_authority: self.next_data_authority.as_ref().map(|nda| nda.callsign.clone()),
+            transfer: self.transfer_view(),
         }
     }
 }
@@ -369,7 +506,9 @@ impl CPDLCServer {
     ///
     /// 1. Validates that the active connection is in `Connected` state.
     /// 2. Validates that client-provided MIN is in the protocol range (1..=63).
-    /// 3. Applies server-side session mutations for selected UM session-management elements.
+    /// 3. Applies server-side session mutations for selected UM session-management elements
+    ///    (NDA, END SERVICE, CONTACT / MONITOR) and the pilot responses that
+    ///    close a pending transfer.
     /// 4. Returns the destination callsign for forwarding.
     pub async fn handle_cpdlc_application_message(
         &self,
@@ -456,6 +595,27 @@ impl CPDLCServer {
                             "UM161" => {
                                 session.termination_request(&source)?;
                             }
+                            // UM117 CONTACT / UM120 MONITOR [unit name] [frequency]
+                            "UM117" | "UM120" => {
+                                let kind = if element.id == "UM117" {
+                                    CpdlcTransferKind::Contact
+                                } else {
+                                    CpdlcTransferKind::Monitor
+                                };
+                                let (unit, frequency) = transfer_arguments(element)?;
+                                session.transfer_instruction(&source, kind, unit, frequency, msg.min)?;
+                            }
+                            // DM0 WILCO / DM89 MONITORING [unit name] [frequency]
+                            "DM0" | "DM89"
+                                if source == session.aircraft.callsign
+                                    && (element.id == "DM89" || msg.mrn.is_some()) =>
+                            {
+                                session.transfer_acknowledged(msg.mrn)?;
+                            }
+                            // DM1 UNABLE
+                            "DM1" if source == session.aircraft.callsign => {
+                                session.transfer_refused(msg.mrn)?;
+                            }
                             _ => {}
                         }
                     }
@@ -634,6 +794,20 @@ impl CPDLCServer {
         Ok(updated)
     }
 
+    /// Return every stored session.
+    pub async fn list_sessions(&self) -> Result<Vec<CPDLCSession>> {
+        let mut keys = self.kv_sessions_store.keys().await?;
+        let mut sessions = Vec::new();
+
+        while let Some(key) = keys.try_next().await? {
+            if let Some(content) = self.kv_sessions_store.get(&key).await? {
+                sessions.push(serde_json::from_slice(content.as_ref())?);
+            }
+        }
+
+        Ok(sessions)
+    }
+
     /// Return all sessions relevant to a participant callsign.
     ///
     /// A session is considered relevant if the callsign is either:
@@ -644,13 +818,11 @@ impl CPDLCServer {
         &self,
         callsign: &AcarsEndpointCallsign,
     ) -> Result<Vec<CPDLCSession>> {
-        let mut keys = self.kv_sessions_store.keys().await?;
-        let mut sessions = Vec::new();
-
-        while let Some(key) = keys.try_next().await? {
-            if let Some(content) = self.kv_sessions_store.get(&key).await? {
-                let session: CPDLCSession = serde_json::from_slice(content.as_ref())?;
-                let relevant = session.aircraft.callsign == *callsign
+        let sessions = self.list_sessions().await?;
+        Ok(sessions
+            .into_iter()
+            .filter(|session| {
+                session.aircraft.callsign == *callsign
                     || session
                         .active_connection
                         .as_ref()
@@ -658,14 +830,63 @@ impl CPDLCServer {
                     || session
                         .inactive_connection
                         .as_ref()
-                        .is_some_and(|c| c.station.callsign == *callsign);
-                if relevant {
-                    sessions.push(session);
-                }
-            }
-        }
+                        .is_some_and(|c| c.station.callsign == *callsign)
+            })
+            .collect())
+    }
 
-        Ok(sessions)
+    /// Return the session owned by an aircraft callsign, if any.
+    pub async fn session_for_aircraft(
+        &self,
+        aircraft_callsign: &AcarsEndpointCallsign,
+    ) -> Result<Option<CPDLCSession>> {
+        Ok(self
+            .list_sessions()
+            .await?
+            .into_iter()
+            .find(|session| session.aircraft.callsign == *aircraft_callsign))
+    }
+
+    /// Terminate the connection between one aircraft and one station.
+    ///
+    /// Returns the updated session, or `None` if the aircraft has no session.
+    pub async fn terminate_connection(
+        &self,
+        aircraft_callsign: &AcarsEndpointCallsign,
+        station_callsign: &AcarsEndpointCallsign,
+    ) -> Result<Option<CPDLCSession>> {
+        let Some(session) = self.session_for_aircraft(aircraft_callsign).await? else {
+            return Ok(None);
+        };
+        self.get_and_update_session_for_aircraft(
+            &session.aircraft,
+            |maybe_session: Option<CPDLCSession>| {
+                let station_callsign = station_callsign.clone();
+                Box::pin(async move {
+                    let Some(mut existing) = maybe_session else {
+                        return Ok(None);
+                    };
+                    existing.termination_request(&station_callsign)?;
+                    Ok(Some(existing))
+                })
+            },
+        )
+        .await
+    }
+
+    /// Delete an aircraft's session outright.
+    ///
+    /// Returns the removed session, or `None` if the aircraft had none.
+    pub async fn purge_session(
+        &self,
+        aircraft_callsign: &AcarsEndpointCallsign,
+    ) -> Result<Option<CPDLCSession>> {
+        let Some(session) = self.session_for_aircraft(aircraft_callsign).await? else {
+            return Ok(None);
+        };
+        let session_id: String = CPDLCSessionId::from(&session.aircraft).into();
+        self.kv_sessions_store.delete(&session_id).await?;
+        Ok(Some(session))
     }
 
     /// Terminate this station from every relevant aircraft session.
@@ -731,10 +952,26 @@ impl CPDLCServer {
 
 
 
+/// Unit name and frequency of a UM117 CONTACT / UM120 MONITOR element.
+///
+/// Both are required: a transfer without them would leave the aircraft
+/// with nowhere to go once it acknowledges.
+fn transfer_arguments(element: &MessageElement) -> Result<(String, String)> {
+    let unit = match element.args.first() {
+        Some(CpdlcArgument::UnitName(unit)) if !unit.is_empty() => unit.clone(),
+        _ => anyhow::bail!("{} is missing its unit name argument", element.id),
+    };
+    let frequency = match element.args.get(1) {
+        Some(CpdlcArgument::Frequency(freq)) if !freq.is_empty() => freq.clone(),
+        _ => anyhow::bail!("{} is missing its frequency argument", element.id),
+    };
+    Ok((unit, frequency))
+}
+
 #[cfg(test)]
 mod tests {
     use anyhow::Result;
-    use openlink_models::{AcarsRoutingEndpoint, NetworkId};
+    use openlink_models::{AcarsRoutingEndpoint, CpdlcTransferKind, NetworkId};
 
     use crate::acars::{CPDLCServer, CPDLCSession};
 
@@ -833,6 +1070,140 @@ mod tests {
         assert!(session.inactive_connection.is_none());
     }
 
+    fn connected_session_with_nda() -> (CPDLCSession, AcarsRoutingEndpoint, AcarsRoutingEndpoint) {
+        let mut session = CPDLCSession::new(AcarsRoutingEndpoint::new("TEST123", "abc"));
+        let station1 = AcarsRoutingEndpoint::new("STATION1", "def");
+        let station2 = AcarsRoutingEndpoint::new("STATION2", "ghi");
+        let _ = session.logon_request(station1.clone());
+        let _ = session.logon_accepted(&station1.callsign);
+        let _ = session.connection_request(&station1.callsign);
+        let _ = session.connection_accepted(&station1.callsign);
+        let _ = session.next_data_authority(station2.clone());
+        (session, station1, station2)
+    }
+
+    #[test]
+    fn test_transfer_completes_on_wilco_when_nda_connected() {
+        let (mut session, station1, station2) = connected_session_with_nda();
+        let _ = session.connection_request(&station2.callsign);
+        let _ = session.connection_accepted(&station2.callsign);
+
+        session
+            .transfer_instruction(
+                &station1.callsign,
+                CpdlcTransferKind::Contact,
+                "PARIS CONTROL".into(),
+                "128.300".into(),
+                7,
+            )
+            .expect("transfer instruction");
+        let view = session.to_aircraft_view().transfer.expect("transfer in view");
+        assert_eq!(view.from, station1.callsign);
+        assert_eq!(view.next_data_authority, Some(station2.callsign.clone()));
+        assert!(!view.acknowledged);
+
+        // WILCO to another message does not touch the transfer.
+        let _ = session.transfer_acknowledged(Some(3));
+        assert!(!session.pending_transfer.as_ref().unwrap().acknowledged);
+
+        let _ = session.transfer_acknowledged(Some(7));
+        assert!(session.pending_transfer.is_none());
+        assert!(session.next_data_authority.is_none());
+        assert_eq!(session.active_connection.as_ref().unwrap().station.callsign, station2.callsign);
+        assert!(session.inactive_connection.is_none());
+    }
+
+    #[test]
+    fn test_transfer_requires_unit_and_frequency() {
+        use openlink_models::{CpdlcArgument, MessageElement};
+
+        use crate::acars::transfer_arguments;
+
+        let complete = MessageElement::new(
+            "UM117",
+            vec![
+                CpdlcArgument::UnitName("PARIS CONTROL".into()),
+                CpdlcArgument::Frequency("128.300".into()),
+            ],
+        );
+        assert_eq!(
+            transfer_arguments(&complete).unwrap(),
+            ("PARIS CONTROL".to_string(), "128.300".to_string())
+        );
+
+        let no_unit = MessageElement::new("UM120", vec![]);
+        let err = transfer_arguments(&no_unit).unwrap_err().to_string();
+        assert!(err.contains("UM120") && err.contains("unit name"), "{err}");
+
+        let no_frequency =
+            MessageElement::new("UM117", vec![CpdlcArgument::UnitName("PARIS CONTROL".into())]);
+        assert!(transfer_arguments(&no_frequency).unwrap_err().to_string().contains("frequency"));
+
+        let mistyped = MessageElement::new(
+            "UM117",
+            vec![
+                CpdlcArgument::Frequency("128.300".into()),
+                CpdlcArgument::UnitName("PARIS CONTROL".into()),
+            ],
+        );
+        assert!(transfer_arguments(&mistyped).unwrap_err().to_string().contains("unit name"));
+    }
+
+    #[test]
+    fn test_transfer_waits_for_nda_connection() {
+        let (mut session, station1, station2) = connected_session_with_nda();
+        let _ = session.transfer_instruction(
+            &station1.callsign,
+            CpdlcTransferKind::Monitor,
+            "PARIS CONTROL".into(),
+            "128.300".into(),
+            4,
+        );
+        let _ = session.transfer_acknowledged(None);
+        assert!(session.pending_transfer.as_ref().unwrap().acknowledged);
+        assert_eq!(session.active_connection.as_ref().unwrap().station, station1);
+
+        let _ = session.connection_request(&station2.callsign);
+        let _ = session.connection_accepted(&station2.callsign);
+        assert!(session.pending_transfer.is_none());
+        assert_eq!(session.active_connection.as_ref().unwrap().station.callsign, station2.callsign);
+    }
+
+    #[test]
+    fn test_transfer_refused_and_end_service() {
+        let (mut session, station1, station2) = connected_session_with_nda();
+        assert!(session
+            .transfer_instruction(
+                &station2.callsign,
+                CpdlcTransferKind::Contact,
+                "PARIS CONTROL".into(),
+                "128.300".into(),
+                2,
+            )
+            .is_err());
+
+        let _ = session.transfer_instruction(
+            &station1.callsign,
+            CpdlcTransferKind::Contact,
+            "PARIS CONTROL".into(),
+            "128.300".into(),
+            2,
+        );
+        let _ = session.transfer_refused(Some(2));
+        assert!(session.pending_transfer.is_none());
+
+        let _ = session.transfer_instruction(
+            &station1.callsign,
+            CpdlcTransferKind::Contact,
+            "PARIS CONTROL".into(),
+            "128.300".into(),
+            3,
+        );
+        let _ = session.termination_request(&station1.callsign);
+        assert!(session.pending_transfer.is_none());
+        assert!(session.to_station_view(&station1.callsign).transfer.is_none());
+    }
+
     async fn setup_cpdlc_server() -> CPDLCServer {
         let nats_url = "nats://localhost:4222";
         let client = async_nats::connect(nats_url)
@@ -0,0 +1,220 @@
+//! Admin HTTP API.
+//!
+//! Lets operators inspect and repair sessions and registry entries of a
+//! running server without wiping the KV buckets with `--clean`. Every
+//! request must carry `Authorization: Bearer {ADMIN_TOKEN}`.
+//!
+//! | Method   | Path                                          | Action |
+//! |----------|-----------------------------------------------|--------|
+//! | `GET`    | `/admin/v1/{network}/sessions[?callsign=X]`   | List sessions, optionally those involving a callsign |
+//! | `DELETE` | `/admin/v1/{network}/sessions/{aircraft}`     | Purge an aircraft's session |
+//! | `POST`   | `/admin/v1/{network}/sessions/{aircraft}/terminate` | Terminate one connection (`{"station": "LFPG"}`) |
+//! | `GET`    | `/admin/v1/{network}/stations`                | List registry entries |
+//! | `POST`   | `/admin/v1/{network}/stations/{callsign}/offline` | Force a station offline |
+//! | `POST`   | `/admin/v1/{network}/stations/{callsign}/resync`  | Resend session snapshots to a station |
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use anyhow::Result;
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get, post};
+use axum::{Json, Router};
+use openlink_models::{AcarsEndpointCallsign, NetworkId};
+use serde::Deserialize;
+use serde_json::json;
+use subtle::ConstantTimeEq;
+use tracing::{info, warn};
+
+use crate::server::OpenLinkServer;
+
+#[derive(Clone)]
+struct AdminState {
+    token: Arc<str>,
+    servers: Arc<HashMap<NetworkId, Arc<OpenLinkServer>>>,
+}
+
+impl AdminState {
+    fn server(&self, network: &str) -> Result<&Arc<OpenLinkServer>, AdminError> {
+        self.servers
+            .get(&NetworkId::new(network))
+            .ok_or_else(|| AdminError::NotFound(format!("unknown network {network}")))
+    }
+}
+
+enum AdminError {
+    NotFound(String),
+    Internal(anyhow::Error),
+}
+
+impl From<anyhow::Error> for AdminError {
+    fn from(e: anyhow::Error) -> Self {
+        AdminError::Internal(e)
+    }
+}
+
+impl IntoResponse for AdminError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            AdminError::NotFound(message) => (StatusCode::NOT_FOUND, message),
+            AdminError::Internal(e) => {
+                warn!(error = %e, "admin request failed");
+                (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+            }
+        };
+        (status, Json(json!({ "error": message }))).into_response()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SessionsQuery {
+    callsign: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TerminateRequest {
+    station: String,
+}
+
+/// Build the admin router over the per-network servers.
+pub fn router(token: &str, servers: HashMap<NetworkId, Arc<OpenLinkServer>>) -> Router {
+    let state = AdminState {
+        token: Arc::from(token),
+        servers: Arc::new(servers),
+    };
+    Router::new()
+        .route("/admin/v1/{network}/sessions", get(list_sessions))
+        .route("/admin/v1/{network}/sessions/{aircraft}", delete(purge_session))
+        .route(
+            "/admin/v1/{network}/sessions/{aircraft}/terminate",
+            post(terminate_connection),
+        )
+        .route("/admin/v1/{network}/stations", get(list_stations))
+        .route("/admin/v1/{network}/stations/{callsign}/offline", post(force_offline))
+        .route("/admin/v1/{network}/stations/{callsign}/resync", post(resync))
+        .layer(middleware::from_fn_with_state(state.clone(), require_token))
+        .with_state(state)
+}
+
+/// Serve the admin API on `addr` until the process exits.
+pub async fn serve(addr: SocketAddr, app: Router) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    info!(%addr, "admin API listening");
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+async fn require_token(State(state): State<AdminState>, request: Request, next: Next) -> Response {
+    if authorized(request.headers(), &state.token) {
+        next.run(request).await
+    } else {
+        (StatusCode::UNAUTHORIZED, Json(json!({ "error": "missing or invalid admin token" })))
+            .into_response()
+    }
+}
+
+fn authorized(headers: &HeaderMap, token: &str) -> bool {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|presented| {
+            !token.is_empty() && bool::from(presented.trim().as_bytes().ct_eq(token.as_bytes()))
+        })
+}
+
+async fn list_sessions(
+    State(state): State<AdminState>,
+    Path(network): Path<String>,
+    Query(query): Query<SessionsQuery>,
+) -> Result<impl IntoResponse, AdminError> {
+    let callsign = query.callsign.as_deref().map(AcarsEndpointCallsign::new);
+    let sessions = state.server(&network)?.admin_sessions(callsign.as_ref()).await?;
+    Ok(Json(sessions))
+}
+
+async fn purge_session(
+    State(state): State<AdminState>,
+    Path((network, aircraft)): Path<(String, String)>,
+) -> Result<impl IntoResponse, AdminError> {
+    state
+        .server(&network)?
+        .admin_purge_session(&AcarsEndpointCallsign::new(&aircraft))
+        .await?
+        .map(Json)
+        .ok_or_else(|| AdminError::NotFound(format!("no session for {aircraft}")))
+}
+
+async fn terminate_connection(
+    State(state): State<AdminState>,
+    Path((network, aircraft)): Path<(String, String)>,
+    Json(request): Json<TerminateRequest>,
+) -> Result<impl IntoResponse, AdminError> {
+    state
+        .server(&network)?
+        .admin_terminate_connection(
+            &AcarsEndpointCallsign::new(&aircraft),
+            &AcarsEndpointCallsign::new(&request.station),
+        )
+        .await?
+        .map(Json)
+        .ok_or_else(|| AdminError::NotFound(format!("no session for {aircraft}")))
+}
+
+async fn list_stations(
+    State(state): State<AdminState>,
+    Path(network): Path<String>,
+) -> Result<impl IntoResponse, AdminError> {
+    Ok(Json(state.server(&network)?.admin_stations().await?))
+}
+
+async fn force_offline(
+    State(state): State<AdminState>,
+    Path((network, callsign)): Path<(String, String)>,
+) -> Result<impl IntoResponse, AdminError> {
+    state
+        .server(&network)?
+        .admin_force_offline(&AcarsEndpointCallsign::new(&callsign))
+        .await?
+        .map(Json)
+        .ok_or_else(|| AdminError::NotFound(format!("no online station holds {callsign}")))
+}
+
+async fn resync(
+    State(state): State<AdminState>,
+    Path((network, callsign)): Path<(String, String)>,
+) -> Result<impl IntoResponse, AdminError> {
+    state
+        .server(&network)?
+        .admin_resync(&AcarsEndpointCallsign::new(&callsign))
+        .await?
+        .map(Json)
+        .ok_or_else(|| AdminError::NotFound(format!("no online station holds {callsign}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_authorization(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn bearer_token_must_match() {
+        assert!(authorized(&with_authorization("Bearer s3cret"), "s3cret"));
+        assert!(!authorized(&with_authorization("Bearer other"), "s3cret"));
+        assert!(!authorized(&with_authorization("Bearer s3cret-but-longer"), "s3cret"));
+        assert!(!authorized(&with_authorization("Bearer s3c"), "s3cret"));
+        assert!(!authorized(&with_authorization("Bearer "), "s3cret"));
+        assert!(!authorized(&with_authorization("s3cret"), "s3cret"));
+        assert!(!authorized(&HeaderMap::new(), "s3cret"));
+        assert!(!authorized(&with_authorization("Bearer "), ""));
+    }
+}
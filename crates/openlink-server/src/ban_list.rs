@@ -0,0 +1,142 @@
+//! Temporary ban list, managed at runtime.
+//!
+//! Bans live in a KV bucket keyed by network address, so operators can add
+//! or lift one while the server runs, e.g.
+//!
+//! ```text
+//! nats kv put openlink-v1-demonetwork-banned-addresses 100000 \
+//!     '{"reason":"flooding","until":"2026-01-01T12:00:00Z"}'
+//! nats kv del openlink-v1-demonetwork-banned-addresses 100000
+//! ```
+//!
+//! The server watches the bucket and keeps an in-memory copy; envelopes
+//! from a banned address are dropped before any processing.
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use anyhow::Result;
+use async_nats::jetstream;
+use async_nats::jetstream::kv::{Entry, Operation, Watch};
+use chrono::{DateTime, Utc};
+use openlink_models::{NetworkAddress, NetworkId};
+use openlink_sdk::NatsSubjects;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+/// One ban. Without `until` the ban lasts until it is deleted.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Ban {
+    #[serde(default)]
+    pub reason: String,
+    #[serde(default)]
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl Ban {
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.until.is_none_or(|until| now < until)
+    }
+}
+
+/// Per-network ban list backed by a KV bucket.
+#[derive(Debug)]
+pub struct BanList {
+    kv: jetstream::kv::Store,
+    bans: RwLock<HashMap<NetworkAddress, Ban>>,
+}
+
+impl BanList {
+    /// Create or bind to the ban list bucket for the given network.
+    pub async fn new(network_id: &NetworkId, js: &jetstream::Context, force_reset: bool) -> Result<Self> {
+        let bucket_name = NatsSubjects::kv_banned_addresses(network_id);
+        if force_reset {
+            info!(bucket = %bucket_name, "force-resetting ban list KV bucket");
+            if let Err(e) = js.delete_key_value(&bucket_name).await {
+                debug!(bucket = %bucket_name, error = %e, "no bucket to delete");
+            }
+        }
+        let config = jetstream::kv::Config {
+            bucket: bucket_name.clone(),
+            history: 1,
+            ..Default::default()
+        };
+        let kv = match js.create_key_value(config).await {
+            Ok(store) => {
+                info!(bucket = %bucket_name, "ban list KV bucket created");
+                store
+            }
+            Err(_) => {
+                debug!(bucket = %bucket_name, "bucket exists, binding");
+                js.get_key_value(&bucket_name).await?
+            }
+        };
+        Ok(Self {
+            kv,
+            bans: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// Current bans followed by every later change; feed each entry to
+    /// [`Self::apply`].
+    pub async fn watch(&self) -> Result<Watch> {
+        Ok(self.kv.watch_with_history(">").await?)
+    }
+
+    /// Update the in-memory copy from a watched KV entry.
+    pub fn apply(&self, entry: Entry) {
+        let address = NetworkAddress::from(entry.key.as_str());
+        let mut bans = self.bans.write().expect("ban list lock poisoned");
+        match entry.operation {
+            Operation::Put => match serde_json::from_slice::<Ban>(&entry.value) {
+                Ok(ban) => {
+                    info!(%address, reason = %ban.reason, until = ?ban.until, "address banned");
+                    bans.insert(address, ban);
+                }
+                Err(e) => warn!(%address, error = %e, "ignoring malformed ban entry"),
+            },
+            Operation::Delete | Operation::Purge => {
+                if bans.remove(&address).is_some() {
+                    info!(%address, "ban lifted");
+                }
+            }
+        }
+    }
+
+    /// The active ban on `address`, if any.
+    pub fn banned(&self, address: &NetworkAddress, now: DateTime<Utc>) -> Option<Ban> {
+        self.bans
+            .read()
+            .expect("ban list lock poisoned")
+            .get(address)
+            .filter(|ban| ban.is_active(now))
+            .cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn ban_expires_at_until() {
+        let now = Utc::now();
+        let permanent = Ban {
+            reason: "flooding".into(),
+            until: None,
+        };
+        assert!(permanent.is_active(now));
+
+        let temporary: Ban = serde_json::from_str(&format!(
+            r#"{{"reason":"flooding","until":"{}"}}"#,
+            (now + Duration::minutes(5)).to_rfc3339()
+        ))
+        .unwrap();
+        assert!(temporary.is_active(now));
+        assert!(!temporary.is_active(now + Duration::minutes(6)));
+
+        let bare: Ban = serde_json::from_str("{}").unwrap();
+        assert!(bare.is_active(now));
+    }
+}
@@ -0,0 +1,140 @@
+//! Envelope deduplication.
+//!
+//! A client that retries a publish after a timeout sends the same envelope
+//! (same `id`) again. Every envelope id is recorded per sender in a KV
+//! bucket whose entries expire after the dedup window; an envelope whose id
+//! is already recorded is dropped instead of being processed a second time.
+//! When handling an envelope fails, its id is forgotten again so that the
+//! client's retry is processed.
+//!
+//! Ids are scoped to the sending address so that a client cannot suppress
+//! another client's messages by reusing their ids.
+
+use anyhow::Result;
+use async_nats::jetstream;
+use async_nats::jetstream::kv::CreateErrorKind;
+use chrono::{Duration, Utc};
+use openlink_models::{NetworkAddress, NetworkId};
+use openlink_sdk::NatsSubjects;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+/// Per-network record of recently processed envelope ids.
+#[derive(Debug, Clone)]
+pub struct SeenEnvelopes {
+    kv: jetstream::kv::Store,
+}
+
+impl SeenEnvelopes {
+    /// Create or bind to the seen-envelopes bucket for the given network.
+    pub async fn new(
+        network_id: &NetworkId,
+        js: &jetstream::Context,
+        force_reset: bool,
+        window: Duration,
+    ) -> Result<Self> {
+        let bucket_name = NatsSubjects::kv_seen_envelopes(network_id);
+        if force_reset {
+            info!(bucket = %bucket_name, "force-resetting seen envelopes KV bucket");
+            if let Err(e) = js.delete_key_value(&bucket_name).await {
+                debug!(bucket = %bucket_name, error = %e, "no bucket to delete");
+            }
+        }
+        let config = jetstream::kv::Config {
+            bucket: bucket_name.clone(),
+            history: 1,
+            max_age: window.to_std()?,
+            ..Default::default()
+        };
+        let kv = match js.create_key_value(config).await {
+            Ok(store) => {
+                info!(bucket = %bucket_name, window_seconds = window.num_seconds(), "seen envelopes KV bucket created");
+                store
+            }
+            Err(_) => {
+                debug!(bucket = %bucket_name, "bucket exists, binding");
+                js.get_key_value(&bucket_name).await?
+            }
+        };
+        Ok(Self { kv })
+    }
+
+    /// Record `id` for `sender`. Returns `false` when it was already seen
+    /// within the window, i.e. the envelope is a retry.
+    ///
+    /// Storage errors are logged and the envelope is treated as new: a
+    /// missed duplicate is better than a dropped message.
+    pub async fn first_receipt(&self, sender: &NetworkAddress, id: &Uuid) -> bool {
+        let key = seen_key(sender, id);
+        match self.kv.create(&key, Utc::now().to_rfc3339().into()).await {
+            Ok(_) => true,
+            Err(e) if e.kind() == CreateErrorKind::AlreadyExists => false,
+            Err(e) => {
+                warn!(%key, error = %e, "failed to record envelope id");
+                true
+            }
+        }
+    }
+
+    /// Forget `id` for `sender` after its handling failed, so that a retry
+    /// of the envelope is processed instead of dropped as a duplicate.
+    pub async fn forget(&self, sender: &NetworkAddress, id: &Uuid) {
+        let key = seen_key(sender, id);
+        if let Err(e) = self.kv.purge(&key).await {
+            warn!(%key, error = %e, "failed to forget envelope id");
+        }
+    }
+}
+
+/// KV key of an envelope id, scoped to its sender.
+fn seen_key(sender: &NetworkAddress, id: &Uuid) -> String {
+    let sender: String = sender
+        .to_string()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    format!("{sender}.{id}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn setup_seen_envelopes() -> SeenEnvelopes {
+        let client = async_nats::connect("nats://localhost:4222")
+            .await
+            .expect("Failed to connect to NATS server");
+        let js = async_nats::jetstream::new(client);
+        SeenEnvelopes::new(&NetworkId::new("test_network"), &js, false, Duration::minutes(5))
+            .await
+            .expect("create seen envelopes bucket")
+    }
+
+    #[tokio::test]
+    async fn retry_after_failed_handling_is_processed() {
+        let seen = setup_seen_envelopes().await;
+        let sender = NetworkAddress::from("100000");
+        let id = Uuid::new_v4();
+
+        // First delivery is recorded, then its handling fails.
+        assert!(seen.first_receipt(&sender, &id).await);
+        seen.forget(&sender, &id).await;
+
+        // The retry is processed, and a further resend is a duplicate.
+        assert!(seen.first_receipt(&sender, &id).await);
+        assert!(!seen.first_receipt(&sender, &id).await);
+    }
+
+    #[test]
+    fn key_is_scoped_to_sender_and_valid() {
+        let id = Uuid::nil();
+        assert_eq!(
+            seen_key(&NetworkAddress::from("100000"), &id),
+            "100000.00000000-0000-0000-0000-000000000000"
+        );
+        assert_eq!(
+            seen_key(&NetworkAddress::from("a.b*c"), &id),
+            "a_b_c.00000000-0000-0000-0000-000000000000"
+        );
+    }
+}
@@ -0,0 +1,126 @@
+//! Station directory.
+//!
+//! Answers directory requests from the registry and derives the change
+//! events published when a station's public listing changes. Heartbeats
+//! that change nothing but the timestamp produce no event.
+
+use openlink_models::{DirectoryEvent, DirectoryQuery, DirectoryResponse};
+
+use crate::station_registry::StationEntry;
+
+/// Reachable entries matching `query`, sorted by callsign.
+pub fn build_response(entries: Vec<StationEntry>, query: &DirectoryQuery) -> DirectoryResponse {
+    let mut stations: Vec<_> = entries
+        .into_iter()
+        .filter(|entry| entry.status.is_reachable())
+        .map(|entry| entry.info())
+        .filter(|info| query.matches(info))
+        .collect();
+    stations.sort_by_key(|info| info.callsign.to_string());
+    DirectoryResponse { stations }
+}
+
+/// Events announcing the transition of a station from `previous` to
+/// `current`.
+pub fn change_events(previous: Option<&StationEntry>, current: &StationEntry) -> Vec<DirectoryEvent> {
+    let listed = previous.filter(|entry| entry.status.is_reachable());
+    let mut events = Vec::new();
+
+    if let Some(before) = listed
+        && (!current.status.is_reachable()
+            || before.acars_endpoint.callsign != current.acars_endpoint.callsign)
+    {
+        events.push(DirectoryEvent::Removed(before.acars_endpoint.callsign.clone()));
+    }
+
+    if current.status.is_reachable() {
+        let unchanged = listed.is_some_and(|before| {
+            before.acars_endpoint == current.acars_endpoint
+                && before.status == current.status
+                && before.metadata == current.metadata
+        });
+        if !unchanged {
+            events.push(DirectoryEvent::Changed(current.info()));
+        }
+    }
+
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use openlink_models::{
+        AcarsEndpointCallsign, AcarsRoutingEndpoint, NetworkAddress, StationId, StationMetadata,
+        StationRole, StationStatus,
+    };
+
+    fn entry(callsign: &str, status: StationStatus, role: StationRole) -> StationEntry {
+        StationEntry {
+            station_id: StationId::new(callsign),
+            status,
+            last_updated: Utc::now(),
+            network_address: NetworkAddress::from(callsign),
+            acars_endpoint: AcarsRoutingEndpoint::new(callsign, "ADDR1"),
+            metadata: StationMetadata {
+                role: Some(role),
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn response_lists_reachable_matching_stations_sorted() {
+        let entries = vec![
+            entry("LFPG", StationStatus::Online, StationRole::Atc),
+            entry("EGLL", StationStatus::Away, StationRole::Atc),
+            entry("LFMN", StationStatus::Offline, StationRole::Atc),
+            entry("AFR123", StationStatus::Online, StationRole::Aircraft),
+        ];
+        let query = DirectoryQuery {
+            role: Some(StationRole::Atc),
+            ..Default::default()
+        };
+        let callsigns: Vec<_> = build_response(entries, &query)
+            .stations
+            .iter()
+            .map(|s| s.callsign.to_string())
+            .collect();
+        assert_eq!(callsigns, vec!["EGLL", "LFPG"]);
+    }
+
+    #[test]
+    fn heartbeat_without_change_emits_nothing() {
+        let before = entry("LFPG", StationStatus::Online, StationRole::Atc);
+        let mut after = before.clone();
+        after.last_updated = Utc::now();
+        assert!(change_events(Some(&before), &after).is_empty());
+        assert!(matches!(
+            change_events(None, &after).as_slice(),
+            [DirectoryEvent::Changed(_)]
+        ));
+    }
+
+    #[test]
+    fn going_offline_or_renaming_removes_listing() {
+        let before = entry("LFPG", StationStatus::Online, StationRole::Atc);
+        let offline = entry("LFPG", StationStatus::Offline, StationRole::Atc);
+        assert_eq!(
+            change_events(Some(&before), &offline),
+            vec![DirectoryEvent::Removed(AcarsEndpointCallsign::new("LFPG"))]
+        );
+
+        let mut renamed = before.clone();
+        renamed.acars_endpoint = AcarsRoutingEndpoint::new("LFPO", "ADDR1");
+        let events = change_events(Some(&before), &renamed);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0], DirectoryEvent::Removed(AcarsEndpointCallsign::new("LFPG")));
+
+        let away = entry("LFPG", StationStatus::Away, StationRole::Atc);
+        assert!(matches!(
+            change_events(Some(&before), &away).as_slice(),
+            [DirectoryEvent::Changed(_)]
+        ));
+    }
+}
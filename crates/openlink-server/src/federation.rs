@@ -0,0 +1,355 @@
+//! Federation between networks.
+//!
+//! A network *exports* some of its station callsigns to a peer network
+//! (`FEDERATION_EXPORTS_{NETWORK}=peer=PATTERN,…`). Aircraft of the peer can
+//! then log on to and exchange CPDLC messages with those stations.
+//!
+//! The CPDLC session is always owned by the aircraft's home network. Servers
+//! relay envelopes to each other on `openlink.v1.{peer}.federation.{origin}`:
+//!
+//! - [`RelayKind::Process`] — a station sends to an aircraft homed on the
+//!   peer. The peer runs its session state machine, then routes the result.
+//! - [`RelayKind::Deliver`] — the session owner has already processed the
+//!   envelope; the peer only hands it to its local, exported station.
+//! - [`RelayKind::Notify`] — an `EnvelopeRejected` notice for the station
+//!   that sent a `Process` relay, e.g. when its aircraft went offline and
+//!   the message could not be queued.
+//!
+//! Relayed envelopes are only ever delivered locally or answered with a
+//! `Deliver` or `Notify` relay, and neither is relayed again, so an
+//! envelope crosses at most two networks and cannot loop.
+
+use std::collections::BTreeSet;
+
+use anyhow::Result;
+use openlink_models::{
+    AcarsEndpointCallsign, CpdlcEnvelope, CpdlcMessageType, CpdlcMetaMessage, MetaMessage,
+    NetworkAddress, NetworkId, OpenLinkEnvelope, OpenLinkMessage, OpenLinkRoutingEndpoint,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::station_registry::{CallsignPattern, CallsignPolicy, StationEntry, StationRegistry};
+
+/// `from` makes stations matching `pattern` reachable from `to`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportRule {
+    pub from: NetworkId,
+    pub to: NetworkId,
+    pub pattern: CallsignPattern,
+}
+
+/// Export rules of every network served by this process.
+#[derive(Debug, Clone, Default)]
+pub struct ExportRules(Vec<ExportRule>);
+
+impl ExportRules {
+    /// Parse `peer=PATTERN` items exported by `from`; malformed items are
+    /// skipped.
+    pub fn parse(from: &NetworkId, items: &[String]) -> Vec<ExportRule> {
+        items
+            .iter()
+            .filter_map(|item| {
+                let (peer, pattern) = item.split_once('=')?;
+                let (peer, pattern) = (peer.trim(), pattern.trim());
+                if peer.is_empty() || pattern.is_empty() || peer == from.as_str() {
+                    return None;
+                }
+                Some(ExportRule {
+                    from: from.clone(),
+                    to: NetworkId::new(peer),
+                    pattern: CallsignPattern::new(pattern),
+                })
+            })
+            .collect()
+    }
+
+    pub fn extend(&mut self, rules: Vec<ExportRule>) {
+        self.0.extend(rules);
+    }
+
+    /// Whether `from` exports `callsign` to `to`.
+    pub fn exports(&self, from: &NetworkId, to: &NetworkId, callsign: &AcarsEndpointCallsign) -> bool {
+        let callsign = callsign.to_string();
+        self.0
+            .iter()
+            .any(|rule| rule.from == *from && rule.to == *to && rule.pattern.matches(&callsign))
+    }
+
+    /// Networks peered with `network`, in either direction.
+    pub fn peers_of(&self, network: &NetworkId) -> BTreeSet<String> {
+        self.0
+            .iter()
+            .filter_map(|rule| {
+                if rule.from == *network {
+                    Some(rule.to.to_string())
+                } else if rule.to == *network {
+                    Some(rule.from.to_string())
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+/// What the receiving server must do with a relayed envelope.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RelayKind {
+    /// Run the session state machine (the receiver owns the session).
+    Process,
+    /// Hand the envelope to a local station as is.
+    Deliver,
+    /// Hand a rejection notice to the local station it is addressed to.
+    Notify,
+}
+
+/// Payload of a federation relay.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Relay {
+    pub kind: RelayKind,
+    pub envelope: OpenLinkEnvelope,
+}
+
+/// Whether a CPDLC envelope is a station's message to `aircraft`, which
+/// the aircraft's home network processes. Station-to-station logon
+/// forwards are not.
+pub fn is_station_uplink(cpdlc: &CpdlcEnvelope, aircraft: &AcarsEndpointCallsign) -> bool {
+    let logon_forward = matches!(
+        cpdlc.message,
+        CpdlcMessageType::Meta(CpdlcMetaMessage::LogonForward { .. })
+    );
+    !logon_forward && cpdlc.source != *aircraft && cpdlc.destination == *aircraft
+}
+
+/// Where a notice to the sender of an envelope handled on `local` goes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NoticeRoute {
+    /// The sender's inbox on this network.
+    Local(NetworkAddress),
+    /// A `Notify` relay to the sender's network, which relayed the envelope.
+    Relay(NetworkId, NetworkAddress),
+}
+
+impl NoticeRoute {
+    /// Route to the address `source` of an envelope, `None` for a server.
+    pub fn to_source(local: &NetworkId, source: &OpenLinkRoutingEndpoint) -> Option<Self> {
+        match source {
+            OpenLinkRoutingEndpoint::Address(network, address) if network == local => {
+                Some(Self::Local(address.clone()))
+            }
+            OpenLinkRoutingEndpoint::Address(network, address) => {
+                Some(Self::Relay(network.clone(), address.clone()))
+            }
+            OpenLinkRoutingEndpoint::Server(_) => None,
+        }
+    }
+}
+
+/// Local recipient of the envelope of a `Notify` relay: only rejection
+/// notices addressed to a station of `local` are handed over.
+pub fn notice_recipient<'a>(local: &NetworkId, envelope: &'a OpenLinkEnvelope) -> Option<&'a NetworkAddress> {
+    let OpenLinkMessage::Meta(MetaMessage::EnvelopeRejected(_)) = envelope.payload else {
+        return None;
+    };
+    match &envelope.routing.destination {
+        OpenLinkRoutingEndpoint::Address(network, address) if network == local => Some(address),
+        _ => None,
+    }
+}
+
+struct Peer {
+    network: NetworkId,
+    registry: StationRegistry,
+}
+
+/// Federation view of one network: its export rules and read access to
+/// the registries of its peers.
+pub struct Federation {
+    network_id: NetworkId,
+    rules: ExportRules,
+    peers: Vec<Peer>,
+}
+
+impl Federation {
+    /// Bind to the registry of every peer of `network_id`.
+    pub async fn new(
+        network_id: NetworkId,
+        js: async_nats::jetstream::Context,
+        rules: ExportRules,
+    ) -> Result<Self> {
+        let mut peers = Vec::new();
+        for peer in rules.peers_of(&network_id) {
+            let network = NetworkId::new(&peer);
+            let registry =
+                StationRegistry::new(network.clone(), js.clone(), false, CallsignPolicy::default())
+                    .await?;
+            peers.push(Peer { network, registry });
+        }
+        Ok(Self {
+            network_id,
+            rules,
+            peers,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.peers.is_empty()
+    }
+
+    pub fn is_peer(&self, network: &NetworkId) -> bool {
+        self.peers.iter().any(|peer| peer.network == *network)
+    }
+
+    pub fn peer_networks(&self) -> impl Iterator<Item = &NetworkId> {
+        self.peers.iter().map(|peer| &peer.network)
+    }
+
+    /// Whether this network exports `callsign` to `peer`.
+    pub fn exports_to(&self, peer: &NetworkId, callsign: &AcarsEndpointCallsign) -> bool {
+        self.rules.exports(&self.network_id, peer, callsign)
+    }
+
+    /// Find a reachable station of a peer that exports it to this network.
+    pub async fn lookup_imported(
+        &self,
+        callsign: &AcarsEndpointCallsign,
+    ) -> Result<Option<(NetworkId, StationEntry)>> {
+        for peer in &self.peers {
+            if !self.rules.exports(&peer.network, &self.network_id, callsign) {
+                continue;
+            }
+            if let Some(entry) = peer.registry.lookup_callsign(callsign).await? {
+                return Ok(Some((peer.network.clone(), entry)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Find the peer where `aircraft` is online, among the peers this
+    /// network exports `station` to.
+    pub async fn aircraft_home(
+        &self,
+        aircraft: &AcarsEndpointCallsign,
+        station: &AcarsEndpointCallsign,
+    ) -> Result<Option<NetworkId>> {
+        for peer in &self.peers {
+            if !self.exports_to(&peer.network, station) {
+                continue;
+            }
+            if peer.registry.lookup_callsign(aircraft).await?.is_some() {
+                return Ok(Some(peer.network.clone()));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Whether `origin` exports `callsign` to this network and the station is
+    /// currently reachable there.
+    pub async fn is_imported_online(
+        &self,
+        origin: &NetworkId,
+        callsign: &AcarsEndpointCallsign,
+    ) -> Result<bool> {
+        if !self.rules.exports(origin, &self.network_id, callsign) {
+            return Ok(false);
+        }
+        let Some(peer) = self.peers.iter().find(|peer| peer.network == *origin) else {
+            return Ok(false);
+        };
+        Ok(peer.registry.lookup_callsign(callsign).await?.is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules() -> ExportRules {
+        let mut rules = ExportRules::default();
+        rules.extend(ExportRules::parse(
+            &NetworkId::new("demonetwork"),
+            &["afrv=LF@@".to_string(), "malformed".to_string(), "demonetwork=*".to_string()],
+        ));
+        rules
+    }
+
+    #[test]
+    fn parse_skips_malformed_and_self_exports() {
+        assert_eq!(rules().0.len(), 1);
+    }
+
+    #[test]
+    fn exports_are_directional() {
+        let rules = rules();
+        let demo = NetworkId::new("demonetwork");
+        let afrv = NetworkId::new("afrv");
+        let lfpg = AcarsEndpointCallsign::new("LFPG");
+        assert!(rules.exports(&demo, &afrv, &lfpg));
+        assert!(!rules.exports(&afrv, &demo, &lfpg));
+        assert!(!rules.exports(&demo, &afrv, &AcarsEndpointCallsign::new("EGLL")));
+        assert_eq!(rules.peers_of(&afrv), BTreeSet::from(["demonetwork".to_string()]));
+        assert_eq!(rules.peers_of(&demo), BTreeSet::from(["afrv".to_string()]));
+    }
+
+    fn cpdlc(source: &str, destination: &str, message: CpdlcMessageType) -> CpdlcEnvelope {
+        CpdlcEnvelope {
+            source: source.into(),
+            destination: destination.into(),
+            message,
+        }
+    }
+
+    #[test]
+    fn only_station_uplinks_go_to_the_aircraft_home() {
+        let aircraft = AcarsEndpointCallsign::new("AFR123");
+        let connection = CpdlcMessageType::Meta(CpdlcMetaMessage::ConnectionRequest);
+        assert!(is_station_uplink(&cpdlc("LFPG", "AFR123", connection.clone()), &aircraft));
+        assert!(!is_station_uplink(&cpdlc("AFR123", "LFPG", connection.clone()), &aircraft));
+        assert!(!is_station_uplink(&cpdlc("LFPG", "LFFF", connection), &aircraft));
+        let forward = CpdlcMessageType::Meta(CpdlcMetaMessage::LogonForward {
+            flight: "AFR123".into(),
+            flight_plan_origin: openlink_models::ICAOAirportCode::new("LFPG"),
+            flight_plan_destination: openlink_models::ICAOAirportCode::new("KJFK"),
+            new_station: "LFFF".into(),
+        });
+        assert!(!is_station_uplink(&cpdlc("LFPG", "AFR123", forward), &aircraft));
+    }
+
+    #[test]
+    fn notices_to_a_peer_sender_are_relayed() {
+        let demo = NetworkId::new("demonetwork");
+        let afrv = NetworkId::new("afrv");
+        let address = NetworkAddress::new("ATC1");
+        assert_eq!(
+            NoticeRoute::to_source(&demo, &OpenLinkRoutingEndpoint::Address(demo.clone(), address.clone())),
+            Some(NoticeRoute::Local(address.clone()))
+        );
+        assert_eq!(
+            NoticeRoute::to_source(&demo, &OpenLinkRoutingEndpoint::Address(afrv.clone(), address.clone())),
+            Some(NoticeRoute::Relay(afrv, address))
+        );
+        assert_eq!(NoticeRoute::to_source(&demo, &OpenLinkRoutingEndpoint::Server(demo.clone())), None);
+    }
+
+    #[test]
+    fn notify_relays_only_hand_over_rejections_for_local_addresses() {
+        let rejection = |network: &str| {
+            openlink_sdk::MessageBuilder::envelope(openlink_sdk::MessageBuilder::envelope_rejected(
+                uuid::Uuid::new_v4(),
+                openlink_models::RejectionCode::DeliveryExpired,
+                "offline",
+            ))
+            .source_server("afrv")
+            .destination_address(network, "ATC1")
+            .build()
+        };
+        let demo = NetworkId::new("demonetwork");
+        let notice = rejection("demonetwork");
+        assert_eq!(notice_recipient(&demo, &notice), Some(&NetworkAddress::new("ATC1")));
+        assert_eq!(notice_recipient(&demo, &rejection("afrv")), None);
+
+        let mut other = notice;
+        other.payload = openlink_sdk::MessageBuilder::station_lookup("LFPG");
+        assert_eq!(notice_recipient(&demo, &other), None);
+    }
+}
@@ -0,0 +1,319 @@
+//! Sender identity from the envelope token.
+//!
+//! Clients put the NATS user JWT issued by openlink-auth in
+//! [`OpenLinkEnvelope::token`](openlink_models::OpenLinkEnvelope). NATS has
+//! already checked it at connect time, but the server never sees the
+//! connection's JWT, so it verifies the token again: signed by the auth
+//! account key, not expired, issued for this network and for the outbox
+//! address the envelope was published on. The role tag of a valid token
+//! is the sender's [`UserRole`] and its callsign tags, carried by service
+//! account tokens, the only callsigns it may use; its key and issue time
+//! are checked against the revocation list.
+//!
+//! The account key is fetched from `{AUTH_URL}/public-key` and fetched again
+//! (at most every [`KEY_REFRESH_COOLDOWN`]) when a token names another
+//! issuer, e.g. after openlink-auth restarted with a new key.
+
+use std::time::{Duration, Instant};
+
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use nkeys::KeyPair;
+use openlink_models::{NetworkAddress, NetworkId, RejectionCode, UserRole};
+use openlink_sdk::NatsSubjects;
+use serde::Deserialize;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::sender_check::SenderRejection;
+
+const KEY_REFRESH_COOLDOWN: Duration = Duration::from_secs(30);
+
+#[derive(Deserialize)]
+struct TokenHeader {
+    alg: String,
+}
+
+#[derive(Deserialize)]
+struct TokenClaims {
+    iss: String,
+    name: String,
+    sub: String,
+    #[serde(default)]
+    iat: u64,
+    exp: u64,
+    nats: NatsClaims,
+}
+
+/// What a valid token says about its holder.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifiedToken {
+    pub role: UserRole,
+    /// NKey the JWT was issued to.
+    pub user_nkey_public: String,
+    /// Issue time (Unix seconds).
+    pub issued_at: u64,
+    /// Callsigns the holder may use; empty when unrestricted.
+    pub callsigns: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct NatsClaims {
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// Why a token was not accepted.
+#[derive(Debug, PartialEq)]
+enum Unverified {
+    /// Signed by an account key other than the one known.
+    UnknownIssuer,
+    Invalid(String),
+}
+
+#[derive(Default)]
+struct AccountKey {
+    key: Option<String>,
+    fetched_at: Option<Instant>,
+}
+
+/// Verifies envelope tokens against the auth service's account key.
+pub struct TokenVerifier {
+    auth_url: String,
+    http: reqwest::Client,
+    account: Mutex<AccountKey>,
+}
+
+impl TokenVerifier {
+    pub fn new(auth_url: &str) -> Self {
+        Self {
+            auth_url: auth_url.trim_end_matches('/').to_string(),
+            http: reqwest::Client::new(),
+            account: Mutex::new(AccountKey::default()),
+        }
+    }
+
+    /// Role and key of `sender` according to `token`.
+    pub async fn verify(
+        &self,
+        token: &str,
+        network: &NetworkId,
+        sender: &NetworkAddress,
+    ) -> Result<VerifiedToken, SenderRejection> {
+        let mut account = self.account.lock().await;
+        if account.key.is_none() {
+            self.refresh(&mut account).await;
+        }
+        let now = unix_now();
+        let mut result = match account.key.as_deref() {
+            Some(key) => verify_token(token, key, network, sender, now),
+            None => Err(Unverified::UnknownIssuer),
+        };
+        if result == Err(Unverified::UnknownIssuer) && self.refresh(&mut account).await {
+            result = match account.key.as_deref() {
+                Some(key) => verify_token(token, key, network, sender, now),
+                None => Err(Unverified::UnknownIssuer),
+            };
+        }
+        result.map_err(|unverified| SenderRejection {
+            code: RejectionCode::Unauthenticated,
+            reason: match unverified {
+                Unverified::UnknownIssuer => "token is not signed by this network's auth service".into(),
+                Unverified::Invalid(reason) => reason,
+            },
+        })
+    }
+
+    /// Fetch the account key unless fetched recently. Returns whether the
+    /// key changed.
+    async fn refresh(&self, account: &mut AccountKey) -> bool {
+        if account
+            .fetched_at
+            .is_some_and(|at| at.elapsed() < KEY_REFRESH_COOLDOWN)
+        {
+            return false;
+        }
+        account.fetched_at = Some(Instant::now());
+        let url = format!("{}/public-key", self.auth_url);
+        let key = match self.http.get(&url).send().await {
+            Ok(res) if res.status().is_success() => res.text().await.ok(),
+            Ok(res) => {
+                warn!(%url, status = %res.status(), "failed to fetch auth account key");
+                None
+            }
+            Err(e) => {
+                warn!(%url, error = %e, "failed to fetch auth account key");
+                None
+            }
+        };
+        let Some(key) = key.map(|k| k.trim().to_string()).filter(|k| !k.is_empty()) else {
+            return false;
+        };
+        if account.key.as_deref() == Some(key.as_str()) {
+            return false;
+        }
+        info!(account_key = %key, "auth account key loaded");
+        account.key = Some(key);
+        true
+    }
+}
+
+/// Verify a NATS user JWT signed by `account_key` for `sender` on `network`.
+fn verify_token(
+    token: &str,
+    account_key: &str,
+    network: &NetworkId,
+    sender: &NetworkAddress,
+    now: u64,
+) -> Result<VerifiedToken, Unverified> {
+    let invalid = |reason: &str| Unverified::Invalid(reason.to_string());
+    if token.is_empty() {
+        return Err(invalid("envelope carries no token"));
+    }
+    let mut parts = token.split('.');
+    let (Some(header), Some(body), Some(signature), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(invalid("token is not a JWT"));
+    };
+    let decode = |part: &str| URL_SAFE_NO_PAD.decode(part).map_err(|_| invalid("token is not a JWT"));
+    let header: TokenHeader =
+        serde_json::from_slice(&decode(header)?).map_err(|_| invalid("token is not a JWT"))?;
+    let claims: TokenClaims =
+        serde_json::from_slice(&decode(body)?).map_err(|_| invalid("token is not a NATS user JWT"))?;
+    if header.alg != "ed25519-nkey" {
+        return Err(invalid("token is not a NATS user JWT"));
+    }
+
+    if claims.iss != account_key {
+        return Err(Unverified::UnknownIssuer);
+    }
+    let signing_input = &token[..token.len() - signature.len() - 1];
+    let signature = decode(signature)?;
+    let verified = KeyPair::from_public_key(account_key)
+        .and_then(|kp| kp.verify(signing_input.as_bytes(), &signature));
+    if verified.is_err() {
+        return Err(invalid("token signature is invalid"));
+    }
+
+    if claims.exp <= now {
+        return Err(invalid("token has expired"));
+    }
+    if claims.name != sender.as_str() {
+        return Err(invalid("token was issued to another address"));
+    }
+    if !claims.nats.tags.contains(&NatsSubjects::network_tag(network)) {
+        return Err(invalid("token was issued for another network"));
+    }
+    let role = claims
+        .nats
+        .tags
+        .iter()
+        .find_map(|tag| NatsSubjects::parse_role_tag(tag))
+        .ok_or_else(|| invalid("token carries no role"))?;
+    let callsigns = claims
+        .nats
+        .tags
+        .iter()
+        .filter_map(|tag| NatsSubjects::parse_callsign_tag(tag))
+        .map(str::to_string)
+        .collect();
+    Ok(VerifiedToken {
+        role,
+        user_nkey_public: claims.sub,
+        issued_at: claims.iat,
+        callsigns,
+    })
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("system clock before epoch")
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Sign claims the way openlink-auth does.
+    fn sign(kp: &KeyPair, claims: serde_json::Value) -> String {
+        let header = URL_SAFE_NO_PAD.encode(json!({"typ": "JWT", "alg": "ed25519-nkey"}).to_string());
+        let body = URL_SAFE_NO_PAD.encode(claims.to_string());
+        let input = format!("{header}.{body}");
+        let signature = URL_SAFE_NO_PAD.encode(kp.sign(input.as_bytes()).unwrap());
+        format!("{input}.{signature}")
+    }
+
+    fn claims(kp: &KeyPair, name: &str, exp: u64, tags: &[&str]) -> serde_json::Value {
+        json!({
+            "iss": kp.public_key(),
+            "name": name,
+            "sub": "UUSER",
+            "iat": exp - 3600,
+            "exp": exp,
+            "nats": { "tags": tags, "type": "user", "version": 2 },
+        })
+    }
+
+    const NOW: u64 = 1_700_000_000;
+    const TAGS: [&str; 2] = ["openlink-network:demonetwork", "openlink-role:controller"];
+
+    #[test]
+    fn valid_token_yields_role() {
+        let kp = KeyPair::new_account();
+        let token = sign(&kp, claims(&kp, "888888", NOW + 60, &TAGS));
+        let verified = verify_token(
+            &token,
+            &kp.public_key(),
+            &NetworkId::new("demonetwork"),
+            &NetworkAddress::from("888888"),
+            NOW,
+        );
+        assert_eq!(
+            verified,
+            Ok(VerifiedToken {
+                role: UserRole::Controller,
+                user_nkey_public: "UUSER".into(),
+                issued_at: NOW + 60 - 3600,
+                callsigns: Vec::new(),
+            })
+        );
+
+        let tags = [TAGS[0], TAGS[1], "openlink-callsign:LFPG_TWR"];
+        let token = sign(&kp, claims(&kp, "888888", NOW + 60, &tags));
+        let verified = verify_token(
+            &token,
+            &kp.public_key(),
+            &NetworkId::new("demonetwork"),
+            &NetworkAddress::from("888888"),
+            NOW,
+        )
+        .unwrap();
+        assert_eq!(verified.callsigns, vec!["LFPG_TWR".to_string()]);
+    }
+
+    #[test]
+    fn rejects_foreign_expired_and_forged_tokens() {
+        let kp = KeyPair::new_account();
+        let key = kp.public_key();
+        let network = NetworkId::new("demonetwork");
+        let sender = NetworkAddress::from("888888");
+        let check = |token: &str| verify_token(token, &key, &network, &sender, NOW);
+
+        assert!(check("").is_err());
+        assert!(check(&sign(&kp, claims(&kp, "100000", NOW + 60, &TAGS))).is_err());
+        assert!(check(&sign(&kp, claims(&kp, "888888", NOW - 1, &TAGS))).is_err());
+        assert!(check(&sign(&kp, claims(&kp, "888888", NOW + 60, &["openlink-network:afrv"]))).is_err());
+        assert!(check(&sign(&kp, claims(&kp, "888888", NOW + 60, &TAGS[..1]))).is_err());
+
+        let other = KeyPair::new_account();
+        let token = sign(&other, claims(&other, "888888", NOW + 60, &TAGS));
+        assert_eq!(check(&token), Err(Unverified::UnknownIssuer));
+
+        // Claims signed by another key but naming the known issuer.
+        let token = sign(&other, claims(&kp, "888888", NOW + 60, &TAGS));
+        assert_eq!(check(&token), Err(Unverified::Invalid("token signature is invalid".into())));
+    }
+}
@@ -0,0 +1,45 @@
+//! Stream backing durable inbox consumption.
+//!
+//! Every message the server delivers to an inbox subject is also captured
+//! here, so that clients reading their inbox through a durable consumer
+//! (`OpenLinkClient::subscribe_inbox_durable`) get what was published while
+//! they were reconnecting.
+//!
+//! The stream uses interest retention: a message is only kept while a
+//! consumer filtering on its inbox has not acknowledged it, so inboxes of
+//! clients using plain subscriptions cost nothing.
+
+use anyhow::Result;
+use async_nats::jetstream;
+use async_nats::jetstream::stream::{RetentionPolicy, StorageType};
+use chrono::Duration;
+use openlink_models::NetworkId;
+use openlink_sdk::NatsSubjects;
+use tracing::{debug, info};
+
+/// Create or bind to the inbox stream for the given network.
+pub async fn ensure_stream(
+    network_id: &NetworkId,
+    js: &jetstream::Context,
+    force_reset: bool,
+    retention: Duration,
+) -> Result<()> {
+    let stream_name = NatsSubjects::stream_inboxes(network_id);
+    if force_reset {
+        info!(stream = %stream_name, "force-resetting inbox stream");
+        if let Err(e) = js.delete_stream(&stream_name).await {
+            debug!(stream = %stream_name, error = %e, "no stream to delete");
+        }
+    }
+    let config = jetstream::stream::Config {
+        name: stream_name.clone(),
+        subjects: vec![NatsSubjects::inbox_wildcard(network_id)],
+        retention: RetentionPolicy::Interest,
+        max_age: retention.to_std()?,
+        storage: StorageType::File,
+        ..Default::default()
+    };
+    js.get_or_create_stream(config).await?;
+    info!(stream = %stream_name, retention_seconds = retention.num_seconds(), "inbox stream ready");
+    Ok(())
+}
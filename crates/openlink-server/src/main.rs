@@ -4,6 +4,19 @@ use clap::Parser;
 use openlink_models::NetworkId;
 
 mod acars;
+mod admin;
+mod ban_list;
+mod dedup;
+mod directory;
+mod federation;
+mod identity;
+mod inboxes;
+mod metrics;
+mod pending;
+mod presence;
+mod rate_limit;
+mod revocations;
+mod sender_check;
 mod server;
 mod station_registry;
 
@@ -14,6 +27,13 @@ fn read_u64_env(name: &str, default: u64) -> u64 {
         .unwrap_or(default)
 }
 
+fn read_u32_env(name: &str, default: u32) -> u32 {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.parse::<u32>().ok())
+        .unwrap_or(default)
+}
+
 fn read_i64_env(name: &str, default: i64) -> i64 {
     std::env::var(name)
         .ok()
@@ -32,6 +52,77 @@ fn read_bool_env(name: &str, default: bool) -> bool {
         .unwrap_or(default)
 }
 
+/// Read a comma-separated list, ignoring blank items.
+fn read_list_env(name: &str) -> Vec<String> {
+    std::env::var(name)
+        .map(|v| {
+            v.split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Credential presented to the auth service: a signed assertion when
+/// `SERVER_NKEY_SEED` is set (with `SERVER_NAME` required), else
+/// `SERVER_SECRET`, registered under `SERVER_NAME` if set.
+fn server_credential() -> Result<openlink_sdk::ServerCredential, String> {
+    let var = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+    let name = var("SERVER_NAME");
+    match var("SERVER_NKEY_SEED") {
+        Some(seed) => Ok(openlink_sdk::ServerCredential::Key {
+            name: name.ok_or("SERVER_NKEY_SEED requires SERVER_NAME")?,
+            seed,
+        }),
+        None => Ok(openlink_sdk::ServerCredential::Secret {
+            name,
+            secret: var("SERVER_SECRET").unwrap_or_else(|| "openlink-dev-secret".to_string()),
+        }),
+    }
+}
+
+/// Callsign ownership rules for `network`, with per-network env overrides
+/// (`CALLSIGN_RESERVED_PATTERNS_{NETWORK}`).
+fn callsign_policy_for(
+    network: &NetworkId,
+    presence_config: &server::PresenceConfig,
+) -> station_registry::CallsignPolicy {
+    let suffix = network.as_str().to_ascii_uppercase();
+    station_registry::CallsignPolicy {
+        lease_ttl: chrono::Duration::seconds(presence_config.lease_ttl_seconds),
+        allow_takeover: read_bool_env("CALLSIGN_ALLOW_TAKEOVER", false),
+        reserved_patterns: read_list_env(&format!("CALLSIGN_RESERVED_PATTERNS_{suffix}"))
+            .iter()
+            .map(|p| station_registry::CallsignPattern::new(p))
+            .collect(),
+    }
+}
+
+/// Resolve on Ctrl-C or, on Unix, SIGTERM.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = ctrl_c => {}
+        () = terminate => {}
+    }
+}
+
 /// OpenLink CPDLC relay server.
 #[derive(Parser, Debug)]
 #[command(name = "openlink-server", about = "OpenLink CPDLC relay server")]
@@ -57,8 +148,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         std::env::var("NATS_URL").unwrap_or_else(|_| "nats://localhost:4222".to_string());
     let auth_url =
         std::env::var("AUTH_URL").unwrap_or_else(|_| "http://localhost:3001".to_string());
-    let server_secret =
-        std::env::var("SERVER_SECRET").unwrap_or_else(|_| "openlink-dev-secret".to_string());
+    let server_credential = server_credential()?;
 
     let presence_config = server::PresenceConfig {
         lease_ttl_seconds: read_i64_env("PRESENCE_LEASE_TTL_SECONDS", 90).max(1),
@@ -67,30 +157,129 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             "AUTO_END_SERVICE_ON_STATION_OFFLINE",
             true,
         ),
+        pending_delivery_ttl_seconds: read_i64_env("PENDING_DELIVERY_TTL_SECONDS", 120).max(0),
+        inbox_retention_seconds: read_i64_env("INBOX_RETENTION_SECONDS", 600).max(0),
+        dedup_window_seconds: read_i64_env("DEDUP_WINDOW_SECONDS", 300).max(0),
+    };
+
+    let default_limits = rate_limit::RateLimitConfig::default();
+    let rate_limit_config = rate_limit::RateLimitConfig {
+        meta: rate_limit::BucketConfig {
+            per_minute: read_u32_env("RATE_LIMIT_META_PER_MINUTE", default_limits.meta.per_minute),
+            burst: read_u32_env("RATE_LIMIT_META_BURST", default_limits.meta.burst),
+        },
+        application: rate_limit::BucketConfig {
+            per_minute: read_u32_env(
+                "RATE_LIMIT_APPLICATION_PER_MINUTE",
+                default_limits.application.per_minute,
+            ),
+            burst: read_u32_env("RATE_LIMIT_APPLICATION_BURST", default_limits.application.burst),
+        },
+    };
+
+    // Optional system-account connection for connect/disconnect advisories.
+    let system_client = match std::env::var("NATS_SYSTEM_CREDS") {
+        Ok(path) if !path.trim().is_empty() => Some(
+            async_nats::ConnectOptions::with_credentials_file(path.trim())
+                .await?
+                .connect(&nats_url)
+                .await?,
+        ),
+        _ => None,
     };
 
+    let metrics = std::sync::Arc::new(metrics::Metrics::default());
+    let metrics_addr = std::env::var("METRICS_ADDR").unwrap_or_else(|_| "0.0.0.0:9464".to_string());
+    if !metrics_addr.trim().is_empty() {
+        let addr: std::net::SocketAddr = metrics_addr.trim().parse()?;
+        let metrics = metrics.clone();
+        tokio::spawn(async move {
+            if let Err(e) = metrics::serve(addr, metrics).await {
+                tracing::error!(error = %e, "metrics endpoint failed");
+            }
+        });
+    }
+
+    let drain_timeout = std::time::Duration::from_secs(read_u64_env("DRAIN_TIMEOUT_SECONDS", 30));
+    let drain_notice = std::env::var("DRAIN_NOTICE").unwrap_or_else(|_| {
+        "server restarting for maintenance; reconnect shortly".to_string()
+    });
+    let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+
     let networks = vec![NetworkId::new("afrv"), NetworkId::new("demonetwork")];
 
+    // Federation export rules of every network, e.g.
+    // FEDERATION_EXPORTS_DEMONETWORK=afrv=LF@@,afrv=LF@@_*
+    let mut export_rules = federation::ExportRules::default();
+    for network in &networks {
+        let suffix = network.as_str().to_ascii_uppercase();
+        export_rules.extend(federation::ExportRules::parse(
+            network,
+            &read_list_env(&format!("FEDERATION_EXPORTS_{suffix}")),
+        ));
+    }
+
+    let mut servers = std::collections::HashMap::new();
     let mut handles = Vec::new();
     for network in networks {
+        let callsign_policy = callsign_policy_for(&network, &presence_config);
         let server =
             server::OpenLinkServer::new(
-                network,
+                network.clone(),
                 &nats_url,
                 &auth_url,
-                &server_secret,
+                &server_credential,
                 args.clean,
                 presence_config,
+                callsign_policy,
             )
+            .await?
+            .with_rate_limits(rate_limit_config)
+            .with_metrics(metrics.clone())
+            .with_federation(export_rules.clone())
             .await?;
+        let server = if drain_notice.trim().is_empty() {
+            server
+        } else {
+            server.with_drain_notice(drain_notice.trim().to_string())
+        };
+        let server = std::sync::Arc::new(match system_client {
+            Some(ref system) => server.with_connection_events(system.clone()),
+            None => server,
+        });
+        servers.insert(network, server.clone());
+        let shutdown = shutdown_rx.clone();
         let handle = tokio::spawn(async move {
-            server.run().await;
+            server.run(shutdown).await;
         });
         handles.push(handle);
     }
 
-    for handle in handles {
-        let _ = handle.await;
+    // The admin API is only exposed when a token is configured.
+    let admin_token = std::env::var("ADMIN_TOKEN").unwrap_or_default();
+    if !admin_token.trim().is_empty() {
+        let addr: std::net::SocketAddr = std::env::var("ADMIN_ADDR")
+            .unwrap_or_else(|_| "127.0.0.1:9465".to_string())
+            .trim()
+            .parse()?;
+        let app = admin::router(admin_token.trim(), servers);
+        tokio::spawn(async move {
+            if let Err(e) = admin::serve(addr, app).await {
+                tracing::error!(error = %e, "admin API failed");
+            }
+        });
+    }
+
+    let mut servers_done = futures::future::join_all(handles);
+    tokio::select! {
+        _ = &mut servers_done => return Ok(()),
+        () = shutdown_signal() => {}
+    }
+
+    tracing::info!(drain_timeout_seconds = drain_timeout.as_secs(), "shutdown requested, draining");
+    let _ = shutdown_tx.send(true);
+    if tokio::time::timeout(drain_timeout, servers_done).await.is_err() {
+        tracing::warn!("drain timeout reached, exiting with work in flight");
     }
 
     Ok(())
@@ -0,0 +1,344 @@
+//! Prometheus metrics.
+//!
+//! One [`Metrics`] registry is shared by every network server of the
+//! process and rendered in the Prometheus text exposition format on
+//! `GET /metrics`. All series are labelled with the network.
+//!
+//! KV latency is measured per routing step (sender check, registry update,
+//! session update, …); each observation covers the KV round-trips made by
+//! that step.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Result;
+use axum::Router;
+use axum::extract::State;
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use openlink_models::{
+    AcarsMessage, CpdlcConnectionPhase, CpdlcMessageType, CpdlcMetaMessage, MetaMessage, NetworkId, OpenLinkMessage,
+};
+use tracing::info;
+
+use crate::acars::CPDLCSession;
+use crate::rate_limit::RateLimitCounters;
+
+/// Histogram bucket upper bounds, in seconds.
+const LATENCY_BUCKETS: [f64; 11] = [
+    0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5,
+];
+
+type Labels = Vec<(&'static str, String)>;
+
+#[derive(Debug, Clone, Default)]
+struct Histogram {
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, value: Duration) {
+        let seconds = value.as_secs_f64();
+        for (bucket, bound) in self.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if seconds <= bound {
+                *bucket += 1;
+            }
+        }
+        self.sum += seconds;
+        self.count += 1;
+    }
+}
+
+#[derive(Debug, Default)]
+struct Registry {
+    counters: BTreeMap<&'static str, BTreeMap<Labels, u64>>,
+    gauges: BTreeMap<&'static str, BTreeMap<Labels, i64>>,
+    histograms: BTreeMap<&'static str, BTreeMap<Labels, Histogram>>,
+}
+
+/// Metric families: name, type and help text.
+const FAMILIES: &[(&str, &str, &str)] = &[
+    ("openlink_messages_routed_total", "counter", "Envelopes handled successfully, by message type."),
+    ("openlink_handler_errors_total", "counter", "Envelopes that could not be routed, by reason."),
+    ("openlink_forward_latency_seconds", "histogram", "Time from receiving an envelope to forwarding it to its recipient."),
+    ("openlink_kv_operation_seconds", "histogram", "Duration of KV-bound routing steps, by operation."),
+    ("openlink_presence_expirations_total", "counter", "Stations marked offline, by reason."),
+    ("openlink_cpdlc_sessions", "gauge", "CPDLC sessions by phase of their active connection."),
+    ("openlink_stations_online", "gauge", "Reachable stations in the registry."),
+    ("openlink_rate_limit_envelopes_total", "counter", "Envelopes seen by the rate limiter, by outcome."),
+];
+
+/// Shared metrics registry.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    registry: Mutex<Registry>,
+}
+
+impl Metrics {
+    fn add(&self, name: &'static str, labels: Labels, value: u64) {
+        let mut registry = self.registry.lock().expect("metrics lock poisoned");
+        *registry.counters.entry(name).or_default().entry(labels).or_default() += value;
+    }
+
+    fn observe(&self, name: &'static str, labels: Labels, value: Duration) {
+        let mut registry = self.registry.lock().expect("metrics lock poisoned");
+        registry
+            .histograms
+            .entry(name)
+            .or_default()
+            .entry(labels)
+            .or_default()
+            .observe(value);
+    }
+
+    pub fn message_routed(&self, network: &NetworkId, message: &OpenLinkMessage) {
+        self.add(
+            "openlink_messages_routed_total",
+            vec![("network", network.to_string()), ("type", message_kind(message).to_string())],
+            1,
+        );
+    }
+
+    pub fn handler_error(&self, network: &NetworkId, reason: &str) {
+        self.add(
+            "openlink_handler_errors_total",
+            vec![("network", network.to_string()), ("reason", reason.to_string())],
+            1,
+        );
+    }
+
+    pub fn forward_latency(&self, network: &NetworkId, elapsed: Duration) {
+        self.observe(
+            "openlink_forward_latency_seconds",
+            vec![("network", network.to_string())],
+            elapsed,
+        );
+    }
+
+    pub fn kv_operation(&self, network: &NetworkId, operation: &'static str, elapsed: Duration) {
+        self.observe(
+            "openlink_kv_operation_seconds",
+            vec![("network", network.to_string()), ("operation", operation.to_string())],
+            elapsed,
+        );
+    }
+
+    pub fn presence_expired(&self, network: &NetworkId, reason: &str, count: usize) {
+        self.add(
+            "openlink_presence_expirations_total",
+            vec![("network", network.to_string()), ("reason", reason.to_string())],
+            count as u64,
+        );
+    }
+
+    /// Replace the session gauges of `network` with `by_phase` counts.
+    pub fn set_sessions(&self, network: &NetworkId, by_phase: BTreeMap<String, i64>) {
+        let mut registry = self.registry.lock().expect("metrics lock poisoned");
+        let family = registry.gauges.entry("openlink_cpdlc_sessions").or_default();
+        let network_label = network.to_string();
+        family.retain(|labels, _| labels[0].1 != network_label);
+        for (phase, count) in by_phase {
+            family.insert(vec![("network", network_label.clone()), ("phase", phase)], count);
+        }
+    }
+
+    pub fn set_stations_online(&self, network: &NetworkId, count: usize) {
+        let mut registry = self.registry.lock().expect("metrics lock poisoned");
+        registry
+            .gauges
+            .entry("openlink_stations_online")
+            .or_default()
+            .insert(vec![("network", network.to_string())], count as i64);
+    }
+
+    /// Publish the limiter's running totals.
+    pub fn set_rate_limit_counters(&self, network: &NetworkId, counters: RateLimitCounters) {
+        let mut registry = self.registry.lock().expect("metrics lock poisoned");
+        let family = registry
+            .counters
+            .entry("openlink_rate_limit_envelopes_total")
+            .or_default();
+        for (outcome, value) in [
+            ("allowed", counters.allowed),
+            ("limited_meta", counters.limited_meta),
+            ("limited_application", counters.limited_application),
+            ("banned", counters.banned),
+        ] {
+            family.insert(
+                vec![("network", network.to_string()), ("outcome", outcome.to_string())],
+                value,
+            );
+        }
+    }
+
+    /// Render every series in the Prometheus text format.
+    pub fn render(&self) -> String {
+        let registry = self.registry.lock().expect("metrics lock poisoned");
+        let mut out = String::new();
+        for (name, kind, help) in FAMILIES {
+            let _ = writeln!(out, "# HELP {name} {help}");
+            let _ = writeln!(out, "# TYPE {name} {kind}");
+            match *kind {
+                "counter" => {
+                    for (labels, value) in registry.counters.get(name).into_iter().flatten() {
+                        let _ = writeln!(out, "{name}{} {value}", format_labels(labels, None));
+                    }
+                }
+                "gauge" => {
+                    for (labels, value) in registry.gauges.get(name).into_iter().flatten() {
+                        let _ = writeln!(out, "{name}{} {value}", format_labels(labels, None));
+                    }
+                }
+                _ => {
+                    for (labels, histogram) in registry.histograms.get(name).into_iter().flatten() {
+                        for (count, bound) in histogram.buckets.iter().zip(LATENCY_BUCKETS) {
+                            let le = bound.to_string();
+                            let _ = writeln!(
+                                out,
+                                "{name}_bucket{} {count}",
+                                format_labels(labels, Some(&le))
+                            );
+                        }
+                        let _ = writeln!(
+                            out,
+                            "{name}_bucket{} {}",
+                            format_labels(labels, Some("+Inf")),
+                            histogram.count
+                        );
+                        let _ = writeln!(out, "{name}_sum{} {}", format_labels(labels, None), histogram.sum);
+                        let _ = writeln!(out, "{name}_count{} {}", format_labels(labels, None), histogram.count);
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+fn format_labels(labels: &Labels, le: Option<&str>) -> String {
+    let mut parts: Vec<String> = labels
+        .iter()
+        .map(|(key, value)| format!("{key}=\"{}\"", escape_label(value)))
+        .collect();
+    if let Some(le) = le {
+        parts.push(format!("le=\"{le}\""));
+    }
+    if parts.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", parts.join(","))
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Phase label of a session's active connection.
+pub fn session_phase(session: &CPDLCSession) -> &'static str {
+    match session.active_connection.as_ref().map(|c| c.phase()) {
+        Some(CpdlcConnectionPhase::LogonPending) => "logon_pending",
+        Some(CpdlcConnectionPhase::LoggedOn) => "logged_on",
+        Some(CpdlcConnectionPhase::Connected) => "connected",
+        Some(CpdlcConnectionPhase::Terminated) => "terminated",
+        None => "no_connection",
+    }
+}
+
+/// Short label for the type of a routed message.
+pub fn message_kind(message: &OpenLinkMessage) -> &'static str {
+    match message {
+        OpenLinkMessage::Meta(meta) => match meta {
+            MetaMessage::StationStatus(..) => "station_status",
+            MetaMessage::StationLookup(_) => "station_lookup",
+            MetaMessage::StationInfo(..) => "station_info",
+            MetaMessage::EnvelopeRejected(_) => "envelope_rejected",
+            MetaMessage::ServerNotice(_) => "server_notice",
+        },
+        OpenLinkMessage::Acars(acars) => {
+            let AcarsMessage::CPDLC(cpdlc) = &acars.message;
+            match &cpdlc.message {
+                CpdlcMessageType::Application(_) => "cpdlc_application",
+                CpdlcMessageType::Meta(meta) => match meta {
+                    CpdlcMetaMessage::LogonRequest { .. } => "cpdlc_logon_request",
+                    CpdlcMetaMessage::LogonResponse { .. } => "cpdlc_logon_response",
+                    CpdlcMetaMessage::ConnectionRequest => "cpdlc_connection_request",
+                    CpdlcMetaMessage::ConnectionResponse { .. } => "cpdlc_connection_response",
+                    CpdlcMetaMessage::LogonForward { .. } => "cpdlc_logon_forward",
+                    CpdlcMetaMessage::SessionUpdate { .. } => "cpdlc_session_update",
+                },
+            }
+        }
+    }
+}
+
+/// Serve `GET /metrics` on `addr` until the process exits.
+pub async fn serve(addr: SocketAddr, metrics: Arc<Metrics>) -> Result<()> {
+    let app = Router::new()
+        .route("/metrics", get(render_metrics))
+        .with_state(metrics);
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    info!(%addr, "metrics endpoint listening");
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+async fn render_metrics(State(metrics): State<Arc<Metrics>>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        metrics.render(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_counters_gauges_and_histograms() {
+        let metrics = Metrics::default();
+        let network = NetworkId::new("demonetwork");
+        metrics.handler_error(&network, "malformed_envelope");
+        metrics.handler_error(&network, "malformed_envelope");
+        metrics.set_stations_online(&network, 3);
+        metrics.forward_latency(&network, Duration::from_millis(20));
+
+        let text = metrics.render();
+        assert!(text.contains(
+            "openlink_handler_errors_total{network=\"demonetwork\",reason=\"malformed_envelope\"} 2"
+        ));
+        assert!(text.contains("openlink_stations_online{network=\"demonetwork\"} 3"));
+        assert!(text.contains(
+            "openlink_forward_latency_seconds_bucket{network=\"demonetwork\",le=\"0.01\"} 0"
+        ));
+        assert!(text.contains(
+            "openlink_forward_latency_seconds_bucket{network=\"demonetwork\",le=\"0.025\"} 1"
+        ));
+        assert!(text.contains("openlink_forward_latency_seconds_count{network=\"demonetwork\"} 1"));
+        assert!(text.contains("# TYPE openlink_cpdlc_sessions gauge"));
+    }
+
+    #[test]
+    fn session_gauges_are_replaced_per_network() {
+        let metrics = Metrics::default();
+        let a = NetworkId::new("a");
+        let b = NetworkId::new("b");
+        metrics.set_sessions(&a, BTreeMap::from([("connected".to_string(), 2)]));
+        metrics.set_sessions(&b, BTreeMap::from([("connected".to_string(), 1)]));
+        metrics.set_sessions(&a, BTreeMap::from([("logged_on".to_string(), 1)]));
+
+        let text = metrics.render();
+        assert!(!text.contains("openlink_cpdlc_sessions{network=\"a\",phase=\"connected\"}"));
+        assert!(text.contains("openlink_cpdlc_sessions{network=\"a\",phase=\"logged_on\"} 1"));
+        assert!(text.contains("openlink_cpdlc_sessions{network=\"b\",phase=\"connected\"} 1"));
+    }
+}
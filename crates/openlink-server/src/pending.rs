@@ -0,0 +1,298 @@
+//! Store-and-forward delivery for temporarily offline recipients.
+//!
+//! When the destination callsign of a routed message is not in the registry,
+//! the message is queued in a JetStream stream, one subject per callsign.
+//! Queued messages are delivered in order when the recipient comes back
+//! online; messages older than the TTL are dropped and their sender is told
+//! with an `EnvelopeRejected` (`DeliveryExpired`).
+//!
+//! All queued messages share one TTL, so stream order is expiry order and the
+//! sweeper only has to look at the head of the stream.
+//!
+//! Messages stay in the stream until the caller [removes](PendingDeliveries::remove)
+//! them, once delivered or reported expired, so that a failed delivery or a
+//! crash does not lose them.
+
+use std::fmt;
+
+use anyhow::Result;
+use async_nats::jetstream;
+use async_nats::jetstream::context::PublishError;
+use async_nats::jetstream::ErrorCode;
+use async_nats::jetstream::stream::{DiscardPolicy, LastRawMessageErrorKind, StorageType};
+use chrono::{DateTime, Duration, Utc};
+use openlink_models::{
+    AcarsEndpointCallsign, NetworkAddress, NetworkId, OpenLinkEnvelope, OpenLinkRoutingEndpoint,
+};
+use openlink_sdk::NatsSubjects;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+/// Upper bound on messages queued for a single callsign.
+const MAX_PENDING_PER_CALLSIGN: i64 = 256;
+
+/// A message waiting for its recipient.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingDelivery {
+    pub recipient: AcarsEndpointCallsign,
+    /// Network address that published the original envelope, if known.
+    pub sender: Option<NetworkAddress>,
+    /// Network of `sender`; absent from deliveries queued before it was
+    /// recorded, which all came from this network.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sender_network: Option<NetworkId>,
+    pub envelope: OpenLinkEnvelope,
+    pub queued_at: DateTime<Utc>,
+}
+
+impl PendingDelivery {
+    /// Whether the delivery has waited longer than `ttl`.
+    pub fn is_expired(&self, ttl: Duration, now: DateTime<Utc>) -> bool {
+        now.signed_duration_since(self.queued_at) > ttl
+    }
+
+    /// Routing endpoint of the sender, queued on the `local` network.
+    pub fn sender_endpoint(&self, local: &NetworkId) -> Option<OpenLinkRoutingEndpoint> {
+        let network = self.sender_network.clone().unwrap_or_else(|| local.clone());
+        Some(OpenLinkRoutingEndpoint::Address(network, self.sender.clone()?))
+    }
+}
+
+/// Reason a message could not be queued.
+#[derive(Debug)]
+pub enum EnqueueError {
+    /// The recipient already has [`MAX_PENDING_PER_CALLSIGN`] messages queued.
+    Full,
+    /// Serialization or JetStream failure.
+    Failed(anyhow::Error),
+}
+
+impl fmt::Display for EnqueueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Full => write!(f, "queue is full"),
+            Self::Failed(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for EnqueueError {}
+
+/// Whether JetStream refused a publish because the subject already holds
+/// `max_messages_per_subject` messages (with `DiscardPolicy::New`).
+fn is_queue_full(error: &PublishError) -> bool {
+    std::error::Error::source(error)
+        .and_then(|source| source.downcast_ref::<jetstream::Error>())
+        .is_some_and(|e| {
+            e.error_code() == ErrorCode::STREAM_STORE_FAILED
+                && e.to_string().contains("maximum messages per subject")
+        })
+}
+
+/// Per-network store-and-forward queue.
+#[derive(Debug, Clone)]
+pub struct PendingDeliveries {
+    network_id: NetworkId,
+    js: jetstream::Context,
+    stream: jetstream::stream::Stream,
+    ttl: Duration,
+}
+
+impl PendingDeliveries {
+    /// Create or bind to the pending-deliveries stream for the given network.
+    pub async fn new(
+        network_id: NetworkId,
+        js: jetstream::Context,
+        force_reset: bool,
+        ttl: Duration,
+    ) -> Result<Self> {
+        let stream_name = NatsSubjects::stream_pending_deliveries(&network_id);
+        if force_reset {
+            info!(stream = %stream_name, "force-resetting pending deliveries stream");
+            if let Err(e) = js.delete_stream(&stream_name).await {
+                debug!(stream = %stream_name, error = %e, "no stream to delete");
+            }
+        }
+        // The stream's own max age is only a safety net: the sweeper expires
+        // messages at `ttl` so that senders are notified.
+        let config = jetstream::stream::Config {
+            name: stream_name.clone(),
+            subjects: vec![NatsSubjects::pending_wildcard(&network_id)],
+            max_age: (ttl * 2).to_std()?,
+            max_messages_per_subject: MAX_PENDING_PER_CALLSIGN,
+            discard: DiscardPolicy::New,
+            storage: StorageType::File,
+            ..Default::default()
+        };
+        let stream = js.get_or_create_stream(config).await?;
+        info!(stream = %stream_name, ttl_seconds = ttl.num_seconds(), "pending deliveries stream ready");
+
+        Ok(Self {
+            network_id,
+            js,
+            stream,
+            ttl,
+        })
+    }
+
+    /// Queue `envelope`, sent by `source`, until `recipient` comes online.
+    pub async fn enqueue(
+        &self,
+        recipient: &AcarsEndpointCallsign,
+        source: &OpenLinkRoutingEndpoint,
+        envelope: &OpenLinkEnvelope,
+    ) -> std::result::Result<(), EnqueueError> {
+        let (sender, sender_network) = match source {
+            OpenLinkRoutingEndpoint::Address(network, address) => {
+                (Some(address.clone()), Some(network.clone()))
+            }
+            OpenLinkRoutingEndpoint::Server(_) => (None, None),
+        };
+        let delivery = PendingDelivery {
+            recipient: recipient.clone(),
+            sender,
+            sender_network,
+            envelope: envelope.clone(),
+            queued_at: Utc::now(),
+        };
+        let payload = serde_json::to_vec(&delivery).map_err(|e| EnqueueError::Failed(e.into()))?;
+        let subject = NatsSubjects::pending(&self.network_id, recipient.as_str());
+        let ack = self
+            .js
+            .publish(subject, payload.into())
+            .await
+            .map_err(|e| EnqueueError::Failed(e.into()))?;
+        match ack.await {
+            Ok(_) => Ok(()),
+            Err(e) if is_queue_full(&e) => Err(EnqueueError::Full),
+            Err(e) => Err(EnqueueError::Failed(e.into())),
+        }
+    }
+
+    /// Every message queued for `recipient` with its stream sequence,
+    /// oldest first.
+    ///
+    /// Expired messages are included; callers check
+    /// [`PendingDelivery::is_expired`] with [`Self::ttl`] and
+    /// [remove](Self::remove) each message once handled.
+    pub async fn queued_for(&self, recipient: &AcarsEndpointCallsign) -> Result<Vec<(u64, PendingDelivery)>> {
+        let subject = NatsSubjects::pending(&self.network_id, recipient.as_str());
+        let mut queued = Vec::new();
+        let mut sequence = 1;
+        while let Some((seq, delivery)) = self.next_from(&subject, sequence).await? {
+            queued.push((seq, delivery));
+            sequence = seq + 1;
+        }
+        Ok(queued)
+    }
+
+    /// Every message that waited longer than the TTL with its stream
+    /// sequence, oldest first. Callers [remove](Self::remove) each message
+    /// once its sender was told.
+    pub async fn expired(&self) -> Result<Vec<(u64, PendingDelivery)>> {
+        let subject = NatsSubjects::pending_wildcard(&self.network_id);
+        let now = Utc::now();
+        let mut expired = Vec::new();
+        let mut sequence = 1;
+        while let Some((seq, delivery)) = self.next_from(&subject, sequence).await? {
+            if !delivery.is_expired(self.ttl, now) {
+                break;
+            }
+            expired.push((seq, delivery));
+            sequence = seq + 1;
+        }
+        Ok(expired)
+    }
+
+    /// Remove a delivered or expired message from the queue.
+    pub async fn remove(&self, sequence: u64) -> Result<()> {
+        self.stream.delete_message(sequence).await?;
+        Ok(())
+    }
+
+    /// Configured store-and-forward delay.
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// First message on `subject` at or after `sequence`.
+    async fn next_from(&self, subject: &str, sequence: u64) -> Result<Option<(u64, PendingDelivery)>> {
+        match self
+            .stream
+            .get_first_raw_message_by_subject(subject, sequence)
+            .await
+        {
+            Ok(message) => {
+                let delivery: PendingDelivery = serde_json::from_slice(&message.payload)?;
+                Ok(Some((message.sequence, delivery)))
+            }
+            Err(e) if e.kind() == LastRawMessageErrorKind::NoMessageFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openlink_models::MessageBuilder;
+
+    #[test]
+    fn delivery_expires_after_ttl() {
+        let envelope = MessageBuilder::cpdlc("AFR123", "39401A")
+            .from("LFPG")
+            .to("AFR123")
+            .connection_request()
+            .envelope()
+            .source_address("demonetwork", "888888")
+            .destination_server("demonetwork")
+            .build();
+        let delivery = PendingDelivery {
+            recipient: AcarsEndpointCallsign::new("AFR123"),
+            sender: Some(NetworkAddress::from("888888")),
+            sender_network: None,
+            envelope,
+            queued_at: Utc::now() - Duration::seconds(60),
+        };
+        let now = Utc::now();
+        assert!(!delivery.is_expired(Duration::seconds(120), now));
+        assert!(delivery.is_expired(Duration::seconds(30), now));
+
+        let json = serde_json::to_vec(&delivery).unwrap();
+        let back: PendingDelivery = serde_json::from_slice(&json).unwrap();
+        assert_eq!(back.recipient, delivery.recipient);
+
+        let demo = NetworkId::new("demonetwork");
+        let sender = NetworkAddress::from("888888");
+        assert_eq!(
+            back.sender_endpoint(&demo),
+            Some(OpenLinkRoutingEndpoint::Address(demo, sender.clone()))
+        );
+        let relayed = PendingDelivery {
+            sender_network: Some(NetworkId::new("afrv")),
+            ..back
+        };
+        assert_eq!(
+            relayed.sender_endpoint(&NetworkId::new("demonetwork")),
+            Some(OpenLinkRoutingEndpoint::Address(NetworkId::new("afrv"), sender))
+        );
+    }
+
+    #[test]
+    fn only_the_per_subject_limit_means_full() {
+        let publish_error = |json: &str| {
+            let error: jetstream::Error = serde_json::from_str(json).unwrap();
+            PublishError::with_source(jetstream::context::PublishErrorKind::Other, error)
+        };
+        assert!(is_queue_full(&publish_error(
+            r#"{"code":503,"err_code":10077,"description":"maximum messages per subject exceeded"}"#
+        )));
+        assert!(!is_queue_full(&publish_error(
+            r#"{"code":503,"err_code":10077,"description":"insufficient resources"}"#
+        )));
+        assert!(!is_queue_full(&publish_error(
+            r#"{"code":503,"err_code":10039,"description":"jetstream not enabled"}"#
+        )));
+        assert!(!is_queue_full(&PublishError::new(jetstream::context::PublishErrorKind::TimedOut)));
+    }
+}
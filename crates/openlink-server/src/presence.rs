@@ -0,0 +1,332 @@
+//! Presence from NATS connection advisories.
+//!
+//! With a system-account connection, the NATS server publishes an advisory
+//! whenever a client connects or disconnects. The advisory carries the JWT
+//! `name` (the CID, which is also the client's [`NetworkAddress`]) and its
+//! tags, so a disconnect can be mapped to the stations registered by that
+//! address without waiting for the heartbeat lease to expire.
+//!
+//! A single address may hold several connections (e.g. a reconnect that
+//! overlaps the old socket); the station only goes offline once none is
+//! left. The user NKey of each connection is kept too, so that a revoked
+//! key can be mapped to the connections using it.
+
+use std::collections::HashMap;
+
+use openlink_models::{NetworkAddress, NetworkId};
+use openlink_sdk::NatsSubjects;
+use serde::Deserialize;
+
+/// Subject of client connect advisories, for every account.
+pub const CONNECT_ADVISORIES: &str = "$SYS.ACCOUNT.*.CONNECT";
+/// Subject of client disconnect advisories, for every account.
+pub const DISCONNECT_ADVISORIES: &str = "$SYS.ACCOUNT.*.DISCONNECT";
+
+/// Subject asking NATS server `server_id` to close one of its client
+/// connections (payload `{"cid": client_id}`; nats-server 2.10 or later).
+pub fn kick_subject(server_id: &str) -> String {
+    format!("$SYS.REQ.SERVER.{server_id}.KICK")
+}
+
+const CONNECT_TYPE: &str = "io.nats.server.advisory.v1.client_connect";
+const DISCONNECT_TYPE: &str = "io.nats.server.advisory.v1.client_disconnect";
+
+/// Identifies one client connection across a NATS cluster.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ConnectionKey {
+    pub server_id: String,
+    pub client_id: u64,
+}
+
+/// A connect or disconnect of a client of this network.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConnectionEvent {
+    /// A client connected, with the user NKey of its JWT when known.
+    Connected(NetworkAddress, ConnectionKey, Option<String>),
+    Disconnected(NetworkAddress, ConnectionKey),
+}
+
+#[derive(Deserialize)]
+struct Advisory {
+    #[serde(rename = "type")]
+    advisory_type: String,
+    server: AdvisoryServer,
+    client: AdvisoryClient,
+}
+
+#[derive(Deserialize)]
+struct AdvisoryServer {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct AdvisoryClient {
+    id: u64,
+    /// User NKey of the client's JWT.
+    #[serde(default)]
+    user: Option<String>,
+    #[serde(default)]
+    name_tag: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// Parse a connect/disconnect advisory for `network`.
+///
+/// Returns `None` for other advisory types, clients without a JWT name and
+/// clients whose JWT is tagged for another network. JWTs without any
+/// network tag are accepted.
+pub fn parse_advisory(payload: &[u8], network: &NetworkId) -> Option<ConnectionEvent> {
+    let advisory: Advisory = serde_json::from_slice(payload).ok()?;
+    let name = advisory.client.name_tag.filter(|name| !name.is_empty())?;
+
+    let tag = NatsSubjects::network_tag(network);
+    let network_tags: Vec<&String> = advisory
+        .client
+        .tags
+        .iter()
+        .filter(|t| t.starts_with("openlink-network:"))
+        .collect();
+    if !network_tags.is_empty() && !network_tags.iter().any(|t| t.eq_ignore_ascii_case(&tag)) {
+        return None;
+    }
+
+    let address = NetworkAddress::from(name.as_str());
+    let key = ConnectionKey {
+        server_id: advisory.server.id,
+        client_id: advisory.client.id,
+    };
+    match advisory.advisory_type.as_str() {
+        CONNECT_TYPE => Some(ConnectionEvent::Connected(
+            address,
+            key,
+            advisory.client.user.filter(|user| !user.is_empty()),
+        )),
+        DISCONNECT_TYPE => Some(ConnectionEvent::Disconnected(address, key)),
+        _ => None,
+    }
+}
+
+/// Connections to close for a revocation, per address.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RevokedAddress {
+    pub address: NetworkAddress,
+    pub connections: Vec<ConnectionKey>,
+    /// Whether no connection of the address is left once these are closed,
+    /// so that its stations go offline.
+    pub release_stations: bool,
+}
+
+/// Live connections per network address, with the user NKey of each, as
+/// seen since server start.
+#[derive(Debug, Default)]
+pub struct ConnectionTracker {
+    live: HashMap<NetworkAddress, HashMap<ConnectionKey, Option<String>>>,
+}
+
+impl ConnectionTracker {
+    /// Known live connections of `address`.
+    pub fn connections(&self, address: &NetworkAddress) -> Vec<ConnectionKey> {
+        self.live
+            .get(address)
+            .map(|keys| keys.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Connections a revocation of `cid` or of `user_nkey_public` covers.
+    ///
+    /// A CID revocation covers every connection of the address of that
+    /// CID, tracked or not. A key revocation covers the tracked connections
+    /// using the key, and releases an address's stations only when it has no
+    /// other connection.
+    pub fn revoked(&self, cid: Option<&str>, user_nkey_public: Option<&str>) -> Vec<RevokedAddress> {
+        if let Some(cid) = cid {
+            let address = NetworkAddress::from(cid);
+            return vec![RevokedAddress {
+                connections: self.connections(&address),
+                address,
+                release_stations: true,
+            }];
+        }
+        let Some(user_nkey_public) = user_nkey_public else {
+            return Vec::new();
+        };
+        let mut revoked: Vec<RevokedAddress> = self
+            .live
+            .iter()
+            .filter_map(|(address, keys)| {
+                let mut connections: Vec<ConnectionKey> = keys
+                    .iter()
+                    .filter(|(_, user)| user.as_deref() == Some(user_nkey_public))
+                    .map(|(key, _)| key.clone())
+                    .collect();
+                if connections.is_empty() {
+                    return None;
+                }
+                connections.sort_by_key(|key| key.client_id);
+                Some(RevokedAddress {
+                    address: address.clone(),
+                    release_stations: connections.len() == keys.len(),
+                    connections,
+                })
+            })
+            .collect();
+        revoked.sort_by(|a, b| a.address.as_str().cmp(b.address.as_str()));
+        revoked
+    }
+
+    /// Apply an event. Returns the address when its last known connection
+    /// went away, i.e. when its stations should be marked offline.
+    ///
+    /// Connections opened before the server started are unknown, so a
+    /// disconnect with no other tracked connection counts as the last one.
+    pub fn apply(&mut self, event: ConnectionEvent) -> Option<NetworkAddress> {
+        match event {
+            ConnectionEvent::Connected(address, key, user) => {
+                self.live.entry(address).or_default().insert(key, user);
+                None
+            }
+            ConnectionEvent::Disconnected(address, key) => {
+                let remaining = self.live.get_mut(&address).map(|keys| {
+                    keys.remove(&key);
+                    keys.len()
+                });
+                match remaining {
+                    Some(0) | None => {
+                        self.live.remove(&address);
+                        Some(address)
+                    }
+                    Some(_) => None,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn advisory(kind: &str, client_id: u64, name: &str, tags: &[&str]) -> Vec<u8> {
+        serde_json::to_vec(&serde_json::json!({
+            "type": format!("io.nats.server.advisory.v1.client_{kind}"),
+            "id": "abc",
+            "timestamp": "2026-01-01T00:00:00Z",
+            "server": { "name": "n1", "host": "0.0.0.0", "id": "NSERVER1" },
+            "client": {
+                "id": client_id,
+                "acc": "ACCOUNT",
+                "user": "UUSER",
+                "name_tag": name,
+                "tags": tags,
+                "kind": "Client"
+            },
+            "reason": "Client Closed"
+        }))
+        .unwrap()
+    }
+
+    fn key(client_id: u64) -> ConnectionKey {
+        ConnectionKey {
+            server_id: "NSERVER1".to_string(),
+            client_id,
+        }
+    }
+
+    #[test]
+    fn parses_connect_and_disconnect_for_network() {
+        let network = NetworkId::new("demonetwork");
+        let event = parse_advisory(
+            &advisory("disconnect", 7, "100000", &["openlink-network:demonetwork"]),
+            &network,
+        );
+        assert_eq!(
+            event,
+            Some(ConnectionEvent::Disconnected(NetworkAddress::from("100000"), key(7)))
+        );
+        let event = parse_advisory(&advisory("connect", 8, "100000", &[]), &network);
+        assert_eq!(
+            event,
+            Some(ConnectionEvent::Connected(
+                NetworkAddress::from("100000"),
+                key(8),
+                Some("UUSER".to_string())
+            ))
+        );
+    }
+
+    #[test]
+    fn ignores_other_networks_and_anonymous_clients() {
+        let network = NetworkId::new("demonetwork");
+        assert_eq!(
+            parse_advisory(&advisory("disconnect", 7, "100000", &["openlink-network:afrv"]), &network),
+            None
+        );
+        assert_eq!(parse_advisory(&advisory("disconnect", 7, "", &[]), &network), None);
+        assert_eq!(parse_advisory(b"not json", &network), None);
+    }
+
+    #[test]
+    fn offline_only_when_last_connection_closes() {
+        let address = NetworkAddress::from("100000");
+        let mut tracker = ConnectionTracker::default();
+
+        tracker.apply(ConnectionEvent::Connected(address.clone(), key(1), None));
+        tracker.apply(ConnectionEvent::Connected(address.clone(), key(2), None));
+        assert_eq!(tracker.apply(ConnectionEvent::Disconnected(address.clone(), key(1))), None);
+        assert_eq!(tracker.connections(&address), vec![key(2)]);
+        assert_eq!(
+            tracker.apply(ConnectionEvent::Disconnected(address.clone(), key(2))),
+            Some(address.clone())
+        );
+
+        // Overlapping reconnect: the old, untracked socket closes after the
+        // new one opened.
+        tracker.apply(ConnectionEvent::Connected(address.clone(), key(3), None));
+        assert_eq!(tracker.apply(ConnectionEvent::Disconnected(address.clone(), key(0))), None);
+
+        // Connection opened before server start.
+        let other = NetworkAddress::from("200000");
+        assert_eq!(
+            tracker.apply(ConnectionEvent::Disconnected(other.clone(), key(9))),
+            Some(other)
+        );
+    }
+
+    #[test]
+    fn revocations_map_to_connections() {
+        let pilot = NetworkAddress::from("100000");
+        let atc = NetworkAddress::from("888888");
+        let user = |key: &str| Some(key.to_string());
+        let mut tracker = ConnectionTracker::default();
+        tracker.apply(ConnectionEvent::Connected(pilot.clone(), key(1), user("UOLD")));
+        tracker.apply(ConnectionEvent::Connected(pilot.clone(), key(2), user("UNEW")));
+        tracker.apply(ConnectionEvent::Connected(atc.clone(), key(3), user("UOLD")));
+
+        // A key: its connections, releasing addresses left without any.
+        assert_eq!(
+            tracker.revoked(None, Some("UOLD")),
+            vec![
+                RevokedAddress {
+                    address: pilot.clone(),
+                    connections: vec![key(1)],
+                    release_stations: false,
+                },
+                RevokedAddress {
+                    address: atc.clone(),
+                    connections: vec![key(3)],
+                    release_stations: true,
+                },
+            ]
+        );
+        assert!(tracker.revoked(None, Some("UNKNOWN")).is_empty());
+
+        // A CID: every connection of its address.
+        let revoked = tracker.revoked(Some("100000"), Some("UOLD"));
+        assert_eq!(revoked.len(), 1);
+        assert_eq!(revoked[0].address, pilot);
+        assert_eq!(revoked[0].connections.len(), 2);
+        assert!(revoked[0].release_stations);
+        assert!(tracker.revoked(None, None).is_empty());
+    }
+}
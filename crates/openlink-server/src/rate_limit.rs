@@ -0,0 +1,257 @@
+//! Per-address rate limiting.
+//!
+//! Every sender address gets one token bucket per message class. Each
+//! envelope takes a token; once a bucket is empty, envelopes from that
+//! address are rejected with `RateLimited` until it refills. Only the first
+//! rejected envelope of a burst is answered, so that a flood does not turn
+//! into a flood of rejections.
+
+use std::collections::HashMap;
+use std::time::Instant;
+
+use openlink_models::{NetworkAddress, OpenLinkMessage};
+
+/// Message classes limited independently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MessageClass {
+    /// Station status, lookups and other `Meta` messages.
+    Meta,
+    /// ACARS traffic (CPDLC meta and application messages).
+    Application,
+}
+
+impl MessageClass {
+    pub fn of(message: &OpenLinkMessage) -> Self {
+        match message {
+            OpenLinkMessage::Meta(_) => MessageClass::Meta,
+            OpenLinkMessage::Acars(_) => MessageClass::Application,
+        }
+    }
+}
+
+/// Sustained rate and burst of one bucket. A rate of `0` disables the limit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BucketConfig {
+    pub per_minute: u32,
+    pub burst: u32,
+}
+
+impl BucketConfig {
+    fn enabled(&self) -> bool {
+        self.per_minute > 0
+    }
+
+    fn capacity(&self) -> f64 {
+        f64::from(self.burst.max(1))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimitConfig {
+    pub meta: BucketConfig,
+    pub application: BucketConfig,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            meta: BucketConfig {
+                per_minute: 120,
+                burst: 20,
+            },
+            application: BucketConfig {
+                per_minute: 300,
+                burst: 30,
+            },
+        }
+    }
+}
+
+impl RateLimitConfig {
+    fn bucket(&self, class: MessageClass) -> BucketConfig {
+        match class {
+            MessageClass::Meta => self.meta,
+            MessageClass::Application => self.application,
+        }
+    }
+}
+
+/// Outcome of [`RateLimiter::check`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    Allowed,
+    /// Over the limit; `notify` is set for the first rejection of a burst.
+    Limited { notify: bool },
+}
+
+/// Envelope counts since server start, for monitoring.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RateLimitCounters {
+    pub allowed: u64,
+    pub limited_meta: u64,
+    pub limited_application: u64,
+    pub banned: u64,
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    updated: Instant,
+    notified: bool,
+}
+
+impl TokenBucket {
+    fn refill(&mut self, config: BucketConfig, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        let rate = f64::from(config.per_minute) / 60.0;
+        self.tokens = (self.tokens + elapsed * rate).min(config.capacity());
+        self.updated = now;
+    }
+}
+
+/// Token buckets for every (address, class) seen recently.
+#[derive(Debug)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: HashMap<(NetworkAddress, MessageClass), TokenBucket>,
+    counters: RateLimitCounters,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            buckets: HashMap::new(),
+            counters: RateLimitCounters::default(),
+        }
+    }
+
+    /// Take a token for an envelope of `class` from `address`.
+    pub fn check(&mut self, address: &NetworkAddress, class: MessageClass, now: Instant) -> Decision {
+        let config = self.config.bucket(class);
+        if !config.enabled() {
+            self.counters.allowed += 1;
+            return Decision::Allowed;
+        }
+        let bucket = self
+            .buckets
+            .entry((address.clone(), class))
+            .or_insert_with(|| TokenBucket {
+                tokens: config.capacity(),
+                updated: now,
+                notified: false,
+            });
+        bucket.refill(config, now);
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            bucket.notified = false;
+            self.counters.allowed += 1;
+            return Decision::Allowed;
+        }
+
+        match class {
+            MessageClass::Meta => self.counters.limited_meta += 1,
+            MessageClass::Application => self.counters.limited_application += 1,
+        }
+        let notify = !bucket.notified;
+        bucket.notified = true;
+        Decision::Limited { notify }
+    }
+
+    /// Count an envelope dropped because its sender is banned.
+    pub fn record_banned(&mut self) {
+        self.counters.banned += 1;
+    }
+
+    pub fn counters(&self) -> RateLimitCounters {
+        self.counters
+    }
+
+    /// Forget buckets that have refilled completely, i.e. idle senders.
+    pub fn prune(&mut self, now: Instant) {
+        let config = self.config;
+        self.buckets.retain(|(_, class), bucket| {
+            let bucket_config = config.bucket(*class);
+            bucket.refill(bucket_config, now);
+            bucket.tokens < bucket_config.capacity()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn limiter() -> RateLimiter {
+        RateLimiter::new(RateLimitConfig {
+            meta: BucketConfig {
+                per_minute: 60,
+                burst: 2,
+            },
+            application: BucketConfig {
+                per_minute: 0,
+                burst: 0,
+            },
+        })
+    }
+
+    #[test]
+    fn bucket_limits_bursts_and_refills() {
+        let mut limiter = limiter();
+        let address = NetworkAddress::from("100000");
+        let start = Instant::now();
+
+        assert_eq!(limiter.check(&address, MessageClass::Meta, start), Decision::Allowed);
+        assert_eq!(limiter.check(&address, MessageClass::Meta, start), Decision::Allowed);
+        assert_eq!(
+            limiter.check(&address, MessageClass::Meta, start),
+            Decision::Limited { notify: true }
+        );
+        assert_eq!(
+            limiter.check(&address, MessageClass::Meta, start),
+            Decision::Limited { notify: false }
+        );
+
+        // One token per second.
+        let later = start + Duration::from_secs(1);
+        assert_eq!(limiter.check(&address, MessageClass::Meta, later), Decision::Allowed);
+        assert_eq!(
+            limiter.check(&address, MessageClass::Meta, later),
+            Decision::Limited { notify: true }
+        );
+
+        let counters = limiter.counters();
+        assert_eq!(counters.allowed, 3);
+        assert_eq!(counters.limited_meta, 3);
+    }
+
+    #[test]
+    fn addresses_and_classes_are_independent() {
+        let mut limiter = limiter();
+        let now = Instant::now();
+        let a = NetworkAddress::from("100000");
+        let b = NetworkAddress::from("200000");
+        limiter.check(&a, MessageClass::Meta, now);
+        limiter.check(&a, MessageClass::Meta, now);
+        assert!(matches!(limiter.check(&a, MessageClass::Meta, now), Decision::Limited { .. }));
+        assert_eq!(limiter.check(&b, MessageClass::Meta, now), Decision::Allowed);
+        // Application class is disabled.
+        for _ in 0..100 {
+            assert_eq!(limiter.check(&a, MessageClass::Application, now), Decision::Allowed);
+        }
+    }
+
+    #[test]
+    fn prune_drops_idle_buckets() {
+        let mut limiter = limiter();
+        let now = Instant::now();
+        let address = NetworkAddress::from("100000");
+        limiter.check(&address, MessageClass::Meta, now);
+        limiter.prune(now);
+        assert_eq!(limiter.buckets.len(), 1);
+        limiter.prune(now + Duration::from_secs(5));
+        assert!(limiter.buckets.is_empty());
+    }
+}
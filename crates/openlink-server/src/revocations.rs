@@ -0,0 +1,170 @@
+//! Credential revocations, recorded by openlink-auth.
+//!
+//! The auth service writes a [`Revocation`] to the network's revocations
+//! bucket when an operator revokes a user's credentials. The server watches
+//! the bucket and keeps an in-memory copy: envelopes whose token the copy
+//! revokes are rejected, and a revocation made while the server runs also
+//! closes the user's NATS connections and marks their stations offline.
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use anyhow::Result;
+use async_nats::jetstream;
+use async_nats::jetstream::kv::{Entry, Operation, Watch};
+use openlink_models::NetworkId;
+use openlink_sdk::revocation::REVOCATION_RETENTION;
+use openlink_sdk::{NatsSubjects, Revocation};
+use tracing::{debug, info, warn};
+
+/// Per-network revocation list backed by a KV bucket.
+#[derive(Debug)]
+pub struct RevocationList {
+    kv: jetstream::kv::Store,
+    revocations: RevocationSet,
+}
+
+impl RevocationList {
+    /// Create or bind to the revocations bucket for the given network.
+    ///
+    /// The bucket belongs to openlink-auth, so `--clean` leaves it alone.
+    pub async fn new(network_id: &NetworkId, js: &jetstream::Context) -> Result<Self> {
+        let bucket_name = NatsSubjects::kv_revocations(network_id);
+        let config = jetstream::kv::Config {
+            bucket: bucket_name.clone(),
+            history: 1,
+            max_age: REVOCATION_RETENTION,
+            ..Default::default()
+        };
+        let kv = match js.create_key_value(config).await {
+            Ok(store) => {
+                info!(bucket = %bucket_name, "revocations KV bucket created");
+                store
+            }
+            Err(_) => {
+                debug!(bucket = %bucket_name, "bucket exists, binding");
+                js.get_key_value(&bucket_name).await?
+            }
+        };
+        Ok(Self {
+            kv,
+            revocations: RevocationSet::default(),
+        })
+    }
+
+    /// Current revocations followed by every later one; feed each entry to
+    /// [`Self::apply`].
+    pub async fn watch(&self) -> Result<Watch> {
+        Ok(self.kv.watch_with_history(">").await?)
+    }
+
+    /// Update the in-memory copy from a watched KV entry. Returns the
+    /// revocation it records, if any.
+    pub fn apply(&self, entry: Entry) -> Option<Revocation> {
+        self.revocations.record(entry.key, entry.operation, &entry.value)
+    }
+
+    /// The revocation of a JWT issued at `issued_at` to `user_nkey_public`
+    /// for `cid`, if any.
+    pub fn revoked(&self, cid: &str, user_nkey_public: &str, issued_at: u64) -> Option<Revocation> {
+        self.revocations.revoked(cid, user_nkey_public, issued_at)
+    }
+}
+
+/// In-memory copy of a revocations bucket, by KV key.
+#[derive(Debug, Default)]
+struct RevocationSet {
+    revocations: RwLock<HashMap<String, Revocation>>,
+}
+
+impl RevocationSet {
+    /// Record a KV operation on `key`. Returns the revocation it records, if any.
+    fn record(&self, key: String, operation: Operation, value: &[u8]) -> Option<Revocation> {
+        let mut revocations = self.revocations.write().expect("revocations lock poisoned");
+        match operation {
+            Operation::Put => match serde_json::from_slice::<Revocation>(value) {
+                Ok(revocation) => {
+                    info!(
+                        %key,
+                        cid = revocation.cid.as_deref().unwrap_or("-"),
+                        reason = %revocation.reason,
+                        revoked_at = revocation.revoked_at,
+                        "credentials revoked"
+                    );
+                    revocations.insert(key, revocation.clone());
+                    Some(revocation)
+                }
+                Err(e) => {
+                    warn!(%key, error = %e, "ignoring malformed revocation entry");
+                    None
+                }
+            },
+            Operation::Delete | Operation::Purge => {
+                revocations.remove(&key);
+                None
+            }
+        }
+    }
+
+    fn revoked(&self, cid: &str, user_nkey_public: &str, issued_at: u64) -> Option<Revocation> {
+        let revocations = self.revocations.read().expect("revocations lock poisoned");
+        [format!("nkey.{user_nkey_public}"), format!("cid.{cid}")]
+            .iter()
+            .filter_map(|key| revocations.get(key))
+            .find(|revocation| revocation.revokes(cid, user_nkey_public, issued_at))
+            .cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NKEY: &str = "UA4EDEL63JERZCEFSCP3RI76BW2NVZLPSG5QW52DZVXDTDZ3BW2HGWIN";
+
+    fn put(set: &RevocationSet, revocation: &Revocation) -> Option<Revocation> {
+        let key = revocation.kv_key().unwrap();
+        set.record(key, Operation::Put, &serde_json::to_vec(revocation).unwrap())
+    }
+
+    fn revocation(cid: Option<&str>, user_nkey_public: Option<&str>) -> Revocation {
+        Revocation {
+            cid: cid.map(str::to_string),
+            user_nkey_public: user_nkey_public.map(str::to_string),
+            revoked_at: 1_000,
+            reason: "spoofing".to_string(),
+        }
+    }
+
+    #[test]
+    fn revokes_jwts_issued_before_by_cid_or_key() {
+        let set = RevocationSet::default();
+        assert!(set.revoked("100000", NKEY, 900).is_none());
+
+        let by_cid = revocation(Some("100000"), None);
+        assert_eq!(put(&set, &by_cid), Some(by_cid.clone()));
+        assert_eq!(set.revoked("100000", "UOTHER", 900), Some(by_cid));
+        assert!(set.revoked("100000", NKEY, 1_001).is_none(), "issued after the revocation");
+        assert!(set.revoked("200000", NKEY, 900).is_none());
+
+        let by_key = revocation(None, Some(NKEY));
+        put(&set, &by_key);
+        assert_eq!(set.revoked("200000", NKEY, 900), Some(by_key));
+    }
+
+    #[test]
+    fn deletes_and_malformed_entries() {
+        let set = RevocationSet::default();
+        let by_cid = revocation(Some("100000"), None);
+        put(&set, &by_cid);
+        assert!(set.record("cid.100000".to_string(), Operation::Delete, b"").is_none());
+        assert!(set.revoked("100000", NKEY, 900).is_none());
+
+        put(&set, &by_cid);
+        assert!(set.record("cid.100000".to_string(), Operation::Purge, b"").is_none());
+        assert!(set.revoked("100000", NKEY, 900).is_none());
+
+        assert!(set.record("cid.300000".to_string(), Operation::Put, b"not json").is_none());
+        assert!(set.revoked("300000", NKEY, 900).is_none());
+    }
+}
@@ -0,0 +1,461 @@
+//! Sender verification (anti-spoofing).
+//!
+//! The only identity the server can trust is the network address embedded in
+//! the outbox subject an envelope was published on: NATS permissions restrict
+//! every client to its own outbox. Everything written inside the envelope —
+//! routing source, CPDLC callsigns, ACARS aircraft routing — is checked
+//! against that address and the station registry before the envelope is
+//! processed. The sender's role, read from its verified token (see
+//! [`crate::identity`]), then decides which kinds of messages it may send.
+
+use std::fmt;
+
+use openlink_models::{
+    AcarsEnvelope, AcarsMessage, CpdlcEnvelope, CpdlcMessageType, CpdlcMetaMessage,
+    MessageDirection, MetaMessage, NetworkAddress, NetworkId, OpenLinkEnvelope, OpenLinkMessage,
+    OpenLinkRoutingEndpoint, RejectionCode, StationMetadata, StationRole, UserRole, find_definition,
+};
+
+use crate::station_registry::StationEntry;
+
+/// Reason an envelope failed sender verification.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SenderRejection {
+    pub code: RejectionCode,
+    pub reason: String,
+}
+
+impl SenderRejection {
+    fn new(code: RejectionCode, reason: impl Into<String>) -> Self {
+        Self {
+            code,
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for SenderRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code, self.reason)
+    }
+}
+
+impl std::error::Error for SenderRejection {}
+
+/// Check that the envelope's routing source is the outbox owner.
+pub fn check_routing_source(
+    network_id: &NetworkId,
+    sender: &NetworkAddress,
+    envelope: &OpenLinkEnvelope,
+) -> Result<(), SenderRejection> {
+    match &envelope.routing.source {
+        OpenLinkRoutingEndpoint::Address(network, address)
+            if network == network_id && address == sender =>
+        {
+            Ok(())
+        }
+        other => Err(SenderRejection::new(
+            RejectionCode::SenderMismatch,
+            format!("routing source {other:?} does not match outbox sender {sender}"),
+        )),
+    }
+}
+
+/// Check that `role` may send this envelope.
+///
+/// Observers may not send anything. Registering as an ATC station and
+/// uplinking (any CPDLC message not sourced by the aircraft) need a
+/// controller or bot; registering as an aircraft and downlinking need a
+/// pilot; a registration without a station role is checked as the role
+/// [`station_role`] gives it. Application elements must also travel in the
+/// direction their definition says, so a pilot cannot slip a `UM` element
+/// into a downlink.
+pub fn check_role(role: UserRole, envelope: &OpenLinkEnvelope) -> Result<(), SenderRejection> {
+    let denied = |reason: String| Err(SenderRejection::new(RejectionCode::RoleNotPermitted, reason));
+    if !role.may_publish() {
+        return denied(format!("{role} accounts are read-only"));
+    }
+
+    match &envelope.payload {
+        OpenLinkMessage::Meta(MetaMessage::StationStatus(_, _, _, metadata)) => match station_role(role, metadata) {
+            Some(StationRole::Atc) if !role.may_uplink() => {
+                denied(format!("{role} accounts may not register ATC stations"))
+            }
+            Some(StationRole::Aircraft) if !role.may_downlink() => {
+                denied(format!("{role} accounts may not register aircraft"))
+            }
+            _ => Ok(()),
+        },
+        OpenLinkMessage::Meta(_) => Ok(()),
+        OpenLinkMessage::Acars(acars) => {
+            let AcarsMessage::CPDLC(ref cpdlc) = acars.message;
+            let (direction, allowed) = if cpdlc.source == acars.routing.aircraft.callsign {
+                (MessageDirection::Downlink, role.may_downlink())
+            } else {
+                (MessageDirection::Uplink, role.may_uplink())
+            };
+            if !allowed {
+                return denied(format!("{role} accounts may not send {direction} messages"));
+            }
+            if let CpdlcMessageType::Application(ref app) = cpdlc.message
+                && let Some(element) = app
+                    .elements
+                    .iter()
+                    .find(|e| find_definition(&e.id).is_some_and(|d| d.direction != direction))
+            {
+                return denied(format!("element {} may not be sent as {direction}", element.id));
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Station role a registration claims: the one in its metadata, else the
+/// one its sender's role implies (aircraft for pilots, ATC for controllers
+/// and bots), so that a station never registers without a role.
+pub fn station_role(role: UserRole, metadata: &StationMetadata) -> Option<StationRole> {
+    metadata.role.or(match role {
+        UserRole::Pilot => Some(StationRole::Aircraft),
+        UserRole::Controller | UserRole::Bot => Some(StationRole::Atc),
+        UserRole::Observer => None,
+    })
+}
+
+/// Check that the envelope only uses callsigns in `allowed`, the callsign
+/// tags of the sender's token. An empty list allows any callsign.
+///
+/// Covers the callsign a station registers and the source of CPDLC
+/// messages; the registry then binds the callsign to the sender.
+pub fn check_callsigns(allowed: &[String], envelope: &OpenLinkEnvelope) -> Result<(), SenderRejection> {
+    if allowed.is_empty() {
+        return Ok(());
+    }
+    let callsign = match &envelope.payload {
+        OpenLinkMessage::Meta(MetaMessage::StationStatus(_, _, endpoint, _)) => &endpoint.callsign,
+        OpenLinkMessage::Acars(acars) => {
+            let AcarsMessage::CPDLC(ref cpdlc) = acars.message;
+            &cpdlc.source
+        }
+        OpenLinkMessage::Meta(_) => return Ok(()),
+    };
+    if allowed.iter().any(|c| c.eq_ignore_ascii_case(callsign.as_str())) {
+        Ok(())
+    } else {
+        Err(SenderRejection::new(
+            RejectionCode::CallsignNotOwned,
+            format!("callsign {callsign} is not allowed for this account"),
+        ))
+    }
+}
+
+/// Check that the CPDLC source callsign belongs to `sender`.
+///
+/// `source_entry` is the registry entry currently indexed for
+/// `cpdlc.source`. For downlinks (source is the aircraft) the ACARS aircraft
+/// routing must also match that registration, since it keys the session.
+/// Station-originated messages must address the aircraft named in the ACARS
+/// routing, except station-to-station logon forwarding.
+pub fn check_cpdlc_sender(
+    sender: &NetworkAddress,
+    cpdlc: &CpdlcEnvelope,
+    acars: &AcarsEnvelope,
+    source_entry: Option<&StationEntry>,
+) -> Result<(), SenderRejection> {
+    let Some(entry) = source_entry else {
+        return Err(SenderRejection::new(
+            RejectionCode::CallsignNotOwned,
+            format!("callsign {} is not registered", cpdlc.source),
+        ));
+    };
+    if entry.network_address != *sender {
+        return Err(SenderRejection::new(
+            RejectionCode::CallsignNotOwned,
+            format!("callsign {} is registered to another address", cpdlc.source),
+        ));
+    }
+    if !entry.status.is_reachable() {
+        return Err(SenderRejection::new(
+            RejectionCode::CallsignOffline,
+            format!("callsign {} is offline", cpdlc.source),
+        ));
+    }
+
+    let aircraft = &acars.routing.aircraft;
+    if cpdlc.source == aircraft.callsign {
+        if entry.acars_endpoint.address != aircraft.address {
+            return Err(SenderRejection::new(
+                RejectionCode::AircraftMismatch,
+                format!(
+                    "aircraft address {} does not match registered address {} for {}",
+                    aircraft.address, entry.acars_endpoint.address, aircraft.callsign
+                ),
+            ));
+        }
+    } else {
+        let station_to_station = matches!(
+            cpdlc.message,
+            CpdlcMessageType::Meta(CpdlcMetaMessage::LogonForward { .. })
+        );
+        if !station_to_station && cpdlc.destination != aircraft.callsign {
+            return Err(SenderRejection::new(
+                RejectionCode::AircraftMismatch,
+                format!(
+                    "destination {} does not match aircraft routing {}",
+                    cpdlc.destination, aircraft.callsign
+                ),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use openlink_models::{
+        AcarsRoutingEndpoint, MessageBuilder, OpenLinkMessage, StationId, StationMetadata,
+        StationStatus,
+    };
+
+    fn entry(callsign: &str, acars_address: &str, address: &str, status: StationStatus) -> StationEntry {
+        StationEntry {
+            station_id: StationId::new(address),
+            status,
+            last_updated: Utc::now(),
+            network_address: NetworkAddress::from(address),
+            acars_endpoint: AcarsRoutingEndpoint::new(callsign, acars_address),
+            metadata: StationMetadata::default(),
+        }
+    }
+
+    fn split(msg: OpenLinkMessage) -> (CpdlcEnvelope, AcarsEnvelope) {
+        let OpenLinkMessage::Acars(acars) = msg else {
+            panic!("expected ACARS message");
+        };
+        let openlink_models::AcarsMessage::CPDLC(ref cpdlc) = acars.message;
+        (cpdlc.clone(), acars)
+    }
+
+    #[test]
+    fn routing_source_must_match_outbox() {
+        let network = NetworkId::new("demonetwork");
+        let envelope = MessageBuilder::station_status("100000", "AFR123", "39401A")
+            .online()
+            .envelope()
+            .source_address("demonetwork", "100000")
+            .destination_server("demonetwork")
+            .build();
+        assert!(check_routing_source(&network, &NetworkAddress::from("100000"), &envelope).is_ok());
+        let err = check_routing_source(&network, &NetworkAddress::from("888888"), &envelope)
+            .unwrap_err();
+        assert_eq!(err.code, RejectionCode::SenderMismatch);
+    }
+
+    #[test]
+    fn uplink_from_station_owner_is_accepted() {
+        let (cpdlc, acars) = split(
+            MessageBuilder::cpdlc("AFR123", "39401A")
+                .from("LFPG")
+                .to("AFR123")
+                .connection_request()
+                .build(),
+        );
+        let lfpg = entry("LFPG", "LFPGAXA", "888888", StationStatus::Online);
+        assert!(check_cpdlc_sender(&NetworkAddress::from("888888"), &cpdlc, &acars, Some(&lfpg)).is_ok());
+    }
+
+    #[test]
+    fn uplink_claiming_foreign_station_is_rejected() {
+        let (cpdlc, acars) = split(
+            MessageBuilder::cpdlc("AFR123", "39401A")
+                .from("LFPG")
+                .to("AFR123")
+                .connection_request()
+                .build(),
+        );
+        let lfpg = entry("LFPG", "LFPGAXA", "888888", StationStatus::Online);
+        let err = check_cpdlc_sender(&NetworkAddress::from("100000"), &cpdlc, &acars, Some(&lfpg))
+            .unwrap_err();
+        assert_eq!(err.code, RejectionCode::CallsignNotOwned);
+
+        let err = check_cpdlc_sender(&NetworkAddress::from("100000"), &cpdlc, &acars, None)
+            .unwrap_err();
+        assert_eq!(err.code, RejectionCode::CallsignNotOwned);
+    }
+
+    #[test]
+    fn offline_callsign_is_rejected() {
+        let (cpdlc, acars) = split(
+            MessageBuilder::cpdlc("AFR123", "39401A")
+                .from("AFR123")
+                .to("LFPG")
+                .logon_request("LFPG", "LFPG", "KJFK")
+                .build(),
+        );
+        let afr = entry("AFR123", "39401A", "100000", StationStatus::Offline);
+        let err = check_cpdlc_sender(&NetworkAddress::from("100000"), &cpdlc, &acars, Some(&afr))
+            .unwrap_err();
+        assert_eq!(err.code, RejectionCode::CallsignOffline);
+    }
+
+    #[test]
+    fn downlink_with_foreign_aircraft_address_is_rejected() {
+        let (cpdlc, acars) = split(
+            MessageBuilder::cpdlc("AFR123", "AAAAAA")
+                .from("AFR123")
+                .to("LFPG")
+                .logon_request("LFPG", "LFPG", "KJFK")
+                .build(),
+        );
+        let afr = entry("AFR123", "39401A", "100000", StationStatus::Online);
+        let err = check_cpdlc_sender(&NetworkAddress::from("100000"), &cpdlc, &acars, Some(&afr))
+            .unwrap_err();
+        assert_eq!(err.code, RejectionCode::AircraftMismatch);
+    }
+
+    #[test]
+    fn uplink_to_other_aircraft_is_rejected_but_logon_forward_allowed() {
+        let (cpdlc, acars) = split(
+            MessageBuilder::cpdlc("AFR123", "39401A")
+                .from("LFPG")
+                .to("BAW456")
+                .connection_request()
+                .build(),
+        );
+        let lfpg = entry("LFPG", "LFPGAXA", "888888", StationStatus::Online);
+        let err = check_cpdlc_sender(&NetworkAddress::from("888888"), &cpdlc, &acars, Some(&lfpg))
+            .unwrap_err();
+        assert_eq!(err.code, RejectionCode::AircraftMismatch);
+
+        let (cpdlc, acars) = split(
+            MessageBuilder::cpdlc("AFR123", "39401A")
+                .from("LFPG")
+                .to("EGLL")
+                .logon_forward("AFR123", "LFPG", "KJFK", "EGLL")
+                .build(),
+        );
+        assert!(check_cpdlc_sender(&NetworkAddress::from("888888"), &cpdlc, &acars, Some(&lfpg)).is_ok());
+    }
+
+    fn wrap(msg: OpenLinkMessage) -> OpenLinkEnvelope {
+        MessageBuilder::envelope(msg)
+            .source_address("demonetwork", "100000")
+            .destination_server("demonetwork")
+            .build()
+    }
+
+    #[test]
+    fn roles_limit_message_direction() {
+        let downlink = wrap(
+            MessageBuilder::cpdlc("AFR123", "39401A")
+                .from("AFR123")
+                .to("LFPG")
+                .downlink("DM6", vec![])
+                .build(),
+        );
+        let uplink = wrap(
+            MessageBuilder::cpdlc("AFR123", "39401A")
+                .from("LFPG")
+                .to("AFR123")
+                .uplink("UM0", vec![])
+                .build(),
+        );
+
+        assert!(check_role(UserRole::Pilot, &downlink).is_ok());
+        assert!(check_role(UserRole::Controller, &uplink).is_ok());
+        assert!(check_role(UserRole::Bot, &uplink).is_ok());
+        for (role, envelope) in [
+            (UserRole::Pilot, &uplink),
+            (UserRole::Controller, &downlink),
+            (UserRole::Observer, &downlink),
+        ] {
+            let err = check_role(role, envelope).unwrap_err();
+            assert_eq!(err.code, RejectionCode::RoleNotPermitted, "{role}");
+        }
+
+        // An uplink element smuggled into a downlink.
+        let smuggled = wrap(
+            MessageBuilder::cpdlc("AFR123", "39401A")
+                .from("AFR123")
+                .to("LFPG")
+                .downlink("UM20", vec![])
+                .build(),
+        );
+        let err = check_role(UserRole::Pilot, &smuggled).unwrap_err();
+        assert_eq!(err.code, RejectionCode::RoleNotPermitted);
+    }
+
+    #[test]
+    fn roles_limit_station_registration() {
+        let station = |role: StationRole| {
+            wrap(
+                MessageBuilder::station_status("100000", "LFPG", "LFPGAXA")
+                    .online()
+                    .metadata(StationMetadata {
+                        role: Some(role),
+                        ..StationMetadata::default()
+                    })
+                    .build(),
+            )
+        };
+        assert!(check_role(UserRole::Controller, &station(StationRole::Atc)).is_ok());
+        assert!(check_role(UserRole::Pilot, &station(StationRole::Aircraft)).is_ok());
+        assert!(check_role(UserRole::Pilot, &station(StationRole::Atc)).is_err());
+        assert!(check_role(UserRole::Controller, &station(StationRole::Aircraft)).is_err());
+        assert!(check_role(UserRole::Observer, &station(StationRole::Aoc)).is_err());
+    }
+
+    #[test]
+    fn station_without_role_takes_the_sender_role() {
+        let station = wrap(
+            MessageBuilder::station_status("100000", "LFPG", "LFPGAXA")
+                .online()
+                .metadata(StationMetadata::default())
+                .build(),
+        );
+        assert!(check_role(UserRole::Pilot, &station).is_ok());
+        assert!(check_role(UserRole::Controller, &station).is_ok());
+        assert!(check_role(UserRole::Observer, &station).is_err());
+
+        let metadata = StationMetadata::default();
+        assert_eq!(station_role(UserRole::Pilot, &metadata), Some(StationRole::Aircraft));
+        assert_eq!(station_role(UserRole::Controller, &metadata), Some(StationRole::Atc));
+        assert_eq!(station_role(UserRole::Bot, &metadata), Some(StationRole::Atc));
+        let atc = StationMetadata {
+            role: Some(StationRole::Atc),
+            ..StationMetadata::default()
+        };
+        assert_eq!(station_role(UserRole::Pilot, &atc), Some(StationRole::Atc));
+    }
+
+    #[test]
+    fn callsign_tags_limit_registration_and_sources() {
+        let allowed = ["LFPG_TWR".to_string()];
+        let station = |callsign: &str| {
+            wrap(
+                MessageBuilder::station_status("900001", callsign, "LFPGAXA")
+                    .online()
+                    .build(),
+            )
+        };
+        let uplink = |source: &str| {
+            wrap(
+                MessageBuilder::cpdlc("AFR123", "39401A")
+                    .from(source)
+                    .to("AFR123")
+                    .uplink("UM0", vec![])
+                    .build(),
+            )
+        };
+
+        assert!(check_callsigns(&allowed, &station("lfpg_twr")).is_ok());
+        assert!(check_callsigns(&allowed, &uplink("LFPG_TWR")).is_ok());
+        assert!(check_callsigns(&[], &station("EGLL_TWR")).is_ok());
+        for envelope in [station("EGLL_TWR"), uplink("EGLL_TWR")] {
+            let err = check_callsigns(&allowed, &envelope).unwrap_err();
+            assert_eq!(err.code, RejectionCode::CallsignNotOwned);
+        }
+    }
+}
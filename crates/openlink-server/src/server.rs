@@ -3,23 +3,47 @@
 use anyhow::Result;
 use chrono::Duration as ChronoDuration;
 use futures::StreamExt;
+use futures::stream::BoxStream;
 use std::collections::HashSet;
-use std::time::Duration as StdDuration;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration as StdDuration, Instant};
 use openlink_models::{
-    AcarsEndpointCallsign, AcarsEnvelope, MetaMessage, NetworkAddress, NetworkId,
-    OpenLinkEnvelope, OpenLinkMessage, OpenLinkRouting, StationStatus,
+    AcarsEndpointCallsign, AcarsEnvelope, AcarsMessage, CpdlcEnvelope, DirectoryEvent, DirectoryQuery,
+    MetaMessage, NetworkAddress, NetworkId, NoticeCode, OpenLinkEnvelope, OpenLinkMessage,
+    OpenLinkRouting, RejectionCode, StationMetadata, UserRole,
 };
-use openlink_sdk::{MessageBuilder, NatsSubjects, OpenLinkClient};
+use openlink_sdk::{MessageBuilder, NatsSubjects, OpenLinkClient, Revocation, ServerCredential};
 use tracing::{debug, error, info, warn};
 
 use crate::acars::{CPDLCServer, CPDLCSession};
-use crate::station_registry;
+use crate::ban_list;
+use crate::dedup;
+use crate::directory;
+use crate::federation;
+use crate::identity::TokenVerifier;
+use crate::inboxes;
+use crate::metrics::{self, Metrics};
+use crate::pending;
+use crate::presence;
+use crate::rate_limit::{self, Decision, MessageClass, RateLimitConfig, RateLimiter};
+use crate::revocations;
+use crate::sender_check::{self, SenderRejection};
+use crate::station_registry::{self, CallsignPolicy, ClaimOutcome};
 
 #[derive(Debug, Clone, Copy)]
 pub struct PresenceConfig {
     pub lease_ttl_seconds: i64,
     pub sweep_interval_seconds: u64,
     pub auto_end_service_on_station_offline: bool,
+    /// How long messages for an offline recipient are kept; `0` disables
+    /// store-and-forward.
+    pub pending_delivery_ttl_seconds: i64,
+    /// How long unacknowledged messages are kept for durable inbox
+    /// consumers; `0` disables the inbox stream.
+    pub inbox_retention_seconds: i64,
+    /// How long envelope ids are remembered to drop retried envelopes;
+    /// `0` disables deduplication.
+    pub dedup_window_seconds: i64,
 }
 
 impl Default for PresenceConfig {
@@ -28,6 +52,9 @@ impl Default for PresenceConfig {
             lease_ttl_seconds: 90,
             sweep_interval_seconds: 20,
             auto_end_service_on_station_offline: true,
+            pending_delivery_ttl_seconds: 120,
+            inbox_retention_seconds: 600,
+            dedup_window_seconds: 300,
         }
     }
 }
@@ -43,31 +70,80 @@ pub struct OpenLinkServer {
     cpdlc_server: CPDLCServer,
     station_registry: station_registry::StationRegistry,
     presence_config: PresenceConfig,
+    system_client: Option<async_nats::Client>,
+    pending: Option<pending::PendingDeliveries>,
+    seen_envelopes: Option<dedup::SeenEnvelopes>,
+    rate_limiter: Mutex<RateLimiter>,
+    ban_list: ban_list::BanList,
+    revocations: revocations::RevocationList,
+    token_verifier: TokenVerifier,
+    metrics: Arc<Metrics>,
+    drain_notice: Option<String>,
+    federation: Option<federation::Federation>,
 }
 
 impl OpenLinkServer {
     /// Create a new server for the given network.
     ///
-    /// Connects to NATS via the SDK using a server secret, obtaining
+    /// Connects to NATS via the SDK using the server's credential, obtaining
     /// wildcard permissions for all outbox/inbox subjects and JetStream.
     pub async fn new(
         network_id: NetworkId,
         nats_url: &str,
         auth_url: &str,
-        server_secret: &str,
+        server_credential: &ServerCredential,
         clean: bool,
         presence_config: PresenceConfig,
+        callsign_policy: CallsignPolicy,
     ) -> Result<Self> {
         let client =
-            OpenLinkClient::connect_as_server(nats_url, auth_url, server_secret, &network_id)
+            OpenLinkClient::connect_as_server(nats_url, auth_url, server_credential, &network_id)
                 .await
                 .map_err(|e| anyhow::anyhow!("SDK connection failed: {e}"))?;
 
         let js = async_nats::jetstream::new(client.nats_client().clone());
 
         let station_registry =
-            station_registry::StationRegistry::new(network_id.clone(), js.clone(), clean).await?;
+            station_registry::StationRegistry::new(network_id.clone(), js.clone(), clean, callsign_policy)
+                .await?;
         let cpdlc_server = CPDLCServer::new(network_id.clone(), js.clone(), clean).await?;
+        let pending = if presence_config.pending_delivery_ttl_seconds > 0 {
+            Some(
+                pending::PendingDeliveries::new(
+                    network_id.clone(),
+                    js.clone(),
+                    clean,
+                    ChronoDuration::seconds(presence_config.pending_delivery_ttl_seconds),
+                )
+                .await?,
+            )
+        } else {
+            None
+        };
+        if presence_config.inbox_retention_seconds > 0 {
+            inboxes::ensure_stream(
+                &network_id,
+                &js,
+                clean,
+                ChronoDuration::seconds(presence_config.inbox_retention_seconds),
+            )
+            .await?;
+        }
+        let seen_envelopes = if presence_config.dedup_window_seconds > 0 {
+            Some(
+                dedup::SeenEnvelopes::new(
+                    &network_id,
+                    &js,
+                    clean,
+                    ChronoDuration::seconds(presence_config.dedup_window_seconds),
+                )
+                .await?,
+            )
+        } else {
+            None
+        };
+        let ban_list = ban_list::BanList::new(&network_id, &js, clean).await?;
+        let revocations = revocations::RevocationList::new(&network_id, &js).await?;
 
         Ok(Self {
             network_id,
@@ -75,13 +151,67 @@ impl OpenLinkServer {
             cpdlc_server,
             station_registry,
             presence_config,
+            system_client: None,
+            pending,
+            seen_envelopes,
+            rate_limiter: Mutex::new(RateLimiter::new(RateLimitConfig::default())),
+            ban_list,
+            revocations,
+            token_verifier: TokenVerifier::new(auth_url),
+            metrics: Arc::new(Metrics::default()),
+            drain_notice: None,
+            federation: None,
         })
     }
 
+    /// Detect disconnections immediately from NATS connection advisories,
+    /// read through a system-account connection. The heartbeat lease stays
+    /// as the fallback.
+    pub fn with_connection_events(mut self, system_client: async_nats::Client) -> Self {
+        self.system_client = Some(system_client);
+        self
+    }
+
+    /// Replace the default per-address rate limits.
+    pub fn with_rate_limits(mut self, config: RateLimitConfig) -> Self {
+        self.rate_limiter = Mutex::new(RateLimiter::new(config));
+        self
+    }
+
+    /// Record into a registry shared with the other networks and the
+    /// metrics endpoint.
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Peer this network with the networks named in `rules`.
+    pub async fn with_federation(mut self, rules: federation::ExportRules) -> Result<Self> {
+        let js = async_nats::jetstream::new(self.client.nats_client().clone());
+        let federation = federation::Federation::new(self.network_id.clone(), js, rules).await?;
+        if federation.is_empty() {
+            return Ok(self);
+        }
+        info!(network = %self.network_id, peers = ?federation.peer_networks().collect::<Vec<_>>(), "federation enabled");
+        self.federation = Some(federation);
+        Ok(self)
+    }
+
+    /// Send `message` as a `Maintenance` notice to every online station when
+    /// the server shuts down.
+    pub fn with_drain_notice(mut self, message: String) -> Self {
+        self.drain_notice = Some(message);
+        self
+    }
+
     /// Subscribe to the network-wide outbox wildcard and route every envelope
     /// to the appropriate handler, then forward the result to the destination
     /// station's inbox.
-    pub async fn run(&self) {
+    ///
+    /// When `shutdown` changes, the outbox subscription is drained: envelopes
+    /// already received are still handled, then the drain notice is sent and
+    /// the method returns.
+    pub async fn run(&self, mut shutdown: tokio::sync::watch::Receiver<bool>) {
         let subject = NatsSubjects::outbox_wildcard(&self.network_id);
         info!(
             network = %self.network_id,
@@ -89,6 +219,9 @@ impl OpenLinkServer {
             lease_ttl_seconds = self.presence_config.lease_ttl_seconds,
             sweep_interval_seconds = self.presence_config.sweep_interval_seconds,
             auto_end_service_on_station_offline = self.presence_config.auto_end_service_on_station_offline,
+            pending_delivery_ttl_seconds = self.presence_config.pending_delivery_ttl_seconds,
+            inbox_retention_seconds = self.presence_config.inbox_retention_seconds,
+            dedup_window_seconds = self.presence_config.dedup_window_seconds,
             "server listening"
         );
 
@@ -100,125 +233,842 @@ impl OpenLinkServer {
             }
         };
 
+        let mut directory_requests = match self
+            .client
+            .nats_client()
+            .subscribe(NatsSubjects::directory_query(&self.network_id))
+            .await
+        {
+            Ok(sub) => sub,
+            Err(e) => {
+                error!(network = %self.network_id, error = %e, "failed to subscribe to directory requests");
+                return;
+            }
+        };
+
+        let mut ban_updates = self.ban_updates().await;
+        let mut revocation_updates = self.revocation_updates().await;
+        let started_at = chrono::Utc::now().timestamp().max(0) as u64;
+        let mut federation_relays = self.federation_relays().await;
+        let mut last_counters = rate_limit::RateLimitCounters::default();
+        let mut connection_events = self.connection_events().await;
+        let mut connections = presence::ConnectionTracker::default();
+
         let ttl = ChronoDuration::seconds(self.presence_config.lease_ttl_seconds.max(1));
         let mut presence_ticker = tokio::time::interval(StdDuration::from_secs(
             self.presence_config.sweep_interval_seconds.max(1),
         ));
+        let mut draining = false;
 
         loop {
             tokio::select! {
+                _ = shutdown.changed(), if !draining => {
+                    info!(network = %self.network_id, "draining outbox subscription");
+                    draining = true;
+                    if let Err(e) = subscription.drain().await {
+                        warn!(network = %self.network_id, error = %e, "failed to drain outbox subscription");
+                        break;
+                    }
+                }
                 maybe_message = subscription.next() => {
                     let Some(message) = maybe_message else {
                         break;
                     };
+                    let received = Instant::now();
+
+                    let Some(sender) = NatsSubjects::parse_outbox_sender(&message.subject)
+                        .map(NetworkAddress::from)
+                    else {
+                        warn!(subject = %message.subject, "ignoring envelope on unexpected subject");
+                        self.metrics.handler_error(&self.network_id, "unexpected_subject");
+                        continue;
+                    };
 
-                    let envelope = match serde_json::from_slice::<OpenLinkEnvelope>(&message.payload) {
+                    if let Some(ban) = self.ban_list.banned(&sender, chrono::Utc::now()) {
+                        self.rate_limiter.lock().expect("rate limiter lock poisoned").record_banned();
+                        debug!(%sender, reason = %ban.reason, "dropping envelope from banned address");
+                        self.metrics.handler_error(&self.network_id, "banned");
+                        continue;
+                    }
+
+                    let mut envelope = match serde_json::from_slice::<OpenLinkEnvelope>(&message.payload) {
                         Ok(env) => env,
                         Err(e) => {
                             warn!(error = %e, "ignoring malformed envelope");
+                            self.metrics.handler_error(&self.network_id, "malformed_envelope");
+                            continue;
+                        }
+                    };
+
+                    let class = MessageClass::of(&envelope.payload);
+                    let decision = self
+                        .rate_limiter
+                        .lock()
+                        .expect("rate limiter lock poisoned")
+                        .check(&sender, class, Instant::now());
+                    if let Decision::Limited { notify } = decision {
+                        debug!(%sender, ?class, envelope_id = %envelope.id, "dropping envelope over rate limit");
+                        if notify {
+                            warn!(%sender, ?class, "sender over rate limit");
+                            let rejection = SenderRejection {
+                                code: RejectionCode::RateLimited,
+                                reason: format!("too many {class:?} messages, retry later"),
+                            };
+                            self.send_rejection(&sender, &envelope, rejection).await;
+                        }
+                        self.metrics.handler_error(&self.network_id, "rate_limited");
+                        continue;
+                    }
+
+                    let started = Instant::now();
+                    let verified = self.verify_sender(&sender, &envelope).await;
+                    self.metrics.kv_operation(&self.network_id, "sender_check", started.elapsed());
+                    let role = match verified {
+                        Ok(role) => role,
+                        Err(rejection) => {
+                            warn!(
+                                %sender,
+                                envelope_id = %envelope.id,
+                                code = %rejection.code,
+                                reason = %rejection.reason,
+                                "rejecting envelope"
+                            );
+                            self.send_rejection(&sender, &envelope, rejection).await;
+                            self.metrics.handler_error(&self.network_id, "sender_rejected");
                             continue;
                         }
                     };
+                    // The sender's JWT is not forwarded, queued or relayed.
+                    envelope.token.clear();
+
+                    if let Some(seen) = &self.seen_envelopes {
+                        let started = Instant::now();
+                        let first = seen.first_receipt(&sender, &envelope.id).await;
+                        self.metrics.kv_operation(&self.network_id, "dedup", started.elapsed());
+                        if !first {
+                            debug!(%sender, envelope_id = %envelope.id, "dropping duplicate envelope");
+                            self.metrics.handler_error(&self.network_id, "duplicate");
+                            continue;
+                        }
+                    }
+
+                    if let Some(expires_at) = envelope.expires_at.filter(|_| envelope.is_expired(chrono::Utc::now())) {
+                        info!(%sender, envelope_id = %envelope.id, %expires_at, "discarding expired envelope");
+                        let rejection = SenderRejection {
+                            code: RejectionCode::Expired,
+                            reason: format!("envelope expired at {expires_at}"),
+                        };
+                        self.send_rejection(&sender, &envelope, rejection).await;
+                        self.metrics.handler_error(&self.network_id, "expired");
+                        continue;
+                    }
+
+                    if let Some(home) = self.federated_aircraft_home(&envelope).await {
+                        debug!(%sender, envelope_id = %envelope.id, %home, "relaying to the aircraft's home network");
+                        self.metrics.message_routed(&self.network_id, &envelope.payload);
+                        self.relay(&home, federation::RelayKind::Process, &envelope).await;
+                        continue;
+                    }
 
                     let (destination_station, maybe_session, forward_envelope) = match envelope.payload {
                         OpenLinkMessage::Meta(ref meta) => {
                             debug!(?meta, "received meta message");
-                            let result = self.handle_meta_message(meta, &envelope).await;
+                            let started = Instant::now();
+                            let result = self.handle_meta_message(meta, &envelope, role).await;
+                            self.metrics.kv_operation(&self.network_id, "registry_update", started.elapsed());
                             match result {
                                 Ok(dest) => (dest, None, envelope.clone()),
                                 Err(e) => {
                                     warn!(error = %e, "handler returned error");
+                                    self.metrics.handler_error(&self.network_id, "meta_handler");
+                                    self.forget_envelope(&sender, &envelope).await;
                                     continue;
                                 }
                             }
                         }
                         OpenLinkMessage::Acars(ref acars) => {
                             debug!(?acars, "received ACARS message");
-                            match self.handle_acars_message(acars, &envelope).await {
+                            let started = Instant::now();
+                            let result = self.handle_acars_message(acars, &envelope).await;
+                            self.metrics.kv_operation(&self.network_id, "session_update", started.elapsed());
+                            match result {
                                 Ok((dest, session, modified_env)) => (dest, session, modified_env),
                                 Err(e) => {
                                     warn!(error = %e, "handler returned error");
+                                    self.metrics.handler_error(&self.network_id, "acars_handler");
+                                    self.forget_envelope(&sender, &envelope).await;
                                     continue;
                                 }
                             }
                         }
                     };
-
-                    // Forward the (possibly modified) message to the destination station
-                    if let Some(ref dest) = destination_station {
-                        debug!(?dest, "forwarding to destination station");
-                        let mut transferred = forward_envelope;
-                        transferred.routing = OpenLinkRouting {
-                            source: envelope.routing.destination.clone(),
-                            destination: openlink_models::OpenLinkRoutingEndpoint::Address(
-                                self.network_id.clone(),
-                                dest.network_address.clone(),
-                            ),
-                        };
-                        if let Err(e) = self
-                            .client
-                            .send_to_station(&dest.network_address, &transferred)
-                            .await
-                        {
-                            error!(error = %e, "failed to forward message");
-                        }
-                    }
-
-                    // Broadcast SessionUpdate to both parties if session was mutated
-                    if let Some(ref session) = maybe_session {
-                        self.broadcast_session_update(session, &envelope).await;
+                    self.metrics.message_routed(&self.network_id, &envelope.payload);
+                    self.deliver_result(&envelope, destination_station, maybe_session, forward_envelope, received)
+                        .await;
+                }
+                Some(relay) = federation_relays.next() => {
+                    self.handle_relay(relay).await;
+                }
+                Some(entry) = ban_updates.next() => {
+                    self.ban_list.apply(entry);
+                }
+                Some(entry) = revocation_updates.next() => {
+                    // Entries older than the server were enforced when made;
+                    // replaying them would disconnect users who logged in
+                    // again since.
+                    if let Some(revocation) = self.revocations.apply(entry)
+                        && revocation.revoked_at >= started_at
+                    {
+                        self.enforce_revocation(&revocation, &connections).await;
                     }
                 }
-                _ = presence_ticker.tick() => {
-                    match self.station_registry.expire_stale_online(ttl).await {
-                        Ok(expired) if !expired.is_empty() => {
-                            for entry in expired {
-                                info!(network = %self.network_id, station = %entry.station_id, callsign = %entry.acars_endpoint.callsign, "presence lease expired: station marked offline");
-                                if let Err(e) = self
-                                    .handle_station_offline(
-                                        &entry.acars_endpoint.callsign,
-                                        format!("presence-expire-{}", entry.station_id),
-                                    )
-                                    .await
-                                {
-                                    warn!(
-                                        error = %e,
-                                        station = %entry.station_id,
-                                        callsign = %entry.acars_endpoint.callsign,
-                                        "failed to process station offline transition"
-                                    );
-                                }
+                Some(request) = directory_requests.next() => {
+                    self.answer_directory_query(request).await;
+                }
+                Some(advisory) = connection_events.next() => {
+                    let Some(event) = presence::parse_advisory(&advisory.payload, &self.network_id) else {
+                        continue;
+                    };
+                    if let Some(address) = connections.apply(event) {
+                        let started = Instant::now();
+                        let released = self.station_registry.mark_address_offline(&address).await;
+                        self.metrics.kv_operation(&self.network_id, "presence_disconnect", started.elapsed());
+                        match released {
+                            Ok(released) => self.handle_presence_lost(released, "disconnect").await,
+                            Err(e) => {
+                                warn!(network = %self.network_id, %address, error = %e, "failed to process client disconnect");
                             }
                         }
-                        Ok(_) => {}
+                    }
+                }
+                _ = presence_ticker.tick() => {
+                    let started = Instant::now();
+                    let expired = self.station_registry.expire_stale_online(ttl).await;
+                    self.metrics.kv_operation(&self.network_id, "presence_sweep", started.elapsed());
+                    match expired {
+                        Ok(expired) => self.handle_presence_lost(expired, "presence-expire").await,
                         Err(e) => {
                             warn!(network = %self.network_id, error = %e, "presence sweeper failed");
                         }
                     }
+                    self.expire_pending_deliveries().await;
+                    self.update_gauges().await;
+
+                    let counters = {
+                        let mut limiter = self.rate_limiter.lock().expect("rate limiter lock poisoned");
+                        limiter.prune(Instant::now());
+                        limiter.counters()
+                    };
+                    if counters != last_counters {
+                        info!(
+                            network = %self.network_id,
+                            allowed = counters.allowed,
+                            limited_meta = counters.limited_meta,
+                            limited_application = counters.limited_application,
+                            banned = counters.banned,
+                            "rate limit counters"
+                        );
+                        last_counters = counters;
+                    }
+                    self.metrics.set_rate_limit_counters(&self.network_id, counters);
+                }
+            }
+        }
+
+        if let Some(ref message) = self.drain_notice {
+            self.broadcast_maintenance_notice(message).await;
+        }
+        if let Err(e) = self.client.nats_client().flush().await {
+            warn!(network = %self.network_id, error = %e, "failed to flush before exit");
+        }
+        info!(network = %self.network_id, "server stopped");
+    }
+
+    /// Send a `Maintenance` notice to every address with an online station.
+    async fn broadcast_maintenance_notice(&self, message: &str) {
+        let entries = match self.station_registry.list_entries().await {
+            Ok(entries) => entries,
+            Err(e) => {
+                warn!(network = %self.network_id, error = %e, "failed to list stations for maintenance notice");
+                return;
+            }
+        };
+        let addresses: HashSet<NetworkAddress> = entries
+            .into_iter()
+            .filter(|entry| entry.status.is_reachable())
+            .map(|entry| entry.network_address)
+            .collect();
+        info!(network = %self.network_id, recipients = addresses.len(), "sending maintenance notice");
+        for address in addresses {
+            let notice = MessageBuilder::envelope(MessageBuilder::server_notice(
+                NoticeCode::Maintenance,
+                message,
+            ))
+            .source_server(self.network_id.as_str())
+            .destination_address(self.network_id.as_str(), address.as_str())
+            .build();
+            if let Err(e) = self.client.send_to_station(&address, &notice).await {
+                warn!(error = %e, %address, "failed to send maintenance notice");
+            }
+        }
+    }
+
+    /// Refresh the session and online station gauges from the KV stores.
+    async fn update_gauges(&self) {
+        match self.cpdlc_server.list_sessions().await {
+            Ok(sessions) => {
+                let mut by_phase = std::collections::BTreeMap::new();
+                for session in &sessions {
+                    *by_phase
+                        .entry(metrics::session_phase(session).to_string())
+                        .or_insert(0) += 1;
+                }
+                self.metrics.set_sessions(&self.network_id, by_phase);
+            }
+            Err(e) => warn!(network = %self.network_id, error = %e, "failed to count sessions for metrics"),
+        }
+        match self.station_registry.list_entries().await {
+            Ok(entries) => {
+                let reachable = entries.iter().filter(|entry| entry.status.is_reachable()).count();
+                self.metrics.set_stations_online(&self.network_id, reachable);
+            }
+            Err(e) => warn!(network = %self.network_id, error = %e, "failed to count stations for metrics"),
+        }
+    }
+
+    /// Forward the handler's output to the destination station, then send
+    /// the session snapshot to both parties if the session changed.
+    async fn deliver_result(
+        &self,
+        root: &OpenLinkEnvelope,
+        destination: Option<station_registry::StationEntry>,
+        session: Option<CPDLCSession>,
+        forward_envelope: OpenLinkEnvelope,
+        received: Instant,
+    ) {
+        if let Some(ref dest) = destination {
+            debug!(?dest, "forwarding to destination station");
+            let mut transferred = forward_envelope;
+            transferred.routing = OpenLinkRouting {
+                source: root.routing.destination.clone(),
+                destination: openlink_models::OpenLinkRoutingEndpoint::Address(
+                    self.network_id.clone(),
+                    dest.network_address.clone(),
+                ),
+            };
+            if let Err(e) = self
+                .client
+                .send_to_station(&dest.network_address, &transferred)
+                .await
+            {
+                error!(error = %e, "failed to forward message");
+                self.metrics.handler_error(&self.network_id, "forward_failed");
+            } else {
+                self.metrics.forward_latency(&self.network_id, received.elapsed());
+            }
+        }
+
+        if let Some(ref session) = session {
+            self.broadcast_session_update(session, root).await;
+        }
+    }
+
+    /// Subscribe to relays from peer networks. Without federation the
+    /// returned stream never yields.
+    async fn federation_relays(&self) -> BoxStream<'static, async_nats::Message> {
+        if self.federation.is_none() {
+            return futures::stream::pending().boxed();
+        }
+        let subject = NatsSubjects::federation_inbound_wildcard(&self.network_id);
+        match self.client.nats_client().subscribe(subject).await {
+            Ok(subscription) => subscription.boxed(),
+            Err(e) => {
+                warn!(network = %self.network_id, error = %e, "failed to subscribe to federation relays");
+                futures::stream::pending().boxed()
+            }
+        }
+    }
+
+    /// Peer network owning the session of a station-originated CPDLC
+    /// envelope, when the aircraft is not on this network but online on a
+    /// peer this network exports the station to.
+    async fn federated_aircraft_home(&self, envelope: &OpenLinkEnvelope) -> Option<NetworkId> {
+        let federation = self.federation.as_ref()?;
+        let OpenLinkMessage::Acars(ref acars) = envelope.payload else {
+            return None;
+        };
+        let AcarsMessage::CPDLC(ref cpdlc) = acars.message;
+        let aircraft = &acars.routing.aircraft.callsign;
+        if !federation::is_station_uplink(cpdlc, aircraft) {
+            return None;
+        }
+        if let Ok(Some(_)) = self.station_registry.lookup_callsign(aircraft).await {
+            return None;
+        }
+        match federation.aircraft_home(aircraft, &cpdlc.source).await {
+            Ok(home) => home,
+            Err(e) => {
+                warn!(error = %e, %aircraft, "federated aircraft lookup failed");
+                None
+            }
+        }
+    }
+
+    /// Relay an envelope to the server of a peer network.
+    async fn relay(&self, peer: &NetworkId, kind: federation::RelayKind, envelope: &OpenLinkEnvelope) {
+        let relay = federation::Relay {
+            kind,
+            envelope: envelope.clone(),
+        };
+        let subject = NatsSubjects::federation(peer, &self.network_id);
+        match serde_json::to_vec(&relay) {
+            Ok(bytes) => {
+                if let Err(e) = self.client.nats_client().publish(subject, bytes.into()).await {
+                    error!(error = %e, %peer, "failed to relay envelope to peer network");
+                    self.metrics.handler_error(&self.network_id, "relay_failed");
+                }
+            }
+            Err(e) => warn!(error = %e, "failed to serialize relay"),
+        }
+    }
+
+    /// Relay an already processed envelope to the peer network holding
+    /// `callsign`, if a peer exports it to this network.
+    ///
+    /// Returns whether the envelope was relayed.
+    async fn relay_to_imported(&self, callsign: &AcarsEndpointCallsign, envelope: &OpenLinkEnvelope) -> bool {
+        let Some(ref federation) = self.federation else {
+            return false;
+        };
+        match federation.lookup_imported(callsign).await {
+            Ok(Some((peer, _))) => {
+                debug!(%callsign, %peer, "relaying to imported station");
+                self.relay(&peer, federation::RelayKind::Deliver, envelope).await;
+                true
+            }
+            Ok(None) => false,
+            Err(e) => {
+                warn!(error = %e, %callsign, "imported station lookup failed");
+                false
+            }
+        }
+    }
+
+    /// Handle an envelope relayed by a peer network's server.
+    async fn handle_relay(&self, message: async_nats::Message) {
+        let received = Instant::now();
+        let Some(ref federation) = self.federation else {
+            return;
+        };
+        let Some(origin) = NatsSubjects::parse_federation_origin(&message.subject).map(NetworkId::new) else {
+            return;
+        };
+        if !federation.is_peer(&origin) {
+            warn!(%origin, "ignoring relay from a network that is not a peer");
+            self.metrics.handler_error(&self.network_id, "relay_refused");
+            return;
+        }
+        let mut relay = match serde_json::from_slice::<federation::Relay>(&message.payload) {
+            Ok(relay) => relay,
+            Err(e) => {
+                warn!(%origin, error = %e, "ignoring malformed relay");
+                self.metrics.handler_error(&self.network_id, "malformed_envelope");
+                return;
+            }
+        };
+        relay.envelope.token.clear();
+
+        match relay.kind {
+            federation::RelayKind::Notify => {
+                let Some(address) = federation::notice_recipient(&self.network_id, &relay.envelope) else {
+                    warn!(%origin, "ignoring notify relay that is not a rejection for a local station");
+                    self.metrics.handler_error(&self.network_id, "relay_refused");
+                    return;
+                };
+                if let Err(e) = self.client.send_to_station(address, &relay.envelope).await {
+                    warn!(error = %e, %origin, %address, "failed to hand over relayed rejection");
+                }
+            }
+            federation::RelayKind::Deliver => {
+                let Some((_, cpdlc)) = self.relayed_cpdlc(&origin, &relay.envelope) else {
+                    return;
+                };
+                if !federation.exports_to(&origin, &cpdlc.destination) {
+                    warn!(%origin, destination = %cpdlc.destination, "relay addresses a station not exported to the origin");
+                    self.metrics.handler_error(&self.network_id, "relay_refused");
+                    return;
+                }
+                let Ok(Some(dest)) = self.station_registry.lookup_callsign(&cpdlc.destination).await else {
+                    debug!(%origin, destination = %cpdlc.destination, "relayed envelope recipient is offline");
+                    return;
+                };
+                self.metrics.message_routed(&self.network_id, &relay.envelope.payload);
+                self.deliver_result(&relay.envelope, Some(dest), None, relay.envelope.clone(), received)
+                    .await;
+            }
+            federation::RelayKind::Process => {
+                let Some((acars, cpdlc)) = self.relayed_cpdlc(&origin, &relay.envelope) else {
+                    return;
+                };
+                let aircraft = &acars.routing.aircraft.callsign;
+                let imported = federation.is_imported_online(&origin, &cpdlc.source).await;
+                if !federation::is_station_uplink(cpdlc, aircraft) || !imported.unwrap_or(false) {
+                    warn!(%origin, source = %cpdlc.source, %aircraft, "relay from a station not exported to this network");
+                    self.metrics.handler_error(&self.network_id, "relay_refused");
+                    return;
+                }
+                let started = Instant::now();
+                let result = self.handle_acars_message(acars, &relay.envelope).await;
+                self.metrics.kv_operation(&self.network_id, "session_update", started.elapsed());
+                match result {
+                    Ok((dest, session, forward_envelope)) => {
+                        self.metrics.message_routed(&self.network_id, &relay.envelope.payload);
+                        self.deliver_result(&relay.envelope, dest, session, forward_envelope, received)
+                            .await;
+                    }
+                    Err(e) => {
+                        warn!(%origin, error = %e, "handler returned error for relayed envelope");
+                        self.metrics.handler_error(&self.network_id, "acars_handler");
+                    }
                 }
             }
         }
     }
 
+    /// ACARS and CPDLC parts of an envelope relayed for delivery or
+    /// processing, `None` (and the relay refused) without them.
+    fn relayed_cpdlc<'a>(
+        &self,
+        origin: &NetworkId,
+        envelope: &'a OpenLinkEnvelope,
+    ) -> Option<(&'a AcarsEnvelope, &'a CpdlcEnvelope)> {
+        let OpenLinkMessage::Acars(ref acars) = envelope.payload else {
+            warn!(%origin, "ignoring relay without an ACARS payload");
+            self.metrics.handler_error(&self.network_id, "relay_refused");
+            return None;
+        };
+        let AcarsMessage::CPDLC(ref cpdlc) = acars.message;
+        Some((acars, cpdlc))
+    }
+
+    /// Watch the ban list bucket. On failure, bans already loaded stay in
+    /// force and the returned stream never yields.
+    async fn ban_updates(&self) -> BoxStream<'static, async_nats::jetstream::kv::Entry> {
+        match self.ban_list.watch().await {
+            Ok(watch) => watch
+                .filter_map(|entry| futures::future::ready(entry.ok()))
+                .boxed(),
+            Err(e) => {
+                warn!(network = %self.network_id, error = %e, "failed to watch ban list");
+                futures::stream::pending().boxed()
+            }
+        }
+    }
+
+    /// Watch the revocations bucket. On failure, revocations already loaded
+    /// stay in force and the returned stream never yields.
+    async fn revocation_updates(&self) -> BoxStream<'static, async_nats::jetstream::kv::Entry> {
+        match self.revocations.watch().await {
+            Ok(watch) => watch
+                .filter_map(|entry| futures::future::ready(entry.ok()))
+                .boxed(),
+            Err(e) => {
+                warn!(network = %self.network_id, error = %e, "failed to watch revocations");
+                futures::stream::pending().boxed()
+            }
+        }
+    }
+
+    /// Close the NATS connections a revocation covers, when a
+    /// system-account connection is configured, and mark the stations of
+    /// the addresses left without a connection offline, ending their CPDLC
+    /// sessions.
+    ///
+    /// A CID revocation covers every connection of the CID's address. A
+    /// revocation of a key alone covers the connections seen using it, so
+    /// it needs the connection advisories of `NATS_SYSTEM_CREDS`.
+    ///
+    /// A client reconnecting with the revoked JWT is still let in by NATS,
+    /// but every envelope it sends is rejected.
+    async fn enforce_revocation(&self, revocation: &Revocation, connections: &presence::ConnectionTracker) {
+        let revoked = connections.revoked(revocation.cid.as_deref(), revocation.user_nkey_public.as_deref());
+        if revoked.is_empty() {
+            debug!(network = %self.network_id, "no live connection uses the revoked credentials");
+        }
+        for revoked in revoked {
+            let address = &revoked.address;
+            if let Some(ref system) = self.system_client {
+                for key in &revoked.connections {
+                    let request = serde_json::json!({ "cid": key.client_id }).to_string();
+                    if let Err(e) = system
+                        .request(presence::kick_subject(&key.server_id), request.into())
+                        .await
+                    {
+                        warn!(network = %self.network_id, %address, server = %key.server_id, error = %e, "failed to close revoked connection");
+                    }
+                }
+            }
+            if !revoked.release_stations {
+                continue;
+            }
+            match self.station_registry.mark_address_offline(address).await {
+                Ok(released) => self.handle_presence_lost(released, "revoked").await,
+                Err(e) => {
+                    warn!(network = %self.network_id, %address, error = %e, "failed to release stations of revoked credentials");
+                }
+            }
+        }
+    }
+
+    /// Subscribe to NATS connect/disconnect advisories when a system-account
+    /// connection is configured; otherwise presence relies on heartbeats
+    /// only and the returned stream never yields.
+    async fn connection_events(&self) -> BoxStream<'static, async_nats::Message> {
+        let Some(ref system) = self.system_client else {
+            return futures::stream::pending().boxed();
+        };
+        let subscriptions = futures::future::try_join(
+            system.subscribe(presence::CONNECT_ADVISORIES),
+            system.subscribe(presence::DISCONNECT_ADVISORIES),
+        )
+        .await;
+        match subscriptions {
+            Ok((connects, disconnects)) => {
+                info!(network = %self.network_id, "presence follows NATS connection advisories");
+                futures::stream::select(connects, disconnects).boxed()
+            }
+            Err(e) => {
+                warn!(network = %self.network_id, error = %e, "failed to subscribe to connection advisories; heartbeat presence only");
+                futures::stream::pending().boxed()
+            }
+        }
+    }
+
+    /// Process stations the registry just marked offline, whether their
+    /// lease expired or their connection closed.
+    async fn handle_presence_lost(&self, released: Vec<station_registry::StationEntry>, reason: &str) {
+        self.metrics.presence_expired(&self.network_id, reason, released.len());
+        for entry in released {
+            info!(network = %self.network_id, station = %entry.station_id, callsign = %entry.acars_endpoint.callsign, reason, "station marked offline");
+            self.publish_directory_event(&DirectoryEvent::Removed(
+                entry.acars_endpoint.callsign.clone(),
+            ))
+            .await;
+            if let Err(e) = self
+                .handle_station_offline(
+                    &entry.acars_endpoint.callsign,
+                    format!("{reason}-{}", entry.station_id),
+                )
+                .await
+            {
+                warn!(
+                    error = %e,
+                    station = %entry.station_id,
+                    callsign = %entry.acars_endpoint.callsign,
+                    "failed to process station offline transition"
+                );
+            }
+        }
+    }
+
+    /// Verify that everything the envelope claims about its sender is bound
+    /// to the outbox address it was published on, and that the sender's
+    /// role allows it. Returns that role.
+    async fn verify_sender(
+        &self,
+        sender: &NetworkAddress,
+        envelope: &OpenLinkEnvelope,
+    ) -> std::result::Result<UserRole, SenderRejection> {
+        sender_check::check_routing_source(&self.network_id, sender, envelope)?;
+        let token = self
+            .token_verifier
+            .verify(&envelope.token, &self.network_id, sender)
+            .await?;
+        if let Some(revocation) =
+            self.revocations
+                .revoked(sender.as_str(), &token.user_nkey_public, token.issued_at)
+        {
+            return Err(SenderRejection {
+                code: RejectionCode::Unauthenticated,
+                reason: match revocation.reason.as_str() {
+                    "" => "credentials revoked".to_string(),
+                    reason => format!("credentials revoked: {reason}"),
+                },
+            });
+        }
+        let role = token.role;
+        sender_check::check_role(role, envelope)?;
+        sender_check::check_callsigns(&token.callsigns, envelope)?;
+
+        if let OpenLinkMessage::Acars(ref acars) = envelope.payload {
+            let AcarsMessage::CPDLC(ref cpdlc) = acars.message;
+            let source_entry = self
+                .station_registry
+                .lookup_callsign(&cpdlc.source)
+                .await
+                .ok()
+                .flatten();
+            sender_check::check_cpdlc_sender(sender, cpdlc, acars, source_entry.as_ref())?;
+        }
+
+        Ok(role)
+    }
+
+    /// Answer a directory request with the matching reachable stations.
+    ///
+    /// An empty or malformed payload is treated as an unfiltered query.
+    async fn answer_directory_query(&self, request: async_nats::Message) {
+        let Some(reply) = request.reply else {
+            debug!("ignoring directory request without reply subject");
+            return;
+        };
+        let query: DirectoryQuery = serde_json::from_slice(&request.payload).unwrap_or_default();
+        let entries = match self.station_registry.list_entries().await {
+            Ok(entries) => entries,
+            Err(e) => {
+                warn!(error = %e, "failed to list stations for directory request");
+                Vec::new()
+            }
+        };
+        let response = directory::build_response(entries, &query);
+        match serde_json::to_vec(&response) {
+            Ok(bytes) => {
+                if let Err(e) = self.client.nats_client().publish(reply, bytes.into()).await {
+                    warn!(error = %e, "failed to answer directory request");
+                }
+            }
+            Err(e) => warn!(error = %e, "failed to serialize directory response"),
+        }
+    }
+
+    /// Publish a live directory change.
+    async fn publish_directory_event(&self, event: &DirectoryEvent) {
+        let subject = NatsSubjects::directory_events(&self.network_id);
+        match serde_json::to_vec(event) {
+            Ok(bytes) => {
+                if let Err(e) = self.client.nats_client().publish(subject, bytes.into()).await {
+                    warn!(error = %e, "failed to publish directory event");
+                }
+            }
+            Err(e) => warn!(error = %e, "failed to serialize directory event"),
+        }
+    }
+
+    /// Forget the id of an envelope whose handling failed, so that the
+    /// sender's retry is not dropped as a duplicate.
+    async fn forget_envelope(&self, sender: &NetworkAddress, envelope: &OpenLinkEnvelope) {
+        if let Some(seen) = &self.seen_envelopes {
+            seen.forget(sender, &envelope.id).await;
+        }
+    }
+
+    /// Tell `sender` that one of its envelopes was not routed.
+    async fn send_rejection(
+        &self,
+        sender: &NetworkAddress,
+        envelope: &OpenLinkEnvelope,
+        rejection: SenderRejection,
+    ) {
+        let notice = self.rejection_notice(&self.network_id, sender, envelope, rejection);
+        if let Err(e) = self.client.send_to_station(sender, &notice).await {
+            warn!(error = %e, %sender, "failed to send envelope rejection");
+        }
+    }
+
+    /// Tell the sender of an envelope that it was not routed: directly when
+    /// it is on this network, through a `Notify` relay to its network when
+    /// a peer relayed the envelope for processing.
+    async fn notify_sender(
+        &self,
+        route: federation::NoticeRoute,
+        envelope: &OpenLinkEnvelope,
+        rejection: SenderRejection,
+    ) {
+        match route {
+            federation::NoticeRoute::Local(sender) => {
+                self.send_rejection(&sender, envelope, rejection).await;
+            }
+            federation::NoticeRoute::Relay(peer, sender) => {
+                let notice = self.rejection_notice(&peer, &sender, envelope, rejection);
+                self.relay(&peer, federation::RelayKind::Notify, &notice).await;
+            }
+        }
+    }
+
+    /// `EnvelopeRejected` notice for `sender` on `network` about `envelope`.
+    fn rejection_notice(
+        &self,
+        network: &NetworkId,
+        sender: &NetworkAddress,
+        envelope: &OpenLinkEnvelope,
+        rejection: SenderRejection,
+    ) -> OpenLinkEnvelope {
+        MessageBuilder::envelope(MessageBuilder::envelope_rejected(
+            envelope.id,
+            rejection.code,
+            rejection.reason,
+        ))
+        .source_server(self.network_id.as_str())
+        .destination_address(network.as_str(), sender.as_str())
+        .correlation_id(envelope.id.to_string())
+        .build()
+    }
+
     /// Handle station meta messages (status updates, etc.).
     async fn handle_meta_message(
         &self,
         meta: &MetaMessage,
         root: &OpenLinkEnvelope,
+        role: UserRole,
     ) -> Result<Option<station_registry::StationEntry>> {
         match meta {
-            MetaMessage::StationStatus(station_id, status, acars_endpoint) => {
+            MetaMessage::StationStatus(station_id, status, acars_endpoint, metadata) => {
                 info!(station = %station_id, ?status, "station status update");
                 if let openlink_models::OpenLinkRoutingEndpoint::Address(_network, address) =
                     &root.routing.source
                 {
-                    if let Err(e) = self
+                    let metadata = StationMetadata {
+                        role: sender_check::station_role(role, metadata),
+                        ..metadata.clone()
+                    };
+                    let update = match self
                         .station_registry
-                        .update_status(station_id, status, acars_endpoint, address)
+                        .update_status(station_id, status, acars_endpoint, address, &metadata, Some(role))
                         .await
                     {
-                        error!(error = %e, "failed to update station status");
-                    } else if *status == StationStatus::Online {
+                        Ok(update) => update,
+                        Err(e) => {
+                            error!(error = %e, "failed to update station status");
+                            return Ok(None);
+                        }
+                    };
+
+                    match update.outcome {
+                        ClaimOutcome::Denied(denial) => {
+                            let rejection = SenderRejection {
+                                code: denial.code,
+                                reason: denial.reason,
+                            };
+                            self.send_rejection(address, root, rejection).await;
+                            return Ok(None);
+                        }
+                        ClaimOutcome::NotHolder => {
+                            debug!(station = %station_id, callsign = %acars_endpoint.callsign, "offline from a station no longer holding its callsign");
+                            return Ok(None);
+                        }
+                        ClaimOutcome::TookOver(ref previous) => {
+                            self.notify_callsign_taken_over(previous, address).await;
+                        }
+                        ClaimOutcome::Applied => {}
+                    }
+
+                    if let Some(ref current) = update.current {
+                        for event in directory::change_events(update.previous.as_ref(), current) {
+                            self.publish_directory_event(&event).await;
+                        }
+                    }
+
+                    if status.is_reachable() {
                         if let Err(e) = self
                             .sync_session_snapshots_for_callsign(
                                 address,
@@ -229,8 +1079,7 @@ impl OpenLinkServer {
                         {
                             warn!(error = %e, callsign = %acars_endpoint.callsign, "failed to sync session snapshots on station online");
                         }
-                    } else if *status == StationStatus::Offline
-                        && let Err(e) = self
+                    } else if let Err(e) = self
                             .handle_station_offline(
                                 &acars_endpoint.callsign,
                                 root.id.to_string(),
@@ -245,10 +1094,80 @@ impl OpenLinkServer {
                     }
                 }
             }
+            MetaMessage::StationLookup(callsign) => {
+                if let openlink_models::OpenLinkRoutingEndpoint::Address(_network, address) =
+                    &root.routing.source
+                {
+                    self.answer_station_lookup(address, callsign, root).await;
+                }
+            }
+            MetaMessage::EnvelopeRejected(_)
+            | MetaMessage::ServerNotice(_)
+            | MetaMessage::StationInfo(..) => {
+                // Server-originated — ignore if received from a client.
+                warn!("ignoring client-sent server notice");
+            }
         }
         Ok(None)
     }
 
+    /// Reply to a [`MetaMessage::StationLookup`] with the registry view of
+    /// the station holding `callsign`, if it is reachable.
+    async fn answer_station_lookup(
+        &self,
+        requester: &NetworkAddress,
+        callsign: &AcarsEndpointCallsign,
+        root: &OpenLinkEnvelope,
+    ) {
+        let info = match self.station_registry.lookup_callsign(callsign).await {
+            Ok(entry) => entry
+                .filter(|entry| entry.status.is_reachable())
+                .map(|entry| entry.info()),
+            Err(e) => {
+                warn!(error = %e, %callsign, "station lookup failed");
+                None
+            }
+        };
+        let reply = MessageBuilder::envelope(OpenLinkMessage::Meta(MetaMessage::StationInfo(
+            callsign.clone(),
+            info,
+        )))
+        .source_server(self.network_id.as_str())
+        .destination_address(self.network_id.as_str(), requester.as_str())
+        .correlation_id(root.id.to_string())
+        .build();
+
+        if let Err(e) = self.client.send_to_station(requester, &reply).await {
+            warn!(error = %e, %requester, "failed to send station info");
+        }
+    }
+
+    /// Tell the previous holder of a callsign that it was taken over.
+    async fn notify_callsign_taken_over(
+        &self,
+        previous: &station_registry::StationEntry,
+        new_holder: &NetworkAddress,
+    ) {
+        let notice = MessageBuilder::envelope(MessageBuilder::server_notice(
+            NoticeCode::CallsignTakenOver,
+            format!(
+                "callsign {} taken over by {}; you are now offline",
+                previous.acars_endpoint.callsign, new_holder
+            ),
+        ))
+        .source_server(self.network_id.as_str())
+        .destination_address(self.network_id.as_str(), previous.network_address.as_str())
+        .build();
+
+        if let Err(e) = self
+            .client
+            .send_to_station(&previous.network_address, &notice)
+            .await
+        {
+            warn!(error = %e, address = %previous.network_address, "failed to send takeover notice");
+        }
+    }
+
     async fn handle_station_offline(
         &self,
         station_callsign: &AcarsEndpointCallsign,
@@ -261,20 +1180,8 @@ impl OpenLinkServer {
 
         for session in updated_sessions {
             let aircraft = &session.aircraft;
-            let end_service_envelope = MessageBuilder::envelope(
-                MessageBuilder::cpdlc(
-                    aircraft.callsign.to_string(),
-                    aircraft.address.to_string(),
-                )
-                .from(station_callsign.to_string())
-                .to(aircraft.callsign.to_string())
-                .end_service()
-                .build(),
-            )
-            .source_server(self.network_id.as_str())
-            .destination_address(self.network_id.as_str(), "aircraft")
-            .correlation_id(correlation_id.clone())
-            .build();
+            let end_service_envelope =
+                self.end_service_envelope(aircraft, station_callsign, correlation_id.clone());
 
             if self.presence_config.auto_end_service_on_station_offline {
                 if let Ok(Some(aircraft_entry)) = self
@@ -304,6 +1211,123 @@ impl OpenLinkServer {
         Ok(())
     }
 
+    /// END SERVICE from `station_callsign` to `aircraft`, also used as the
+    /// root envelope of the session updates it triggers.
+    fn end_service_envelope(
+        &self,
+        aircraft: &openlink_models::AcarsRoutingEndpoint,
+        station_callsign: &AcarsEndpointCallsign,
+        correlation_id: String,
+    ) -> OpenLinkEnvelope {
+        MessageBuilder::envelope(
+            MessageBuilder::cpdlc(aircraft.callsign.to_string(), aircraft.address.to_string())
+                .from(station_callsign.to_string())
+                .to(aircraft.callsign.to_string())
+                .end_service()
+                .build(),
+        )
+        .source_server(self.network_id.as_str())
+        .destination_address(self.network_id.as_str(), "aircraft")
+        .correlation_id(correlation_id)
+        .build()
+    }
+
+    /// Sessions stored on this network, optionally only those involving
+    /// `callsign`.
+    pub async fn admin_sessions(
+        &self,
+        callsign: Option<&AcarsEndpointCallsign>,
+    ) -> Result<Vec<CPDLCSession>> {
+        match callsign {
+            Some(callsign) => self.cpdlc_server.list_sessions_for_callsign(callsign).await,
+            None => self.cpdlc_server.list_sessions().await,
+        }
+    }
+
+    /// Every station registry entry on this network.
+    pub async fn admin_stations(&self) -> Result<Vec<station_registry::StationEntry>> {
+        self.station_registry.list_entries().await
+    }
+
+    /// Force-terminate the connection between an aircraft and a station and
+    /// send the resulting session snapshot to both.
+    ///
+    /// Returns `None` if the aircraft has no session.
+    pub async fn admin_terminate_connection(
+        &self,
+        aircraft: &AcarsEndpointCallsign,
+        station: &AcarsEndpointCallsign,
+    ) -> Result<Option<CPDLCSession>> {
+        let updated = self.cpdlc_server.terminate_connection(aircraft, station).await?;
+        if let Some(ref session) = updated {
+            info!(network = %self.network_id, %aircraft, %station, "admin terminated connection");
+            let root = self.end_service_envelope(&session.aircraft, station, "admin-terminate".to_string());
+            self.broadcast_session_update(session, &root).await;
+        }
+        Ok(updated)
+    }
+
+    /// Delete an aircraft's session and send an empty snapshot to the
+    /// aircraft and to every station it was connected to.
+    ///
+    /// Returns the removed session, or `None` if the aircraft had none.
+    pub async fn admin_purge_session(
+        &self,
+        aircraft: &AcarsEndpointCallsign,
+    ) -> Result<Option<CPDLCSession>> {
+        let removed = self.cpdlc_server.purge_session(aircraft).await?;
+        if let Some(ref session) = removed {
+            info!(network = %self.network_id, %aircraft, "admin purged session");
+            let cleared = CPDLCSession::new(session.aircraft.clone());
+            let stations = session
+                .active_connection
+                .iter()
+                .chain(session.inactive_connection.iter())
+                .map(|c| c.station.callsign.clone());
+            for station in stations {
+                let root = self.end_service_envelope(&session.aircraft, &station, "admin-purge".to_string());
+                self.broadcast_session_update(&cleared, &root).await;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Mark the station holding `callsign` offline, with the same effects as
+    /// an expired presence lease.
+    ///
+    /// Returns `None` if no reachable station holds the callsign.
+    pub async fn admin_force_offline(
+        &self,
+        callsign: &AcarsEndpointCallsign,
+    ) -> Result<Option<station_registry::StationEntry>> {
+        let Some(entry) = self.station_registry.lookup_callsign(callsign).await? else {
+            return Ok(None);
+        };
+        let released = self.station_registry.mark_station_offline(entry.clone()).await?;
+        self.handle_presence_lost(released, "admin").await;
+        Ok(Some(entry))
+    }
+
+    /// Resend every session snapshot involving `callsign` to the station
+    /// holding it.
+    ///
+    /// Returns `None` if no reachable station holds the callsign.
+    pub async fn admin_resync(
+        &self,
+        callsign: &AcarsEndpointCallsign,
+    ) -> Result<Option<station_registry::StationEntry>> {
+        let Some(entry) = self.station_registry.lookup_callsign(callsign).await? else {
+            return Ok(None);
+        };
+        self.sync_session_snapshots_for_callsign(
+            &entry.network_address,
+            callsign,
+            "admin-resync".to_string(),
+        )
+        .await?;
+        Ok(Some(entry))
+    }
+
     /// Route ACARS envelopes (currently only CPDLC) to the appropriate sub-handler.
     async fn handle_acars_message(
         &self,
@@ -324,6 +1348,10 @@ impl OpenLinkServer {
                     .await
                     .ok()
                     .flatten();
+                if dest.is_none() && !self.relay_to_imported(&dest_callsign, &modified_envelope).await {
+                    self.queue_for_offline_recipient(&dest_callsign, &modified_envelope, envelope)
+                        .await;
+                }
                 Ok((dest, session, modified_envelope))
             } // future: handle other ACARS types here
         }
@@ -428,6 +1456,8 @@ impl OpenLinkServer {
                 } else {
                     debug!(station = %station_callsign, "sent SessionUpdate to station");
                 }
+            } else {
+                self.relay_to_imported(&station_callsign, &station_envelope).await;
             }
         }
     }
@@ -469,6 +1499,130 @@ impl OpenLinkServer {
             self.client.send_to_station(network_address, &envelope).await?;
         }
 
+        self.flush_pending_deliveries(network_address, callsign).await;
+
         Ok(())
     }
+
+    /// Queue a message whose recipient is not online, if store-and-forward
+    /// is enabled.
+    async fn queue_for_offline_recipient(
+        &self,
+        recipient: &AcarsEndpointCallsign,
+        envelope: &OpenLinkEnvelope,
+        root: &OpenLinkEnvelope,
+    ) {
+        let Some(ref pending) = self.pending else {
+            debug!(%recipient, "recipient offline, message dropped");
+            return;
+        };
+        let rejection = match pending.enqueue(recipient, &root.routing.source, envelope).await {
+            Ok(()) => {
+                info!(%recipient, envelope_id = %root.id, "recipient offline, message queued");
+                return;
+            }
+            Err(pending::EnqueueError::Full) => {
+                info!(%recipient, envelope_id = %root.id, "recipient offline and its queue is full");
+                SenderRejection {
+                    code: RejectionCode::DeliveryExpired,
+                    reason: format!("{recipient} is offline and its queue is full"),
+                }
+            }
+            Err(pending::EnqueueError::Failed(e)) => {
+                error!(error = %e, %recipient, envelope_id = %root.id, "failed to queue message for offline recipient");
+                SenderRejection {
+                    code: RejectionCode::DeliveryExpired,
+                    reason: format!("{recipient} is offline and the message could not be queued: {e}"),
+                }
+            }
+        };
+        if let Some(route) = federation::NoticeRoute::to_source(&self.network_id, &root.routing.source) {
+            self.notify_sender(route, root, rejection).await;
+        }
+    }
+
+    /// Deliver, in order, the messages queued while `callsign` was offline.
+    async fn flush_pending_deliveries(
+        &self,
+        network_address: &NetworkAddress,
+        callsign: &AcarsEndpointCallsign,
+    ) {
+        let Some(ref pending) = self.pending else {
+            return;
+        };
+        let deliveries = match pending.queued_for(callsign).await {
+            Ok(deliveries) => deliveries,
+            Err(e) => {
+                warn!(error = %e, %callsign, "failed to read pending deliveries");
+                return;
+            }
+        };
+        let now = chrono::Utc::now();
+        for (sequence, delivery) in deliveries {
+            if delivery.is_expired(pending.ttl(), now) || delivery.envelope.is_expired(now) {
+                self.notify_delivery_expired(&delivery).await;
+                self.remove_pending_delivery(pending, sequence).await;
+                continue;
+            }
+            let mut envelope = delivery.envelope;
+            envelope.routing = OpenLinkRouting {
+                source: openlink_models::OpenLinkRoutingEndpoint::Server(self.network_id.clone()),
+                destination: openlink_models::OpenLinkRoutingEndpoint::Address(
+                    self.network_id.clone(),
+                    network_address.clone(),
+                ),
+            };
+            // Stop at the first failure: the rest stays queued, in order,
+            // for the next time the station comes online.
+            if let Err(e) = self.client.send_to_station(network_address, &envelope).await {
+                error!(error = %e, %callsign, "failed to deliver queued message, keeping it queued");
+                return;
+            }
+            debug!(%callsign, envelope_id = %envelope.id, "delivered queued message");
+            self.remove_pending_delivery(pending, sequence).await;
+        }
+    }
+
+    async fn remove_pending_delivery(&self, pending: &pending::PendingDeliveries, sequence: u64) {
+        if let Err(e) = pending.remove(sequence).await {
+            warn!(error = %e, sequence, "failed to remove handled pending delivery");
+        }
+    }
+
+    /// Drop queued messages older than the store-and-forward TTL.
+    async fn expire_pending_deliveries(&self) {
+        let Some(ref pending) = self.pending else {
+            return;
+        };
+        match pending.expired().await {
+            Ok(expired) => {
+                for (sequence, delivery) in expired {
+                    self.notify_delivery_expired(&delivery).await;
+                    self.remove_pending_delivery(pending, sequence).await;
+                }
+            }
+            Err(e) => warn!(network = %self.network_id, error = %e, "pending delivery sweep failed"),
+        }
+    }
+
+    /// Tell the sender of an expired queued message that it was not delivered.
+    async fn notify_delivery_expired(&self, delivery: &pending::PendingDelivery) {
+        info!(recipient = %delivery.recipient, envelope_id = %delivery.envelope.id, "queued message expired");
+        let route = delivery
+            .sender_endpoint(&self.network_id)
+            .and_then(|source| federation::NoticeRoute::to_source(&self.network_id, &source));
+        if let Some(route) = route {
+            let rejection = match delivery.envelope.expires_at {
+                Some(expires_at) if delivery.envelope.is_expired(chrono::Utc::now()) => SenderRejection {
+                    code: RejectionCode::Expired,
+                    reason: format!("envelope expired at {expires_at} while {} was offline", delivery.recipient),
+                },
+                _ => SenderRejection {
+                    code: RejectionCode::DeliveryExpired,
+                    reason: format!("{} did not come back online in time", delivery.recipient),
+                },
+            };
+            self.notify_sender(route, &delivery.envelope, rejection).await;
+        }
+    }
 }
\ No newline at end of file
@@ -1,24 +1,210 @@
 //! Station registry backed by a JetStream KV store.
 //!
-//! Maps [`StationId`]s to their runtime status, network address, and ACARS
-//! routing endpoint. Used by the server to resolve callsigns to routable
+//! Maps [`StationId`]s to their runtime status, network address, ACARS
+//! routing endpoint and advertised [`StationMetadata`]. Used by the server to resolve callsigns to routable
 //! destinations.
+//!
+//! Presence has two sources: clients refresh a heartbeat lease by
+//! republishing their status, and the server marks an address offline as
+//! soon as NATS reports its last connection closed (see `presence`).
+//!
+//! Callsigns are leased first-come: a reachable station whose heartbeat is
+//! fresh keeps its callsign until it goes offline or its lease expires,
+//! unless the [`CallsignPolicy`] allows takeovers. Reserved patterns restrict
+//! some callsigns (typically ICAO facility designators) to senders whose
+//! token carries the controller role.
 
 use anyhow::Result;
 use chrono::{DateTime, Duration, Utc};
 use futures::TryStreamExt;
 use openlink_models::{
-    AcarsEndpointCallsign, AcarsRoutingEndpoint, NetworkAddress, NetworkId, StationId,
-    StationStatus,
+    AcarsEndpointCallsign, AcarsRoutingEndpoint, NetworkAddress, NetworkId, RejectionCode,
+    StationId, StationInfo, StationMetadata, StationStatus, UserRole,
 };
 use serde::{Deserialize, Serialize};
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 /// A registry of ground stations on a single network.
 #[derive(Debug, Clone)]
 pub struct StationRegistry {
     kv_registry_store: async_nats::jetstream::kv::Store,
     kv_callsign_index_store: async_nats::jetstream::kv::Store,
+    policy: CallsignPolicy,
+}
+
+/// Callsign ownership rules for one network.
+#[derive(Debug, Clone)]
+pub struct CallsignPolicy {
+    /// Lease duration of a claim without heartbeat refresh.
+    pub lease_ttl: Duration,
+    /// Let a new claimant take over a callsign still leased by another
+    /// station. The previous holder is marked offline and notified.
+    pub allow_takeover: bool,
+    /// Callsigns only controllers may claim.
+    pub reserved_patterns: Vec<CallsignPattern>,
+}
+
+impl Default for CallsignPolicy {
+    fn default() -> Self {
+        Self {
+            lease_ttl: Duration::seconds(90),
+            allow_takeover: false,
+            reserved_patterns: Vec::new(),
+        }
+    }
+}
+
+impl CallsignPolicy {
+    fn is_reserved(&self, callsign: &AcarsEndpointCallsign) -> bool {
+        let callsign = callsign.to_string();
+        self.reserved_patterns.iter().any(|p| p.matches(&callsign))
+    }
+}
+
+/// Case-insensitive callsign pattern.
+///
+/// `?` matches any character, `@` a letter, `#` a digit and `*` any run of
+/// characters (including none). E.g. `@@@@` matches ICAO facility
+/// designators, `@@@@_*` their sector suffixes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CallsignPattern(String);
+
+impl CallsignPattern {
+    pub fn new(pattern: &str) -> Self {
+        Self(pattern.trim().to_ascii_uppercase())
+    }
+
+    pub fn matches(&self, callsign: &str) -> bool {
+        let pattern: Vec<char> = self.0.chars().collect();
+        let text: Vec<char> = callsign.to_ascii_uppercase().chars().collect();
+        glob_match(&pattern, &text)
+    }
+}
+
+fn glob_match(pattern: &[char], text: &[char]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some(('*', rest)) => (0..=text.len()).any(|skip| glob_match(rest, &text[skip..])),
+        Some((p, rest)) => match text.split_first() {
+            Some((c, text_rest)) => {
+                let ok = match p {
+                    '?' => true,
+                    '@' => c.is_ascii_alphabetic(),
+                    '#' => c.is_ascii_digit(),
+                    other => other == c,
+                };
+                ok && glob_match(rest, text_rest)
+            }
+            None => false,
+        },
+    }
+}
+
+/// Claim refused by the callsign policy.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClaimDenial {
+    pub code: RejectionCode,
+    pub reason: String,
+}
+
+/// Result of a status update.
+#[derive(Debug, Clone)]
+pub enum ClaimOutcome {
+    /// The status was recorded.
+    Applied,
+    /// The status was recorded after taking the callsign over from `previous`,
+    /// which is now marked offline.
+    TookOver(StationEntry),
+    /// The claim was refused and nothing was recorded.
+    Denied(ClaimDenial),
+    /// The offline status was recorded, but the callsign is held by another
+    /// station which is left untouched.
+    NotHolder,
+}
+
+/// What [`StationRegistry::update_status`] did.
+#[derive(Debug, Clone)]
+pub struct StatusUpdate {
+    pub outcome: ClaimOutcome,
+    /// Entry stored for the station before the update.
+    pub previous: Option<StationEntry>,
+    /// Entry stored by the update; `None` when the claim was denied.
+    pub current: Option<StationEntry>,
+}
+
+/// Whether `entry` still holds its callsign lease at `now`.
+fn lease_active(policy: &CallsignPolicy, entry: &StationEntry, now: DateTime<Utc>) -> bool {
+    entry.status.is_reachable() && now.signed_duration_since(entry.last_updated) <= policy.lease_ttl
+}
+
+/// Refuse a status update for `station_id` sent by `sender` when the
+/// station is registered by another address, given its `existing` entry.
+///
+/// Status updates of another address's station are always refused; a
+/// station that went offline, or whose lease lapsed, may be registered
+/// again from a new address.
+fn check_station_owner(
+    policy: &CallsignPolicy,
+    station_id: &StationId,
+    status: &StationStatus,
+    sender: &NetworkAddress,
+    existing: Option<&StationEntry>,
+    now: DateTime<Utc>,
+) -> Option<ClaimDenial> {
+    let existing = existing.filter(|e| e.network_address != *sender)?;
+    if status.is_reachable() && !lease_active(policy, existing, now) {
+        return None;
+    }
+    Some(ClaimDenial {
+        code: RejectionCode::CallsignNotOwned,
+        reason: format!("station {station_id} is registered by another address"),
+    })
+}
+
+/// Decide whether `station_id`, sent by `sender` as a user with `role`, may
+/// claim `callsign`, given the station currently holding it in the index.
+fn evaluate_claim(
+    policy: &CallsignPolicy,
+    station_id: &StationId,
+    sender: &NetworkAddress,
+    role: Option<UserRole>,
+    callsign: &AcarsEndpointCallsign,
+    holder: Option<&StationEntry>,
+    now: DateTime<Utc>,
+) -> ClaimOutcome {
+    if policy.is_reserved(callsign) && !role.is_some_and(|r| r.may_claim_reserved()) {
+        return ClaimOutcome::Denied(ClaimDenial {
+            code: RejectionCode::CallsignReserved,
+            reason: format!("callsign {callsign} is reserved to controllers"),
+        });
+    }
+
+    let Some(holder) = holder else {
+        return ClaimOutcome::Applied;
+    };
+    let refresh = holder.station_id == *station_id && holder.network_address == *sender;
+    if refresh || !lease_active(policy, holder, now) {
+        return ClaimOutcome::Applied;
+    }
+    if policy.allow_takeover {
+        return ClaimOutcome::TookOver(holder.clone());
+    }
+    ClaimOutcome::Denied(ClaimDenial {
+        code: RejectionCode::CallsignInUse,
+        reason: format!(
+            "callsign {callsign} is in use by another station until {}",
+            holder.last_updated + policy.lease_ttl
+        ),
+    })
+}
+
+/// Outcome of recording an unreachable status for `station_id` while the
+/// callsign index points at `holder`: another holder keeps the callsign.
+fn release_outcome(station_id: &StationId, holder: Option<&StationId>) -> ClaimOutcome {
+    match holder {
+        Some(holder) if holder != station_id => ClaimOutcome::NotHolder,
+        _ => ClaimOutcome::Applied,
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,6 +220,21 @@ pub struct StationEntry {
     pub last_updated: DateTime<Utc>,
     pub network_address: NetworkAddress,
     pub acars_endpoint: AcarsRoutingEndpoint,
+    #[serde(default)]
+    pub metadata: StationMetadata,
+}
+
+impl StationEntry {
+    /// Client-facing view of this entry.
+    pub fn info(&self) -> StationInfo {
+        StationInfo {
+            callsign: self.acars_endpoint.callsign.clone(),
+            acars_address: self.acars_endpoint.address.clone(),
+            status: self.status,
+            metadata: self.metadata.clone(),
+            last_updated: self.last_updated,
+        }
+    }
 }
 
 impl StationRegistry {
@@ -42,6 +243,7 @@ impl StationRegistry {
         network_id: NetworkId,
         js: async_nats::jetstream::Context,
         force_reset: bool,
+        policy: CallsignPolicy,
     ) -> Result<Self> {
         let bucket_name = openlink_sdk::NatsSubjects::kv_station_registry(&network_id);
         let callsign_index_bucket_name =
@@ -94,11 +296,11 @@ impl StationRegistry {
         Ok(Self {
             kv_registry_store,
             kv_callsign_index_store,
+            policy,
         })
     }
 
     /// Look up a station by its [`StationId`].
-    #[allow(dead_code)] // used in tests, will be used from handler code later
     pub async fn get_status(&self, station_id: &StationId) -> Result<Option<StationEntry>> {
         self.kv_registry_store
             .get(station_id.to_string())
@@ -135,21 +337,83 @@ impl StationRegistry {
         Ok(Some(station_entry))
     }
 
-    /// Insert or update a station's status in the registry.
+    /// Insert or update a station's status and metadata in the registry.
+    ///
+    /// Only `network_address`, the address that registered the station, may
+    /// update it. Going to any reachable status claims the callsign under
+    /// the registry's [`CallsignPolicy`]; a refused update leaves the
+    /// registry untouched and is reported as [`ClaimOutcome::Denied`]. `role` is the sender's
+    /// verified role, `None` for updates made by the server itself.
     pub async fn update_status(
         &self,
         station_id: &StationId,
         status: &StationStatus,
         acars_endpoint: &AcarsRoutingEndpoint,
         network_address: &NetworkAddress,
-    ) -> Result<()> {
+        metadata: &StationMetadata,
+        role: Option<UserRole>,
+    ) -> Result<StatusUpdate> {
+        let callsign_key = callsign_index_key(&acars_endpoint.callsign);
+        let (index_revision, index_entry) = self.index_entry(&callsign_key).await?;
+        let previous = self.get_status(station_id).await?;
+        if let Some(denial) =
+            check_station_owner(&self.policy, station_id, status, network_address, previous.as_ref(), Utc::now())
+        {
+            info!(station = %station_id, %network_address, reason = %denial.reason, "status update denied");
+            return Ok(StatusUpdate {
+                outcome: ClaimOutcome::Denied(denial),
+                previous,
+                current: None,
+            });
+        }
+
+        let mut outcome = ClaimOutcome::Applied;
+        if status.is_reachable() {
+            let holder = match index_entry {
+                Some(ref idx) => self.get_status(&idx.station_id).await?,
+                None => None,
+            };
+            outcome = evaluate_claim(
+                &self.policy,
+                station_id,
+                network_address,
+                role,
+                &acars_endpoint.callsign,
+                holder.as_ref(),
+                Utc::now(),
+            );
+            match outcome {
+                ClaimOutcome::Denied(ref denial) => {
+                    info!(station = %station_id, callsign = %acars_endpoint.callsign, reason = %denial.reason, "callsign claim denied");
+                    return Ok(StatusUpdate {
+                        outcome,
+                        previous,
+                        current: None,
+                    });
+                }
+                ClaimOutcome::TookOver(ref previous) => {
+                    info!(station = %station_id, callsign = %acars_endpoint.callsign, previous = %previous.station_id, "callsign taken over");
+                    let mut released = previous.clone();
+                    released.status = StationStatus::Offline;
+                    released.last_updated = Utc::now();
+                    self.kv_registry_store
+                        .put(released.station_id.to_string(), serde_json::to_vec(&released)?.into())
+                        .await?;
+                }
+                ClaimOutcome::Applied | ClaimOutcome::NotHolder => {}
+            }
+        }
+
         // Remove stale callsign index if callsign changed for an existing station.
-        if let Some(existing_content) = self.kv_registry_store.get(station_id.to_string()).await? {
-            let existing: StationEntry = serde_json::from_slice(existing_content.as_ref())?;
-            if existing.acars_endpoint.callsign != acars_endpoint.callsign {
-                let old_key = callsign_index_key(&existing.acars_endpoint.callsign);
+        if let Some(ref existing) = previous
+            && existing.acars_endpoint.callsign != acars_endpoint.callsign
+        {
+            let old_key = callsign_index_key(&existing.acars_endpoint.callsign);
+            if let (revision, Some(idx)) = self.index_entry(&old_key).await?
+                && idx.station_id == *station_id
+            {
                 self.kv_callsign_index_store
-                    .delete(old_key)
+                    .delete_expect_revision(old_key, Some(revision))
                     .await
                     .ok();
             }
@@ -157,32 +421,71 @@ impl StationRegistry {
 
         let entry = StationEntry {
             station_id: station_id.clone(),
-            status: status.clone(),
+            status: *status,
             last_updated: Utc::now(),
             acars_endpoint: acars_endpoint.clone(),
             network_address: network_address.clone(),
+            metadata: metadata.clone(),
         };
         self.kv_registry_store
             .put(station_id.to_string(), serde_json::to_vec(&entry)?.into())
             .await?;
 
-        // Keep reverse index only for connected (online) stations.
-        let callsign_key = callsign_index_key(&acars_endpoint.callsign);
-        if *status == StationStatus::Online {
+        // Keep reverse index only for reachable stations. The index is
+        // updated against the revision read above so two concurrent claims
+        // cannot both win.
+        if status.is_reachable() {
             let idx = CallsignIndexEntry {
                 station_id: station_id.clone(),
             };
-            self.kv_callsign_index_store
-                .put(callsign_key, serde_json::to_vec(&idx)?.into())
-                .await?;
-        } else {
-            self.kv_callsign_index_store
-                .delete(callsign_key)
+            if let Err(e) = self
+                .kv_callsign_index_store
+                .update(&callsign_key, serde_json::to_vec(&idx)?.into(), index_revision)
                 .await
-                .ok();
+            {
+                warn!(error = %e, callsign = %acars_endpoint.callsign, "concurrent callsign claim");
+                let mut lost = entry;
+                lost.status = StationStatus::Offline;
+                self.kv_registry_store
+                    .put(station_id.to_string(), serde_json::to_vec(&lost)?.into())
+                    .await?;
+                return Ok(StatusUpdate {
+                    outcome: ClaimOutcome::Denied(ClaimDenial {
+                        code: RejectionCode::CallsignInUse,
+                        reason: format!("callsign {} was claimed concurrently", acars_endpoint.callsign),
+                    }),
+                    previous,
+                    current: None,
+                });
+            }
+        } else {
+            let holder = index_entry.as_ref().map(|idx| &idx.station_id);
+            outcome = release_outcome(station_id, holder);
+            if holder == Some(station_id) {
+                self.kv_callsign_index_store
+                    .delete_expect_revision(callsign_key, Some(index_revision))
+                    .await
+                    .ok();
+            }
         }
 
-        Ok(())
+        Ok(StatusUpdate {
+            outcome,
+            previous,
+            current: Some(entry),
+        })
+    }
+
+    /// Read the callsign index entry and its revision (0 when absent).
+    async fn index_entry(&self, callsign_key: &str) -> Result<(u64, Option<CallsignIndexEntry>)> {
+        match self.kv_callsign_index_store.entry(callsign_key).await? {
+            Some(entry) if !entry.value.is_empty() => {
+                let idx: CallsignIndexEntry = serde_json::from_slice(entry.value.as_ref())?;
+                Ok((entry.revision, Some(idx)))
+            }
+            Some(entry) => Ok((entry.revision, None)),
+            None => Ok((0, None)),
+        }
     }
 
     /// List all station entries from the registry bucket.
@@ -200,33 +503,60 @@ impl StationRegistry {
         Ok(entries)
     }
 
-    /// Mark stale online stations as offline based on a TTL lease.
+    /// Mark stale reachable stations as offline based on a TTL lease.
     ///
     /// Returns the entries that were transitioned to offline.
     pub async fn expire_stale_online(&self, ttl: Duration) -> Result<Vec<StationEntry>> {
         let now = Utc::now();
-        let entries = self.list_entries().await?;
-        let mut expired = Vec::new();
+        let stale = self.list_entries().await?.into_iter().filter(|entry| {
+            entry.status.is_reachable() && now.signed_duration_since(entry.last_updated) > ttl
+        });
+        self.mark_offline(stale).await
+    }
 
+    /// Mark every reachable station registered by `address` as offline.
+    ///
+    /// Used when NATS reports that the address's last connection closed;
+    /// the heartbeat lease stays as the fallback.
+    ///
+    /// Returns the entries that were transitioned to offline.
+    pub async fn mark_address_offline(&self, address: &NetworkAddress) -> Result<Vec<StationEntry>> {
+        let owned = self
+            .list_entries()
+            .await?
+            .into_iter()
+            .filter(|entry| entry.status.is_reachable() && entry.network_address == *address);
+        self.mark_offline(owned).await
+    }
+
+    /// Mark a single station entry as offline, whatever its lease.
+    pub async fn mark_station_offline(&self, entry: StationEntry) -> Result<Vec<StationEntry>> {
+        self.mark_offline(std::iter::once(entry)).await
+    }
+
+    async fn mark_offline(
+        &self,
+        entries: impl Iterator<Item = StationEntry>,
+    ) -> Result<Vec<StationEntry>> {
+        let mut released = Vec::new();
         for entry in entries {
-            if entry.status != StationStatus::Online {
-                continue;
-            }
-            if now.signed_duration_since(entry.last_updated) <= ttl {
-                continue;
+            let update = self
+                .update_status(
+                    &entry.station_id,
+                    &StationStatus::Offline,
+                    &entry.acars_endpoint,
+                    &entry.network_address,
+                    &entry.metadata,
+                    None,
+                )
+                .await?;
+            // The callsign now belongs to another station: its directory
+            // entry and sessions are not this station's to drop.
+            if !matches!(update.outcome, ClaimOutcome::NotHolder) {
+                released.push(entry);
             }
-
-            self.update_status(
-                &entry.station_id,
-                &StationStatus::Offline,
-                &entry.acars_endpoint,
-                &entry.network_address,
-            )
-            .await?;
-            expired.push(entry);
         }
-
-        Ok(expired)
+        Ok(released)
     }
 }
 
@@ -246,11 +576,176 @@ mod tests {
             .expect("Failed to connect to NATS server");
         let js = async_nats::jetstream::new(client.clone());
         let network_id = NetworkId::new("test_network");
-        StationRegistry::new(network_id, js, false)
+        StationRegistry::new(network_id, js, false, CallsignPolicy::default())
             .await
             .expect("create registry")
     }
 
+    fn holder(station_id: &str, status: StationStatus, age_seconds: i64) -> StationEntry {
+        StationEntry {
+            station_id: StationId::new(station_id),
+            status,
+            last_updated: Utc::now() - Duration::seconds(age_seconds),
+            network_address: NetworkAddress::from(station_id),
+            acars_endpoint: AcarsRoutingEndpoint::new("LFPG", "ADDR1"),
+            metadata: StationMetadata::default(),
+        }
+    }
+
+    #[test]
+    fn test_callsign_pattern_matching() {
+        let facility = CallsignPattern::new("@@@@");
+        assert!(facility.matches("LFPG"));
+        assert!(facility.matches("egll"));
+        assert!(!facility.matches("AFR1"));
+        assert!(!facility.matches("LFPGA"));
+
+        let sector = CallsignPattern::new("@@@@_*");
+        assert!(sector.matches("LFFF_N"));
+        assert!(sector.matches("LFFF_"));
+        assert!(!sector.matches("LFFF"));
+
+        assert!(CallsignPattern::new("AFR###").matches("AFR123"));
+        assert!(CallsignPattern::new("L?PG").matches("LFPG"));
+    }
+
+    #[test]
+    fn test_claim_first_come_lease() {
+        let policy = CallsignPolicy::default();
+        let callsign = AcarsEndpointCallsign::new("LFPG");
+        let now = Utc::now();
+        let claimant = StationId::new("B");
+        let b = NetworkAddress::from("B");
+        let role = Some(UserRole::Controller);
+
+        let outcome = evaluate_claim(&policy, &claimant, &b, role, &callsign, None, now);
+        assert!(matches!(outcome, ClaimOutcome::Applied));
+
+        let live = holder("A", StationStatus::Online, 10);
+        let outcome = evaluate_claim(&policy, &claimant, &b, role, &callsign, Some(&live), now);
+        let ClaimOutcome::Denied(denial) = outcome else {
+            panic!("expected denial");
+        };
+        assert_eq!(denial.code, RejectionCode::CallsignInUse);
+
+        // The holder itself refreshes its lease.
+        let outcome = evaluate_claim(&policy, &live.station_id, &live.network_address, role, &callsign, Some(&live), now);
+        assert!(matches!(outcome, ClaimOutcome::Applied));
+
+        // Expired or offline leases are free.
+        let stale = holder("A", StationStatus::Online, 600);
+        let outcome = evaluate_claim(&policy, &claimant, &b, role, &callsign, Some(&stale), now);
+        assert!(matches!(outcome, ClaimOutcome::Applied));
+        let offline = holder("A", StationStatus::Offline, 10);
+        let outcome = evaluate_claim(&policy, &claimant, &b, role, &callsign, Some(&offline), now);
+        assert!(matches!(outcome, ClaimOutcome::Applied));
+
+        // An away station still holds its lease.
+        let away = holder("A", StationStatus::Away, 10);
+        let outcome = evaluate_claim(&policy, &claimant, &b, role, &callsign, Some(&away), now);
+        assert!(matches!(outcome, ClaimOutcome::Denied(_)));
+    }
+
+    #[test]
+    fn test_station_id_reused_by_another_address() {
+        let policy = CallsignPolicy::default();
+        let callsign = AcarsEndpointCallsign::new("LFPG");
+        let now = Utc::now();
+        let live = holder("A", StationStatus::Online, 10);
+        let intruder = NetworkAddress::from("B");
+
+        // Neither refreshing nor releasing another address's station.
+        for status in [StationStatus::Online, StationStatus::Offline] {
+            let denial = check_station_owner(&policy, &live.station_id, &status, &intruder, Some(&live), now)
+                .expect("expected denial");
+            assert_eq!(denial.code, RejectionCode::CallsignNotOwned);
+        }
+        let outcome = evaluate_claim(&policy, &live.station_id, &intruder, None, &callsign, Some(&live), now);
+        assert!(matches!(outcome, ClaimOutcome::Denied(_)));
+
+        // The owner, and anyone once the station is gone, may update it.
+        let owner = &live.network_address;
+        assert!(check_station_owner(&policy, &live.station_id, &StationStatus::Offline, owner, Some(&live), now).is_none());
+        let offline = holder("A", StationStatus::Offline, 10);
+        assert!(check_station_owner(&policy, &offline.station_id, &StationStatus::Online, &intruder, Some(&offline), now).is_none());
+        assert!(check_station_owner(&policy, &offline.station_id, &StationStatus::Offline, &intruder, Some(&offline), now).is_some());
+    }
+
+    #[test]
+    fn test_release_leaves_a_new_holder_alone() {
+        let a = StationId::new("A");
+        let b = StationId::new("B");
+
+        // A's lease lapsed and B claimed the callsign: sweeping A must not
+        // release it.
+        assert!(matches!(release_outcome(&a, Some(&b)), ClaimOutcome::NotHolder));
+        assert!(matches!(release_outcome(&a, Some(&a)), ClaimOutcome::Applied));
+        assert!(matches!(release_outcome(&a, None), ClaimOutcome::Applied));
+    }
+
+    #[test]
+    fn test_claim_takeover_when_allowed() {
+        let policy = CallsignPolicy {
+            allow_takeover: true,
+            ..Default::default()
+        };
+        let live = holder("A", StationStatus::Online, 10);
+        let outcome = evaluate_claim(
+            &policy,
+            &StationId::new("B"),
+            &NetworkAddress::from("B"),
+            Some(UserRole::Controller),
+            &AcarsEndpointCallsign::new("LFPG"),
+            Some(&live),
+            Utc::now(),
+        );
+        let ClaimOutcome::TookOver(previous) = outcome else {
+            panic!("expected takeover");
+        };
+        assert_eq!(previous.station_id, StationId::new("A"));
+    }
+
+    #[test]
+    fn test_claim_reserved_pattern_requires_controller() {
+        let policy = CallsignPolicy {
+            reserved_patterns: vec![CallsignPattern::new("@@@@")],
+            ..Default::default()
+        };
+        let callsign = AcarsEndpointCallsign::new("LFPG");
+        let station_id = StationId::new("100000");
+        let address = NetworkAddress::from("100000");
+        for role in [None, Some(UserRole::Pilot), Some(UserRole::Bot)] {
+            let outcome = evaluate_claim(&policy, &station_id, &address, role, &callsign, None, Utc::now());
+            let ClaimOutcome::Denied(denial) = outcome else {
+                panic!("expected denial for {role:?}");
+            };
+            assert_eq!(denial.code, RejectionCode::CallsignReserved);
+        }
+
+        let outcome = evaluate_claim(
+            &policy,
+            &station_id,
+            &address,
+            Some(UserRole::Controller),
+            &callsign,
+            None,
+            Utc::now(),
+        );
+        assert!(matches!(outcome, ClaimOutcome::Applied));
+
+        // Aircraft callsigns are unaffected.
+        let outcome = evaluate_claim(
+            &policy,
+            &station_id,
+            &address,
+            Some(UserRole::Pilot),
+            &AcarsEndpointCallsign::new("AFR123"),
+            None,
+            Utc::now(),
+        );
+        assert!(matches!(outcome, ClaimOutcome::Applied));
+    }
+
     #[tokio::test]
     async fn test_update_and_get_status() {
         let registry = setup_registry().await;
@@ -260,7 +755,7 @@ mod tests {
         let network_address = NetworkAddress::from("1234");
 
         registry
-            .update_status(&station_id, &status, &acars_endpoint, &network_address)
+            .update_status(&station_id, &status, &acars_endpoint, &network_address, &StationMetadata::default(), None)
             .await
             .expect("update status");
 
@@ -289,7 +784,7 @@ mod tests {
         let network_address = NetworkAddress::from("1234");
 
         registry
-            .update_status(&station_id, &status, &acars_endpoint, &network_address)
+            .update_status(&station_id, &status, &acars_endpoint, &network_address, &StationMetadata::default(), None)
             .await
             .expect("update status");
 
@@ -309,12 +804,12 @@ mod tests {
         let network_address = NetworkAddress::from("5678");
 
         registry
-            .update_status(&station_id, &StationStatus::Online, &acars_endpoint, &network_address)
+            .update_status(&station_id, &StationStatus::Online, &acars_endpoint, &network_address, &StationMetadata::default(), None)
             .await
             .expect("online status");
 
         registry
-            .update_status(&station_id, &StationStatus::Offline, &acars_endpoint, &network_address)
+            .update_status(&station_id, &StationStatus::Offline, &acars_endpoint, &network_address, &StationMetadata::default(), None)
             .await
             .expect("offline status");
 
@@ -324,5 +819,38 @@ mod tests {
             .expect("lookup callsign");
         assert!(found.is_none());
     }
+
+    #[tokio::test]
+    async fn test_station_id_reused_by_another_address_is_denied() {
+        let registry = setup_registry().await;
+        let station_id = StationId::new("station_reused");
+        let acars_endpoint = AcarsRoutingEndpoint::new("LFMN", "ADDR3");
+        let owner = NetworkAddress::from("2222");
+        let intruder = NetworkAddress::from("3333");
+
+        registry
+            .update_status(&station_id, &StationStatus::Online, &acars_endpoint, &owner, &StationMetadata::default(), None)
+            .await
+            .expect("online status");
+
+        for status in [StationStatus::Online, StationStatus::Offline] {
+            let update = registry
+                .update_status(&station_id, &status, &acars_endpoint, &intruder, &StationMetadata::default(), None)
+                .await
+                .expect("update status");
+            let ClaimOutcome::Denied(denial) = update.outcome else {
+                panic!("expected denial for {status:?}");
+            };
+            assert_eq!(denial.code, RejectionCode::CallsignNotOwned);
+        }
+
+        let found = registry
+            .lookup_callsign(&AcarsEndpointCallsign::new("LFMN"))
+            .await
+            .expect("lookup callsign")
+            .expect("callsign still held");
+        assert_eq!(found.network_address, owner);
+        assert_eq!(found.status, StationStatus::Online);
+    }
 }
 